@@ -128,7 +128,7 @@ impl Serialize for ImageReference {
 
 impl<'de> Deserialize<'de> for ImageReference {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let s = <&str>::deserialize(deserializer)?;
+        let s = String::deserialize(deserializer)?;
         s.parse().map_err(D::Error::custom)
     }
 }
@@ -259,7 +259,7 @@ fn validate_domain(input: &str) -> Result<(), ImageReferenceError> {
     Ok(())
 }
 
-#[derive(Debug, Error, Eq, PartialEq)]
+#[derive(Debug, Clone, Error, Eq, PartialEq)]
 pub enum ImageReferenceError {
     #[error("invalid reference format")]
     ErrReferenceInvalidFormat,
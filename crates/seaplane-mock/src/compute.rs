@@ -0,0 +1,152 @@
+//! The `/v2beta/formations` API, which launches Formations made up of Flights
+
+use seaplane_oid::Oid;
+use serde_json::{json, Value};
+
+use crate::http::{Method, Request, Response, StatusCode};
+
+/// The launched Formations in the order they were created
+#[derive(Debug, Default)]
+pub(crate) struct Formations {
+    formations: Vec<Value>,
+}
+
+impl Formations {
+    /// Handles a request whose path is `path` after `/v2beta/formations`. `origin` is used to build
+    /// the links to other pages.
+    pub(crate) fn handle(
+        &mut self,
+        req: &Request,
+        path: &str,
+        origin: &str,
+        subdomain: &str,
+        page_size: usize,
+    ) -> Response {
+        match path.trim_start_matches('/') {
+            "" => match req.method {
+                Method::GET => self.get_page(req, origin, page_size),
+                Method::POST => self.create(req, subdomain),
+                _ => Response::method_not_allowed(&req.method),
+            },
+            oid => {
+                let i = match self.formations.iter().position(|f| f["oid"] == oid) {
+                    Some(i) => i,
+                    None => return Response::not_found(format!("formation {oid} not found")),
+                };
+                match req.method {
+                    Method::GET => Response::json(StatusCode::OK, &self.formations[i]),
+                    Method::PUT => self.update(req, i, subdomain),
+                    Method::DELETE => {
+                        self.formations.remove(i);
+                        Response::ok()
+                    }
+                    _ => Response::method_not_allowed(&req.method),
+                }
+            }
+        }
+    }
+
+    fn create(&mut self, req: &Request, subdomain: &str) -> Response {
+        let (mut formation, name) = match parse_formation(&req.body) {
+            Ok(parsed) => parsed,
+            Err(resp) => return resp,
+        };
+        if self.formations.iter().any(|f| f["name"] == name.as_str()) {
+            return Response::conflict(format!("a formation named {name} already exists"));
+        }
+        assign_flight_oids(&mut formation, &Value::Null);
+
+        formation["oid"] = json!(new_oid("frm"));
+        formation["url"] = json!(format!("https://{name}.{subdomain}.on.cplane.cloud/"));
+        self.formations.push(formation.clone());
+        Response::json(StatusCode::CREATED, &formation)
+    }
+
+    // Replaces the configuration of the Formation at index `i`, keeping the IDs of its Flights
+    fn update(&mut self, req: &Request, i: usize, subdomain: &str) -> Response {
+        let (mut formation, name) = match parse_formation(&req.body) {
+            Ok(parsed) => parsed,
+            Err(resp) => return resp,
+        };
+        if self
+            .formations
+            .iter()
+            .enumerate()
+            .any(|(j, f)| j != i && f["name"] == name.as_str())
+        {
+            return Response::conflict(format!("a formation named {name} already exists"));
+        }
+        let existing = &self.formations[i];
+        assign_flight_oids(&mut formation, &existing["flights"]);
+
+        formation["oid"] = existing["oid"].clone();
+        formation["url"] = json!(format!("https://{name}.{subdomain}.on.cplane.cloud/"));
+        self.formations[i] = formation.clone();
+        Response::json(StatusCode::OK, &formation)
+    }
+
+    // A page of Formations, pages being numbered from 1
+    fn get_page(&self, req: &Request, origin: &str, page_size: usize) -> Response {
+        let page = match req.query("page").map(str::parse::<usize>) {
+            None => 1,
+            Some(Ok(page)) if page > 0 => page,
+            _ => return Response::bad_request("page must be a positive number"),
+        };
+        let start = (page - 1) * page_size;
+        let link = |page: usize| format!("{origin}{}?page={page}", req.path);
+        let total = self.formations.len();
+        Response::json(
+            StatusCode::OK,
+            &json!({
+                "objects": self.formations.iter().skip(start).take(page_size).collect::<Vec<_>>(),
+                "meta": {
+                    "total": total,
+                    "next": (start + page_size < total).then(|| link(page + 1)),
+                    "prev": (page > 1).then(|| link(page - 1)),
+                },
+            }),
+        )
+    }
+}
+
+// Parses and validates the Formation in a request body, returning it along with its name
+fn parse_formation(body: &[u8]) -> Result<(Value, String), Response> {
+    let formation: Value = match serde_json::from_slice(body) {
+        Ok(f @ Value::Object(_)) => f,
+        _ => return Err(Response::bad_request("the body must be a Formation object")),
+    };
+    let name = match formation["name"].as_str() {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => return Err(Response::bad_request("the Formation must have a name")),
+    };
+    match formation["flights"].as_array() {
+        Some(flights) if !flights.is_empty() => {
+            if flights
+                .iter()
+                .any(|f| !f.is_object() || !f["name"].is_string() || !f["image"].is_string())
+            {
+                return Err(Response::bad_request("each Flight must have a name and image"));
+            }
+        }
+        _ => return Err(Response::bad_request("the Formation must have at least one Flight")),
+    }
+    Ok((formation, name))
+}
+
+// Gives each Flight the ID of the Flight of the same name in `existing`, or a new one
+fn assign_flight_oids(formation: &mut Value, existing: &Value) {
+    let existing = existing.as_array().map(Vec::as_slice).unwrap_or_default();
+    for flight in formation["flights"].as_array_mut().into_iter().flatten() {
+        flight["oid"] = existing
+            .iter()
+            .find(|f| f["name"] == flight["name"])
+            .map_or_else(|| json!(new_oid("flt")), |f| f["oid"].clone());
+        flight["status"] = json!("healthy");
+    }
+}
+
+fn new_oid(prefix: &str) -> String {
+    Oid::new(prefix)
+        .expect("prefixes are three lowercase characters")
+        .to_string()
+}
@@ -0,0 +1,118 @@
+//! The requests and responses handled by the mock APIs, independent of the HTTP server itself
+
+use std::net::IpAddr;
+
+use base64::{
+    alphabet::URL_SAFE,
+    engine::{DecodePaddingMode, Engine, GeneralPurpose, GeneralPurposeConfig},
+};
+pub(crate) use hyper::{Method, StatusCode};
+use serde_json::{json, Value};
+
+// URL-safe base64, accepting input with or without padding and never producing any
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// Encodes bytes as URL-safe base64 without padding, the way the APIs return keys
+pub(crate) fn encode(bytes: &[u8]) -> String { BASE64.encode(bytes) }
+
+/// Decodes URL-safe base64 with or without padding
+pub(crate) fn decode(encoded: &str) -> Option<Vec<u8>> { BASE64.decode(encoded).ok() }
+
+/// Decodes a `base64:{value}` path segment or query value
+pub(crate) fn decode_prefixed(s: &str) -> Option<Vec<u8>> { decode(s.strip_prefix("base64:")?) }
+
+/// A request as seen by the mock APIs
+#[derive(Debug)]
+pub(crate) struct Request {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) query: Vec<(String, String)>,
+    pub(crate) authorization: Option<String>,
+    pub(crate) accept: Option<String>,
+    pub(crate) ip: IpAddr,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Request {
+    /// The first value of the query parameter `name`
+    pub(crate) fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find_map(|(k, v)| if k == name { Some(v.as_str()) } else { None })
+    }
+
+    /// The credential of the `Authorization: Bearer` header
+    pub(crate) fn bearer(&self) -> Option<&str> {
+        self.authorization.as_deref()?.strip_prefix("Bearer ")
+    }
+
+    /// Whether the client asked for a JSON response
+    pub(crate) fn accepts_json(&self) -> bool {
+        self.accept
+            .as_deref()
+            .map_or(false, |a| a.contains("application/json"))
+    }
+}
+
+/// A response from the mock APIs
+#[derive(Debug)]
+pub(crate) struct Response {
+    pub(crate) status: StatusCode,
+    pub(crate) content_type: &'static str,
+    pub(crate) body: Vec<u8>,
+}
+
+impl Response {
+    /// A JSON response
+    pub(crate) fn json(status: StatusCode, body: &Value) -> Self {
+        Self { status, content_type: "application/json", body: body.to_string().into_bytes() }
+    }
+
+    /// A plain text response
+    pub(crate) fn text(status: StatusCode, body: impl Into<String>) -> Self {
+        Self { status, content_type: "text/plain", body: body.into().into_bytes() }
+    }
+
+    /// The body the coordination APIs respond with when a write succeeds
+    pub(crate) fn ok() -> Self {
+        Self::json(StatusCode::OK, &json!({"status": 200, "title": "Ok"}))
+    }
+
+    /// An [RFC 7807][rfc_7807] problem response
+    ///
+    /// [rfc_7807]: https://www.rfc-editor.org/rfc/rfc7807
+    pub(crate) fn problem(status: StatusCode, detail: impl Into<String>) -> Self {
+        let body = json!({
+            "type": "about:blank",
+            "title": status.canonical_reason().unwrap_or("Error"),
+            "status": status.as_u16(),
+            "detail": detail.into(),
+        });
+        Self { content_type: "application/problem+json", ..Self::json(status, &body) }
+    }
+
+    /// A `400 Bad Request` problem response
+    pub(crate) fn bad_request(detail: impl Into<String>) -> Self {
+        Self::problem(StatusCode::BAD_REQUEST, detail)
+    }
+
+    /// A `404 Not Found` problem response
+    pub(crate) fn not_found(detail: impl Into<String>) -> Self {
+        Self::problem(StatusCode::NOT_FOUND, detail)
+    }
+
+    /// A `409 Conflict` problem response
+    pub(crate) fn conflict(detail: impl Into<String>) -> Self {
+        Self::problem(StatusCode::CONFLICT, detail)
+    }
+
+    /// A `405 Method Not Allowed` problem response
+    pub(crate) fn method_not_allowed(method: &Method) -> Self {
+        Self::problem(StatusCode::METHOD_NOT_ALLOWED, format!("{method} is not supported here"))
+    }
+}
@@ -0,0 +1,92 @@
+//! The `/v1/token` API, which exchanges API keys for short lived access tokens
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::json;
+
+use crate::http::{encode, Method, Request, Response, StatusCode};
+
+/// Issues access tokens and decides which requests are authorized
+#[derive(Debug)]
+pub(crate) struct Identity {
+    // The API keys tokens are issued for, or any key if empty
+    pub(crate) api_keys: HashSet<String>,
+    // Tokens which are always accepted and never expire
+    pub(crate) static_tokens: HashSet<String>,
+    // How long issued tokens are valid for
+    pub(crate) token_ttl: Duration,
+    pub(crate) tenant: String,
+    pub(crate) subdomain: String,
+    // The tokens which have been issued along with when they expire
+    issued: HashMap<String, SystemTime>,
+}
+
+impl Identity {
+    pub(crate) fn new() -> Self {
+        Self {
+            api_keys: HashSet::new(),
+            static_tokens: HashSet::new(),
+            token_ttl: Duration::from_secs(60),
+            tenant: "tnt-mock".into(),
+            subdomain: "mock".into(),
+            issued: HashMap::new(),
+        }
+    }
+
+    /// Forgets all issued tokens
+    pub(crate) fn clear(&mut self) { self.issued.clear(); }
+
+    /// Whether the request carries a static token, or an issued token which hasn't expired yet
+    pub(crate) fn is_authorized(&self, now: SystemTime, req: &Request) -> bool {
+        match req.bearer() {
+            Some(token) => {
+                self.static_tokens.contains(token)
+                    || self.issued.get(token).map_or(false, |exp| *exp > now)
+            }
+            None => false,
+        }
+    }
+
+    /// `POST /v1/token`
+    pub(crate) fn handle(&mut self, now: SystemTime, req: &Request) -> Response {
+        if req.method != Method::POST {
+            return Response::method_not_allowed(&req.method);
+        }
+        let key = match req.bearer() {
+            Some(key) if self.api_keys.is_empty() || self.api_keys.contains(key) => key,
+            _ => return Response::problem(StatusCode::UNAUTHORIZED, "invalid API key"),
+        };
+
+        let expires_at = now + self.token_ttl;
+        let token = jwt(key, self.issued.len(), expires_at);
+        self.issued.insert(token.clone(), expires_at);
+
+        if req.accepts_json() {
+            Response::json(
+                StatusCode::CREATED,
+                &json!({"token": token, "tenant": self.tenant, "subdomain": self.subdomain}),
+            )
+        } else {
+            Response::text(StatusCode::CREATED, token)
+        }
+    }
+}
+
+// An unsigned JWT unique to this key and issue, whose claims hold the expiration time so clients
+// know when to refresh it
+fn jwt(key: &str, n: usize, expires_at: SystemTime) -> String {
+    let exp = expires_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let header = json!({"alg": "none", "typ": "JWT"});
+    let claims = json!({"sub": key, "jti": n, "exp": exp});
+    format!(
+        "{}.{}.mock",
+        encode(header.to_string().as_bytes()),
+        encode(claims.to_string().as_bytes())
+    )
+}
@@ -0,0 +1,52 @@
+//! An in-process, stateful stand-in for the Seaplane APIs, so that clients of them can be tested
+//! end to end without network access.
+//!
+//! Unlike canned request/response mocks, a [`MockServer`] implements the behavior of each API
+//! against in-memory storage: access tokens are issued and expire, Formations are assigned IDs,
+//! locks are exclusive until released or their TTL runs out, range requests are paged, and
+//! failures are reported as `application/problem+json` errors with the same status codes as the
+//! real APIs (`401`, `404`, `409`, ...).
+//!
+//! # Examples
+//!
+//! ```
+//! use seaplane_mock::MockServer;
+//!
+//! let server = MockServer::start();
+//! let client = reqwest::blocking::Client::new();
+//!
+//! let token = client
+//!     .post(server.url("/v1/token"))
+//!     .bearer_auth("my-api-key")
+//!     .send()
+//!     .unwrap()
+//!     .text()
+//!     .unwrap();
+//!
+//! // "foo" and "bar" encoded in URL-safe base64
+//! let resp = client
+//!     .put(server.url("/v1/config/base64:Zm9v"))
+//!     .bearer_auth(&token)
+//!     .body("YmFy")
+//!     .send()
+//!     .unwrap();
+//! assert!(resp.status().is_success());
+//!
+//! let resp = client
+//!     .get(server.url("/v1/config/base64:Zm9v"))
+//!     .bearer_auth(&token)
+//!     .send()
+//!     .unwrap();
+//! assert_eq!(resp.text().unwrap(), r#"{"key":"Zm9v","value":"YmFy"}"#);
+//! ```
+
+mod compute;
+mod http;
+mod identity;
+mod locks;
+mod metadata;
+mod range;
+mod restrict;
+mod server;
+
+pub use server::{MockServer, MockServerBuilder};
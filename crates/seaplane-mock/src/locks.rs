@@ -0,0 +1,156 @@
+//! The `/v1/locks` API, distributed locks which lapse once their TTL expires
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use serde_json::{json, Value};
+
+use crate::{
+    http::{decode_prefixed, encode, Method, Request, Response, StatusCode},
+    range::RangeQuery,
+};
+
+/// A lock which is currently held
+#[derive(Debug)]
+struct HeldLock {
+    id: String,
+    client_id: String,
+    ip: IpAddr,
+    ttl: u32,
+    expires_at: Instant,
+}
+
+/// The held locks along with the sequencer of every lock which has ever been acquired
+#[derive(Debug, Default)]
+pub(crate) struct Locks {
+    held: BTreeMap<Vec<u8>, HeldLock>,
+    sequencers: HashMap<Vec<u8>, u32>,
+}
+
+impl Locks {
+    /// Handles a request whose path is `path` after `/v1/locks/`
+    pub(crate) fn handle(
+        &mut self,
+        now: Instant,
+        req: &Request,
+        path: &str,
+        page_size: usize,
+    ) -> Response {
+        // Locks whose TTL has expired are no longer held by anyone
+        self.held.retain(|_, lock| lock.expires_at > now);
+
+        if path.is_empty() || path.ends_with('/') {
+            return match req.method {
+                Method::GET => self.get_page(req, path, page_size),
+                _ => Response::method_not_allowed(&req.method),
+            };
+        }
+
+        let name = match decode_prefixed(path) {
+            Some(name) => name,
+            None => return Response::bad_request(format!("invalid lock name {path}")),
+        };
+        match req.method {
+            Method::POST => self.acquire(now, req, name),
+            Method::DELETE => match self.held_by(req, &name) {
+                Ok(()) => {
+                    self.held.remove(&name);
+                    Response::ok()
+                }
+                Err(resp) => resp,
+            },
+            Method::PATCH => {
+                let ttl = match ttl(req) {
+                    Ok(ttl) => ttl,
+                    Err(resp) => return resp,
+                };
+                if let Err(resp) = self.held_by(req, &name) {
+                    return resp;
+                }
+                let lock = self.held.get_mut(&name).expect("checked above");
+                lock.ttl = ttl;
+                lock.expires_at = now + Duration::from_secs(ttl.into());
+                Response::ok()
+            }
+            Method::GET => match self.held.get(&name) {
+                Some(lock) => Response::json(StatusCode::OK, &lock_info(&name, lock)),
+                None => Response::not_found("lock not held"),
+            },
+            _ => Response::method_not_allowed(&req.method),
+        }
+    }
+
+    fn acquire(&mut self, now: Instant, req: &Request, name: Vec<u8>) -> Response {
+        let ttl = match ttl(req) {
+            Ok(ttl) => ttl,
+            Err(resp) => return resp,
+        };
+        let client_id = match req.query("client-id") {
+            Some(id) => id.to_string(),
+            None => return Response::bad_request("missing client-id"),
+        };
+        if self.held.contains_key(&name) {
+            return Response::conflict("lock is already held");
+        }
+
+        let sequencer = self.sequencers.entry(name.clone()).or_default();
+        *sequencer += 1;
+        let sequencer = *sequencer;
+        let id = encode(&uuid::Uuid::new_v4().as_bytes()[..8]);
+        self.held.insert(
+            name,
+            HeldLock {
+                id: id.clone(),
+                client_id,
+                ip: req.ip,
+                ttl,
+                expires_at: now + Duration::from_secs(ttl.into()),
+            },
+        );
+        Response::json(StatusCode::CREATED, &json!({"id": id, "sequencer": sequencer}))
+    }
+
+    // Ensures the lock `name` is held with the ID given in the request
+    fn held_by(&self, req: &Request, name: &[u8]) -> Result<(), Response> {
+        let id = req
+            .query("id")
+            .ok_or_else(|| Response::bad_request("missing id"))?;
+        match self.held.get(name) {
+            Some(lock) if lock.id == id => Ok(()),
+            Some(_) => Err(Response::conflict("lock is held with a different ID")),
+            None => Err(Response::not_found("lock not held")),
+        }
+    }
+
+    fn get_page(&self, req: &Request, dir: &str, page_size: usize) -> Response {
+        let range = match RangeQuery::parse(dir, req) {
+            Ok(range) => range,
+            Err(resp) => return resp,
+        };
+        let (page, next) = range.page(&self.held, page_size);
+        let locks: Vec<_> = page
+            .into_iter()
+            .map(|(name, lock)| lock_info(name, lock))
+            .collect();
+        Response::json(StatusCode::OK, &json!({"next": next, "locks": locks}))
+    }
+}
+
+// Parses the `ttl` query parameter
+fn ttl(req: &Request) -> Result<u32, Response> {
+    match req.query("ttl").map(str::parse) {
+        Some(Ok(ttl)) if ttl > 0 => Ok(ttl),
+        _ => Err(Response::bad_request("ttl must be a positive number of seconds")),
+    }
+}
+
+fn lock_info(name: &[u8], lock: &HeldLock) -> Value {
+    json!({
+        "name": encode(name),
+        "id": lock.id,
+        "info": {"ttl": lock.ttl, "client-id": lock.client_id, "ip": lock.ip.to_string()},
+    })
+}
@@ -0,0 +1,70 @@
+//! The `/v1/config` API, a key-value store whose keys are grouped into directories
+
+use std::collections::BTreeMap;
+
+use serde_json::json;
+
+use crate::{
+    http::{decode, decode_prefixed, encode, Method, Request, Response, StatusCode},
+    range::RangeQuery,
+};
+
+/// The stored key-value pairs, both unencoded
+#[derive(Debug, Default)]
+pub(crate) struct Metadata {
+    kvs: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl Metadata {
+    /// Handles a request whose path is `path` after `/v1/config/`
+    pub(crate) fn handle(&mut self, req: &Request, path: &str, page_size: usize) -> Response {
+        if path.is_empty() || path.ends_with('/') {
+            return match req.method {
+                Method::GET => self.get_page(req, path, page_size),
+                _ => Response::method_not_allowed(&req.method),
+            };
+        }
+
+        let key = match decode_prefixed(path) {
+            Some(key) => key,
+            None => return Response::bad_request(format!("invalid key {path}")),
+        };
+        match req.method {
+            Method::GET => match self.kvs.get(&key) {
+                Some(value) => Response::json(
+                    StatusCode::OK,
+                    &json!({"key": encode(&key), "value": encode(value)}),
+                ),
+                None => Response::not_found("key not found"),
+            },
+            Method::PUT => {
+                let value = std::str::from_utf8(&req.body).ok().and_then(decode);
+                match value {
+                    Some(value) => {
+                        self.kvs.insert(key, value);
+                        Response::ok()
+                    }
+                    None => Response::bad_request("values must be encoded in URL-safe base64"),
+                }
+            }
+            Method::DELETE => match self.kvs.remove(&key) {
+                Some(_) => Response::ok(),
+                None => Response::not_found("key not found"),
+            },
+            _ => Response::method_not_allowed(&req.method),
+        }
+    }
+
+    fn get_page(&self, req: &Request, dir: &str, page_size: usize) -> Response {
+        let range = match RangeQuery::parse(dir, req) {
+            Ok(range) => range,
+            Err(resp) => return resp,
+        };
+        let (page, next_key) = range.page(&self.kvs, page_size);
+        let kvs: Vec<_> = page
+            .into_iter()
+            .map(|(k, v)| json!({"key": encode(k), "value": encode(v)}))
+            .collect();
+        Response::json(StatusCode::OK, &json!({"next_key": next_key, "kvs": kvs}))
+    }
+}
@@ -0,0 +1,134 @@
+//! Range queries over the directories of the coordination APIs, which page through entries sorted
+//! by their decoded keys
+
+use std::collections::BTreeMap;
+
+use crate::http::{decode_prefixed, encode, Request, Response};
+
+/// The directory and lower bound of a range request
+#[derive(Debug, Default)]
+pub(crate) struct RangeQuery {
+    dir: Option<Vec<u8>>,
+    from: Option<Vec<u8>>,
+}
+
+impl RangeQuery {
+    /// Parses the query from what remains of the path after the API's base path, either nothing
+    /// for the root directory or `base64:{dir}/`, and the `from` query parameter
+    pub(crate) fn parse(dir: &str, req: &Request) -> Result<Self, Response> {
+        let dir = match dir {
+            "" => None,
+            dir => Some(
+                dir.strip_suffix('/')
+                    .and_then(decode_prefixed)
+                    .ok_or_else(|| Response::bad_request(format!("invalid directory {dir}")))?,
+            ),
+        };
+        let from = match req.query("from") {
+            None => None,
+            Some(from) => Some(
+                decode_prefixed(from)
+                    .ok_or_else(|| Response::bad_request(format!("invalid from key {from}")))?,
+            ),
+        };
+        Ok(Self { dir, from })
+    }
+
+    /// Whether `key` lives within the directory of this query, the root containing every key
+    pub(crate) fn contains(&self, key: &[u8]) -> bool {
+        match &self.dir {
+            None => true,
+            Some(dir) => key.starts_with(dir) && key.get(dir.len()) == Some(&b'/'),
+        }
+    }
+
+    /// Returns up to `size` entries of the directory beginning at the `from` key, along with the
+    /// encoded key the next page begins at if there are more
+    pub(crate) fn page<'a, V>(
+        &self,
+        entries: &'a BTreeMap<Vec<u8>, V>,
+        size: usize,
+    ) -> (Vec<(&'a [u8], &'a V)>, Option<String>) {
+        let start = self.from.clone().unwrap_or_default();
+        let mut page: Vec<_> = entries
+            .range(start..)
+            .filter(|(k, _)| self.contains(k))
+            .map(|(k, v)| (k.as_slice(), v))
+            .take(size + 1)
+            .collect();
+        let next = if page.len() > size { page.pop().map(|(k, _)| encode(k)) } else { None };
+        (page, next)
+    }
+}
+
+#[cfg(test)]
+mod range_tests {
+    use super::*;
+    use crate::http::Method;
+
+    fn request(from: Option<&[u8]>) -> Request {
+        Request {
+            method: Method::GET,
+            path: String::new(),
+            query: from
+                .map(|f| vec![("from".into(), format!("base64:{}", encode(f)))])
+                .unwrap_or_default(),
+            authorization: None,
+            accept: None,
+            ip: "127.0.0.1".parse().unwrap(),
+            body: Vec::new(),
+        }
+    }
+
+    fn entries() -> BTreeMap<Vec<u8>, ()> {
+        ["a", "b/1", "b/2", "b/3", "bc/1", "c"]
+            .into_iter()
+            .map(|k| (k.as_bytes().to_vec(), ()))
+            .collect()
+    }
+
+    fn keys(page: Vec<(&[u8], &())>) -> Vec<String> {
+        page.into_iter()
+            .map(|(k, _)| String::from_utf8(k.to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn root() {
+        let entries = entries();
+        let (page, next) = RangeQuery::parse("", &request(None))
+            .unwrap()
+            .page(&entries, 4);
+        assert_eq!(keys(page), vec!["a", "b/1", "b/2", "b/3"]);
+        assert_eq!(next, Some(encode(b"bc/1")));
+
+        let (page, next) = RangeQuery::parse("", &request(Some(b"bc/1")))
+            .unwrap()
+            .page(&entries, 4);
+        assert_eq!(keys(page), vec!["bc/1", "c"]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn directory() {
+        let entries = entries();
+        let dir = format!("base64:{}/", encode(b"b"));
+        let (page, next) = RangeQuery::parse(&dir, &request(None))
+            .unwrap()
+            .page(&entries, 2);
+        assert_eq!(keys(page), vec!["b/1", "b/2"]);
+        assert_eq!(next, Some(encode(b"b/3")));
+
+        let (page, next) = RangeQuery::parse(&dir, &request(Some(b"b/3")))
+            .unwrap()
+            .page(&entries, 2);
+        assert_eq!(keys(page), vec!["b/3"]);
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(RangeQuery::parse("base64:b", &request(None)).is_err());
+        assert!(RangeQuery::parse("b/", &request(None)).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! The `/v1/restrict` API, which restricts the regions and providers the directories of the other
+//! coordination APIs may be stored in
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value};
+
+use crate::{
+    http::{decode_prefixed, encode, Method, Request, Response, StatusCode},
+    range::RangeQuery,
+};
+
+/// The APIs which support restrictions, in the order they are paged through
+const APIS: &[&str] = &["config", "locks"];
+
+/// The restriction details of each API's directories
+#[derive(Debug, Default)]
+pub(crate) struct Restrictions {
+    by_api: BTreeMap<&'static str, BTreeMap<Vec<u8>, Value>>,
+}
+
+impl Restrictions {
+    /// Handles a request whose path is `path` after `/v1/restrict/`
+    pub(crate) fn handle(&mut self, req: &Request, path: &str, page_size: usize) -> Response {
+        if path.is_empty() {
+            return match req.method {
+                Method::GET => self.get_all_page(req, page_size),
+                _ => Response::method_not_allowed(&req.method),
+            };
+        }
+
+        let (api, dir) = path.split_once('/').unwrap_or((path, ""));
+        let api = match APIS.iter().find(|a| a.eq_ignore_ascii_case(api)) {
+            Some(api) => *api,
+            None => return Response::not_found(format!("unknown API {api}")),
+        };
+        if dir.is_empty() {
+            return match req.method {
+                Method::GET => self.get_api_page(req, api, page_size),
+                _ => Response::method_not_allowed(&req.method),
+            };
+        }
+
+        let dir = match dir.strip_suffix('/').and_then(decode_prefixed) {
+            Some(dir) => dir,
+            None => return Response::bad_request(format!("invalid directory {dir}")),
+        };
+        let restrictions = self.by_api.entry(api).or_default();
+        match req.method {
+            Method::GET => match restrictions.get(&dir) {
+                Some(details) => Response::json(StatusCode::OK, &restriction(api, &dir, details)),
+                None => Response::not_found("restriction not found"),
+            },
+            Method::PUT => match parse_details(&req.body) {
+                Ok(details) => {
+                    restrictions.insert(dir, details);
+                    Response::ok()
+                }
+                Err(resp) => resp,
+            },
+            Method::DELETE => match restrictions.remove(&dir) {
+                Some(_) => Response::ok(),
+                None => Response::not_found("restriction not found"),
+            },
+            _ => Response::method_not_allowed(&req.method),
+        }
+    }
+
+    // A page of the restrictions of a single API
+    fn get_api_page(&self, req: &Request, api: &'static str, page_size: usize) -> Response {
+        let range = match RangeQuery::parse("", req) {
+            Ok(range) => range,
+            Err(resp) => return resp,
+        };
+        let empty = BTreeMap::new();
+        let (page, next_key) = range.page(self.by_api.get(api).unwrap_or(&empty), page_size);
+        let restrictions: Vec<_> = page
+            .into_iter()
+            .map(|(dir, details)| restriction(api, dir, details))
+            .collect();
+        Response::json(
+            StatusCode::OK,
+            &json!({
+                "next_api": next_key.as_ref().map(|_| api),
+                "next_key": next_key,
+                "restrictions": restrictions,
+            }),
+        )
+    }
+
+    // A page of the restrictions of all APIs, beginning at the `from` key of `from_api`
+    fn get_all_page(&self, req: &Request, page_size: usize) -> Response {
+        let from_api = match req.query("from_api") {
+            None => APIS[0],
+            Some(from_api) => match APIS.iter().find(|a| a.eq_ignore_ascii_case(from_api)) {
+                Some(api) => *api,
+                None => return Response::bad_request(format!("unknown API {from_api}")),
+            },
+        };
+        let from = match req.query("from").map(decode_prefixed) {
+            None => Vec::new(),
+            Some(Some(from)) => from,
+            Some(None) => return Response::bad_request("invalid from key"),
+        };
+
+        let from = &from;
+        let mut entries = APIS.iter().skip_while(|a| **a != from_api).flat_map(|api| {
+            let restrictions = self.by_api.get(api).into_iter().flatten();
+            restrictions.filter_map(move |(dir, details)| {
+                if *api == from_api && dir < from {
+                    None
+                } else {
+                    Some((*api, dir, details))
+                }
+            })
+        });
+        let restrictions: Vec<_> = entries
+            .by_ref()
+            .take(page_size)
+            .map(|(api, dir, details)| restriction(api, dir, details))
+            .collect();
+        let (next_api, next_key) = match entries.next() {
+            Some((api, dir, _)) => (Some(api), Some(encode(dir))),
+            None => (None, None),
+        };
+        Response::json(
+            StatusCode::OK,
+            &json!({"next_api": next_api, "next_key": next_key, "restrictions": restrictions}),
+        )
+    }
+}
+
+// Parses the restriction details of a PUT, rejecting any which both allow and deny something
+fn parse_details(body: &[u8]) -> Result<Value, Response> {
+    let mut details: Value = serde_json::from_slice(body)
+        .map_err(|e| Response::bad_request(format!("invalid restriction details: {e}")))?;
+    let details_map = details
+        .as_object_mut()
+        .ok_or_else(|| Response::bad_request("restriction details must be an object"))?;
+    for field in ["regions_allowed", "regions_denied", "providers_allowed", "providers_denied"] {
+        details_map.entry(field).or_insert_with(|| json!([]));
+    }
+    for (allowed, denied) in
+        [("regions_allowed", "regions_denied"), ("providers_allowed", "providers_denied")]
+    {
+        let conflicting = details[allowed]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|a| details[denied].as_array().map_or(false, |d| d.contains(a)));
+        if conflicting {
+            return Err(Response::bad_request(format!("{allowed} conflicts with {denied}")));
+        }
+    }
+    Ok(details)
+}
+
+fn restriction(api: &str, dir: &[u8], details: &Value) -> Value {
+    json!({"api": api, "directory": encode(dir), "details": details, "state": "enforced"})
+}
@@ -0,0 +1,300 @@
+//! The HTTP server which hosts the mock APIs on a background thread
+
+use std::{
+    convert::Infallible,
+    net::{SocketAddr, TcpListener},
+    sync::{Arc, Mutex, MutexGuard},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
+};
+
+use hyper::{
+    body,
+    header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Server,
+};
+use tokio::sync::oneshot;
+
+use crate::{
+    compute::Formations,
+    http::{Request, Response, StatusCode},
+    identity::Identity,
+    locks::Locks,
+    metadata::Metadata,
+    restrict::Restrictions,
+};
+
+/// A builder struct for configuring and starting a [`MockServer`]
+#[derive(Debug)]
+pub struct MockServerBuilder {
+    identity: Identity,
+    page_size: usize,
+}
+
+impl Default for MockServerBuilder {
+    fn default() -> Self { Self { identity: Identity::new(), page_size: 100 } }
+}
+
+impl MockServerBuilder {
+    /// Create a new `Default` builder
+    pub fn new() -> Self { Self::default() }
+
+    /// Only issue access tokens for this API key. May be called multiple times to accept several
+    /// keys. (default: any key is accepted)
+    #[must_use]
+    pub fn api_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.identity.api_keys.insert(key.into());
+        self
+    }
+
+    /// Accept this access token for all requests without it having been issued by `/v1/token`.
+    /// Such tokens never expire. May be called multiple times to accept several tokens.
+    #[must_use]
+    pub fn token<S: Into<String>>(mut self, token: S) -> Self {
+        self.identity.static_tokens.insert(token.into());
+        self
+    }
+
+    /// How long issued access tokens are valid for (default: 60 seconds)
+    #[must_use]
+    pub fn token_ttl(mut self, ttl: Duration) -> Self {
+        self.identity.token_ttl = ttl;
+        self
+    }
+
+    /// The tenant returned along with access tokens (default: `tnt-mock`)
+    #[must_use]
+    pub fn tenant<S: Into<String>>(mut self, tenant: S) -> Self {
+        self.identity.tenant = tenant.into();
+        self
+    }
+
+    /// The subdomain returned along with access tokens and used in Formation URLs (default:
+    /// `mock`)
+    #[must_use]
+    pub fn subdomain<S: Into<String>>(mut self, subdomain: S) -> Self {
+        self.identity.subdomain = subdomain.into();
+        self
+    }
+
+    /// The maximum number of entries in each page of a range or list request (default: 100)
+    ///
+    /// # Panics
+    ///
+    /// If `size` is `0`
+    #[must_use]
+    pub fn page_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "page size must be at least 1");
+        self.page_size = size;
+        self
+    }
+
+    /// Start the server on a random local port, where it runs until the returned [`MockServer`]
+    /// is dropped
+    ///
+    /// # Panics
+    ///
+    /// If no local port could be bound
+    pub fn start(self) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("failed to bind a local port");
+        listener
+            .set_nonblocking(true)
+            .expect("failed to configure the listener");
+        let addr = listener
+            .local_addr()
+            .expect("failed to read the local address");
+
+        let state = Arc::new(Mutex::new(State {
+            origin: format!("http://{addr}"),
+            page_size: self.page_size,
+            offset: Duration::ZERO,
+            identity: self.identity,
+            formations: Formations::default(),
+            metadata: Metadata::default(),
+            locks: Locks::default(),
+            restrictions: Restrictions::default(),
+        }));
+
+        let (shutdown, rx) = oneshot::channel::<()>();
+        let service_state = Arc::clone(&state);
+        let thread = thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("failed to build the server runtime");
+            rt.block_on(async move {
+                let make_service = make_service_fn(move |conn: &AddrStream| {
+                    let ip = conn.remote_addr().ip();
+                    let state = Arc::clone(&service_state);
+                    async move {
+                        Ok::<_, Infallible>(service_fn(move |req| {
+                            serve(Arc::clone(&state), ip, req)
+                        }))
+                    }
+                });
+                let server = Server::from_tcp(listener)
+                    .expect("failed to serve on the bound port")
+                    .serve(make_service);
+                // Rather than waiting for clients to close their idle connections, any which are
+                // still open are dropped along with the runtime
+                tokio::select! {
+                    _ = server => {}
+                    _ = rx => {}
+                }
+            });
+        });
+
+        MockServer { addr, state, shutdown: Some(shutdown), thread: Some(thread) }
+    }
+}
+
+/// A stateful stand-in for the Seaplane APIs which runs in the background of the current process.
+///
+/// The Identity (`/v1/token`), Compute (`/v2beta/formations`), Metadata (`/v1/config`), Locks
+/// (`/v1/locks`) and Restrict (`/v1/restrict`) APIs are all served from the same address, so the
+/// same URL can be used for each of them. Everything is stored in memory and lives as long as the
+/// server.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Create a new server builder
+    pub fn builder() -> MockServerBuilder { MockServerBuilder::new() }
+
+    /// Start a server with the default configuration, see [`MockServerBuilder::start`]
+    pub fn start() -> Self { Self::builder().start() }
+
+    /// The address the server is listening on
+    pub fn address(&self) -> SocketAddr { self.addr }
+
+    /// The URL of the server, such as `http://127.0.0.1:5000`
+    pub fn base_url(&self) -> String { format!("http://{}", self.addr) }
+
+    /// The URL of `path` on the server, such as `http://127.0.0.1:5000/v1/config/`
+    pub fn url<S: AsRef<str>>(&self, path: S) -> String {
+        format!("http://{}{}", self.addr, path.as_ref())
+    }
+
+    /// Moves the clock of the server forward, for example to let locks or access tokens expire
+    /// without waiting for them
+    pub fn advance(&self, by: Duration) { self.state().offset += by; }
+
+    /// Removes everything stored by the server and forgets all issued access tokens, keeping its
+    /// configuration
+    pub fn reset(&self) {
+        let mut state = self.state();
+        state.identity.clear();
+        state.formations = Formations::default();
+        state.metadata = Metadata::default();
+        state.locks = Locks::default();
+        state.restrictions = Restrictions::default();
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panic while handling one request shouldn't take down the server for all the others
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            _ = shutdown.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+// Everything the server knows, shared by all connections
+#[derive(Debug)]
+struct State {
+    // The scheme and authority of the server, used to build links
+    origin: String,
+    page_size: usize,
+    // How far the server's clock has been moved ahead of the real one
+    offset: Duration,
+    identity: Identity,
+    formations: Formations,
+    metadata: Metadata,
+    locks: Locks,
+    restrictions: Restrictions,
+}
+
+impl State {
+    fn handle(&mut self, req: &Request) -> Response {
+        let now = Instant::now() + self.offset;
+        let wall_now = SystemTime::now() + self.offset;
+
+        let path = req.path.as_str();
+        if path == "/v1/token" {
+            return self.identity.handle(wall_now, req);
+        }
+        if !self.identity.is_authorized(wall_now, req) {
+            return Response::problem(StatusCode::UNAUTHORIZED, "missing or expired access token");
+        }
+
+        if let Some(rest) = path.strip_prefix("/v2beta/formations") {
+            self.formations.handle(
+                req,
+                rest,
+                &self.origin,
+                &self.identity.subdomain,
+                self.page_size,
+            )
+        } else if let Some(rest) = path.strip_prefix("/v1/config/") {
+            self.metadata.handle(req, rest, self.page_size)
+        } else if let Some(rest) = path.strip_prefix("/v1/locks/") {
+            self.locks.handle(now, req, rest, self.page_size)
+        } else if let Some(rest) = path.strip_prefix("/v1/restrict/") {
+            self.restrictions.handle(req, rest, self.page_size)
+        } else {
+            Response::not_found(format!("no API at {path}"))
+        }
+    }
+}
+
+// Converts between hyper and the mock APIs
+async fn serve(
+    state: Arc<Mutex<State>>,
+    ip: std::net::IpAddr,
+    req: hyper::Request<Body>,
+) -> Result<hyper::Response<Body>, Infallible> {
+    let (parts, body) = req.into_parts();
+    let header = |name| {
+        parts
+            .headers
+            .get(name)
+            .and_then(|v: &hyper::header::HeaderValue| v.to_str().ok())
+            .map(String::from)
+    };
+    let req = Request {
+        method: parts.method.clone(),
+        path: parts.uri.path().to_string(),
+        query: url::form_urlencoded::parse(parts.uri.query().unwrap_or_default().as_bytes())
+            .into_owned()
+            .collect(),
+        authorization: header(AUTHORIZATION),
+        accept: header(ACCEPT),
+        ip,
+        body: body::to_bytes(body)
+            .await
+            .map(|b| b.to_vec())
+            .unwrap_or_default(),
+    };
+
+    let resp = state.lock().unwrap_or_else(|e| e.into_inner()).handle(&req);
+    Ok(hyper::Response::builder()
+        .status(resp.status)
+        .header(CONTENT_TYPE, resp.content_type)
+        .body(Body::from(resp.body))
+        .expect("responses are always valid"))
+}
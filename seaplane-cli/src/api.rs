@@ -10,9 +10,49 @@ pub use locks::LocksReq;
 pub use metadata::MetadataReq;
 use reqwest::Url;
 pub use restrict::RestrictReq;
-use seaplane::api::identity::v1::{AccessToken, TokenRequest};
+use seaplane::api::{
+    identity::v1::{AccessToken, TokenRequest},
+    SeaplaneClient,
+};
 
-use crate::error::{CliError, Context, Result};
+use crate::{
+    context::Ctx,
+    error::{CliError, Context, Result},
+};
+
+/// Builds a `SeaplaneClient` using the API key, endpoints and TLS options of the context. All
+/// requests made with the client share one connection pool and access token, which is refreshed as
+/// needed.
+pub fn build_client(ctx: &Ctx) -> Result<SeaplaneClient> {
+    let mut builder = SeaplaneClient::builder().api_key(ctx.args.api_key()?);
+
+    #[cfg(feature = "allow_insecure_urls")]
+    {
+        builder = builder.allow_http(ctx.insecure_urls);
+    }
+    #[cfg(feature = "allow_invalid_certs")]
+    {
+        builder = builder.allow_invalid_certs(ctx.invalid_certs);
+    }
+    if let Some(url) = &ctx.identity_url {
+        builder = builder.identity_url(url.clone());
+    }
+    if let Some(url) = &ctx.compute_url {
+        builder = builder.compute_url(url.clone());
+    }
+    if let Some(url) = &ctx.metadata_url {
+        // Restrictions are served alongside Metadata
+        builder = builder.metadata_url(url.clone()).restrict_url(url.clone());
+    }
+    if let Some(url) = &ctx.locks_url {
+        builder = builder.locks_url(url.clone());
+    }
+
+    builder
+        .build()
+        .map_err(CliError::from)
+        .context("Context: failed to build API client\n")
+}
 
 /// Follows the same process as `request_token` but only returns the raw JWT string part of the
 /// token
@@ -1,56 +1,36 @@
-use reqwest::Url;
-use seaplane::{
-    api::{
-        identity::v1::AccessToken,
-        metadata::v1::{
-            Key, KeyValue as KeyValueModel, KeyValueRange as KeyValueRangeModel, MetadataRequest,
-            Value as ValueModel,
-        },
-        shared::v1::RangeQueryContext,
+use std::time::Duration;
+
+use seaplane::api::{
+    metadata::v1::{
+        DeleteReport, Key, KeyValue as KeyValueModel, KeyValueRange as KeyValueRangeModel,
+        MetadataRequest, MetadataWatcher, Value as ValueModel,
     },
-    error::SeaplaneError,
+    shared::v1::RangeQueryContext,
+    SeaplaneClient,
 };
 
 use crate::{
-    api::request_token,
+    api::build_client,
     context::Ctx,
     error::{CliError, Result},
 };
 
-/// Wraps an SDK `MetadataRequest` where we do additional things like re-use request access
-/// tokens, allow changing the Formation this request is pointed to, and map errors appropriately.
+/// Wraps an SDK `MetadataRequest` where we do additional things like allow changing the key this
+/// request is pointed to, and map errors appropriately.
 #[derive(Debug)]
 pub struct MetadataReq {
-    api_key: String,
+    client: SeaplaneClient,
     key: Option<String>,
     range: Option<RangeQueryContext<Key>>,
-    token: Option<AccessToken>,
     inner: Option<MetadataRequest>,
-    identity_url: Option<Url>,
-    metadata_url: Option<Url>,
-    insecure_urls: bool,
-    invalid_certs: bool,
 }
 
 impl MetadataReq {
-    pub fn new(ctx: &Ctx) -> Result<Self> {
-        Ok(Self {
-            api_key: ctx.args.api_key()?.into(),
-            key: None,
-            range: None,
-            token: None,
-            inner: None,
-            identity_url: ctx.identity_url.clone(),
-            metadata_url: ctx.metadata_url.clone(),
-            #[cfg(feature = "allow_insecure_urls")]
-            insecure_urls: ctx.insecure_urls,
-            #[cfg(not(feature = "allow_insecure_urls"))]
-            insecure_urls: false,
-            #[cfg(feature = "allow_invalid_certs")]
-            invalid_certs: ctx.invalid_certs,
-            #[cfg(not(feature = "allow_invalid_certs"))]
-            invalid_certs: false,
-        })
+    pub fn new(ctx: &Ctx) -> Result<Self> { Ok(Self::from_client(build_client(ctx)?)) }
+
+    /// Builds a MetadataRequest sharing the client of another request
+    pub fn from_client(client: SeaplaneClient) -> Self {
+        Self { client, key: None, range: None, inner: None }
     }
 
     pub fn set_key<S: Into<String>>(&mut self, key: S) -> Result<()> {
@@ -65,34 +45,10 @@ impl MetadataReq {
         self.refresh_inner()
     }
 
-    /// Request a new Access Token
-    pub fn refresh_token(&mut self) -> Result<()> {
-        self.token = Some(request_token(
-            &self.api_key,
-            self.identity_url.as_ref(),
-            self.insecure_urls,
-            self.invalid_certs,
-        )?);
-        Ok(())
-    }
-
     /// Re-build the inner `MetadataRequest`. This is mostly useful when one wants to point at
-    /// different Metadata than the original request was pointed at. This method will also refresh
-    /// the access token, only if required.
+    /// different Metadata than the original request was pointed at.
     fn refresh_inner(&mut self) -> Result<()> {
-        let mut builder = MetadataRequest::builder().token(self.token_or_refresh()?);
-
-        #[cfg(feature = "allow_insecure_urls")]
-        {
-            builder = builder.allow_http(self.insecure_urls);
-        }
-        #[cfg(feature = "allow_invalid_certs")]
-        {
-            builder = builder.allow_invalid_certs(self.invalid_certs);
-        }
-        if let Some(url) = &self.metadata_url {
-            builder = builder.base_url(url);
-        }
+        let mut builder = self.client.metadata();
 
         if let Some(key) = &self.key {
             builder = builder.encoded_key(key);
@@ -106,27 +62,46 @@ impl MetadataReq {
         Ok(())
     }
 
-    /// Retrieves the JWT access token, requesting a new one if required.
-    pub fn token_or_refresh(&mut self) -> Result<&str> {
-        if self.token.is_none() {
-            self.refresh_token()?;
+    /// Watches the key or directory this request is pointed at, checking it every `interval`
+    pub fn into_watcher(mut self, interval: Duration) -> Result<MetadataWatcher> {
+        if self.inner.is_none() {
+            self.refresh_inner()?;
         }
-        Ok(&self.token.as_ref().unwrap().token)
+        MetadataWatcher::new(self.inner.take().unwrap(), interval).map_err(CliError::from)
     }
 }
 
-// Wrapped MetadataRequest methods to handle expired token retries
+// Wrapped MetadataRequest methods to map errors
 impl MetadataReq {
-    pub fn get_value(&mut self) -> Result<ValueModel> { maybe_retry!(self.get_value()) }
+    pub fn get_value(&mut self) -> Result<ValueModel> { call_inner!(self.get_value()) }
+    pub fn get_key_value(&mut self) -> Result<KeyValueModel> { call_inner!(self.get_key_value()) }
     pub fn put_value_unencoded<S: AsRef<[u8]>>(&mut self, value: S) -> Result<()> {
-        maybe_retry!(self.put_value_unencoded(value.as_ref()))
+        call_inner!(self.put_value_unencoded(value.as_ref()))
     }
     pub fn put_value(&mut self, value: ValueModel) -> Result<()> {
-        maybe_retry_cloned!(self.put_value(value))
+        call_inner!(self.put_value(value))
+    }
+    pub fn put_value_if(&mut self, value: ValueModel, version: u64) -> Result<()> {
+        call_inner!(self.put_value_if(value, version))
+    }
+    pub fn put_value_if_absent(&mut self, value: ValueModel) -> Result<()> {
+        call_inner!(self.put_value_if_absent(value))
+    }
+    pub fn delete_value(&mut self) -> Result<()> { call_inner!(self.delete_value()) }
+    pub fn delete_value_if(&mut self, version: u64) -> Result<()> {
+        call_inner!(self.delete_value_if(version))
+    }
+    pub fn delete_keys(&mut self, keys: Vec<Key>) -> Result<DeleteReport> {
+        if self.inner.is_none() {
+            self.refresh_inner()?;
+        }
+        Ok(self.inner.as_ref().unwrap().delete_keys(keys))
+    }
+    pub fn delete_directory(&mut self) -> Result<DeleteReport> {
+        call_inner!(self.delete_directory())
     }
-    pub fn delete_value(&mut self) -> Result<()> { maybe_retry!(self.delete_value()) }
-    pub fn get_page(&mut self) -> Result<KeyValueRangeModel> { maybe_retry!(self.get_page()) }
+    pub fn get_page(&mut self) -> Result<KeyValueRangeModel> { call_inner!(self.get_page()) }
     pub fn get_all_pages(&mut self) -> Result<Vec<KeyValueModel>> {
-        maybe_retry_cloned!(self.get_all_pages())
+        call_inner!(self.get_all_pages())
     }
 }
@@ -1,58 +1,38 @@
-use reqwest::Url;
-use seaplane::{
-    api::{
-        identity::v1::AccessToken,
-        restrict::v1::{
-            RestrictRequest, RestrictedDirectory, Restriction, RestrictionDetails, RestrictionRange,
-        },
-        shared::v1::RangeQueryContext,
+use seaplane::api::{
+    restrict::v1::{
+        RestrictRequest, RestrictedDirectory, Restriction, RestrictionDetails, RestrictionRange,
     },
-    error::SeaplaneError,
+    shared::v1::RangeQueryContext,
+    SeaplaneClient,
 };
 
 use crate::{
-    api::request_token,
+    api::build_client,
     context::Ctx,
     error::{CliError, Result},
 };
 
-/// Wraps an SDK `RestrictRequest` where we do additional things like re-use
-/// request access tokens, and map errors appropriately.
+/// Wraps an SDK `RestrictRequest` where we do additional things like allow changing the
+/// Restriction this request is pointed to, and map errors appropriately.
 #[derive(Debug)]
 pub struct RestrictReq {
-    api_key: String,
+    client: SeaplaneClient,
     api: Option<String>,
     directory: Option<String>,
     from_api: Option<String>,
     from_dir: Option<String>,
-    token: Option<AccessToken>,
     inner: Option<RestrictRequest>,
-    identity_url: Option<Url>,
-    metadata_url: Option<Url>,
-    insecure_urls: bool,
-    invalid_certs: bool,
 }
 
 impl RestrictReq {
     pub fn new(ctx: &Ctx) -> Result<Self> {
         Ok(Self {
-            api_key: ctx.args.api_key()?.into(),
+            client: build_client(ctx)?,
             api: None,
             directory: None,
             from_api: None,
             from_dir: None,
-            token: None,
             inner: None,
-            identity_url: ctx.identity_url.clone(),
-            metadata_url: ctx.metadata_url.clone(),
-            #[cfg(feature = "allow_insecure_urls")]
-            insecure_urls: ctx.insecure_urls,
-            #[cfg(not(feature = "allow_insecure_urls"))]
-            insecure_urls: false,
-            #[cfg(feature = "allow_invalid_certs")]
-            invalid_certs: ctx.invalid_certs,
-            #[cfg(not(feature = "allow_invalid_certs"))]
-            invalid_certs: false,
         })
     }
 
@@ -76,36 +56,11 @@ impl RestrictReq {
         self.refresh_inner()
     }
 
-    /// Request a new Access Token
-    pub fn refresh_token(&mut self) -> Result<()> {
-        self.token = Some(request_token(
-            &self.api_key,
-            self.identity_url.as_ref(),
-            self.insecure_urls,
-            self.invalid_certs,
-        )?);
-        Ok(())
-    }
-
     /// Re-build the inner `RestrictRequest`. This is mostly useful when one
     /// wants to point at different Restriction than the original request was
-    /// pointed at. This method will also refresh the access token, only if
-    /// required.
+    /// pointed at.
     fn refresh_inner(&mut self) -> Result<()> {
-        let mut builder = RestrictRequest::builder().token(self.token_or_refresh()?);
-
-        #[cfg(feature = "allow_insecure_urls")]
-        {
-            builder = builder.allow_http(self.insecure_urls);
-        }
-        #[cfg(feature = "allow_invalid_certs")]
-        {
-            builder = builder.allow_invalid_certs(self.invalid_certs);
-        }
-
-        if let Some(url) = &self.metadata_url {
-            builder = builder.base_url(url);
-        }
+        let mut builder = self.client.restrict();
 
         match [&self.api, &self.directory] {
             [Some(api), Some(directory)] => builder = builder.single_restriction(api, directory),
@@ -129,29 +84,19 @@ impl RestrictReq {
         self.inner = Some(builder.build().map_err(CliError::from)?);
         Ok(())
     }
-
-    /// Retrieves the JWT access token, requesting a new one if required.
-    pub fn token_or_refresh(&mut self) -> Result<&str> {
-        if self.token.is_none() {
-            self.refresh_token()?;
-        }
-        Ok(&self.token.as_ref().unwrap().token)
-    }
 }
 
-// Wrapped RestrictRequest methods to handle expired token retries
+// Wrapped RestrictRequest methods to map errors
 impl RestrictReq {
-    pub fn get_restriction(&mut self) -> Result<Restriction> {
-        maybe_retry!(self.get_restriction())
-    }
+    pub fn get_restriction(&mut self) -> Result<Restriction> { call_inner!(self.get_restriction()) }
 
     pub fn set_restriction(&mut self, details: RestrictionDetails) -> Result<()> {
-        maybe_retry_cloned!(self.set_restriction(details))
+        call_inner!(self.set_restriction(details))
     }
-    pub fn delete_restriction(&mut self) -> Result<()> { maybe_retry!(self.delete_restriction()) }
+    pub fn delete_restriction(&mut self) -> Result<()> { call_inner!(self.delete_restriction()) }
 
-    pub fn get_page(&mut self) -> Result<RestrictionRange> { maybe_retry!(self.get_page()) }
+    pub fn get_page(&mut self) -> Result<RestrictionRange> { call_inner!(self.get_page()) }
     pub fn get_all_pages(&mut self) -> Result<Vec<Restriction>> {
-        maybe_retry!(self.get_all_pages())
+        call_inner!(self.get_all_pages())
     }
 }
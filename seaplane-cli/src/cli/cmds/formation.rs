@@ -1,22 +1,28 @@
+mod apply;
 pub mod common;
 mod delete;
+mod diff;
+mod edit;
 mod fetch;
 mod land;
 mod launch;
 mod list;
 mod plan;
+mod rollback;
+mod rollout;
 mod status;
-#[cfg(feature = "unstable")]
 mod template;
+mod update;
 
 use clap::{ArgMatches, Command};
 
-#[cfg(feature = "unstable")]
-use self::template::SeaplaneFormationTemplate;
 pub use self::{
-    delete::SeaplaneFormationDelete, fetch::SeaplaneFormationFetch, land::SeaplaneFormationLand,
+    apply::SeaplaneFormationApply, delete::SeaplaneFormationDelete, diff::SeaplaneFormationDiff,
+    edit::SeaplaneFormationEdit, fetch::SeaplaneFormationFetch, land::SeaplaneFormationLand,
     launch::SeaplaneFormationLaunch, list::SeaplaneFormationList, plan::SeaplaneFormationPlan,
-    status::SeaplaneFormationStatus,
+    rollback::SeaplaneFormationRollback, rollout::SeaplaneFormationRollout,
+    status::SeaplaneFormationStatus, template::SeaplaneFormationTemplate,
+    update::SeaplaneFormationUpdate,
 };
 use crate::{cli::CliCommand, error::Result, Ctx};
 
@@ -25,27 +31,26 @@ pub struct SeaplaneFormation;
 
 impl SeaplaneFormation {
     pub fn command() -> Command {
-        #[cfg_attr(not(feature = "unstable"), allow(unused_mut))]
-        let mut app = Command::new("formation")
+        Command::new("formation")
             .about(
                 "Operate on local Formations Plans and remote Formation Instances of those Plans",
             )
             .subcommand_required(true)
             .arg_required_else_help(true)
             .subcommand(SeaplaneFormationPlan::command())
+            .subcommand(SeaplaneFormationApply::command())
             .subcommand(SeaplaneFormationDelete::command())
+            .subcommand(SeaplaneFormationDiff::command())
+            .subcommand(SeaplaneFormationEdit::command())
             .subcommand(SeaplaneFormationFetch::command())
             .subcommand(SeaplaneFormationLand::command())
             .subcommand(SeaplaneFormationLaunch::command())
             .subcommand(SeaplaneFormationList::command())
-            .subcommand(SeaplaneFormationStatus::command());
-
-        #[cfg(feature = "unstable")]
-        {
-            app = app.subcommand(SeaplaneFormationTemplate::command());
-        }
-
-        app
+            .subcommand(SeaplaneFormationRollback::command())
+            .subcommand(SeaplaneFormationRollout::command())
+            .subcommand(SeaplaneFormationStatus::command())
+            .subcommand(SeaplaneFormationTemplate::command())
+            .subcommand(SeaplaneFormationUpdate::command())
     }
 }
 
@@ -56,14 +61,19 @@ impl CliCommand for SeaplaneFormation {
     ) -> Option<(Box<dyn CliCommand>, &'a ArgMatches)> {
         match &matches.subcommand() {
             Some(("plan", m)) => Some((Box::new(SeaplaneFormationPlan), m)),
+            Some(("apply", m)) => Some((Box::new(SeaplaneFormationApply), m)),
             Some(("delete", m)) => Some((Box::new(SeaplaneFormationDelete), m)),
+            Some(("diff", m)) => Some((Box::new(SeaplaneFormationDiff), m)),
+            Some(("edit", m)) => Some((Box::new(SeaplaneFormationEdit), m)),
             Some(("fetch-remote", m)) => Some((Box::new(SeaplaneFormationFetch), m)),
             Some(("land", m)) => Some((Box::new(SeaplaneFormationLand), m)),
             Some(("launch", m)) => Some((Box::new(SeaplaneFormationLaunch), m)),
             Some(("list", m)) => Some((Box::new(SeaplaneFormationList), m)),
+            Some(("rollback", m)) => Some((Box::new(SeaplaneFormationRollback), m)),
+            Some(("rollout", m)) => Some((Box::new(SeaplaneFormationRollout), m)),
             Some(("status", m)) => Some((Box::new(SeaplaneFormationStatus), m)),
-            #[cfg(feature = "unstable")]
             Some(("template", m)) => Some((Box::new(SeaplaneFormationTemplate), m)),
+            Some(("update", m)) => Some((Box::new(SeaplaneFormationUpdate), m)),
             _ => None,
         }
     }
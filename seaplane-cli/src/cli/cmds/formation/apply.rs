@@ -0,0 +1,189 @@
+use clap::{ArgMatches, Command};
+use seaplane::{api::compute::v2::Formation as FormationModel, error::SeaplaneError};
+
+use crate::{
+    api::FormationsReq,
+    cli::{cmds::formation::common, CliCommand},
+    context::Ctx,
+    error::{CliErrorKind, Result},
+    ops::{
+        flight::check_secrets,
+        formation::{
+            diff::FormationDiff, document::DocumentFormat, FlightChanges, Formation,
+            FormationNameId,
+        },
+    },
+    printer::Pb,
+};
+
+static LONG_ABOUT: &str = "Create or update a remote Formation Instance from a Formation document
+
+A Formation document describes a whole Formation, its name, gateway Flight and all of its Flights,
+in TOML, JSON or YAML. For example in TOML:
+
+    name = \"stubb\"
+    gateway-flight = \"flask\"
+
+    [[flights]]
+    name = \"flask\"
+    image = \"registry.cplane.cloud/seaplane-demo/flask:latest\"
+
+    [[flights]]
+    name = \"pequod\"
+    image = \"registry.cplane.cloud/seaplane-demo/pequod:latest\"
+    minimum = 2
+
+Each Flight takes the same keys as a Flight given to 'seaplane formation plan' as JSON, but must be
+named.
+
+The document is compared to the remote Formation Instance with the same name. A new Instance is
+launched if there is none, the Instance is updated if it differs from the document, and otherwise
+it is left untouched. The local Formation Plan with the same name is then replaced by the document.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneFormationApply;
+
+impl SeaplaneFormationApply {
+    pub fn command() -> Command {
+        Command::new("apply")
+            .about("Create or update a remote Formation Instance to match a Formation document")
+            .long_about(LONG_ABOUT)
+            .arg(
+                common::file()
+                    .required(true)
+                    .help("The Formation document to apply, or '-' to read it from STDIN"),
+            )
+            .arg(common::file_format())
+            .arg(arg!(--("dry-run")).help(
+                "Only display what would be changed, without changing the remote Formation Instance or local Formation Plan",
+            ))
+    }
+}
+
+impl CliCommand for SeaplaneFormationApply {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        common::read_document(ctx)?;
+        let mut model = ctx.formation_ctx.get_or_init().model()?;
+        check_secrets(ctx, &model)?;
+        let name = model.name.clone();
+
+        let pb = Pb::new(ctx);
+        pb.set_message(format!("Comparing Formation {name} to its remote Instance..."));
+        let mut req = FormationsReq::new(ctx)?;
+        let remote = remote_instance(ctx, &mut req, &name)?;
+
+        // No verb is printed for an Instance which already matches the document
+        let (verb, resp, changes) = match remote {
+            None => {
+                let changes = FlightChanges {
+                    added: model.flights.iter().map(|f| f.name.clone()).collect(),
+                    ..Default::default()
+                };
+                if ctx.args.dry_run {
+                    (Some("Would launch"), None, changes)
+                } else {
+                    pb.set_message(format!("Launching Formation {name}..."));
+                    (Some("Successfully Launched"), Some(req.create(&model)?), changes)
+                }
+            }
+            Some(mut remote) => {
+                remote.restore_secrets(&model);
+                // The URL is assigned by the API, so a document never differs from it
+                model.url = remote.url.clone();
+                let diff = FormationDiff::between(&model, &remote);
+                if diff.is_empty() {
+                    (None, Some(remote), FlightChanges::default())
+                } else if ctx.args.dry_run {
+                    (Some("Would update"), Some(remote), FlightChanges::from(&diff))
+                } else {
+                    pb.set_message(format!("Updating Formation {name}..."));
+                    // the ID of the remote Instance was set when it was found
+                    let resp = req.update(&model)?;
+                    ctx.db.formation_history.record(&remote);
+                    (Some("Successfully Updated"), Some(resp), FlightChanges::from(&diff))
+                }
+            }
+        };
+
+        pb.finish_and_clear();
+        if let Some(verb) = verb {
+            cli_print!("{verb} remote Formation Instance ");
+            cli_print!(@Green, "{name}");
+            if let Some(oid) = resp.as_ref().and_then(|f| f.oid) {
+                cli_print!(" (");
+                cli_print!(@Green, "{oid}");
+                cli_print!(")");
+            }
+            cli_println!("");
+            changes.print();
+        } else {
+            cli_print!("Remote Formation Instance ");
+            cli_print!(@Green, "{name}");
+            cli_println!(" is already up to date");
+        }
+
+        if !ctx.args.dry_run {
+            // either the API responded with the changed Instance, or it already matched
+            let resp = resp.unwrap();
+            if ctx
+                .db
+                .formations
+                .index_of(&FormationNameId::Name(name))
+                .is_some()
+            {
+                ctx.db.formations.create_or_update(resp);
+            } else {
+                ctx.db
+                    .formations
+                    .add(Formation { model: resp, local: true, deployed: true });
+            }
+            ctx.persist_state()?;
+        }
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        ctx.args.dry_run = matches.get_flag("dry-run");
+
+        let fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.file = matches.get_one::<String>("file").cloned();
+        fctx.file_format = matches.get_one::<DocumentFormat>("file-format").copied();
+
+        Ok(())
+    }
+}
+
+/// The remote Instance of the Formation named `name`, if there is one. It is looked up by the ID
+/// in the local DB when possible, and otherwise by searching all remote Formation Instances.
+fn remote_instance(
+    ctx: &Ctx,
+    req: &mut FormationsReq,
+    name: &str,
+) -> Result<Option<FormationModel>> {
+    let local_oid = ctx
+        .db
+        .formations
+        .iter()
+        .find(|f| f.model.name == name)
+        .and_then(|f| f.model.oid);
+    if let Some(oid) = local_oid {
+        req.set_id(oid)?;
+        match req.get() {
+            Ok(remote) if remote.name == name => return Ok(Some(remote)),
+            Ok(_) => (),
+            // The Instance may have been landed since the local DB was last synchronized
+            Err(e) => match e.kind() {
+                CliErrorKind::Seaplane(SeaplaneError::ApiResponse(ae))
+                    if ae.is_http_not_found() => {}
+                _ => return Err(e),
+            },
+        }
+    }
+
+    let remote = req.find_by_name(name)?;
+    if let Some(oid) = remote.as_ref().and_then(|f| f.oid) {
+        req.set_id(oid)?;
+    }
+    Ok(remote)
+}
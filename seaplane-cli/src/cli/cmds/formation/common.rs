@@ -4,14 +4,29 @@
 //! The only additional information is the formation name, which is not part of the configuration,
 //! but many commands need as well.
 
-use clap::Arg;
-use seaplane::api::compute::v2::FormationId;
+use std::{
+    fs,
+    io::{self, Read},
+};
+
+use clap::{value_parser, Arg, ArgMatches};
+use seaplane::api::compute::v2::{Formation as FormationModel, FormationId};
 
 use crate::{
-    cli::{cmds::formation::SeaplaneFormationFetch, errors, CliCommand},
-    context::Ctx,
-    error::{CliErrorKind, Result},
-    ops::formation::FormationNameId,
+    cli::{
+        cmds::{
+            formation::SeaplaneFormationFetch,
+            restrict::common::{Provider, Region},
+        },
+        errors, CliCommand,
+    },
+    context::{Ctx, FormationCtx},
+    error::{CliError, CliErrorKind, Context, Result},
+    ops::formation::{
+        document::{DocumentFormat, FormationDocument},
+        FormationNameId,
+    },
+    printer::Color,
 };
 
 pub fn name_id(required: bool) -> Arg {
@@ -35,6 +50,110 @@ pub fn all() -> Arg {
         .help("Operate on all matching local Formation Plans even when the name or ID is ambiguous")
 }
 
+pub fn file() -> Arg {
+    arg!(--file - ('f') = ["PATH"]).help("A Formation document, or '-' to read it from STDIN")
+}
+
+pub fn file_format() -> Arg {
+    arg!(--("file-format") = ["FORMAT"])
+        .value_parser(value_parser!(DocumentFormat))
+        .help("The format of the Formation document (default: inferred from the file extension)")
+}
+
+/// The `--region`, `--exclude-region`, `--provider` and `--exclude-provider` arguments which
+/// restrict where a Formation's Flights may run
+pub fn placement() -> Vec<Arg> {
+    vec![
+        arg!(--region|regions =["REGION"]... ignore_case)
+            .help("A region where the Flights may run, or 'all' (supports comma separated list, or multiple uses) (default: any region not excluded)")
+            .value_parser(value_parser!(Region)),
+        arg!(--("exclude-region")|("exclude-regions") =["REGION"]... ignore_case)
+            .help("A region where the Flights may *NOT* run (supports comma separated list, or multiple uses)")
+            .value_parser(value_parser!(Region)),
+        arg!(--provider|providers =["PROVIDER"]... ignore_case)
+            .help("A cloud provider the Flights may run on, or 'all' (supports comma separated list, or multiple uses) (default: any provider not excluded)")
+            .value_parser(value_parser!(Provider)),
+        arg!(--("exclude-provider")|("exclude-providers") =["PROVIDER"]... ignore_case)
+            .help("A cloud provider the Flights may *NOT* run on (supports comma separated list, or multiple uses)")
+            .value_parser(value_parser!(Provider)),
+    ]
+}
+
+/// Sets where the Formation's Flights may run from the arguments of [`placement`]
+pub fn update_placement(matches: &ArgMatches, fctx: &mut FormationCtx) {
+    fctx.regions_allowed = matches
+        .get_many::<Region>("region")
+        .unwrap_or_default()
+        .filter_map(Region::into_model)
+        .collect();
+    fctx.regions_denied = matches
+        .get_many::<Region>("exclude-region")
+        .unwrap_or_default()
+        .filter_map(Region::into_model)
+        .collect();
+    fctx.providers_allowed = matches
+        .get_many::<Provider>("provider")
+        .unwrap_or_default()
+        .filter_map(Provider::into_model)
+        .collect();
+    fctx.providers_denied = matches
+        .get_many::<Provider>("exclude-provider")
+        .unwrap_or_default()
+        .filter_map(Provider::into_model)
+        .collect();
+}
+
+/// Reads the Formation document given by `--file` if there was one, in the format given by
+/// `--file-format` or implied by its file extension, and uses it as the Formation's name, Flights,
+/// gateway Flight and placement
+pub fn read_document(ctx: &mut Ctx) -> Result<()> {
+    let fctx = ctx.formation_ctx.get_mut_or_init();
+    let path = match &fctx.file {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let format = match fctx.file_format.or_else(|| DocumentFormat::from_path(path)) {
+        Some(format) => format,
+        None => {
+            return Err(CliErrorKind::CliArgNotUsed("--file-format")
+                .into_err()
+                .context("(hint: the format of '")
+                .color_context(Color::Yellow, path)
+                .context("' can't be inferred from its file extension)\n"))
+        }
+    };
+
+    let doc = if path == "-" {
+        let mut buf = String::new();
+        io::stdin().lock().read_to_string(&mut buf)?;
+        FormationDocument::from_str(&buf, format)?
+    } else {
+        fs::read_to_string(path)
+            .map_err(CliError::from)
+            .and_then(|s| FormationDocument::from_str(&s, format))
+            .context("\n\tpath: ")
+            .with_color_context(|| (Color::Yellow, format!("{path:?}\n")))?
+    };
+    let FormationCtx {
+        name_id,
+        flights,
+        gateway_flight,
+        regions_allowed,
+        regions_denied,
+        providers_allowed,
+        providers_denied,
+        ..
+    } = doc.into();
+    fctx.name_id = name_id;
+    fctx.flights = flights;
+    fctx.gateway_flight = gateway_flight;
+    fctx.regions_allowed = regions_allowed;
+    fctx.regions_denied = regions_denied;
+    fctx.providers_allowed = providers_allowed;
+    fctx.providers_denied = providers_denied;
+    Ok(())
+}
+
 pub fn oids_matching_name_id(ctx: &Ctx) -> Result<Vec<FormationId>> {
     let formation_ctx = ctx.formation_ctx.get_or_init();
     let name_id = formation_ctx.name_id.as_ref().unwrap();
@@ -100,6 +219,31 @@ pub fn indices_matching_name_id(ctx: &Ctx) -> Result<Vec<usize>> {
     Ok(indices)
 }
 
+/// The index of the one local Formation Plan matching NAME|ID, for commands without --all, or an
+/// error if there is none
+pub fn index_matching_name_id(ctx: &Ctx) -> Result<usize> {
+    // Without --all there is at most one match
+    indices_matching_name_id(ctx)?
+        .first()
+        .copied()
+        .ok_or_else(|| {
+            let name_id = ctx.formation_ctx.get_or_init().name_id.as_ref().unwrap();
+            CliErrorKind::NoMatchingItem(name_id.to_string()).into_err()
+        })
+}
+
+/// The ID of the remote Instance of a local Formation Plan, or an error if it hasn't been launched
+pub fn launched_oid(model: &FormationModel) -> Result<FormationId> {
+    model.oid.ok_or_else(|| {
+        let name = &model.name;
+        CliErrorKind::OneOff(format!(
+            "Formation {name} has not been launched, run 'seaplane formation launch {name}' to \
+            create a remote Formation Instance first"
+        ))
+        .into_err()
+    })
+}
+
 pub fn run_fetch(ctx: &mut Ctx) -> Result<()> {
     if ctx.args.fetch {
         let old_name = { ctx.formation_ctx.get_mut_or_init().name_id.take() };
@@ -0,0 +1,83 @@
+use clap::{ArgMatches, Command};
+
+use crate::{
+    api::FormationsReq,
+    cli::{cmds::formation::common, common as cli_common, errors, CliCommand},
+    context::Ctx,
+    error::{CliErrorKind, Result},
+    ops::formation::{diff::FormationDiff, FormationNameId},
+    printer::{Output, Pb},
+    OutputFormat,
+};
+
+static LONG_ABOUT: &str =
+    "Show how a local Formation Plan differs from its remote Formation Instance
+
+The remote Formation Instance is fetched and compared to the local Plan, without changing either of
+them. Flights which have been added to or removed from the Plan are listed, along with any Flight
+fields, gateway Flight or URL which differ. Images must match exactly, so an image without a digest
+differs from the same image with one.
+
+Lines starting with '-' are from the remote Instance, and lines starting with '+' are from the local
+Plan. Running 'seaplane formation update' makes the remote Instance match the Plan.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneFormationDiff;
+
+impl SeaplaneFormationDiff {
+    pub fn command() -> Command {
+        Command::new("diff")
+            .about("Show how a local Formation Plan differs from its remote Formation Instance")
+            .long_about(LONG_ABOUT)
+            .arg(common::name_id(true).help("The name or ID of the Formation Plan to compare"))
+            .arg(common::all())
+            .arg(cli_common::format())
+    }
+}
+
+impl CliCommand for SeaplaneFormationDiff {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let indices = common::indices_matching_name_id(ctx)?;
+        if indices.is_empty() {
+            let name_id = ctx.formation_ctx.get_or_init().name_id.clone().unwrap();
+            errors::no_matching_item(name_id.to_string(), false, ctx.args.all)?;
+        }
+
+        let pb = Pb::new(ctx);
+        let mut req = FormationsReq::new(ctx)?;
+        let mut diffs = Vec::with_capacity(indices.len());
+        for idx in indices {
+            let formation = ctx.db.formations.get(idx).expect("invalid index");
+            let name = &formation.model.name;
+            let oid = formation.model.oid.ok_or_else(|| {
+                CliErrorKind::OneOff(format!(
+                    "Formation {name} has not been launched, so there is no remote Formation \
+                    Instance to compare it to"
+                ))
+                .into_err()
+            })?;
+            pb.set_message(format!("Fetching remote Formation Instance {name}..."));
+
+            req.set_id(oid)?;
+            let mut remote = req.get()?;
+            remote.restore_secrets(&formation.model);
+            diffs.push(FormationDiff::between(&formation.model, &remote));
+        }
+
+        pb.finish_and_clear();
+        match ctx.args.out_format {
+            OutputFormat::Json => diffs.print_json(ctx)?,
+            OutputFormat::Table => diffs.print_table(ctx)?,
+        }
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        ctx.args.all = matches.get_flag("all");
+        ctx.args.out_format = matches.get_one("format").copied().unwrap_or_default();
+        let mut fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.name_id = matches.get_one::<FormationNameId>("name_id").cloned();
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fs, io::Write, process};
+
+use clap::{ArgMatches, Command};
+
+use crate::{
+    cli::{
+        cmds::formation::common, specs::FLIGHT_SPEC, validator::validate_path_inline, CliCommand,
+    },
+    context::{formation::no_matching_flight, Ctx, FlightCtx},
+    error::{CliError, CliErrorKind, Context, Result},
+    ops::{
+        flight::{check_secrets, str_to_image_ref},
+        formation::{
+            diff::FormationDiff,
+            document::{DocumentFormat, FormationDocument},
+            FlightChanges, FormationNameId,
+        },
+    },
+    printer::Color,
+};
+
+static LONG_ABOUT: &str = "Edit an existing local Formation Plan
+
+Without any options the Formation Plan is opened as a Formation document in $VISUAL or $EDITOR (or
+'vi' if neither are set), and saved once the editor exits. If the edited document isn't valid the
+Plan is left unchanged, and the document is kept so that the edits aren't lost. Otherwise the Plan
+is changed as given by --add-flight, --remove-flight, --set-image and --gateway-flight.
+
+The edited Plan must still be a valid Formation. Flights which were not changed keep their IDs, so
+running 'seaplane formation update' afterwards only changes what was edited.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneFormationEdit;
+
+impl SeaplaneFormationEdit {
+    pub fn command() -> Command {
+        Command::new("edit")
+            .after_help(FLIGHT_SPEC)
+            .about("Edit an existing local Formation Plan")
+            .long_about(LONG_ABOUT)
+            .arg(common::name_id(true).help("The name or ID of the Formation Plan to edit"))
+            .arg(arg!(--("add-flight") -('F') =["SPEC"]...)
+                .help("Add a Flight in the form of SPEC|path|- (supports semicolon (';') separated list, or multiple uses) (See FLIGHT SPEC below)")
+                .value_delimiter(';')
+                .value_parser(validate_path_inline)
+            )
+            .arg(arg!(--("remove-flight") =["NAME"]...)
+                .help("Remove the Flight with this name (supports comma (',') separated list, or multiple uses)")
+                .value_delimiter(',')
+            )
+            .arg(arg!(--("set-image") -('I') =["FLIGHT=IMAGE"]...)
+                .help("Change the container image of a Flight (supports multiple uses)")
+            )
+            .arg(arg!(--("gateway-flight") -('G') =["NAME"])
+                .help("Change the Flight which receives all traffic that arrives on the public URL")
+            )
+    }
+}
+
+impl CliCommand for SeaplaneFormationEdit {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let idx = common::index_matching_name_id(ctx)?;
+        let old = ctx
+            .db
+            .formations
+            .get(idx)
+            .expect("invalid index")
+            .model
+            .clone();
+
+        let fctx = ctx.formation_ctx.get_or_init();
+        let mut doc = FormationDocument::from(&old);
+        if fctx.flights.is_empty()
+            && fctx.remove_flights.is_empty()
+            && fctx.set_images.is_empty()
+            && fctx.gateway_flight.is_none()
+        {
+            doc = edit_in_editor(&ctx.editor, &doc)?;
+        } else {
+            for name in &fctx.remove_flights {
+                let len = doc.flights.len();
+                doc.flights
+                    .retain(|f| f.name_id.as_deref() != Some(name.as_str()));
+                if doc.flights.len() == len {
+                    return Err(no_matching_flight(name));
+                }
+            }
+            for (name, image) in &fctx.set_images {
+                match doc
+                    .flights
+                    .iter_mut()
+                    .find(|f| f.name_id.as_deref() == Some(name.as_str()))
+                {
+                    Some(flight) => flight.image = image.clone(),
+                    None => return Err(no_matching_flight(name)),
+                }
+            }
+            for flight in &fctx.flights {
+                let mut flight = flight.clone();
+                // Flights in a document must be named
+                flight.name_id = Some(flight.model().name);
+                doc.flights.push(flight);
+            }
+            if let Some(gw) = &fctx.gateway_flight {
+                doc.gateway_flight = Some(gw.clone());
+            }
+        }
+
+        doc.validate()?;
+        let mut new = doc.model()?;
+        check_secrets(ctx, &new)?;
+        // The ID, URL and traffic weights of the Formation aren't part of the document
+        new.oid = old.oid;
+        new.url = old.url.clone();
+        new.revision = old.revision;
+        new.active_revisions = old.active_revisions.clone();
+        if new.name != old.name
+            && ctx
+                .db
+                .formations
+                .contains(&FormationNameId::Name(new.name.clone()))
+        {
+            return Err(CliErrorKind::DuplicateName(new.name).into_err());
+        }
+        // Images are compared exactly, so that adding a tag or digest is a change
+        let diff = FormationDiff::between(&new, &old);
+        if diff.is_empty() && new.name == old.name {
+            cli_print!("No changes made to local Formation Plan ");
+            cli_println!(@Green, "{}", old.name);
+            return Ok(());
+        }
+        diff.keep_flight_ids(&old, &mut new);
+
+        let changes = FlightChanges::from(&diff);
+        let name = new.name.clone();
+        let formation = ctx.db.formations.get_mut(idx).expect("invalid index");
+        formation.model = new;
+        let deployed = formation.deployed;
+        ctx.persist_state()?;
+
+        cli_print!("Successfully edited local Formation Plan ");
+        cli_println!(@Green, "{name}");
+        changes.print();
+        if deployed {
+            cli_print!("(hint: update its remote Formation Instance with '");
+            cli_print!(@Green, "seaplane formation update {name}");
+            cli_println!("')");
+        }
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        let mut fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.name_id = matches.get_one::<FormationNameId>("name_id").cloned();
+
+        for flight in matches.get_many::<String>("add-flight").unwrap_or_default() {
+            fctx.flights
+                .extend(FlightCtx::from_str(flight, &ctx.registry)?);
+        }
+        fctx.remove_flights = matches
+            .get_many::<String>("remove-flight")
+            .unwrap_or_default()
+            .cloned()
+            .collect();
+        for set_image in matches.get_many::<String>("set-image").unwrap_or_default() {
+            let (name, image) = set_image.split_once('=').ok_or_else(|| {
+                CliErrorKind::InvalidCliValue(Some("set-image"), set_image.clone()).into_err()
+            })?;
+            fctx.set_images
+                .push((name.to_string(), str_to_image_ref(&ctx.registry, image)?));
+        }
+        fctx.gateway_flight = matches.get_one::<String>("gateway-flight").cloned();
+
+        Ok(())
+    }
+}
+
+// Writes the document to a temporary file, opens it in the editor and reads it back once the editor
+// exits. If the edited document isn't valid the file is kept, so that the changes aren't lost.
+fn edit_in_editor(editor: &str, doc: &FormationDocument) -> Result<FormationDocument> {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile()?;
+    file.write_all(doc.template(DocumentFormat::Toml).as_bytes())?;
+    file.flush()?;
+
+    let mut args = editor.split_whitespace();
+    let program = args.next().unwrap_or("vi");
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(file.path())
+        .status()
+        .map_err(CliError::from)
+        .context("(hint: failed to run the editor '")
+        .color_context(Color::Yellow, editor)
+        .context("')\n")?;
+    if !status.success() {
+        return Err(CliErrorKind::OneOff(format!(
+            "the editor '{editor}' exited with {status}, the Formation Plan was not changed"
+        ))
+        .into_err());
+    }
+
+    let edited =
+        FormationDocument::from_str(&fs::read_to_string(file.path())?, DocumentFormat::Toml)
+            .and_then(|doc| doc.validate().map(|()| doc));
+    if edited.is_err() {
+        let (_, path) = file.keep().map_err(|e| CliError::from(e.error))?;
+        return edited
+            .context("(hint: the Formation Plan was not changed, the edited document was kept at ")
+            .color_context(Color::Yellow, path.display().to_string())
+            .context(")\n");
+    }
+    edited
+}
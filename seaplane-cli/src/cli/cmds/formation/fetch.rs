@@ -40,7 +40,7 @@ impl CliCommand for SeaplaneFormationFetch {
             if name_id.is_some() { "s" } else { "" }
         ));
 
-        let mut req = FormationsReq::new_delay_token(ctx)?;
+        let mut req = FormationsReq::new(ctx)?;
         let names_ids: Vec<_> = if let Some(name_id) = &name_id {
             if name_id.is_oid() {
                 req.set_id(*name_id.oid().unwrap())?;
@@ -52,7 +52,7 @@ impl CliCommand for SeaplaneFormationLand {
             .into_err());
         }
 
-        let mut req = FormationsReq::new_delay_token(ctx)?;
+        let mut req = FormationsReq::new(ctx)?;
         for oid in &oids {
             req.set_id(*oid)?;
             #[cfg_attr(feature = "api_tests", allow(clippy::question_mark))]
@@ -68,6 +68,7 @@ impl CliCommand for SeaplaneFormationLand {
                 }
                 return Err(e);
             }
+            ctx.db.formation_history.remove(*oid);
             // prints:
             //   Successfully Landed remote Formation Instance frm-abcdef12345 (stubb)
             cli_print!("Successfully Landed remote Formation Instance ");
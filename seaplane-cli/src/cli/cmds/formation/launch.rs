@@ -38,7 +38,7 @@ impl CliCommand for SeaplaneFormationLaunch {
         };
 
         let pb = Pb::new(ctx);
-        let mut req = FormationsReq::new_delay_token(ctx)?;
+        let mut req = FormationsReq::new(ctx)?;
         let mut formations_created = Vec::with_capacity(indices.len());
         for idx in indices {
             let formation = ctx.db.formations.get(idx).expect("invalid index");
@@ -8,15 +8,24 @@ use crate::{
         CliCommand,
     },
     context::{Ctx, FlightCtx},
-    error::{CliErrorKind, Result},
-    ops::{formation::FormationNameId, generate_name, validator::validate_name},
+    error::{CliErrorKind, Context, Result},
+    ops::{
+        flight::check_secrets,
+        formation::{document::DocumentFormat, FormationNameId},
+        generate_name,
+        validator::validate_name,
+    },
+    printer::Color,
 };
 
 static LONG_ABOUT: &str =
     "Make a new local Formation Plan (and optionally launch an instance of it)
 
 Include Flights by using `--flight`. Multiple Flights may be included in a Formation Plan using a
-SEMICOLON separated list, or using the argument multiple times.";
+SEMICOLON separated list, or using the argument multiple times.
+
+Alternatively the whole Formation Plan may be described by a Formation document in TOML, JSON or
+YAML using `--file`. A skeleton document can be generated with `seaplane formation template`.";
 
 static LONG_FLIGHT: &str =
     "A Flight to include in this Formation in the form of SPEC|path|- (See FLIGHT SPEC below)
@@ -64,29 +73,44 @@ impl SeaplaneFormationPlan {
             .arg(arg!(--flight|flights -('F') =["SPEC"]...)
                 .help("Use Flight in this Formation in the form of SPEC|path|- (supports semicolon (';') separated list, or multiple uses) (See FLIGHT SPEC below)")
                 .value_delimiter(';')
-                .required(true)
+                .required_unless_present("file")
                 .long_help(LONG_FLIGHT)
                 .value_parser(validate_path_inline)
             )
             .arg(arg!(--("gateway-flight") -('G') =["NAME"])
                 .help("The name of the Flight to be used as the public traffic gateway that will receive all traffic that arrives on the public URL (if only a single Flight is included in this Formation, it will be implied as the gateway)")
             )
+            .args(common::placement())
             .arg(arg!(--force).help("Override any existing Formation with the same NAME"))
+            .arg(common::file()
+                .conflicts_with_all(["name", "flight", "gateway-flight", "region", "exclude-region", "provider", "exclude-provider"])
+                .help("Create the Formation Plan from a Formation document instead of --name, --flight, --gateway-flight and the placement arguments, or '-' to read it from STDIN (see 'seaplane formation template')")
+            )
+            .arg(common::file_format().requires("file"))
     }
 }
 
 impl CliCommand for SeaplaneFormationPlan {
     fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        common::read_document(ctx)?;
         common::run_fetch(ctx)?;
 
         let formation_ctx = ctx.formation_ctx.get_or_init();
+        // Check the Formation is valid, such as having a gateway Flight, and that its secrets can
+        // be read before changing anything
+        check_secrets(ctx, &formation_ctx.model()?)?;
 
-        // TODO: eventually check for duplicates and suggest `seaplane formation edit`
         // name is either given or auto-generated so safe to unwrap
         let name = &formation_ctx.name_id.clone().unwrap();
         if ctx.db.formations.contains(name) {
             if !ctx.args.force {
-                return Err(CliErrorKind::DuplicateName(name.to_string()).into_err());
+                return Err(CliErrorKind::DuplicateName(name.to_string())
+                    .into_err()
+                    .context("(hint: edit the existing Formation Plan with '")
+                    .color_context(Color::Green, format!("seaplane formation edit {name}"))
+                    .context("' or replace it using '")
+                    .color_context(Color::Green, "--force")
+                    .context("')\n"));
             }
 
             // We have duplicates, but the user passed --force. So first we remove the existing
@@ -124,6 +148,12 @@ impl CliCommand for SeaplaneFormationPlan {
 
         let mut fctx = ctx.formation_ctx.get_mut_or_init();
         fctx.launch = matches.get_flag("launch");
+        // The Formation is read from the document once the command is run
+        if let Some(file) = matches.get_one::<String>("file") {
+            fctx.file = Some(file.clone());
+            fctx.file_format = matches.get_one::<DocumentFormat>("file-format").copied();
+            return Ok(());
+        }
         fctx.name_id = Some(FormationNameId::Name(
             matches
                 .get_one::<String>("name")
@@ -137,6 +167,7 @@ impl CliCommand for SeaplaneFormationPlan {
         }
 
         fctx.gateway_flight = matches.get_one::<String>("gateway-flight").cloned();
+        common::update_placement(matches, fctx);
 
         Ok(())
     }
@@ -0,0 +1,138 @@
+use clap::{value_parser, ArgMatches, Command};
+use seaplane::api::compute::v2::{Formation as FormationModel, FormationRevision};
+
+use crate::{
+    api::FormationsReq,
+    cli::{cmds::formation::common, CliCommand},
+    context::Ctx,
+    error::{CliErrorKind, Result},
+    ops::formation::{diff::FormationDiff, FlightChanges, FormationNameId},
+    printer::Pb,
+};
+
+static LONG_ABOUT: &str = "Roll a remote Formation Instance back to an earlier revision
+
+Without --revision, a rollout which is in progress is undone by returning all public traffic to
+the earlier revision which is still active. Otherwise the Instance is returned to the configuration
+it had before it was last updated.
+
+Configurations the Instance had before being updated from this machine are kept in the local
+state, so --revision may name any of them or any revision which is still active.
+
+The local Formation Plan is replaced with the configuration which was rolled back to, so that a
+later 'seaplane formation update' doesn't undo the rollback.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneFormationRollback;
+
+impl SeaplaneFormationRollback {
+    pub fn command() -> Command {
+        Command::new("rollback")
+            .about("Return a remote Formation Instance to an earlier revision")
+            .long_about(LONG_ABOUT)
+            .arg(common::name_id(true).help("The name or ID of the Formation Plan to roll back"))
+            .arg(
+                arg!(--revision =["NUM"])
+                    .value_parser(value_parser!(u32))
+                    .help("The revision to roll back to (default: the last revision before the current one)"),
+            )
+    }
+}
+
+impl CliCommand for SeaplaneFormationRollback {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let idx = common::index_matching_name_id(ctx)?;
+        let local = &ctx.db.formations.get(idx).expect("invalid index").model;
+        let name = local.name.clone();
+        let oid = common::launched_oid(local)?;
+        let revision = ctx.formation_ctx.get_or_init().revision;
+
+        let pb = Pb::new(ctx);
+        pb.set_message(format!("Rolling back Formation {name}..."));
+        let mut req = FormationsReq::new(ctx)?;
+        req.set_id(oid)?;
+        let mut remote = req.get()?;
+        remote.restore_secrets(local);
+
+        let history = &ctx.db.formation_history;
+        let active = |rev: &FormationRevision| {
+            let mut config = remote.clone();
+            config.flights = rev.flights.clone();
+            config.gateway_flight = rev.gateway_flight.clone();
+            config.revision = Some(rev.revision);
+            config
+        };
+        let target: Option<FormationModel> = match revision {
+            Some(revision) if remote.revision == Some(revision) => Some(remote.clone()),
+            Some(revision) => remote
+                .active_revisions
+                .iter()
+                .find(|r| r.revision == revision)
+                .map(active)
+                .or_else(|| history.get(oid, revision).cloned()),
+            None => remote.active_revisions.last().map(active).or_else(|| {
+                history
+                    .of(oid)
+                    .rev()
+                    .find(|m| m.revision.is_some() && m.revision != remote.revision)
+                    .cloned()
+            }),
+        };
+        let mut new = match target {
+            Some(target) => target,
+            None => {
+                let known: Vec<_> = history
+                    .of(oid)
+                    .filter_map(|m| m.revision)
+                    .chain(remote.active_revisions.iter().map(|r| r.revision))
+                    .map(|r| r.to_string())
+                    .collect();
+                let wanted = match revision {
+                    Some(revision) => format!("revision {revision}"),
+                    None => "earlier revision".into(),
+                };
+                return Err(CliErrorKind::OneOff(format!(
+                    "no {wanted} of Formation {name} is known to roll back to (known revisions: {})",
+                    if known.is_empty() { "none".into() } else { known.join(", ") }
+                ))
+                .into_err());
+            }
+        };
+        let rolled_back_to = new.revision;
+        // The Instance keeps its identity, and the revision it is rolled back to receives all of
+        // its traffic
+        new.oid = remote.oid;
+        new.url = remote.url.clone();
+        new.revision = remote.revision;
+        new.active_revisions.clear();
+        let diff = FormationDiff::between(&new, &remote);
+        diff.keep_flight_ids(&remote, &mut new);
+
+        let resp = req.update(&new)?;
+        if !diff.is_empty() {
+            ctx.db.formation_history.record(&remote);
+        }
+        let formation = ctx.db.formations.get_mut(idx).expect("invalid index");
+        formation.model = resp;
+        ctx.persist_state()?;
+
+        pb.finish_and_clear();
+        cli_print!("Successfully Rolled Back remote Formation Instance ");
+        cli_print!(@Green, "{name}");
+        if let Some(revision) = rolled_back_to {
+            cli_print!(" to revision {revision}");
+        }
+        cli_println!("");
+        FlightChanges::from(&diff).print();
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        let mut fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.name_id = matches.get_one::<FormationNameId>("name_id").cloned();
+        fctx.revision = matches.get_one::<u32>("revision").copied();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use clap::{value_parser, ArgMatches, Command};
+use seaplane::api::compute::v2::FormationRevision;
+
+use crate::{
+    api::FormationsReq,
+    cli::{cmds::formation::common, CliCommand},
+    context::Ctx,
+    error::{CliErrorKind, Result},
+    ops::formation::{diff::FormationDiff, FlightChanges, FormationNameId},
+    printer::Pb,
+};
+
+static LONG_ABOUT: &str = "Gradually roll out a local Formation Plan to its remote Instance
+
+When the local Plan differs from the remote Instance, the Plan is sent as a new revision which
+receives PERCENT of the public traffic, while the Instance's current revision stays active and
+receives the rest. Running the command again once the Plan is rolled out moves the traffic between
+the two revisions, and the rollout is complete once the new revision receives 100% of the traffic.
+
+If the new revision misbehaves, return all traffic to the earlier one with 'seaplane formation
+rollback'.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneFormationRollout;
+
+impl SeaplaneFormationRollout {
+    pub fn command() -> Command {
+        Command::new("rollout")
+            .about("Gradually shift traffic to a changed local Formation Plan")
+            .long_about(LONG_ABOUT)
+            .arg(common::name_id(true).help("The name or ID of the Formation Plan to roll out"))
+            .arg(
+                arg!(--("to-weight") =["PERCENT"] required)
+                    .value_parser(value_parser!(u8).range(1..=100))
+                    .help("The percentage of public traffic the new revision should receive, from 1 to 100"),
+            )
+    }
+}
+
+impl CliCommand for SeaplaneFormationRollout {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let idx = common::index_matching_name_id(ctx)?;
+        let local = ctx
+            .db
+            .formations
+            .get(idx)
+            .expect("invalid index")
+            .model
+            .clone();
+        let name = local.name.clone();
+        let oid = common::launched_oid(&local)?;
+        let weight = ctx.formation_ctx.get_or_init().traffic_weight;
+
+        let pb = Pb::new(ctx);
+        pb.set_message(format!("Rolling out Formation {name}..."));
+        let mut req = FormationsReq::new(ctx)?;
+        req.set_id(oid)?;
+        let mut remote = req.get()?;
+        remote.restore_secrets(&local);
+
+        let diff = FormationDiff::between(&local, &remote);
+        let mut new = if diff.is_empty() {
+            // The Instance already has the Plan's configuration, so only the traffic moves
+            let mut new = remote.clone();
+            match remote.active_revisions.last() {
+                Some(earlier) if weight < 100 => {
+                    new.active_revisions =
+                        vec![FormationRevision { weight: 100 - weight, ..earlier.clone() }];
+                }
+                Some(_) => new.active_revisions.clear(),
+                None => {
+                    pb.finish_and_clear();
+                    cli_print!("Remote Formation Instance ");
+                    cli_print!(@Green, "{name}");
+                    cli_println!(" is already receiving all of its traffic");
+                    return Ok(());
+                }
+            }
+            new
+        } else {
+            if let Some(earlier) = remote.active_revisions.last() {
+                return Err(CliErrorKind::OneOff(format!(
+                    "revision {} of Formation {name} is still receiving {}% of its traffic, \
+                    finish the current rollout with 'seaplane formation rollout {name} \
+                    --to-weight 100' or undo it with 'seaplane formation rollback {name}' before \
+                    rolling out another change",
+                    earlier.revision, earlier.weight
+                ))
+                .into_err());
+            }
+            let mut new = local;
+            new.active_revisions.clear();
+            if weight < 100 {
+                let current = FormationRevision::of(&remote, 100 - weight).ok_or_else(|| {
+                    CliErrorKind::OneOff(format!(
+                        "the remote Instance of Formation {name} has no revision to keep active, \
+                        use 'seaplane formation update {name}' instead"
+                    ))
+                    .into_err()
+                })?;
+                new.active_revisions.push(current);
+            }
+            diff.keep_flight_ids(&remote, &mut new);
+            new
+        };
+        new.revision = remote.revision;
+
+        let resp = req.update(&new)?;
+        if !diff.is_empty() {
+            ctx.db.formation_history.record(&remote);
+        }
+        let formation = ctx.db.formations.get_mut(idx).expect("invalid index");
+        formation.model = resp.clone();
+        ctx.persist_state()?;
+
+        pb.finish_and_clear();
+        cli_print!("Successfully Rolled Out ");
+        if let Some(revision) = resp.revision {
+            cli_print!("revision {revision} of ");
+        }
+        cli_print!("remote Formation Instance ");
+        cli_print!(@Green, "{name}");
+        cli_println!(" to {}% of its traffic", resp.weight());
+        FlightChanges::from(&diff).print();
+        for earlier in &resp.active_revisions {
+            cli_println!(
+                "    Revision {} receives {}% of the traffic",
+                earlier.revision,
+                earlier.weight
+            );
+        }
+        if !resp.active_revisions.is_empty() {
+            cli_print!("(hint: finish the rollout with '");
+            cli_print!(@Green, "seaplane formation rollout {name} --to-weight 100");
+            cli_print!("' or undo it with '");
+            cli_print!(@Green, "seaplane formation rollback {name}");
+            cli_println!("')");
+        }
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        let mut fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.name_id = matches.get_one::<FormationNameId>("name_id").cloned();
+        fctx.traffic_weight = *matches.get_one::<u8>("to-weight").unwrap();
+
+        Ok(())
+    }
+}
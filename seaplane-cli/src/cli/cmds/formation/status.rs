@@ -1,9 +1,16 @@
-use clap::{ArgMatches, Command};
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use clap::{value_parser, ArgGroup, ArgMatches, Command};
+use seaplane::api::compute::v2::{FlightStatus, Formation as FormationModel, FormationId};
 
 use crate::{
-    cli::{cmds::formation::common, common as cli_common, CliCommand},
-    error::Result,
-    ops::formation::FormationNameId,
+    api::FormationsReq,
+    cli::{cmds::formation::common, common as cli_common, errors, CliCommand},
+    error::{CliErrorKind, Result},
+    ops::formation::{Formation, FormationNameId},
     printer::Output,
     Ctx, OutputFormat,
 };
@@ -12,7 +19,14 @@ static LONG_ABOUT: &str = "Show the status of a remote Formation Instance
 
 This command will display the status of one or more Formation Instances such as how many actual
 containers are running compared to the minimum and maximums per Flight that the configuration
-defines.";
+defines.
+
+When a NAME|ID is given only the Formations whose name or ID matches it exactly are displayed, or
+if there are none, those whose name or ID contains it. Without one all Formations are displayed.
+
+With --watch the status is fetched and displayed again every --interval seconds until interrupted.
+With --wait-healthy the command instead exits successfully once every Flight is Healthy, or with
+an error as soon as any Flight is Unhealthy or once --timeout seconds have passed.";
 
 #[derive(Copy, Clone, Debug)]
 pub struct SeaplaneFormationStatus;
@@ -28,21 +42,113 @@ impl SeaplaneFormationStatus {
                     .help("The name or ID of the Formation to check, must be unambiguous"),
             )
             .arg(arg!(--("no-fetch")).help("Skip fetching and synchronizing of remote instances"))
+            .arg(
+                arg!(--watch - ('w'))
+                    .conflicts_with("no-fetch")
+                    .help("Keep displaying the status every --interval seconds"),
+            )
+            .arg(
+                arg!(--("wait-healthy"))
+                    .conflicts_with("no-fetch")
+                    .help("Wait until all Flights are Healthy, failing if any are Unhealthy"),
+            )
+            .arg(
+                arg!(--timeout = ["SECS"])
+                    .requires("wait-healthy")
+                    .value_parser(value_parser!(u64))
+                    .default_value("300")
+                    .help("How long to wait for the Flights to become Healthy"),
+            )
+            .group(
+                ArgGroup::new("watch_or_wait")
+                    .args(["watch", "wait-healthy"])
+                    .multiple(true),
+            )
+            .arg(
+                arg!(--interval = ["SECS"])
+                    .requires("watch_or_wait")
+                    .value_parser(value_parser!(u64).range(1..))
+                    .default_value("5")
+                    .help("How often to check the status when watching or waiting"),
+            )
     }
 }
 
 impl CliCommand for SeaplaneFormationStatus {
     fn run(&self, ctx: &mut Ctx) -> Result<()> {
-        common::run_fetch(ctx)?;
+        let fctx = ctx.formation_ctx.get_or_init();
+        let (watch, interval) = (fctx.watch, fctx.interval);
+        let deadline = fctx.wait_healthy.map(|timeout| Instant::now() + timeout);
 
-        let statuses = ctx.db.formations.statuses();
+        loop {
+            if ctx.args.fetch {
+                fetch_matching(ctx)?;
+            }
 
-        match ctx.args.out_format {
-            OutputFormat::Json => statuses.print_json(ctx)?,
-            OutputFormat::Table => statuses.print_table(ctx)?,
-        }
+            let statuses = ctx.db.formations.statuses(&matching_indices(ctx));
+            if statuses.is_empty() {
+                if let Some(name_id) = &ctx.formation_ctx.get_or_init().name_id {
+                    errors::no_matching_item(name_id.to_string(), false, false)?;
+                }
+            }
+            let print = || match ctx.args.out_format {
+                OutputFormat::Json => statuses.print_json(ctx),
+                OutputFormat::Table => statuses.print_table(ctx),
+            };
 
-        Ok(())
+            let deadline = match deadline {
+                Some(deadline) => deadline,
+                None => {
+                    print()?;
+                    if !watch {
+                        return Ok(());
+                    }
+                    thread::sleep(interval);
+                    cli_println!("");
+                    continue;
+                }
+            };
+
+            if statuses.is_empty() {
+                return Err(
+                    CliErrorKind::OneOff("there are no Formations to wait for".into()).into_err()
+                );
+            }
+            // When only waiting the final status is displayed once we're done
+            let health = FlightStatus::worst(statuses.iter().map(|s| s.status()));
+            let now = Instant::now();
+            let done = health != FlightStatus::Starting || now >= deadline;
+            if watch || done {
+                print()?;
+            }
+            match health {
+                FlightStatus::Healthy => return Ok(()),
+                FlightStatus::Unhealthy => {
+                    let unhealthy: Vec<_> = statuses
+                        .iter()
+                        .filter(|s| s.status() == FlightStatus::Unhealthy)
+                        .map(|s| s.name())
+                        .collect();
+                    let msg = if unhealthy.len() == 1 {
+                        format!("Formation {} is Unhealthy", unhealthy[0])
+                    } else {
+                        format!("Formations {} are Unhealthy", unhealthy.join(", "))
+                    };
+                    return Err(CliErrorKind::OneOff(msg).into_err());
+                }
+                FlightStatus::Starting if done => {
+                    return Err(CliErrorKind::OneOff(
+                        "timed out waiting for the Formation to become Healthy".into(),
+                    )
+                    .into_err());
+                }
+                FlightStatus::Starting => (),
+            }
+            thread::sleep(interval.min(deadline - now));
+            if watch {
+                cli_println!("");
+            }
+        }
     }
 
     fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
@@ -50,6 +156,96 @@ impl CliCommand for SeaplaneFormationStatus {
         ctx.args.fetch = !matches.get_flag("no-fetch");
         let mut fctx = ctx.formation_ctx.get_mut_or_init();
         fctx.name_id = matches.get_one::<FormationNameId>("name_id").cloned();
+        fctx.watch = matches.get_flag("watch");
+        fctx.wait_healthy = if matches.get_flag("wait-healthy") {
+            matches
+                .get_one::<u64>("timeout")
+                .copied()
+                .map(Duration::from_secs)
+        } else {
+            None
+        };
+        fctx.interval = Duration::from_secs(*matches.get_one::<u64>("interval").unwrap());
         Ok(())
     }
 }
+
+// The indices of the local Formations matching the NAME|ID, preferring exact matches over partial
+// ones, or of all Formations if none was given
+fn matching_indices(ctx: &Ctx) -> Vec<usize> {
+    match &ctx.formation_ctx.get_or_init().name_id {
+        Some(name_id) => {
+            let indices = ctx.db.formations.formation_indices_of_matches(name_id);
+            if indices.is_empty() {
+                ctx.db
+                    .formations
+                    .formation_indices_of_partial_matches(name_id)
+            } else {
+                indices
+            }
+        }
+        None => (0..ctx.db.formations.inner.len()).collect(),
+    }
+}
+
+// Fetches only the remote Instances of the matching Formations. All of them are fetched instead if
+// no NAME|ID was given, or if a matching Formation doesn't have an ID to fetch it by. When no local
+// Formation matches a NAME, the remote Instances it matches are looked for on every page.
+fn fetch_matching(ctx: &mut Ctx) -> Result<()> {
+    let name_id = match ctx.formation_ctx.get_or_init().name_id.clone() {
+        Some(name_id) => name_id,
+        None => return common::run_fetch(ctx),
+    };
+    let indices = matching_indices(ctx);
+    let oids = ctx.db.formations.oids_from_indices(&indices);
+    if oids.len() < indices.len() {
+        return common::run_fetch(ctx);
+    }
+
+    let mut req = FormationsReq::new(ctx)?;
+    let models = match name_id {
+        // A remote Instance we don't know about yet
+        FormationNameId::Oid(oid) if indices.is_empty() => vec![get_by_oid(&mut req, oid)?],
+        FormationNameId::Name(name) if indices.is_empty() => remote_matches(&mut req, &name)?,
+        _ => oids
+            .into_iter()
+            .map(|oid| get_by_oid(&mut req, oid))
+            .collect::<Result<_>>()?,
+    };
+
+    for model in models {
+        let formations = &mut ctx.db.formations;
+        if model
+            .oid
+            .map_or(false, |oid| formations.contains(&FormationNameId::Oid(oid)))
+            || formations.contains(&FormationNameId::Name(model.name.clone()))
+        {
+            formations.create_or_update(model);
+        } else {
+            formations.add(Formation { model, local: false, deployed: true });
+        }
+    }
+    ctx.persist_state()
+}
+
+// The remote Instances matching `name`, preferring exact matches of the name or ID over partial
+// ones as `matching_indices` does
+fn remote_matches(req: &mut FormationsReq, name: &str) -> Result<Vec<FormationModel>> {
+    let mut exact = Vec::new();
+    let mut partial = Vec::new();
+    for formation in req.entries()? {
+        let formation = formation?;
+        let oid = formation.oid.map(|o| o.to_string()).unwrap_or_default();
+        if formation.name == name || oid == name {
+            exact.push(formation);
+        } else if formation.name.contains(name) || (!oid.is_empty() && oid.contains(name)) {
+            partial.push(formation);
+        }
+    }
+    Ok(if exact.is_empty() { partial } else { exact })
+}
+
+fn get_by_oid(req: &mut FormationsReq, oid: FormationId) -> Result<FormationModel> {
+    req.set_id(oid)?;
+    req.get()
+}
@@ -1,16 +1,128 @@
-use clap::Command;
+use clap::{value_parser, ArgMatches, Command};
+use seaplane::api::compute::v2::Formation as FormationModel;
 
-use crate::{cli::CliCommand, error::Result, Ctx};
+use crate::{
+    cli::CliCommand,
+    context::{Ctx, FlightCtx},
+    error::{CliErrorKind, Result},
+    ops::{
+        flight::str_to_image_ref,
+        formation::{
+            document::{DocumentFormat, FormationDocument},
+            FormationNameId,
+        },
+        generate_name,
+        validator::validate_name,
+    },
+};
+
+static LONG_ABOUT: &str = "Generate a template skeleton of a Formation document
+
+The document is printed in TOML (the default), JSON or YAML, and may be edited and then used with
+'seaplane formation apply --file PATH' or 'seaplane formation plan --file PATH'. TOML and YAML
+documents include comments explaining each key.
+
+The document can be filled in from an existing local Formation Plan using --from, or from a list
+of container images using --image, which creates a Flight named after each image. Otherwise it
+contains a single example Flight.";
+
+/// The image of the example Flight, when no other images are given
+const EXAMPLE_IMAGE: &str = "seaplane-demo/nginx:latest";
 
 #[derive(Copy, Clone, Debug)]
 pub struct SeaplaneFormationTemplate;
 
 impl SeaplaneFormationTemplate {
     pub fn command() -> Command {
-        Command::new("template").about("Generate a template skeleton of a Formation")
+        Command::new("template")
+            .about("Generate a template skeleton of a Formation document")
+            .long_about(LONG_ABOUT)
+            .arg(
+                arg!(--name -('n') =["STRING"])
+                    .value_parser(validate_name)
+                    .help("The name of the Formation (if omitted a pseudo random name will be assigned)"),
+            )
+            .arg(
+                arg!(--from =["NAME"])
+                    .conflicts_with_all(["name", "image"])
+                    .value_parser(validate_name)
+                    .help("Fill in the document from the local Formation Plan with this name"),
+            )
+            .arg(
+                arg!(--image|images -('I') =["IMAGE"]...)
+                    .value_delimiter(',')
+                    .help("Include a Flight running each of these container images (supports comma (',') separated list, or multiple uses)"),
+            )
+            .arg(
+                arg!(--("file-format") =["FORMAT"=>"toml"])
+                    .value_parser(value_parser!(DocumentFormat))
+                    .help("The format of the generated Formation document"),
+            )
     }
 }
 
 impl CliCommand for SeaplaneFormationTemplate {
-    fn run(&self, _ctx: &mut Ctx) -> Result<()> { todo!("impl SeaplaneFormationTemplate::run") }
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let fctx = ctx.formation_ctx.get_or_init();
+        // With --from there are no Flights, which are instead those of the local Plan
+        let model = match &fctx.name_id {
+            Some(name_id) if fctx.flights.is_empty() => ctx
+                .db
+                .formations
+                .get_by_name_id(name_id)
+                .map(|formation| formation.model.clone())
+                .ok_or_else(|| CliErrorKind::NoMatchingItem(name_id.to_string()).into_err())?,
+            _ => fctx.model()?,
+        };
+
+        let format = fctx.file_format.unwrap_or(DocumentFormat::Toml);
+        cli_print!("{}", FormationDocument::from(&plan_only(model)).template(format));
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        let fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.file_format = matches.get_one::<DocumentFormat>("file-format").copied();
+        if let Some(from) = matches.get_one::<String>("from") {
+            fctx.name_id = Some(FormationNameId::Name(from.clone()));
+            return Ok(());
+        }
+
+        fctx.name_id = Some(FormationNameId::Name(
+            matches
+                .get_one::<String>("name")
+                .cloned()
+                .unwrap_or_else(generate_name),
+        ));
+        let images: Vec<_> = matches
+            .get_many::<String>("image")
+            .map(|images| images.map(String::as_str).collect())
+            .unwrap_or_else(|| vec![EXAMPLE_IMAGE]);
+        let mut names: Vec<String> = Vec::with_capacity(images.len());
+        for image in images {
+            let image = str_to_image_ref(&ctx.registry, image)?;
+            // Flights are named after the last part of their image path, such as 'nginx' for
+            // 'seaplane-demo/nginx:latest', unless that's not a valid or unique name
+            let name = image
+                .path()
+                .rsplit('/')
+                .next()
+                .filter(|name| validate_name(name).is_ok() && !names.iter().any(|n| n == name))
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(generate_name);
+            fctx.flights.push(FlightCtx::new(name.clone(), image));
+            names.push(name);
+        }
+        fctx.gateway_flight = names.into_iter().next();
+
+        Ok(())
+    }
+}
+
+// The Formation without anything assigned by the API, which doesn't belong in a document
+fn plan_only(mut model: FormationModel) -> FormationModel {
+    model.oid = None;
+    model.url = None;
+    model
 }
@@ -0,0 +1,99 @@
+use clap::{ArgMatches, Command};
+
+use crate::{
+    api::FormationsReq,
+    cli::{cmds::formation::common, CliCommand},
+    context::Ctx,
+    error::Result,
+    ops::{
+        formation::{diff::FormationDiff, FlightChanges, FormationNameId},
+        validator::validate_name,
+    },
+    printer::Pb,
+};
+
+static LONG_ABOUT: &str = "Update a remote Formation Instance to match its local Formation Plan
+
+The local Plan is compared to the remote Instance and only sent if they differ. Flights which have
+been added, removed or changed in the Plan are listed once the update is complete.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneFormationUpdate;
+
+impl SeaplaneFormationUpdate {
+    pub fn command() -> Command {
+        Command::new("update")
+            .visible_alias("redeploy")
+            .about("Push changes to a local Formation Plan to its remote Formation Instance")
+            .long_about(LONG_ABOUT)
+            .arg(common::all())
+            .arg(
+                arg!(name = ["NAME"] required)
+                    .value_parser(validate_name)
+                    .help("The name of the Formation Plan to update the remote Instance of"),
+            )
+    }
+}
+
+impl CliCommand for SeaplaneFormationUpdate {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let indices = common::indices_matching_name_id(ctx)?;
+
+        let pb = Pb::new(ctx);
+        let mut req = FormationsReq::new(ctx)?;
+        let mut results = Vec::with_capacity(indices.len());
+        for idx in indices {
+            let formation = ctx.db.formations.get(idx).expect("invalid index");
+            let name = formation.model.name.clone();
+            let oid = common::launched_oid(&formation.model)?;
+            pb.set_message(format!("Updating Formation {name}..."));
+
+            req.set_id(oid)?;
+            let mut remote = req.get()?;
+            remote.restore_secrets(&formation.model);
+            let diff = FormationDiff::between(&formation.model, &remote);
+            if diff.is_empty() {
+                results.push((name, oid, None));
+                continue;
+            }
+
+            let resp = req.update(&formation.model)?;
+            ctx.db.formation_history.record(&remote);
+            // update our local DB with the OIDs of any Flights which were just created
+            ctx.db.formations.update(&resp);
+            // saved before the next update, which may fail, so the history to roll this one back
+            // with isn't lost
+            ctx.persist_state()?;
+            results.push((name, oid, Some(FlightChanges::from(&diff))));
+        }
+
+        pb.finish_and_clear();
+        for (name, oid, changes) in results {
+            let changes = match changes {
+                Some(changes) => changes,
+                None => {
+                    cli_print!("Remote Formation Instance ");
+                    cli_print!(@Green, "{name}");
+                    cli_println!(" is already up to date");
+                    continue;
+                }
+            };
+            cli_print!("Successfully Updated remote Formation Instance ");
+            cli_print!(@Green, "{name}");
+            cli_print!(" (");
+            cli_print!(@Green, "{oid}");
+            cli_println!(")");
+            changes.print();
+        }
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        ctx.args.all = matches.get_flag("all");
+        let mut fctx = ctx.formation_ctx.get_mut_or_init();
+        fctx.name_id =
+            Some(FormationNameId::Name(matches.get_one::<String>("name").cloned().unwrap()));
+        Ok(())
+    }
+}
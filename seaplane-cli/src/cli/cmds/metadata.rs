@@ -1,17 +1,23 @@
 mod common;
 mod delete;
+mod export;
 mod get;
+mod import;
 mod list;
 mod set;
+mod watch;
 
 use clap::{value_parser, ArgMatches, Command};
 
 pub use self::{
     common::SeaplaneMetadataCommonArgMatches,
     delete::SeaplaneMetadataDelete,
+    export::SeaplaneMetadataExport,
     get::SeaplaneMetadataGet,
+    import::SeaplaneMetadataImport,
     list::SeaplaneMetadataList,
     set::{SeaplaneMetadataSet, SeaplaneMetadataSetArgMatches},
+    watch::SeaplaneMetadataWatch,
 };
 use crate::{cli::CliCommand, printer::OutputFormat};
 
@@ -34,6 +40,9 @@ impl SeaplaneMetadata {
             .subcommand(SeaplaneMetadataSet::command())
             .subcommand(SeaplaneMetadataDelete::command())
             .subcommand(SeaplaneMetadataList::command())
+            .subcommand(SeaplaneMetadataExport::command())
+            .subcommand(SeaplaneMetadataImport::command())
+            .subcommand(SeaplaneMetadataWatch::command())
     }
 }
 
@@ -47,6 +56,9 @@ impl CliCommand for SeaplaneMetadata {
             Some(("set", m)) => Some((Box::new(SeaplaneMetadataSet), m)),
             Some(("delete", m)) => Some((Box::new(SeaplaneMetadataDelete), m)),
             Some(("list", m)) => Some((Box::new(SeaplaneMetadataList), m)),
+            Some(("export", m)) => Some((Box::new(SeaplaneMetadataExport), m)),
+            Some(("import", m)) => Some((Box::new(SeaplaneMetadataImport), m)),
+            Some(("watch", m)) => Some((Box::new(SeaplaneMetadataWatch), m)),
             _ => None,
         }
     }
@@ -1,4 +1,6 @@
-use clap::{builder::ArgPredicate, Arg, ArgGroup, ArgMatches};
+use clap::{builder::ArgPredicate, value_parser, Arg, ArgGroup, ArgMatches};
+
+use crate::ops::metadata::archive::ArchiveFormat;
 
 const LONG_DECODE: &str = "Decode the keys and values before printing them
 
@@ -51,6 +53,12 @@ pub fn base64() -> Arg {
     arg!(--base64 - ('B')).help("The keys/values are already encoded in URL safe Base64")
 }
 
+pub fn archive_format() -> Arg {
+    arg!(--("archive-format") = ["FORMAT"])
+        .value_parser(value_parser!(ArchiveFormat))
+        .help("The format of the archive (default: inferred from the file extension, or jsonl)")
+}
+
 pub fn single_key() -> Arg {
     arg!(key =["KEY"] required ).help("The key of the metadata key-value pair")
 }
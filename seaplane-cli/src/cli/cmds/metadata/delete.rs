@@ -1,14 +1,25 @@
 use clap::{ArgMatches, Command};
+use seaplane::api::{
+    metadata::v1::DeleteReport,
+    shared::v1::{Directory, RangeQueryContext},
+};
 use serde_json::json;
 
 use crate::{
     api::MetadataReq,
     cli::cmds::metadata::{common, common::SeaplaneMetadataCommonArgMatches, CliCommand},
     context::{Ctx, MetadataCtx},
-    error::Result,
-    printer::OutputFormat,
+    error::{CliErrorKind, Result},
+    printer::{OutputFormat, Pb},
 };
 
+static LONG_ABOUT: &str = "Delete one or more metadata key-value pairs
+
+With --recursive, each KEY is a directory and every key-value pair in it is deleted. The keys
+which will be deleted are listed first and must be confirmed, unless --yes is used. Several keys
+are deleted at the same time, and a key which can't be deleted doesn't stop the others from being
+deleted.";
+
 #[derive(Copy, Clone, Debug)]
 pub struct SeaplaneMetadataDelete;
 
@@ -17,12 +28,26 @@ impl SeaplaneMetadataDelete {
         Command::new("delete")
             .visible_aliases(["del", "remove", "rm"])
             .about("Delete one or more metadata key-value pairs")
+            .long_about(LONG_ABOUT)
             .args(common::args())
+            .arg(
+                arg!(--recursive - ('r'))
+                    .help("Delete every key-value pair in the directories given as KEY"),
+            )
+            .arg(
+                arg!(--yes - ('y'))
+                    .requires("recursive")
+                    .help("Delete recursively without asking for confirmation"),
+            )
     }
 }
 
 impl CliCommand for SeaplaneMetadataDelete {
     fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        if ctx.md_ctx.get_or_init().recursive {
+            return run_recursive(ctx);
+        }
+
         let mut len = 0;
         let mut req = MetadataReq::new(ctx)?;
         for kv in ctx.md_ctx.get_mut().unwrap().kvs.iter_mut() {
@@ -48,9 +73,124 @@ impl CliCommand for SeaplaneMetadataDelete {
     }
 
     fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
-        ctx.md_ctx
-            .init(MetadataCtx::from_md_common(&SeaplaneMetadataCommonArgMatches(matches))?);
+        let mut mdctx = MetadataCtx::from_md_common(&SeaplaneMetadataCommonArgMatches(matches))?;
+        mdctx.recursive = matches.get_flag("recursive");
+        mdctx.yes = matches.get_flag("yes");
+        ctx.md_ctx.init(mdctx);
         ctx.args.out_format = matches.get_one("format").copied().unwrap_or_default();
         Ok(())
     }
 }
+
+// Deletes every key-value pair in the directories given as keys
+fn run_recursive(ctx: &mut Ctx) -> Result<()> {
+    let mdctx = ctx.md_ctx.get_or_init().clone();
+    let mut req = MetadataReq::new(ctx)?;
+    let dirs: Vec<_> = mdctx
+        .kvs
+        .keys()
+        .map(|dir| {
+            let mut range = RangeQueryContext::new();
+            range.set_directory(Directory::from_encoded(dir.to_string()));
+            range
+        })
+        .collect();
+
+    let report = if mdctx.yes {
+        let pb = Pb::new(ctx);
+        pb.set_message("Removing metadata key-value pairs...");
+        let mut report = DeleteReport::default();
+        for dir in dirs {
+            req.set_dir(dir)?;
+            let removed = req.delete_directory()?;
+            report.deleted.extend(removed.deleted);
+            report.failed.extend(removed.failed);
+        }
+        pb.finish_and_clear();
+        report
+    } else {
+        let pb = Pb::new(ctx);
+        pb.set_message("Listing metadata key-value pairs...");
+        let mut keys = Vec::new();
+        for dir in dirs {
+            req.set_dir(dir)?;
+            keys.extend(req.get_all_pages()?.into_iter().map(|kv| kv.key));
+        }
+        pb.finish_and_clear();
+        if keys.is_empty() {
+            DeleteReport::default()
+        } else {
+            // The confirmation goes to STDERR to keep STDOUT for the results
+            cli_eprintln!("The following key-value pairs will be removed:");
+            for key in &keys {
+                cli_eprintln!("    {key}");
+            }
+            if !confirmed(keys.len())? {
+                cli_eprintln!("Nothing was removed");
+                return Ok(());
+            }
+
+            // Only the listed keys are removed, rather than any added since they were confirmed
+            let pb = Pb::new(ctx);
+            pb.set_message(format!("Removing {} metadata key-value pairs...", keys.len()));
+            let report = req.delete_keys(keys)?;
+            pb.finish_and_clear();
+            report
+        }
+    };
+    let total = report.deleted.len() + report.failed.len();
+
+    if ctx.args.out_format == OutputFormat::Table {
+        for key in &report.deleted {
+            cli_println!("Removed {key}");
+        }
+        for (key, e) in &report.failed {
+            cli_eprint!(@Red, "error: ");
+            cli_eprintln!("failed to remove {key}: {e}");
+        }
+        let len = report.deleted.len();
+        cli_println!("\nSuccessfully removed {len} item{}", if len == 1 { "" } else { "s" });
+    } else {
+        cli_println!(
+            "{}",
+            json!({
+                "removed": report.deleted.iter().map(|k| k.to_string()).collect::<Vec<_>>(),
+                "failed": report
+                    .failed
+                    .iter()
+                    .map(|(k, e)| json!({"key": k.to_string(), "error": e.to_string()}))
+                    .collect::<Vec<_>>(),
+            })
+        );
+    }
+
+    if !report.is_complete() {
+        return Err(CliErrorKind::OneOff(format!(
+            "failed to remove {} of {total} metadata key-value pairs",
+            report.failed.len()
+        ))
+        .into_err());
+    }
+
+    Ok(())
+}
+
+// Asks on STDIN whether to remove the `len` listed key-value pairs, where anything but "y" or
+// "yes" is a no
+fn confirmed(len: usize) -> Result<bool> {
+    cli_eprint!("Remove {len} key-value pair{}? [y/N] ", if len == 1 { "" } else { "s" });
+
+    // We don't want to read from STDIN during tests
+    #[cfg(any(feature = "api_tests", feature = "semantic_ui_tests", feature = "ui_tests"))]
+    let answer = String::new();
+    #[cfg(not(any(feature = "api_tests", feature = "semantic_ui_tests", feature = "ui_tests")))]
+    let answer = {
+        use std::io::BufRead;
+
+        let mut answer = String::new();
+        std::io::stdin().lock().read_line(&mut answer)?;
+        answer
+    };
+
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
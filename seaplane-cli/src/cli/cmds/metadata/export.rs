@@ -0,0 +1,107 @@
+use std::{fs, io::Write};
+
+use clap::{ArgMatches, Command};
+use seaplane::api::shared::v1::{Directory, RangeQueryContext};
+use serde_json::json;
+
+use crate::{
+    api::MetadataReq,
+    cli::{cmds::metadata::common, CliCommand},
+    context::{Ctx, MetadataCtx},
+    error::{CliError, Context, Result},
+    ops::metadata::archive::{ArchiveFormat, ArchiveWriter},
+    printer::{printer, Color, OutputFormat, Pb},
+};
+
+static LONG_ABOUT: &str = "Write metadata key-value pairs to an archive
+
+Every key-value pair in DIR (default: the root directory) is written to a JSON Lines file with one
+{\"key\": KEY, \"value\": VALUE} object per line, or a tar file holding a file named after each key
+which contains its value. The archive can be replayed with 'seaplane metadata import'.
+
+Keys and values are written in URL safe base64 encoded format by default because they may contain
+arbitrary binary data. With --decode they are written as is, which requires keys (and for JSON
+Lines also values) to be valid UTF-8.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneMetadataExport;
+
+impl SeaplaneMetadataExport {
+    pub fn command() -> Command {
+        Command::new("export")
+            .about("Write metadata key-value pairs to an archive")
+            .long_about(LONG_ABOUT)
+            .arg(
+                arg!(dir = ["DIR"])
+                    .help("The root directory of the metadata key-value pairs to export"),
+            )
+            .arg(common::base64())
+            .arg(
+                arg!(--output - ('o') = ["PATH"])
+                    .help("The archive to write, or '-' to write it to STDOUT (default: STDOUT)"),
+            )
+            .arg(common::archive_format())
+            .arg(arg!(--decode - ('D')).help("Decode the keys and values before writing them"))
+    }
+}
+
+impl CliCommand for SeaplaneMetadataExport {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let mdctx = ctx.md_ctx.get_or_init().clone();
+        let path = mdctx.archive.as_deref().filter(|p| *p != "-");
+
+        let pb = Pb::new(ctx);
+        pb.set_message("Exporting metadata key-value pairs...");
+        let mut range = RangeQueryContext::new();
+        if let Some(dir) = &mdctx.directory {
+            range.set_directory(dir.clone());
+        }
+        let mut req = MetadataReq::new(ctx)?;
+        req.set_dir(range)?;
+        let kvs = req.get_all_pages()?;
+
+        let mut archive = ArchiveWriter::new(Vec::new(), mdctx.archive_format(), mdctx.decode);
+        for kv in &kvs {
+            archive.append(kv)?;
+        }
+        let archive = archive.finish()?;
+        pb.finish_and_clear();
+
+        let path = match path {
+            Some(path) => path,
+            None => {
+                let mut ptr = printer();
+                ptr.write_all(&archive)?;
+                ptr.flush()?;
+                return Ok(());
+            }
+        };
+        fs::write(path, archive)
+            .map_err(CliError::from)
+            .context("\n\tpath: ")
+            .with_color_context(|| (Color::Yellow, format!("{path:?}\n")))?;
+
+        if ctx.args.out_format == OutputFormat::Table {
+            cli_print!("Successfully exported {} key-value pair", kvs.len());
+            cli_print!("{} to ", if kvs.len() == 1 { "" } else { "s" });
+            cli_println!(@Green, "{path}");
+        } else {
+            cli_println!("{}", json!({"exported": kvs.len(), "path": path}));
+        }
+
+        Ok(())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        let base64 = matches.get_flag("base64");
+        ctx.md_ctx.init(MetadataCtx {
+            directory: maybe_base64_arg!(matches, "dir", base64).map(Directory::from_encoded),
+            decode: matches.get_flag("decode"),
+            archive: matches.get_one::<String>("output").cloned(),
+            archive_format: matches.get_one::<ArchiveFormat>("archive-format").copied(),
+            ..MetadataCtx::default()
+        });
+        ctx.args.out_format = matches.get_one("format").copied().unwrap_or_default();
+        Ok(())
+    }
+}
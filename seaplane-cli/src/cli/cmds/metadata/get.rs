@@ -11,7 +11,10 @@ use crate::{
 static LONG_ABOUT: &str = "Retrieve a metadata key-value pair
 
 Keys and values will be displayed in base64 encoded format by default because they may contain
-arbitrary binary data. Use --decode to output the decoded values instead.";
+arbitrary binary data. Use --decode to output the decoded values instead.
+
+With --format=json the version of the value is included as well, which can be given to 'seaplane
+metadata set --if-version' to only change the value if nobody else has changed it since.";
 
 #[derive(Copy, Clone, Debug)]
 pub struct SeaplaneMetadataGet;
@@ -40,10 +43,10 @@ impl CliCommand for SeaplaneMetadataGet {
             let mdctx = ctx.md_ctx.get_mut_or_init();
             for kv in mdctx.kvs.iter_mut() {
                 req.set_key(kv.key.to_string())?;
-                kv.set_value(
-                    // The key is already in Base64 so no need to convert
-                    req.get_value()?.to_string(),
-                );
+                let found = req.get_key_value()?;
+                // The key is already in Base64 so no need to convert
+                kv.set_value(found.value.to_string());
+                kv.version = found.version;
             }
 
             mdctx.kvs.clone()
@@ -0,0 +1,239 @@
+use std::{fs::File, io};
+
+use clap::{value_parser, ArgMatches, Command};
+use seaplane::{
+    api::metadata::{
+        error::MetadataError,
+        v1::{Key, Value},
+    },
+    error::SeaplaneError,
+};
+use serde_json::json;
+
+use crate::{
+    api::MetadataReq,
+    cli::{cmds::metadata::common, CliCommand},
+    context::{Ctx, MetadataCtx},
+    error::{CliError, CliErrorKind, Context, Result},
+    ops::metadata::archive::{read_archive, ArchiveFormat, PrefixRewrite},
+    printer::{Color, OutputFormat, Pb},
+};
+
+static LONG_ABOUT: &str = "Write the metadata key-value pairs of an archive to the store
+
+Replays an archive written by 'seaplane metadata export'. Keys which don't exist yet are created
+and keys which hold a different value are updated, unless --skip-existing is used. Keys which
+already hold the value in the archive are skipped. A key which someone else changes while it's being
+imported is left as they changed it and reported as a conflict.
+
+With --prefix-rewrite OLD=NEW, keys which begin with OLD are imported as keys beginning with NEW
+instead, such as to restore a directory into a different one. When more than one rewrite is given,
+the first which matches a key is used.";
+
+/// What importing a single key did, or would do with --dry-run
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Outcome {
+    Created,
+    Updated,
+    /// The key already held the value in the archive
+    Unchanged,
+    /// The key held a different value, which --skip-existing left untouched
+    Existing,
+    /// The key was created or changed by someone else between reading and writing it
+    Conflict,
+}
+
+impl Outcome {
+    fn verb(self, dry_run: bool) -> &'static str {
+        match (self, dry_run) {
+            (Outcome::Created, false) => "Created",
+            (Outcome::Created, true) => "Would create",
+            (Outcome::Updated, false) => "Updated",
+            (Outcome::Updated, true) => "Would update",
+            (Outcome::Conflict, _) => "Conflict",
+            (_, false) => "Skipped",
+            (_, true) => "Would skip",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneMetadataImport;
+
+impl SeaplaneMetadataImport {
+    pub fn command() -> Command {
+        Command::new("import")
+            .about("Write the metadata key-value pairs of an archive to the store")
+            .long_about(LONG_ABOUT)
+            .arg(arg!(file =["FILE"] required).help("The archive to read, or '-' to read it from STDIN"))
+            .arg(common::archive_format())
+            .arg(arg!(--decoded).help("The keys and values in the archive aren't encoded, as written by 'seaplane metadata export --decode'"))
+            .arg(arg!(--("dry-run")).help("Only report what would be changed, without writing anything"))
+            .arg(arg!(--("skip-existing")).help("Leave keys which already exist untouched, even when their value differs"))
+            .arg(
+                arg!(--("prefix-rewrite") =["OLD=NEW"]...)
+                    .value_parser(value_parser!(PrefixRewrite))
+                    .help("Import keys beginning with OLD as keys beginning with NEW instead (may be used multiple times)"),
+            )
+    }
+}
+
+impl CliCommand for SeaplaneMetadataImport {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let mdctx = ctx.md_ctx.get_or_init().clone();
+        let path = mdctx.archive.as_deref().unwrap();
+        let entries = if path == "-" {
+            read_archive(io::stdin().lock(), mdctx.archive_format(), mdctx.decode)?
+        } else {
+            File::open(path)
+                .map_err(CliError::from)
+                .and_then(|f| read_archive(f, mdctx.archive_format(), mdctx.decode))
+                .context("\n\tpath: ")
+                .with_color_context(|| (Color::Yellow, format!("{path:?}\n")))?
+        };
+
+        let total = entries.len();
+        let pb = Pb::with_length(ctx, total as u64);
+        pb.set_message("Importing keys...");
+        let mut req = MetadataReq::new(ctx)?;
+        let mut outcomes = Vec::with_capacity(total);
+        for entry in entries {
+            let key =
+                Key::from_unencoded(PrefixRewrite::rewrite(&mdctx.prefix_rewrites, entry.key));
+            let value = Value::from_unencoded(entry.value);
+            req.set_key(key.encoded())?;
+            let (outcome, version) = match req.get_key_value() {
+                Ok(existing) if existing.value == value => (Outcome::Unchanged, None),
+                Ok(_) if mdctx.skip_existing => (Outcome::Existing, None),
+                Ok(existing) => (Outcome::Updated, existing.version),
+                Err(e) => match e.kind() {
+                    CliErrorKind::Seaplane(SeaplaneError::ApiResponse(ae))
+                        if ae.is_http_not_found() =>
+                    {
+                        (Outcome::Created, None)
+                    }
+                    _ => return Err(e),
+                },
+            };
+            // Only write what was read, so a concurrent change isn't overwritten
+            let written = match outcome {
+                _ if mdctx.dry_run => Ok(()),
+                Outcome::Created => req.put_value_if_absent(value),
+                Outcome::Updated => match version {
+                    Some(version) => req.put_value_if(value, version),
+                    None => Err(SeaplaneError::from(MetadataError::MissingVersion).into()),
+                },
+                _ => Ok(()),
+            };
+            let outcome = match written {
+                Ok(()) => outcome,
+                Err(e) => match e.kind() {
+                    CliErrorKind::Seaplane(SeaplaneError::MetadataRequest(
+                        MetadataError::VersionConflict,
+                    )) => Outcome::Conflict,
+                    _ => return Err(e),
+                },
+            };
+            outcomes.push((key.encoded().to_owned(), outcome));
+            pb.inc(1);
+        }
+        pb.finish_and_clear();
+
+        let keys_of = |wanted: &[Outcome]| -> Vec<&str> {
+            outcomes
+                .iter()
+                .filter(|(_, o)| wanted.contains(o))
+                .map(|(k, _)| k.as_str())
+                .collect()
+        };
+        let created = keys_of(&[Outcome::Created]);
+        let updated = keys_of(&[Outcome::Updated]);
+        let skipped = keys_of(&[Outcome::Unchanged, Outcome::Existing]);
+        let conflicts = keys_of(&[Outcome::Conflict]);
+
+        if ctx.args.out_format == OutputFormat::Json {
+            cli_println!(
+                "{}",
+                json!({
+                    "created": created,
+                    "updated": updated,
+                    "skipped": skipped,
+                    "conflicts": conflicts,
+                    "dry_run": mdctx.dry_run,
+                })
+            );
+            return conflicts_err(conflicts.len());
+        }
+
+        for (key, outcome) in &outcomes {
+            cli_print!("{} {key}", outcome.verb(mdctx.dry_run));
+            match outcome {
+                Outcome::Unchanged => cli_println!(" (unchanged)"),
+                Outcome::Existing => cli_println!(" (already exists)"),
+                Outcome::Conflict => cli_println!(" (changed while importing, left untouched)"),
+                _ => cli_println!(""),
+            }
+        }
+        if mdctx.dry_run {
+            cli_print!("\nDry run, nothing was written: ");
+            cli_println!(
+                "{} to create, {} to update, {} to skip",
+                created.len(),
+                updated.len(),
+                skipped.len()
+            );
+        } else {
+            if conflicts.is_empty() {
+                cli_print!("\nSuccessfully imported ");
+            } else {
+                cli_print!("\nImported ");
+            }
+            cli_print!("{total} key{}: ", if total == 1 { "" } else { "s" });
+            cli_print!(
+                "{} created, {} updated, {} skipped",
+                created.len(),
+                updated.len(),
+                skipped.len()
+            );
+            if conflicts.is_empty() {
+                cli_println!("");
+            } else {
+                cli_println!(", {} conflicting", conflicts.len());
+            }
+        }
+
+        conflicts_err(conflicts.len())
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        ctx.md_ctx.init(MetadataCtx {
+            archive: matches.get_one::<String>("file").cloned(),
+            archive_format: matches.get_one::<ArchiveFormat>("archive-format").copied(),
+            decode: matches.get_flag("decoded"),
+            dry_run: matches.get_flag("dry-run"),
+            skip_existing: matches.get_flag("skip-existing"),
+            prefix_rewrites: matches
+                .get_many::<PrefixRewrite>("prefix-rewrite")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            ..MetadataCtx::default()
+        });
+        ctx.args.out_format = matches.get_one("format").copied().unwrap_or_default();
+        Ok(())
+    }
+}
+
+// Fails the import when any key was changed by someone else while it was being imported
+fn conflicts_err(conflicts: usize) -> Result<()> {
+    if conflicts == 0 {
+        return Ok(());
+    }
+    Err(CliErrorKind::OneOff(format!(
+        "{conflicts} key{} changed while being imported and {} left untouched, import the \
+        archive again to retry",
+        if conflicts == 1 { "" } else { "s" },
+        if conflicts == 1 { "was" } else { "were" },
+    ))
+    .into_err())
+}
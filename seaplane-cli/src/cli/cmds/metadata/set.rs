@@ -1,4 +1,4 @@
-use clap::{ArgMatches, Command};
+use clap::{value_parser, ArgMatches, Command};
 use seaplane::api::metadata::v1::Value;
 
 use crate::{
@@ -9,6 +9,13 @@ use crate::{
     printer::{Output, OutputFormat},
 };
 
+static LONG_ABOUT: &str = "Set a metadata key-value pair
+
+To avoid overwriting a change made by someone else, use --if-version with the version reported by
+'seaplane metadata get KEY --format json' to only set the key if it hasn't changed since it was
+read, or --if-absent to only set the key if it doesn't exist yet. If the key was changed, nothing
+is written and the command fails.";
+
 /// A newtype wrapper to enforce where the ArgMatches came from which reduces errors in checking if
 /// values of arguments were used or not. i.e. `seaplane formation create` may not have the same
 /// arguments as `seaplane account token` even though both produce an `ArgMatches`.
@@ -23,9 +30,20 @@ impl SeaplaneMetadataSet {
         Command::new("set")
             .visible_alias("put")
             .about("Set a metadata key-value pair")
+            .long_about(LONG_ABOUT)
             .arg(common::base64())
             .arg(arg!(key =["KEY"] required ).help("The key to set"))
             .arg(arg!(value =["VALUE"] required ).help("The value (@path will load the value from a path and @- will load the value from STDIN)"))
+            .arg(
+                arg!(--("if-version") =["VERSION"])
+                    .value_parser(value_parser!(u64))
+                    .help("Only set the key if its value is still at this version"),
+            )
+            .arg(
+                arg!(--("if-absent"))
+                    .conflicts_with("if-version")
+                    .help("Only set the key if it doesn't exist yet"),
+            )
     }
 }
 
@@ -33,11 +51,16 @@ impl CliCommand for SeaplaneMetadataSet {
     fn run(&self, ctx: &mut Ctx) -> Result<()> {
         let mut req = MetadataReq::new(ctx)?;
         let mdctx = ctx.md_ctx.get_mut_or_init();
+        let (if_version, if_absent) = (mdctx.if_version, mdctx.if_absent);
         for kv in mdctx.kvs.iter_mut() {
             let key = kv.key.to_string();
-            let value = kv.value.to_string();
+            let value = Value::from_encoded(kv.value.to_string());
             req.set_key(&key)?;
-            req.put_value(Value::from_encoded(value.clone()))?;
+            match (if_version, if_absent) {
+                (Some(version), _) => req.put_value_if(value, version)?,
+                (None, true) => req.put_value_if_absent(value)?,
+                (None, false) => req.put_value(value)?,
+            }
             if ctx.args.out_format == OutputFormat::Table {
                 cli_println!("Success");
             }
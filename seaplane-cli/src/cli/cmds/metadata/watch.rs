@@ -0,0 +1,115 @@
+use std::{ops::ControlFlow, time::Duration};
+
+use clap::{value_parser, ArgMatches, Command};
+use seaplane::api::{
+    metadata::v1::MetadataEvent,
+    shared::v1::{Directory, RangeQueryContext},
+};
+use serde_json::json;
+
+use crate::{
+    api::MetadataReq,
+    cli::{cmds::metadata::common, CliCommand},
+    context::{Ctx, MetadataCtx},
+    error::{CliError, Result},
+    printer::OutputFormat,
+};
+
+static LONG_ABOUT: &str = "Print changes to metadata key-value pairs as they happen
+
+The key-value pairs in DIR (default: the root directory) are checked every --interval seconds, and
+each key which was added, changed or removed since the last check is printed on a line of its own,
+or as a JSON object per line with --format=json. The first check prints every key-value pair which
+exists as added.
+
+A check which fails is tried again at the next interval. Watching continues until interrupted, or
+until several checks in a row have failed.";
+
+#[derive(Copy, Clone, Debug)]
+pub struct SeaplaneMetadataWatch;
+
+impl SeaplaneMetadataWatch {
+    pub fn command() -> Command {
+        Command::new("watch")
+            .about("Print changes to metadata key-value pairs as they happen")
+            .long_about(LONG_ABOUT)
+            .arg(
+                arg!(dir = ["DIR"])
+                    .help("The root directory of the metadata key-value pairs to watch"),
+            )
+            .arg(common::base64())
+            .arg(arg!(--decode - ('D')).help("Decode the keys and values before printing them"))
+            .arg(
+                arg!(--interval = ["SECS"])
+                    .value_parser(value_parser!(u64).range(1..))
+                    .default_value("5")
+                    .help("How often to check for changes"),
+            )
+    }
+}
+
+impl CliCommand for SeaplaneMetadataWatch {
+    fn run(&self, ctx: &mut Ctx) -> Result<()> {
+        let mdctx = ctx.md_ctx.get_or_init().clone();
+        let mut range = RangeQueryContext::new();
+        if let Some(dir) = &mdctx.directory {
+            range.set_directory(dir.clone());
+        }
+        let mut req = MetadataReq::new(ctx)?;
+        req.set_dir(range)?;
+
+        let json = ctx.args.out_format == OutputFormat::Json;
+        req.into_watcher(mdctx.interval)?
+            .watch(|event| {
+                print_event(&event, json, mdctx.decode);
+                ControlFlow::Continue(())
+            })
+            .map_err(CliError::from)
+    }
+
+    fn update_ctx(&self, matches: &ArgMatches, ctx: &mut Ctx) -> Result<()> {
+        let base64 = matches.get_flag("base64");
+        ctx.md_ctx.init(MetadataCtx {
+            directory: maybe_base64_arg!(matches, "dir", base64).map(Directory::from_encoded),
+            decode: matches.get_flag("decode"),
+            interval: Duration::from_secs(*matches.get_one::<u64>("interval").unwrap()),
+            ..MetadataCtx::default()
+        });
+        ctx.args.out_format = matches.get_one("format").copied().unwrap_or_default();
+        Ok(())
+    }
+}
+
+fn print_event(event: &MetadataEvent, json: bool, decode: bool) {
+    let kv = event.key_value();
+    let (key, value) = if decode {
+        // Values may be binary, which shouldn't garble the stream of events
+        (
+            String::from_utf8_lossy(&kv.key.decode()).into_owned(),
+            String::from_utf8_lossy(&kv.value.decode()).into_owned(),
+        )
+    } else {
+        (kv.key.to_string(), kv.value.to_string())
+    };
+
+    if json {
+        let name = match event {
+            MetadataEvent::Added(_) => "added",
+            MetadataEvent::Changed(_) => "changed",
+            MetadataEvent::Removed(_) => "removed",
+        };
+        cli_println!("{}", json!({"event": name, "key": key, "value": value}));
+        return;
+    }
+
+    match event {
+        MetadataEvent::Added(_) => cli_print!(@Green, "Added"),
+        MetadataEvent::Changed(_) => cli_print!(@Yellow, "Changed"),
+        MetadataEvent::Removed(_) => {
+            cli_print!(@Red, "Removed");
+            cli_println!(" {key}");
+            return;
+        }
+    }
+    cli_println!(" {key} {value}");
+}
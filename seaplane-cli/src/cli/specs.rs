@@ -6,9 +6,24 @@ pub const FLIGHT_SPEC: &str = "FLIGHT SPEC
     PATH        := PATH is an existing file with a Flight in JSON format
     -           := STDIN will be read for a Flight in JSON format
     INLINE-SPEC := Comma separated LIST of ATTRIBUTE
-    ATTRIBUTE   := image=IMAGE [ | name=NAME | minimum=NUM | maximum=NUM | api-permission | architecture=ARCH ]
-    NUM         := Positive integer (minimum default is 1 if omitted; maximum default is 'autoscale as needed')
+    ATTRIBUTE   := image=IMAGE [ | name=NAME | minimum=NUM | maximum=NUM | api-permission | architecture=ARCH
+                   | env=KEY=VALUE | env-file=ENV-FILE | secret=KEY=MD-KEY | port=PORT
+                   | health-check=HC-PATH | health-check-interval=SECS | health-check-timeout=SECS
+                   | healthy-threshold=NUM | unhealthy-threshold=NUM | millicpu=NUM | memory-mib=NUM ]
+    NUM         := Positive integer (minimum default is 1 if omitted; maximum default is 'autoscale as needed';
+                   healthy-threshold default is 1; unhealthy-threshold default is 3; millicpu is
+                   thousandths of a CPU and memory-mib is MiB of memory, both default to the runtime's)
     ARCH        := amd64 | arm64
+    KEY         := The name of an environment variable, made of ASCII letters, digits and '_'
+    ENV-FILE    := A file of KEY=VALUE lines, where empty lines and lines starting with '#' are ignored
+    MD-KEY      := A Metadata key holding the value, which must exist when the Flight is planned.
+                   The value is read each time the Formation is launched or updated and only sent
+                   to the Compute API; the local Plan stores the key, never the value itself
+    PORT        := NUM[/PROTOCOL] where PROTOCOL is http (default), tcp or udp. When no port is given
+                   the Flight receives HTTP traffic on the default port
+    HC-PATH     := The HTTP path health checks request on the first port, starting with a '/'. The
+                   first port must be an HTTP one
+    SECS        := Positive integer number of seconds (interval default is 10; timeout default is 5)
 
     NOTE that when using - only one Flight may be provided via STDIN";
 
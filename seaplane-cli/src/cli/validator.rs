@@ -68,6 +68,10 @@ pub fn validate_inline_flight_spec(s: &str) -> StdResult<String, String> {
                 CliErrorKind::InlineFlightInvalidName(s) => {
                     format!("Flight name '{s}' isn't valid")
                 }
+                CliErrorKind::InlineFlightInvalidValue(s) => {
+                    format!("item {s} has an invalid value")
+                }
+                CliErrorKind::Seaplane(e) => e.to_string(),
                 CliErrorKind::ImageReference(e) => format!("invalid IMAGE-SPEC: {e}"),
                 _ => unreachable!(),
             }
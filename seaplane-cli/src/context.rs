@@ -31,7 +31,10 @@ pub use metadata::MetadataCtx;
 pub mod locks;
 pub use locks::LocksCtx;
 pub mod restrict;
-use std::path::{Path, PathBuf};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use clap_complete::Shell;
 use once_cell::unsync::OnceCell;
@@ -95,6 +98,9 @@ pub struct Args {
 
     /// Should we fetch remote refs?
     pub fetch: bool,
+
+    /// Only show what would be changed, without changing anything
+    pub dry_run: bool,
 }
 
 impl Args {
@@ -146,6 +152,9 @@ pub struct Ctx {
     /// The container image registry to infer if not provided
     pub registry: String,
 
+    /// The command used to edit files, which may include arguments such as 'code --wait'
+    pub editor: String,
+
     /// Set the base URL for the request
     pub compute_url: Option<Url>,
     pub identity_url: Option<Url>,
@@ -194,6 +203,7 @@ impl Clone for Ctx {
             did_init: self.did_init,
             disable_pb: self.disable_pb,
             registry: self.registry.clone(),
+            editor: self.editor.clone(),
             compute_url: self.compute_url.clone(),
             identity_url: self.identity_url.clone(),
             metadata_url: self.metadata_url.clone(),
@@ -225,6 +235,9 @@ impl Default for Ctx {
             insecure_urls: false,
             invalid_certs: false,
             registry: DEFAULT_IMAGE_REGISTRY_URL.into(),
+            editor: env::var("VISUAL")
+                .or_else(|_| env::var("EDITOR"))
+                .unwrap_or_else(|_| "vi".into()),
         }
     }
 }
@@ -1,20 +1,23 @@
+use std::{collections::BTreeMap, fs};
 #[cfg(not(any(feature = "ui_tests", feature = "semantic_ui_tests")))]
 use std::{
-    fs,
     io::{self, Read},
     path::Path,
 };
 
 use seaplane::{
-    api::compute::v2::Flight as FlightModel, rexports::container_image_ref::ImageReference,
+    api::compute::v2::{
+        Architecture, EnvValue, Flight as FlightModel, FlightBuilder as FlightModelBuilder,
+        HealthCheck, Port, Resources,
+    },
+    rexports::container_image_ref::ImageReference,
 };
 use serde::Deserialize;
 
-#[cfg(not(any(feature = "ui_tests", feature = "semantic_ui_tests")))]
-use crate::{error::Context, printer::Color};
 use crate::{
-    error::{CliError, CliErrorKind, Result},
+    error::{CliError, CliErrorKind, Context, Result},
     ops::{flight::str_to_image_ref, generate_name, validator::validate_name},
+    printer::Color,
 };
 
 /// Represents the "Source of Truth" i.e. it combines all the CLI options, ENV vars, and config
@@ -29,14 +32,55 @@ pub struct FlightCtx {
     // True if we randomly generated the name. False if the user provided it
     #[serde(skip)]
     pub generated_name: bool,
+    #[serde(default = "default_minimum")]
+    pub minimum: u64,
+    #[serde(default)]
+    pub maximum: Option<u64>,
+    #[serde(default)]
+    pub architecture: Vec<Architecture>,
+    #[serde(default, rename = "api-permission")]
+    pub api_permission: bool,
+    #[serde(default)]
+    pub env: BTreeMap<String, EnvValue>,
+    #[serde(default)]
+    pub ports: Vec<Port>,
+    #[serde(default, rename = "health-check")]
+    pub health_check: Option<HealthCheck>,
+    #[serde(default)]
+    pub resources: Option<Resources>,
 }
 
+fn default_minimum() -> u64 { 1 }
+
 impl FlightCtx {
+    /// A Flight named `name` running `image`, with the default value of every other setting
+    pub fn new<S: Into<String>>(name: S, image: ImageReference) -> Self {
+        Self {
+            image,
+            name_id: Some(name.into()),
+            generated_name: false,
+            minimum: default_minimum(),
+            maximum: None,
+            architecture: Vec::new(),
+            api_permission: false,
+            env: BTreeMap::new(),
+            ports: Vec::new(),
+            health_check: None,
+            resources: None,
+        }
+    }
+
     /// Builds a FlightCtx from a string value using the inline flight spec syntax:
     ///
-    /// name=FOO,image=nginx:latest
+    /// name=FOO,image=nginx:latest,minimum=2,maximum=5,api-permission,architecture=amd64,
+    /// env=KEY=VAL,env-file=PATH,secret=KEY=METADATA-KEY,port=8080,port=5432/tcp,
+    /// health-check=/healthz,health-check-interval=10,health-check-timeout=5,healthy-threshold=1,
+    /// unhealthy-threshold=3,millicpu=500,memory-mib=512
     ///
-    /// Where only image=... is required
+    /// Where only image=... is required, and architecture=..., env=..., env-file=..., secret=...
+    /// and port=... may be given multiple times. Later environment variables replace earlier
+    /// ones of the same name. Any health-check-* or *-threshold item without a
+    /// health-check=PATH item checks the path '/'.
     pub fn from_inline_flight(inline_flight: &str, registry: &str) -> Result<FlightCtx> {
         if inline_flight.contains(' ') {
             return Err(CliErrorKind::InlineFlightHasSpace.into_err());
@@ -64,9 +108,26 @@ impl FlightCtx {
             }};
         }
 
+        // Parses the value of a key=value item, or errors with the whole item
+        macro_rules! parse_value {
+            ($item:expr) => {{
+                parse_item!($item, |v: &str| v.parse().map_err(|_| {
+                    CliErrorKind::InlineFlightInvalidValue($item.to_string()).into_err()
+                }))
+            }};
+        }
+
         let mut image = None;
         let mut generated_name = true;
         let mut name_id = None;
+        let mut minimum = default_minimum();
+        let mut maximum = None;
+        let mut architecture = Vec::new();
+        let mut api_permission = false;
+        let mut env = BTreeMap::new();
+        let mut ports = Vec::new();
+        let mut health_check: Option<HealthCheck> = None;
+        let mut resources: Option<Resources> = None;
 
         for part in parts {
             match part.trim() {
@@ -85,6 +146,60 @@ impl FlightCtx {
                 img if part.starts_with("image") => {
                     image = Some(str_to_image_ref(registry, parse_item!(img)?)?);
                 }
+                min if part.starts_with("minimum") => minimum = parse_value!(min)?,
+                max if part.starts_with("maximum") => maximum = Some(parse_value!(max)?),
+                arch if part.starts_with("architecture") => {
+                    let arch = parse_value!(arch)?;
+                    if !architecture.contains(&arch) {
+                        architecture.push(arch);
+                    }
+                }
+                file if part.starts_with("env-file") => {
+                    for (key, value) in read_env_file(parse_item!(file)?)? {
+                        env.insert(key, EnvValue::Value(value));
+                    }
+                }
+                var if part.starts_with("env") => {
+                    let (key, value) = parse_env_item(var)?;
+                    env.insert(key.into(), EnvValue::Value(value.into()));
+                }
+                secret if part.starts_with("secret") => {
+                    let (key, md_key) = parse_env_item(secret)?;
+                    env.insert(key.into(), EnvValue::Secret { secret: md_key.into() });
+                }
+                port if part.starts_with("port") => ports.push(parse_value!(port)?),
+                interval if part.starts_with("health-check-interval") => {
+                    health_check.get_or_insert_with(Default::default).interval =
+                        parse_value!(interval)?
+                }
+                timeout if part.starts_with("health-check-timeout") => {
+                    health_check.get_or_insert_with(Default::default).timeout =
+                        parse_value!(timeout)?
+                }
+                path if part.starts_with("health-check") => {
+                    health_check.get_or_insert_with(Default::default).path =
+                        parse_item!(path)?.to_string()
+                }
+                healthy if part.starts_with("healthy-threshold") => {
+                    health_check
+                        .get_or_insert_with(Default::default)
+                        .healthy_threshold = parse_value!(healthy)?
+                }
+                unhealthy if part.starts_with("unhealthy-threshold") => {
+                    health_check
+                        .get_or_insert_with(Default::default)
+                        .unhealthy_threshold = parse_value!(unhealthy)?
+                }
+                cpu if part.starts_with("millicpu") => {
+                    resources.get_or_insert_with(Default::default).millicpu =
+                        Some(parse_value!(cpu)?)
+                }
+                mem if part.starts_with("memory-mib") => {
+                    resources.get_or_insert_with(Default::default).memory_mib =
+                        Some(parse_value!(mem)?)
+                }
+                "api-permission" => api_permission = true,
+                perm if part.starts_with("api-permission=") => api_permission = parse_value!(perm)?,
                 _ => {
                     return Err(CliErrorKind::InlineFlightUnknownItem(part.to_string()).into_err());
                 }
@@ -98,11 +213,36 @@ impl FlightCtx {
             name_id = Some(generate_name());
         }
 
-        Ok(FlightCtx { image: image.unwrap(), name_id, generated_name })
+        let flight = FlightCtx {
+            image: image.unwrap(),
+            name_id,
+            generated_name,
+            minimum,
+            maximum,
+            architecture,
+            api_permission,
+            env,
+            ports,
+            health_check,
+            resources,
+        };
+        flight.validate()?;
+        Ok(flight)
     }
 
     /// Try to deserialize a Flight from a JSON string or convert to a CLI Error
-    pub fn from_json(s: &str) -> Result<Self> { serde_json::from_str(s).map_err(CliError::from) }
+    pub fn from_json(s: &str) -> Result<Self> {
+        let flight: Self = serde_json::from_str(s).map_err(CliError::from)?;
+        flight.validate()?;
+        Ok(flight)
+    }
+
+    /// Checks the values are valid for a Flight model, such as the minimum being no greater than
+    /// the maximum
+    pub fn validate(&self) -> Result<()> {
+        self.builder().build().map_err(CliError::from)?;
+        Ok(())
+    }
 
     /// Create from an string which can be a PATH, `-` (STDIN), or the INLINE spec.
     pub fn from_str(flight: &str, registry: &str) -> Result<Vec<Self>> {
@@ -152,25 +292,99 @@ impl FlightCtx {
 
     /// Creates a new seaplane::api::compute::v2::Flight from the contained values
     pub fn model(&self) -> FlightModel {
-        // Create the new Flight model from the CLI inputs
-        let flight_model = FlightModel::builder()
+        // Create a new Flight struct we can add to our local JSON "DB"
+        self.builder()
+            .build()
+            .expect("Failed to build Flight from inputs")
+    }
+
+    // Create the new Flight model builder from the CLI inputs
+    fn builder(&self) -> FlightModelBuilder {
+        let mut builder = FlightModel::builder()
             .name(
                 self.name_id
                     .clone()
                     .or_else(|| Some(generate_name()))
                     .unwrap(),
             )
-            .image_reference(self.image.clone());
+            .image_reference(self.image.clone())
+            .minimum(self.minimum)
+            .api_permission(self.api_permission);
+        if let Some(maximum) = self.maximum {
+            builder = builder.maximum(maximum);
+        }
+        for arch in &self.architecture {
+            builder = builder.add_architecture(*arch);
+        }
+        for (key, value) in &self.env {
+            builder = match value {
+                EnvValue::Value(v) => builder.add_env(key, v),
+                EnvValue::Secret { secret } => builder.add_secret_env(key, secret),
+            };
+        }
+        for port in &self.ports {
+            builder = builder.add_port(*port);
+        }
+        if let Some(health_check) = &self.health_check {
+            builder = builder.health_check(health_check.clone());
+        }
+        if let Some(resources) = self.resources {
+            builder = builder.resources(resources);
+        }
+        builder
+    }
+}
 
-        // Create a new Flight struct we can add to our local JSON "DB"
-        flight_model
-            .build()
-            .expect("Failed to build Flight from inputs")
+// Splits an env=KEY=VAL or secret=KEY=METADATA-KEY item into its KEY and value, where the value may
+// itself contain '='
+fn parse_env_item(item: &str) -> Result<(&str, &str)> {
+    match item
+        .split_once('=')
+        .and_then(|(_, var)| var.split_once('='))
+    {
+        Some((key, value)) if !key.is_empty() => Ok((key, value)),
+        _ => Err(CliErrorKind::InlineFlightMissingValue(item.to_string()).into_err()),
     }
 }
 
+/// Reads the environment variables of a file of KEY=VAL lines. Empty lines and lines starting
+/// with '#' are ignored, as is a leading `export `.
+fn read_env_file(path: &str) -> Result<Vec<(String, String)>> {
+    let contents = fs::read_to_string(path)
+        .map_err(CliError::from)
+        .context("\n\tpath: ")
+        .with_color_context(|| (Color::Yellow, path))?;
+    parse_env_file(&contents)
+        .context("\n\tpath: ")
+        .with_color_context(|| (Color::Yellow, path))
+}
+
+fn parse_env_file(contents: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                vars.push((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => {
+                return Err(CliErrorKind::OneOff(format!(
+                    "'{line}' is not a valid environment variable, which must be KEY=VAL"
+                ))
+                .into_err())
+            }
+        }
+    }
+    Ok(vars)
+}
+
 #[cfg(test)]
 mod tests {
+    use seaplane::api::compute::v2::Protocol;
+
     use super::*;
     use crate::context::DEFAULT_IMAGE_REGISTRY_URL as IR;
 
@@ -178,6 +392,186 @@ mod tests {
     fn from_inline_flight_valid() {
         assert!(FlightCtx::from_inline_flight("image=demos/nginx:latest,name=foo", IR).is_ok());
         assert!(FlightCtx::from_inline_flight("image=demos/nginx:latest", IR).is_ok());
+        assert!(FlightCtx::from_inline_flight(
+            "image=demos/nginx:latest,minimum=2,maximum=5,api-permission,architecture=amd64",
+            IR
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn from_inline_flight_scale() {
+        let flight = FlightCtx::from_inline_flight(
+            "image=demos/nginx:latest,name=foo,minimum=2,maximum=5,api-permission=true,\
+            architecture=amd64,architecture=ARM64,architecture=amd64",
+            IR,
+        )
+        .unwrap();
+        assert_eq!(flight.minimum, 2);
+        assert_eq!(flight.maximum, Some(5));
+        assert!(flight.api_permission);
+        assert_eq!(flight.architecture, vec![Architecture::Amd64, Architecture::Arm64]);
+
+        let model = flight.model();
+        assert_eq!(model.minimum(), 2);
+        assert_eq!(model.maximum(), Some(5));
+        assert!(model.api_permission());
+        assert_eq!(model.architecture(), &[Architecture::Amd64, Architecture::Arm64]);
+
+        let flight = FlightCtx::from_inline_flight("image=demos/nginx:latest", IR).unwrap();
+        assert_eq!(flight.minimum, 1);
+        assert_eq!(flight.maximum, None);
+        assert!(!flight.api_permission);
+        assert!(flight.architecture.is_empty());
+    }
+
+    #[test]
+    fn from_inline_flight_env() {
+        let flight = FlightCtx::from_inline_flight(
+            "image=demos/nginx:latest,env=LOG_LEVEL=info,env=QUERY=a=b,env=EMPTY=,\
+            secret=DB_PASS=app/db-pass,env=LOG_LEVEL=debug",
+            IR,
+        )
+        .unwrap();
+        assert_eq!(flight.env["LOG_LEVEL"], EnvValue::from("debug"));
+        assert_eq!(flight.env["QUERY"], EnvValue::from("a=b"));
+        assert_eq!(flight.env["EMPTY"], EnvValue::from(""));
+        assert_eq!(flight.env["DB_PASS"], EnvValue::Secret { secret: "app/db-pass".into() });
+
+        let model = flight.model();
+        assert_eq!(model.env().len(), 4);
+        assert_eq!(model.secrets().collect::<Vec<_>>(), vec![("DB_PASS", "app/db-pass")]);
+
+        assert_eq!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,env=LOG_LEVEL", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::InlineFlightMissingValue("env=LOG_LEVEL".into())
+        );
+        assert_eq!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,secret==app/db-pass", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::InlineFlightMissingValue("secret==app/db-pass".into())
+        );
+        assert!(matches!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,env=LOG-LEVEL=info", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::Seaplane(_)
+        ));
+    }
+
+    #[test]
+    fn from_inline_flight_workload() {
+        let flight = FlightCtx::from_inline_flight(
+            "image=demos/nginx:latest,port=9090,port=5432/tcp,health-check=/healthz,\
+            health-check-interval=30,unhealthy-threshold=5,millicpu=500,memory-mib=1024",
+            IR,
+        )
+        .unwrap();
+        assert_eq!(
+            flight.ports,
+            vec![Port::new(9090, Protocol::Http), Port::new(5432, Protocol::Tcp)]
+        );
+        assert_eq!(
+            flight.health_check,
+            Some(HealthCheck {
+                interval: 30,
+                unhealthy_threshold: 5,
+                ..HealthCheck::new("/healthz")
+            })
+        );
+        assert_eq!(
+            flight.resources,
+            Some(Resources { millicpu: Some(500), memory_mib: Some(1024) })
+        );
+        assert_eq!(flight.model().ports().len(), 2);
+
+        // Health check settings without a path check '/'
+        let flight =
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,health-check-timeout=1", IR)
+                .unwrap();
+        assert_eq!(flight.health_check, Some(HealthCheck { timeout: 1, ..Default::default() }));
+
+        assert_eq!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,port=53/sctp", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::InlineFlightInvalidValue("port=53/sctp".into())
+        );
+        assert!(matches!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,health-check=healthz", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::Seaplane(_)
+        ));
+        assert!(matches!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,port=80,port=80", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::Seaplane(_)
+        ));
+    }
+
+    #[test]
+    fn env_file() {
+        let vars =
+            parse_env_file("# comment\n\nLOG_LEVEL=info\nexport QUERY = a=b \n  EMPTY=\n").unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("LOG_LEVEL".to_string(), "info".to_string()),
+                ("QUERY".to_string(), "a=b".to_string()),
+                ("EMPTY".to_string(), "".to_string()),
+            ]
+        );
+        assert!(parse_env_file("LOG_LEVEL").is_err());
+        assert!(parse_env_file("=info").is_err());
+    }
+
+    #[test]
+    fn from_json_scale() {
+        let flight = FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","maximum":3,"architecture":["arm64"],"api-permission":true}"#,
+        )
+        .unwrap();
+        assert_eq!(flight.minimum, 1);
+        assert_eq!(flight.maximum, Some(3));
+        assert_eq!(flight.architecture, vec![Architecture::Arm64]);
+        assert!(flight.api_permission);
+
+        assert!(FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","minimum":4,"maximum":3}"#
+        )
+        .is_err());
+        assert!(FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","architecture":["x86"]}"#
+        )
+        .is_err());
+
+        let flight = FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","env":{"LOG_LEVEL":"info","DB_PASS":{"secret":"app/db-pass"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(flight.env["LOG_LEVEL"], EnvValue::from("info"));
+        assert_eq!(flight.env["DB_PASS"], EnvValue::Secret { secret: "app/db-pass".into() });
+
+        let flight = FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","ports":[{"port":80},{"port":53,"protocol":"udp"}],"health-check":{"path":"/healthz"},"resources":{"memory-mib":256}}"#,
+        )
+        .unwrap();
+        assert_eq!(flight.ports, vec![Port::new(80, Protocol::Http), Port::new(53, Protocol::Udp)]);
+        assert_eq!(flight.health_check, Some(HealthCheck::new("/healthz")));
+        assert_eq!(flight.resources, Some(Resources { millicpu: None, memory_mib: Some(256) }));
+        assert!(FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","resources":{"millicpu":0}}"#
+        )
+        .is_err());
+        assert!(FlightCtx::from_json(
+            r#"{"name":"foo","image":"foo.com/demos/nginx:latest","ports":[{"port":53,"protocol":"udp"}],"health-check":{"path":"/healthz"}}"#
+        )
+        .is_err());
     }
 
     #[test]
@@ -236,5 +630,35 @@ mod tests {
                 .kind(),
             &CliErrorKind::InlineFlightMissingValue("image".into())
         );
+        assert_eq!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,minimum=two", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::InlineFlightInvalidValue("minimum=two".into())
+        );
+        assert_eq!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,architecture=x86", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::InlineFlightInvalidValue("architecture=x86".into())
+        );
+        assert_eq!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,api-permission=yes", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::InlineFlightInvalidValue("api-permission=yes".into())
+        );
+        assert!(matches!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,minimum=3,maximum=2", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::Seaplane(_)
+        ));
+        assert!(matches!(
+            FlightCtx::from_inline_flight("image=demos/nginx:latest,minimum=0", IR)
+                .unwrap_err()
+                .kind(),
+            &CliErrorKind::Seaplane(_)
+        ));
     }
 }
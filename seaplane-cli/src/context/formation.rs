@@ -1,9 +1,20 @@
-use seaplane::api::compute::v2::Formation as FormationModel;
+use std::{collections::BTreeSet, time::Duration};
+
+use seaplane::{
+    api::{
+        compute::v2::Formation as FormationModel,
+        shared::v1::{Provider as ProviderModel, Region as RegionModel},
+    },
+    rexports::container_image_ref::ImageReference,
+};
 
 use crate::{
     context::flight::FlightCtx,
     error::{CliError, CliErrorKind, Context, Result},
-    ops::{formation::FormationNameId, generate_name},
+    ops::{
+        formation::{document::DocumentFormat, FormationNameId},
+        generate_name,
+    },
     printer::Color,
 };
 
@@ -32,8 +43,31 @@ pub struct FormationCtx {
     pub local: bool,
     pub flights: Vec<FlightCtx>,
     pub gateway_flight: Option<String>,
+    // Where the Flights may or may not run
+    pub regions_allowed: BTreeSet<RegionModel>,
+    pub regions_denied: BTreeSet<RegionModel>,
+    pub providers_allowed: BTreeSet<ProviderModel>,
+    pub providers_denied: BTreeSet<ProviderModel>,
     // Used internally to pass already gathered DB indices between operations
     pub indices: Option<Vec<usize>>,
+    // Keep re-displaying the status of Formations
+    pub watch: bool,
+    // How long to wait for Formations to become healthy, if waiting at all
+    pub wait_healthy: Option<Duration>,
+    // How often to check the status of Formations when watching or waiting
+    pub interval: Duration,
+    // A Formation document to read the Formation from, or '-' for STDIN
+    pub file: Option<String>,
+    // The format of the Formation document, if not implied by its file extension
+    pub file_format: Option<DocumentFormat>,
+    // The names of Flights to remove from an existing Formation Plan
+    pub remove_flights: Vec<String>,
+    // New images for Flights of an existing Formation Plan, by Flight name
+    pub set_images: Vec<(String, ImageReference)>,
+    // The percentage of traffic to roll a new configuration out to
+    pub traffic_weight: u8,
+    // The revision to roll a Formation Instance back to, if not the last known good one
+    pub revision: Option<u32>,
 }
 
 impl Default for FormationCtx {
@@ -46,6 +80,19 @@ impl Default for FormationCtx {
             flights: Vec::new(),
             indices: None,
             gateway_flight: None,
+            regions_allowed: BTreeSet::new(),
+            regions_denied: BTreeSet::new(),
+            providers_allowed: BTreeSet::new(),
+            providers_denied: BTreeSet::new(),
+            watch: false,
+            wait_healthy: None,
+            interval: Duration::from_secs(5),
+            file: None,
+            file_format: None,
+            remove_flights: Vec::new(),
+            set_images: Vec::new(),
+            traffic_weight: 100,
+            revision: None,
         }
     }
 }
@@ -70,6 +117,19 @@ impl FormationCtx {
             f_model = f_model.gateway_flight(gw);
         }
 
+        for region in &self.regions_allowed {
+            f_model = f_model.add_allowed_region(*region);
+        }
+        for region in &self.regions_denied {
+            f_model = f_model.add_denied_region(*region);
+        }
+        for provider in &self.providers_allowed {
+            f_model = f_model.add_allowed_provider(*provider);
+        }
+        for provider in &self.providers_denied {
+            f_model = f_model.add_denied_provider(*provider);
+        }
+
         // TODO: probably match and check errors
         f_model.build().map_err(Into::into)
     }
@@ -1,6 +1,7 @@
 use std::{
     fs::File,
     io::{self, Read},
+    time::Duration,
 };
 
 use base64::{
@@ -12,7 +13,10 @@ use seaplane::api::{metadata::v1::Key, shared::v1::Directory};
 use crate::{
     cli::cmds::metadata::{SeaplaneMetadataCommonArgMatches, SeaplaneMetadataSetArgMatches},
     error::{CliError, Context, Result},
-    ops::metadata::{KeyValue, KeyValues},
+    ops::metadata::{
+        archive::{ArchiveFormat, PrefixRewrite},
+        KeyValue, KeyValues,
+    },
     printer::Color,
 };
 
@@ -44,9 +48,36 @@ pub struct MetadataCtx {
     pub keys_width_limit: usize,
     /// Max width of values
     pub values_width_limit: usize,
+    /// Only write the key if its value is still at this version
+    pub if_version: Option<u64>,
+    /// Only write the key if it doesn't exist yet
+    pub if_absent: bool,
+    /// The archive to export to or import from, where `-` is STDOUT or STDIN
+    pub archive: Option<String>,
+    /// The format of the archive, when it isn't implied by its file extension
+    pub archive_format: Option<ArchiveFormat>,
+    /// Only report what an import would change
+    pub dry_run: bool,
+    /// Leave keys which already exist untouched during an import
+    pub skip_existing: bool,
+    /// Rewrites of the beginning of keys during an import
+    pub prefix_rewrites: Vec<PrefixRewrite>,
+    /// The keys are directories, everything in which is deleted
+    pub recursive: bool,
+    /// Delete recursively without asking for confirmation
+    pub yes: bool,
+    /// How often to check for changes when watching
+    pub interval: Duration,
 }
 
 impl MetadataCtx {
+    /// The format of the archive given by `--archive-format`, or implied by its file extension
+    pub fn archive_format(&self) -> ArchiveFormat {
+        self.archive_format
+            .or_else(|| self.archive.as_deref().and_then(ArchiveFormat::from_path))
+            .unwrap_or_default()
+    }
+
     /// Builds a MetadataCtx from ArgMatches
     pub fn from_md_common(matches: &SeaplaneMetadataCommonArgMatches) -> Result<MetadataCtx> {
         let matches = matches.0;
@@ -116,6 +147,12 @@ impl MetadataCtx {
         let mut kvs = KeyValues::default();
         kvs.push(kv);
 
-        Ok(MetadataCtx { kvs, base64: true, ..MetadataCtx::default() })
+        Ok(MetadataCtx {
+            kvs,
+            base64: true,
+            if_version: matches.get_one::<u64>("if-version").copied(),
+            if_absent: matches.get_flag("if-absent"),
+            ..MetadataCtx::default()
+        })
     }
 }
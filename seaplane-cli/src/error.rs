@@ -6,6 +6,7 @@ use std::{
 };
 
 use seaplane::{
+    api::metadata::error::MetadataError,
     error::SeaplaneError,
     rexports::{container_image_ref::ImageReferenceError, seaplane_oid::error::Error as OidError},
 };
@@ -187,6 +188,7 @@ macro_rules! impl_err {
 // about
 impl_err!(base64::DecodeError, Base64Decode);
 impl_err!(serde_json::Error, SerdeJson);
+impl_err!(serde_yaml::Error, SerdeYaml);
 impl_err!(@boxed, toml::de::Error, TomlDe);
 impl_err!(@boxed, toml::ser::Error, TomlSer);
 impl_err!(seaplane::error::SeaplaneError, Seaplane);
@@ -246,6 +248,7 @@ pub enum CliErrorKind {
     ImageReference(ImageReferenceError),
     InlineFlightHasSpace,
     InlineFlightInvalidName(String),
+    InlineFlightInvalidValue(String),
     InlineFlightMissingImage,
     InlineFlightMissingValue(String),
     InlineFlightUnknownItem(String),
@@ -262,6 +265,7 @@ pub enum CliErrorKind {
     PermissionDenied,
     Seaplane(SeaplaneError),
     SerdeJson(serde_json::Error),
+    SerdeYaml(serde_yaml::Error),
     StrumParse(strum::ParseError),
     TomlDe(Box<toml::de::Error>),
     TomlSer(Box<toml::ser::Error>),
@@ -349,6 +353,9 @@ impl CliErrorKind {
             SerdeJson(e) => {
                 cli_eprintln!("json: {e}")
             }
+            SerdeYaml(e) => {
+                cli_eprintln!("yaml: {e}")
+            }
             TomlDe(e) => {
                 cli_eprintln!("toml: {e}")
             }
@@ -391,6 +398,12 @@ impl CliErrorKind {
                 SeaplaneError::ApiResponse(ae) => {
                     cli_eprintln!("{ae}");
                 }
+                SeaplaneError::MetadataRequest(MetadataError::VersionConflict) => {
+                    cli_eprintln!("{e}");
+                    cli_eprint!("(hint: read the current value and its version with '");
+                    cli_eprint!(@Yellow, "seaplane metadata get KEY --format json");
+                    cli_eprintln!("' and try again)");
+                }
                 _ => {
                     cli_eprintln!("Seaplane API: {e}")
                 }
@@ -400,12 +413,18 @@ impl CliErrorKind {
             }
             InlineFlightUnknownItem(item) => {
                 cli_eprintln!(
-                    "{item} is not a valid INLINE-FLIGHT-SPEC item (valid keys are: name, image)"
+                    "{item} is not a valid INLINE-FLIGHT-SPEC item (valid keys are: name, image, \
+                    minimum, maximum, api-permission, architecture, env, env-file, secret, port, \
+                    health-check, health-check-interval, health-check-timeout, healthy-threshold, \
+                    unhealthy-threshold, millicpu, memory-mib)"
                 );
             }
             InlineFlightInvalidName(name) => {
                 cli_eprintln!("'{name}' is not a valid Flight name");
             }
+            InlineFlightInvalidValue(item) => {
+                cli_eprintln!("INLINE-FLIGHT-SPEC item {item} does not have a valid value");
+            }
             InlineFlightHasSpace => {
                 cli_eprintln!("INLINE-FLIGHT-SPEC contains a space ' ' which isn't allowed.");
             }
@@ -452,6 +471,7 @@ impl PartialEq<Self> for CliErrorKind {
             MultipleAtStdin => matches!(rhs, MultipleAtStdin),
             Seaplane(_) => matches!(rhs, Seaplane(_)),
             SerdeJson(_) => matches!(rhs, SerdeJson(_)),
+            SerdeYaml(_) => matches!(rhs, SerdeYaml(_)),
             TomlSer(_) => matches!(rhs, TomlSer(_)),
             TomlDe(_) => matches!(rhs, TomlDe(_)),
             Unknown => matches!(rhs, Unknown),
@@ -467,6 +487,7 @@ impl PartialEq<Self> for CliErrorKind {
             ConflictingArguments(_, _) => matches!(rhs, ConflictingArguments(_, _)),
             InlineFlightUnknownItem(_) => matches!(rhs, InlineFlightUnknownItem(_)),
             InlineFlightInvalidName(_) => matches!(rhs, InlineFlightInvalidName(_)),
+            InlineFlightInvalidValue(_) => matches!(rhs, InlineFlightInvalidValue(_)),
             InlineFlightHasSpace => matches!(rhs, InlineFlightHasSpace),
             InlineFlightMissingImage => matches!(rhs, InlineFlightMissingImage),
             InlineFlightMissingValue(_) => matches!(rhs, InlineFlightMissingValue(_)),
@@ -390,36 +390,14 @@ macro_rules! maybe_base64_arg {
     }};
 }
 
-/// Performs the wrapped method request against the SDK API. If the response is that the access
-/// token is expired, it will refresh the access token and try again. All other errors are mapped
+/// Performs the wrapped method request against the SDK API, building the request first if
+/// required. Expired access tokens are refreshed by the SDK, so all that remains is mapping errors
 /// to the CliError type.
-macro_rules! maybe_retry {
+macro_rules! call_inner {
     ($this:ident . $fn:ident ( $($arg:expr),* ) ) => {{
         if $this.inner.is_none() {
             $this.refresh_inner()?;
         }
-        let req = &mut $this.inner.as_mut().unwrap();
-
-        let res = match req.$fn($( $arg ),*) {
-            Ok(ret) => Ok(ret),
-            Err(SeaplaneError::ApiResponse(ae))
-            if ae.is_http_unauthorized() => {
-                $this.token = Some(request_token(
-                        &$this.api_key,
-                        $this.identity_url.as_ref(),
-                        $this.insecure_urls,
-                        $this.invalid_certs)?);
-                Ok(req.$fn($( $arg ,)*)?)
-            }
-            Err(e) => Err(e),
-        };
-        res.map_err(CliError::from)
-    }};
-}
-
-/// Same as maybe_retry but will clone the function arguments
-macro_rules! maybe_retry_cloned {
-    ($this:ident . $fn:ident ( $($arg:expr),* ) ) => {{
-        maybe_retry!($this.$fn($( $arg.clone() ),*))
+        $this.inner.as_mut().unwrap().$fn($( $arg ),*).map_err(CliError::from)
     }};
 }
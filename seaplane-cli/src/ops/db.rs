@@ -6,7 +6,10 @@ use crate::{
     context::Ctx,
     error::Result,
     fs::{FromDisk, ToDisk},
-    ops::{formation::Formations, state_version::StateVersion},
+    ops::{
+        formation::{history::FormationHistory, Formations},
+        state_version::StateVersion,
+    },
 };
 
 /// The in memory "Databases"
@@ -23,6 +26,10 @@ pub struct Db {
     #[serde(default)]
     pub formations: Formations,
 
+    /// Earlier configurations of remote Formation Instances, which they can be rolled back to
+    #[serde(default)]
+    pub formation_history: FormationHistory,
+
     /// A *hint* that we should persist at some point. Not gospel
     #[serde(skip)]
     pub needs_persist: bool,
@@ -1,11 +1,17 @@
 use seaplane::{
-    api::compute::v2::FlightId,
+    api::{
+        compute::v2::{Architecture, FlightId, Formation as FormationModel},
+        metadata::v1::Key,
+    },
     rexports::container_image_ref::{ImageReference, ImageReferenceError},
 };
 
 use crate::{
-    error::{CliError, Result},
+    api::MetadataReq,
+    context::Ctx,
+    error::{CliError, Context, Result},
     ops::NameId,
+    printer::Color,
 };
 
 pub type FlightNameId = NameId<FlightId>;
@@ -21,3 +27,58 @@ pub fn str_to_image_ref(registry: &str, image_str: &str) -> Result<ImageReferenc
         Err(e) => Err(CliError::from(e)),
     }
 }
+
+/// Checks the Metadata key referenced by each secret environment variable of the Formation can be
+/// read, so that a missing key is reported when the Formation is planned rather than when it's
+/// launched.
+pub fn check_secrets(ctx: &Ctx, model: &FormationModel) -> Result<()> {
+    if model.flights.iter().all(|f| f.secrets().next().is_none()) {
+        return Ok(());
+    }
+    resolve_secrets(&mut MetadataReq::new(ctx)?, model).map(drop)
+}
+
+/// Returns a copy of the Formation in which each secret environment variable has the value of the
+/// Metadata key it references, to be sent to the Compute API. Only the copy holds the values, so
+/// they never land in the local state in clear text.
+pub fn resolve_secrets(req: &mut MetadataReq, model: &FormationModel) -> Result<FormationModel> {
+    let mut resolved = model.clone();
+    resolved.resolve_secrets(|flight, var, key| {
+        req.set_key(Key::from_unencoded(key).encoded())?;
+        req.get_value()
+            .and_then(|value| String::from_utf8(value.decode()).map_err(CliError::from))
+            .context("\n\tsecret: ")
+            .with_color_context(|| (Color::Yellow, format!("{var}={key}")))
+            .context(" of Flight ")
+            .with_color_context(|| (Color::Yellow, format!("{flight}\n")))
+    })?;
+    Ok(resolved)
+}
+
+/// The column headers of [`flight_details`]
+pub const FLIGHT_DETAIL_HEADERS: [&str; 4] = ["MIN", "MAX", "ARCH", "API"];
+
+/// The scaling limits, architectures and API permission of a Flight as they're displayed in tables
+pub fn flight_details(
+    minimum: u64,
+    maximum: Option<u64>,
+    architecture: &[Architecture],
+    api_permission: bool,
+) -> [String; 4] {
+    [
+        minimum.to_string(),
+        maximum
+            .map(|max| max.to_string())
+            .unwrap_or_else(|| "auto".into()),
+        if architecture.is_empty() {
+            "any".into()
+        } else {
+            architecture
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        },
+        api_permission.to_string(),
+    ]
+}
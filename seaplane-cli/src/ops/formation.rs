@@ -1,4 +1,7 @@
-mod status;
+pub mod diff;
+pub mod document;
+pub mod history;
+pub mod status;
 
 use std::io::Write;
 
@@ -9,7 +12,11 @@ use tabwriter::TabWriter;
 use crate::{
     context::{Ctx, FormationCtx},
     error::{CliError, Result},
-    ops::{formation::status::FormationStatus, NameId},
+    ops::{
+        flight::{flight_details, FLIGHT_DETAIL_HEADERS},
+        formation::status::FormationStatus,
+        NameId,
+    },
     printer::Output,
 };
 
@@ -120,26 +127,21 @@ impl Formations {
             .any(|f| f.model.flights.iter().any(|f| f.name == flight))
     }
 
-    /// Either updates a matching local Formations, or creates a new one
-    pub fn create_or_update(&mut self, formation: FormationModel) {
-        if let Some(idx) = self.inner.iter().enumerate().find_map(|(i, f)| {
-            if f.model.oid.is_some() && f.model.oid == formation.oid {
-                Some(i)
-            } else {
-                None
-            }
-        }) {
-            self.inner.swap_remove(idx);
-            self.inner
-                .push(Formation { model: formation, deployed: true, local: true });
-        } else if let Some(idx) = self.inner.iter().enumerate().find_map(|(i, f)| {
-            if f.model.name == formation.name {
-                Some(i)
-            } else {
-                None
-            }
-        }) {
-            self.inner.swap_remove(idx);
+    /// Either updates a matching local Formations, or creates a new one. The secret references of
+    /// the local Formation are kept in place of the values the Compute API returns for them.
+    pub fn create_or_update(&mut self, mut formation: FormationModel) {
+        let idx = self
+            .inner
+            .iter()
+            .position(|f| f.model.oid.is_some() && f.model.oid == formation.oid)
+            .or_else(|| {
+                self.inner
+                    .iter()
+                    .position(|f| f.model.name == formation.name)
+            });
+        if let Some(idx) = idx {
+            let old = self.inner.swap_remove(idx);
+            formation.restore_secrets(&old.model);
             self.inner
                 .push(Formation { model: formation, deployed: true, local: true });
         }
@@ -180,7 +182,14 @@ impl Formations {
         None
     }
 
-    pub fn statuses(&self) -> Vec<FormationStatus> { self.inner.iter().map(Into::into).collect() }
+    /// The statuses of the Formations at the given indices
+    pub fn statuses(&self, indices: &[usize]) -> Vec<FormationStatus> {
+        indices
+            .iter()
+            .filter_map(|&i| self.get(i))
+            .map(Into::into)
+            .collect()
+    }
 
     pub fn index_of(&self, name_id: &FormationNameId) -> Option<usize> {
         self.inner
@@ -225,10 +234,9 @@ impl Output for Formations {
     fn print_table(&self, _ctx: &Ctx) -> Result<()> {
         let buf = Vec::new();
         let mut tw = TabWriter::new(buf);
-        writeln!(tw, "NAME\tLOCAL\tDEPLOYED\tOID")?;
+        writeln!(tw, "NAME\tLOCAL\tDEPLOYED\tOID\tFLIGHT\t{}", FLIGHT_DETAIL_HEADERS.join("\t"))?;
         for formation in &self.inner {
-            writeln!(
-                tw,
+            let formation_cols = format!(
                 "{}\t{:?}\t{:?}\t{}",
                 formation.model.name,
                 formation.local,
@@ -238,7 +246,26 @@ impl Output for Formations {
                     .oid
                     .map(|oid| oid.to_string())
                     .unwrap_or_else(String::new),
-            )?;
+            );
+            if formation.model.flights.is_empty() {
+                writeln!(tw, "{formation_cols}")?;
+            }
+            // Each Flight is on its own row, with the Formation only named on the first of them
+            for (i, flight) in formation.model.flights.iter().enumerate() {
+                let details = flight_details(
+                    flight.minimum,
+                    flight.maximum,
+                    &flight.architecture,
+                    flight.api_permission,
+                );
+                writeln!(
+                    tw,
+                    "{}\t{}\t{}",
+                    if i == 0 { formation_cols.as_str() } else { "\t\t\t" },
+                    flight.name,
+                    details.join("\t")
+                )?;
+            }
         }
         tw.flush()?;
 
@@ -267,3 +294,25 @@ impl<'a> From<&'a FormationCtx> for Formation {
         Formation { model: model.clone(), local: true, deployed: model.oid.is_some() }
     }
 }
+
+/// The names of the Flights which differ between two configurations of a Formation
+#[derive(Debug, Default)]
+pub struct FlightChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl FlightChanges {
+    /// Prints each added, removed or changed Flight on its own indented line
+    pub fn print(&self) {
+        for (verb, flights) in
+            [("Added", &self.added), ("Removed", &self.removed), ("Changed", &self.changed)]
+        {
+            for flight in flights {
+                cli_print!("    {verb} Flight ");
+                cli_println!(@Green, "{flight}");
+            }
+        }
+    }
+}
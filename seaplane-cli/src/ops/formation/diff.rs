@@ -0,0 +1,408 @@
+//! The diff module compares a local Formation Plan to its remote Formation Instance and prints the
+//! differences as a unified view that looks like:
+//!
+//! ```not_rust
+//! --- remote Formation Instance stubb (frm-5wacbutjwbdexonddvdb2lnyxu)
+//! +++ local Formation Plan stubb
+//! - gateway-flight: flask
+//! + gateway-flight: pequod
+//! - regions-allowed: any
+//! + regions-allowed: XE,XN
+//! + flight pequod
+//! +     image: registry.cplane.cloud/seaplane-demo/pequod:latest
+//! - flight ahab
+//! -     image: registry.cplane.cloud/seaplane-demo/ahab:latest
+//!   flight flask
+//! -     image: registry.cplane.cloud/seaplane-demo/flask:v1
+//! +     image: registry.cplane.cloud/seaplane-demo/flask:v2
+//! ```
+//!
+//! Removed lines are colored red, and added lines green.
+use reqwest::Url;
+use seaplane::api::compute::v2::{Flight as FlightModel, Formation as FormationModel, FormationId};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{context::Ctx, error::Result, ops::formation::FlightChanges, printer::Output};
+
+/// A value which differs between the local Plan and the remote Instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    pub local: T,
+    pub remote: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn of(local: T, remote: T) -> Option<Self> {
+        if local == remote {
+            None
+        } else {
+            Some(Self { local, remote })
+        }
+    }
+}
+
+/// A field of a Formation or Flight which differs between the local Plan and the remote Instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub local: Value,
+    pub remote: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum FlightDiff {
+    /// The Flight is only in the local Plan
+    Added { name: String, image: String },
+    /// The Flight is only in the remote Instance
+    Removed { name: String, image: String },
+    /// The Flight is in both, but some of its fields differ
+    Changed { name: String, fields: Vec<FieldChange> },
+}
+
+/// All differences between a local Formation Plan and its remote Formation Instance
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct FormationDiff {
+    pub name: String,
+    pub oid: Option<FormationId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gateway_flight: Option<Change<Option<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<Change<Option<Url>>>,
+    /// Where the Flights may run
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub placement: Vec<FieldChange>,
+    pub flights: Vec<FlightDiff>,
+}
+
+impl FormationDiff {
+    pub fn between(local: &FormationModel, remote: &FormationModel) -> Self {
+        let mut flights = Vec::new();
+        for flight in &local.flights {
+            match remote.flights.iter().find(|f| f.name == flight.name) {
+                None => flights.push(FlightDiff::Added {
+                    name: flight.name.clone(),
+                    image: flight.image_str(),
+                }),
+                Some(remote_flight) => {
+                    let fields = field_changes(flight, remote_flight);
+                    if !fields.is_empty() {
+                        flights.push(FlightDiff::Changed { name: flight.name.clone(), fields });
+                    }
+                }
+            }
+        }
+        for flight in &remote.flights {
+            if !local.flights.iter().any(|f| f.name == flight.name) {
+                flights.push(FlightDiff::Removed {
+                    name: flight.name.clone(),
+                    image: flight.image_str(),
+                });
+            }
+        }
+
+        Self {
+            name: local.name.clone(),
+            oid: remote.oid,
+            gateway_flight: Change::of(local.gateway_flight.clone(), remote.gateway_flight.clone()),
+            url: Change::of(local.url.clone(), remote.url.clone()),
+            placement: placement_changes(local, remote),
+            flights,
+        }
+    }
+
+    /// Returns true if the local Plan and the remote Instance are the same
+    pub fn is_empty(&self) -> bool {
+        self.gateway_flight.is_none()
+            && self.url.is_none()
+            && self.placement.is_empty()
+            && self.flights.is_empty()
+    }
+
+    /// Copies the IDs and status of any Flights which this diff shows were not changed from `old`
+    /// to `new`
+    pub fn keep_flight_ids(&self, old: &FormationModel, new: &mut FormationModel) {
+        for flight in new.flights.iter_mut() {
+            let changed = self.flights.iter().any(|f| match f {
+                FlightDiff::Added { name, .. } | FlightDiff::Changed { name, .. } => {
+                    name == &flight.name
+                }
+                FlightDiff::Removed { .. } => false,
+            });
+            if changed {
+                continue;
+            }
+            if let Some(old_flight) = old.flights.iter().find(|f| f.name == flight.name) {
+                flight.oid = old_flight.oid;
+                flight.status = old_flight.status;
+            }
+        }
+    }
+}
+
+impl From<&FormationDiff> for FlightChanges {
+    fn from(diff: &FormationDiff) -> Self {
+        let mut changes = Self::default();
+        for flight in &diff.flights {
+            match flight {
+                FlightDiff::Added { name, .. } => changes.added.push(name.clone()),
+                FlightDiff::Removed { name, .. } => changes.removed.push(name.clone()),
+                FlightDiff::Changed { name, .. } => changes.changed.push(name.clone()),
+            }
+        }
+        changes
+    }
+}
+
+// The placement fields of a Formation which differ
+fn placement_changes(local: &FormationModel, remote: &FormationModel) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    macro_rules! compare {
+        ($field:literal, $f:ident) => {{
+            if local.$f != remote.$f {
+                fields.push(FieldChange {
+                    field: $field,
+                    local: json!(local.$f),
+                    remote: json!(remote.$f),
+                });
+            }
+        }};
+    }
+    compare!("regions-allowed", regions_allowed);
+    compare!("regions-denied", regions_denied);
+    compare!("providers-allowed", providers_allowed);
+    compare!("providers-denied", providers_denied);
+    fields
+}
+
+// The fields of a Flight which differ, where images must match exactly rather than a missing tag or
+// digest matching any other
+fn field_changes(local: &FlightModel, remote: &FlightModel) -> Vec<FieldChange> {
+    let mut fields = Vec::new();
+    if !local.image.eq_strict(&remote.image) {
+        fields.push(FieldChange {
+            field: "image",
+            local: json!(local.image_str()),
+            remote: json!(remote.image_str()),
+        });
+    }
+    macro_rules! compare {
+        ($field:literal, $f:ident) => {{
+            if local.$f() != remote.$f() {
+                fields.push(FieldChange {
+                    field: $field,
+                    local: json!(local.$f()),
+                    remote: json!(remote.$f()),
+                });
+            }
+        }};
+    }
+    compare!("minimum", minimum);
+    compare!("maximum", maximum);
+    compare!("architecture", architecture);
+    compare!("api-permission", api_permission);
+    compare!("env", env);
+    compare!("ports", ports);
+    compare!("health-check", health_check);
+    compare!("resources", resources);
+    fields
+}
+
+// A value as displayed in the unified view
+fn display<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "none".into(), |v| v.to_string())
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => "auto".into(),
+        Value::String(s) => s.clone(),
+        Value::Array(a) if a.is_empty() => "any".into(),
+        Value::Array(a) => a.iter().map(display_value).collect::<Vec<_>>().join(","),
+        v => v.to_string(),
+    }
+}
+
+impl Output for FormationDiff {
+    fn print_json(&self, _ctx: &Ctx) -> Result<()> {
+        cli_println!("{}", serde_json::to_string(self)?);
+
+        Ok(())
+    }
+
+    fn print_table(&self, _ctx: &Ctx) -> Result<()> {
+        if self.is_empty() {
+            cli_print!("Local Formation Plan ");
+            cli_print!(@Green, "{}", self.name);
+            cli_println!(" is the same as its remote Formation Instance");
+            return Ok(());
+        }
+
+        cli_println!(@Red, "--- remote Formation Instance {} ({})", self.name, display(self.oid));
+        cli_println!(@Green, "+++ local Formation Plan {}", self.name);
+        if let Some(gw) = &self.gateway_flight {
+            cli_println!(@Red, "- gateway-flight: {}", display(gw.remote.as_ref()));
+            cli_println!(@Green, "+ gateway-flight: {}", display(gw.local.as_ref()));
+        }
+        if let Some(url) = &self.url {
+            cli_println!(@Red, "- url: {}", display(url.remote.as_ref()));
+            cli_println!(@Green, "+ url: {}", display(url.local.as_ref()));
+        }
+        for f in &self.placement {
+            // Nothing being denied is shown as none rather than any
+            let display = |v: &Value| match v {
+                Value::Array(a) if a.is_empty() && f.field.ends_with("-denied") => "none".into(),
+                v => display_value(v),
+            };
+            cli_println!(@Red, "- {}: {}", f.field, display(&f.remote));
+            cli_println!(@Green, "+ {}: {}", f.field, display(&f.local));
+        }
+        for flight in &self.flights {
+            match flight {
+                FlightDiff::Added { name, image } => {
+                    cli_println!(@Green, "+ flight {name}");
+                    cli_println!(@Green, "+     image: {image}");
+                }
+                FlightDiff::Removed { name, image } => {
+                    cli_println!(@Red, "- flight {name}");
+                    cli_println!(@Red, "-     image: {image}");
+                }
+                FlightDiff::Changed { name, fields } => {
+                    cli_println!("  flight {name}");
+                    for f in fields {
+                        cli_println!(@Red, "-     {}: {}", f.field, display_value(&f.remote));
+                        cli_println!(@Green, "+     {}: {}", f.field, display_value(&f.local));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Output for Vec<FormationDiff> {
+    fn print_json(&self, _ctx: &Ctx) -> Result<()> {
+        cli_println!("{}", serde_json::to_string(self)?);
+
+        Ok(())
+    }
+
+    fn print_table(&self, ctx: &Ctx) -> Result<()> {
+        for diff in self.iter() {
+            diff.print_table(ctx)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seaplane::api::shared::v1::{Provider as ProviderModel, Region as RegionModel};
+
+    use super::*;
+
+    fn formation(flights: &[(&str, &str)]) -> FormationModel {
+        let mut builder = FormationModel::builder()
+            .name("stubb")
+            .gateway_flight(flights[0].0);
+        for (name, image) in flights {
+            builder = builder.add_flight(
+                FlightModel::builder()
+                    .name(*name)
+                    .image(*image)
+                    .build()
+                    .unwrap(),
+            );
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn same() {
+        let model = formation(&[("flask", "foo.com/flask:latest")]);
+        assert!(FormationDiff::between(&model, &model).is_empty());
+    }
+
+    #[test]
+    fn flights() {
+        let remote = formation(&[("flask", "foo.com/flask:v1"), ("ahab", "foo.com/ahab:latest")]);
+        let mut local =
+            formation(&[("flask", "foo.com/flask:v2"), ("pequod", "foo.com/pequod:latest")]);
+        local.flights[0].minimum = 2;
+        local.gateway_flight = Some("pequod".into());
+
+        let diff = FormationDiff::between(&local, &remote);
+        assert_eq!(
+            diff.gateway_flight,
+            Some(Change { local: Some("pequod".into()), remote: Some("flask".into()) })
+        );
+        assert_eq!(diff.url, None);
+        assert_eq!(
+            diff.flights,
+            vec![
+                FlightDiff::Changed {
+                    name: "flask".into(),
+                    fields: vec![
+                        FieldChange {
+                            field: "image",
+                            local: json!("foo.com/flask:v2"),
+                            remote: json!("foo.com/flask:v1")
+                        },
+                        FieldChange { field: "minimum", local: json!(2), remote: json!(1) },
+                    ]
+                },
+                FlightDiff::Added { name: "pequod".into(), image: "foo.com/pequod:latest".into() },
+                FlightDiff::Removed { name: "ahab".into(), image: "foo.com/ahab:latest".into() },
+            ]
+        );
+    }
+
+    #[test]
+    fn placement() {
+        let remote = formation(&[("flask", "foo.com/flask:latest")]);
+        let mut local = remote.clone();
+        local.regions_allowed.insert(RegionModel::XN);
+        local.providers_denied.insert(ProviderModel::AWS);
+
+        let diff = FormationDiff::between(&local, &remote);
+        assert!(!diff.is_empty());
+        assert!(diff.flights.is_empty());
+        assert_eq!(
+            diff.placement,
+            vec![
+                FieldChange { field: "regions-allowed", local: json!(["XN"]), remote: json!([]) },
+                FieldChange { field: "providers-denied", local: json!(["AWS"]), remote: json!([]) },
+            ]
+        );
+    }
+
+    #[test]
+    fn keep_flight_ids() {
+        let mut old = formation(&[("flask", "foo.com/flask:v1"), ("ahab", "foo.com/ahab:latest")]);
+        old.flights[0].oid = Some("flt-dkk055g7qp5cdfr1ui9rc363dc".parse().unwrap());
+        old.flights[1].oid = Some("flt-va5g8orh1503ja6r9mqf1gvvj8".parse().unwrap());
+        let mut new = formation(&[("flask", "foo.com/flask:v1"), ("ahab", "foo.com/ahab:v2")]);
+
+        FormationDiff::between(&new, &old).keep_flight_ids(&old, &mut new);
+        assert_eq!(new.flights[0].oid, old.flights[0].oid);
+        // the changed Flight is recreated
+        assert_eq!(new.flights[1].oid, None);
+    }
+
+    #[test]
+    fn strict_images() {
+        // A missing digest would otherwise match any digest
+        let remote = formation(&[(
+            "flask",
+            "foo.com/flask:latest@sha256:\
+            0000000000000000000000000000000000000000000000000000000000000000",
+        )]);
+        let local = formation(&[("flask", "foo.com/flask:latest")]);
+        assert_eq!(local.flights[0].image, remote.flights[0].image);
+        assert!(!FormationDiff::between(&local, &remote).is_empty());
+    }
+}
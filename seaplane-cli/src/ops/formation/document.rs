@@ -0,0 +1,556 @@
+//! Formation documents describe a whole Formation in a single TOML, JSON or YAML file, so that it
+//! can be kept in version control and applied with `seaplane formation apply`. In TOML they look
+//! like:
+//!
+//! ```not_rust
+//! name = "stubb"
+//! gateway-flight = "flask"
+//! regions-allowed = ["XN", "XE"]
+//!
+//! [[flights]]
+//! name = "flask"
+//! image = "registry.cplane.cloud/seaplane-demo/flask:latest"
+//!
+//! [[flights]]
+//! name = "pequod"
+//! image = "registry.cplane.cloud/seaplane-demo/pequod:latest"
+//! minimum = 2
+//! maximum = 5
+//! env = { LOG_LEVEL = "info", DB_PASS = { secret = "app/db-pass" } }
+//! ```
+//!
+//! Each Flight takes the same keys as a Flight given to `seaplane formation plan` as JSON, except
+//! that its name is required.
+use std::{collections::BTreeSet, path::Path};
+
+use seaplane::api::{
+    compute::v2::Formation as FormationModel,
+    shared::v1::{Provider as ProviderModel, Region as RegionModel},
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use strum::{Display, EnumString};
+
+use crate::{
+    context::{FlightCtx, FormationCtx},
+    error::{CliErrorKind, Result},
+    ops::formation::FormationNameId,
+};
+
+/// The formats a Formation document may be written in
+#[derive(Copy, Clone, Debug, PartialEq, Eq, clap::ValueEnum, Display, EnumString)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum DocumentFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    /// The format implied by the extension of `path`, if it has a known one
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path
+            .as_ref()
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "toml" => Some(Self::Toml),
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// A complete Formation as described by a document
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FormationDocument {
+    pub name: String,
+    #[serde(default)]
+    pub gateway_flight: Option<String>,
+    #[serde(default)]
+    pub regions_allowed: BTreeSet<RegionModel>,
+    #[serde(default)]
+    pub regions_denied: BTreeSet<RegionModel>,
+    #[serde(default)]
+    pub providers_allowed: BTreeSet<ProviderModel>,
+    #[serde(default)]
+    pub providers_denied: BTreeSet<ProviderModel>,
+    pub flights: Vec<FlightCtx>,
+}
+
+impl FormationDocument {
+    /// Parses a document in the given format, and checks that it describes a valid Formation
+    pub fn from_str(s: &str, format: DocumentFormat) -> Result<Self> {
+        let doc: Self = match format {
+            DocumentFormat::Toml => toml::from_str(s)?,
+            DocumentFormat::Json => serde_json::from_str(s)?,
+            DocumentFormat::Yaml => serde_yaml::from_str(s)?,
+        };
+        doc.validate()?;
+        Ok(doc)
+    }
+
+    /// Checks that the document describes a valid Formation, with uniquely named Flights
+    pub fn validate(&self) -> Result<()> {
+        for (i, flight) in self.flights.iter().enumerate() {
+            let name = match &flight.name_id {
+                Some(name) => name,
+                None => {
+                    return Err(CliErrorKind::OneOff(format!(
+                        "Flight {} of Formation {} has no name, which is required in a \
+                        Formation document",
+                        i + 1,
+                        self.name
+                    ))
+                    .into_err())
+                }
+            };
+            if self.flights[..i]
+                .iter()
+                .any(|f| f.name_id.as_ref() == Some(name))
+            {
+                return Err(CliErrorKind::DuplicateName(name.clone()).into_err());
+            }
+            flight.validate()?;
+        }
+        FormationCtx::from(self.clone()).model()?;
+        Ok(())
+    }
+
+    /// Writes the document in the given format. TOML and YAML documents include comments
+    /// explaining each key, and keys which are left out are included as commented out examples.
+    pub fn template(&self, format: DocumentFormat) -> String {
+        let flights = self.flights.iter().map(flight_values);
+        match format {
+            DocumentFormat::Json => {
+                let flights: Vec<_> = flights
+                    .map(|values| {
+                        FLIGHT_KEYS
+                            .iter()
+                            .zip(values)
+                            .map(|((key, _, _), v)| (key.to_string(), v))
+                            .collect::<serde_json::Map<_, _>>()
+                    })
+                    .collect();
+                let mut doc = json!({"name": self.name});
+                if let Some(gw) = &self.gateway_flight {
+                    doc["gateway-flight"] = json!(gw);
+                }
+                for ((key, _, _), value) in PLACEMENT_KEYS.iter().zip(self.placement_values()) {
+                    if !value.as_array().map_or(false, Vec::is_empty) {
+                        doc[key] = value;
+                    }
+                }
+                doc["flights"] = json!(flights);
+                // a Value can always be serialized
+                serde_json::to_string_pretty(&doc).unwrap() + "\n"
+            }
+            DocumentFormat::Toml | DocumentFormat::Yaml => {
+                let yaml = format == DocumentFormat::Yaml;
+                let sep = if yaml { ": " } else { " = " };
+                let mut out = String::new();
+
+                comment(&mut out, "", TEMPLATE_HEADER);
+                out += "\n";
+                comment(
+                    &mut out,
+                    "",
+                    "The name of the Formation, which must be unique within the tenant",
+                );
+                out += &format!("name{sep}{}\n", json!(self.name));
+                if let Some(gw) = &self.gateway_flight {
+                    out += "\n";
+                    comment(
+                        &mut out,
+                        "",
+                        "The Flight which receives all public traffic arriving on the Formation URL",
+                    );
+                    out += &format!("gateway-flight{sep}{}\n", json!(gw));
+                }
+                out += "\n";
+                for ((key, help, example), value) in
+                    PLACEMENT_KEYS.iter().zip(self.placement_values())
+                {
+                    comment(&mut out, "", help);
+                    if value.as_array().map_or(false, Vec::is_empty) {
+                        comment(&mut out, "", &format!("{key}{sep}{example}"));
+                    } else {
+                        out += &format!("{key}{sep}{}\n", inline(&value, sep));
+                    }
+                }
+                if yaml {
+                    out += "\nflights:\n";
+                }
+                for (i, values) in flights.enumerate() {
+                    if !yaml {
+                        out += "\n[[flights]]\n";
+                    } else if i > 0 {
+                        out += "\n";
+                    }
+                    for (j, ((key, help, example), value)) in
+                        FLIGHT_KEYS.iter().zip(values).enumerate()
+                    {
+                        // YAML Flights are list items, which start with a '-'
+                        let indent = match (yaml, j) {
+                            (false, _) => "",
+                            (true, 0) => "  - ",
+                            (true, _) => "    ",
+                        };
+                        // Only the first Flight is commented, as every Flight has the same keys
+                        if i == 0 {
+                            comment(&mut out, if yaml { "    " } else { "" }, help);
+                        }
+                        let left_out = value.is_null()
+                            || value.as_array().map_or(false, Vec::is_empty)
+                            || value.as_object().map_or(false, |o| o.is_empty());
+                        if !left_out {
+                            out += &format!("{indent}{key}{sep}{}\n", inline(&value, sep));
+                        } else if i == 0 {
+                            // the examples are all valid JSON
+                            let example = serde_json::from_str(example).unwrap();
+                            comment(
+                                &mut out,
+                                indent,
+                                &format!("{key}{sep}{}", inline(&example, sep)),
+                            );
+                        }
+                    }
+                }
+                out
+            }
+        }
+    }
+
+    // The values of the keys in PLACEMENT_KEYS, which are empty when they're left out
+    fn placement_values(&self) -> [Value; 4] {
+        [
+            json!(self.regions_allowed),
+            json!(self.regions_denied),
+            json!(self.providers_allowed),
+            json!(self.providers_denied),
+        ]
+    }
+
+    /// Creates a new seaplane::api::compute::v2::Formation from the document
+    pub fn model(&self) -> Result<FormationModel> { FormationCtx::from(self.clone()).model() }
+}
+
+impl From<&FormationModel> for FormationDocument {
+    fn from(model: &FormationModel) -> Self {
+        FormationDocument {
+            name: model.name.clone(),
+            gateway_flight: model.gateway_flight.clone(),
+            regions_allowed: model.regions_allowed.clone(),
+            regions_denied: model.regions_denied.clone(),
+            providers_allowed: model.providers_allowed.clone(),
+            providers_denied: model.providers_denied.clone(),
+            flights: model
+                .flights
+                .iter()
+                .map(|f| FlightCtx {
+                    image: f.image.clone(),
+                    name_id: Some(f.name.clone()),
+                    generated_name: false,
+                    minimum: f.minimum(),
+                    maximum: f.maximum(),
+                    architecture: f.architecture().to_vec(),
+                    api_permission: f.api_permission(),
+                    env: f.env().clone(),
+                    ports: f.ports().to_vec(),
+                    health_check: f.health_check().cloned(),
+                    resources: f.resources().copied(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<FormationDocument> for FormationCtx {
+    fn from(doc: FormationDocument) -> Self {
+        FormationCtx {
+            name_id: Some(FormationNameId::Name(doc.name)),
+            flights: doc.flights,
+            gateway_flight: doc.gateway_flight,
+            regions_allowed: doc.regions_allowed,
+            regions_denied: doc.regions_denied,
+            providers_allowed: doc.providers_allowed,
+            providers_denied: doc.providers_denied,
+            ..Default::default()
+        }
+    }
+}
+
+static TEMPLATE_HEADER: &str = "A Formation document, which can be used with either of:
+
+    seaplane formation apply --file PATH
+    seaplane formation plan --file PATH";
+
+/// The keys restricting where the Flights may run, along with a comment explaining them and an
+/// example value used when they're left out
+static PLACEMENT_KEYS: &[(&str, &str, &str)] = &[
+    (
+        "regions-allowed",
+        "The regions the Flights may run in, of XA, XC, XE, XF, XN, XO, XQ, XS or XU (default: \
+        any)",
+        r#"["XN", "XE"]"#,
+    ),
+    ("regions-denied", "The regions the Flights may *NOT* run in", r#"["XC"]"#),
+    (
+        "providers-allowed",
+        "The cloud providers the Flights may run on, of AWS, Azure, DigitalOcean, Equinix or GCP \
+        (default: any)",
+        r#"["AWS", "GCP"]"#,
+    ),
+    ("providers-denied", "The cloud providers the Flights may *NOT* run on", r#"["Azure"]"#),
+];
+
+/// The keys of each Flight in the order they're written, along with a comment explaining them and
+/// an example value in JSON used when they're left out
+static FLIGHT_KEYS: &[(&str, &str, &str)] = &[
+    ("name", "The name of the Flight, which must be unique within the Formation", ""),
+    ("image", "The container image of the Flight, including its registry", ""),
+    ("minimum", "The minimum number of instances of the Flight (default: 1)", ""),
+    ("maximum", "The maximum number of instances of the Flight (default: no maximum)", "10"),
+    (
+        "architecture",
+        "The architectures the image can run on, amd64 or arm64 (default: any)",
+        "[\"amd64\"]",
+    ),
+    ("api-permission", "Whether the Flight can access the Seaplane APIs (default: false)", ""),
+    (
+        "env",
+        "The environment variables of the Flight, where a secret is read from a Metadata key",
+        r#"{"LOG_LEVEL": "info", "DB_PASS": {"secret": "app/db-pass"}}"#,
+    ),
+    (
+        "ports",
+        "The ports the Flight listens on, with a protocol of http, tcp or udp (default: HTTP on the \
+        default port)",
+        r#"[{"port": 8080, "protocol": "http"}]"#,
+    ),
+    (
+        "health-check",
+        "How the Flight is health checked, with the interval and timeout in seconds (default: \
+        decided by the runtime)",
+        r#"{"path": "/healthz", "interval": 10, "timeout": 5, "healthy-threshold": 1, "unhealthy-threshold": 3}"#,
+    ),
+    (
+        "resources",
+        "Thousandths of a CPU and MiB of memory for each instance (default: decided by the \
+        runtime)",
+        r#"{"millicpu": 500, "memory-mib": 512}"#,
+    ),
+];
+
+// Appends each line of `text` as a TOML or YAML comment
+fn comment(out: &mut String, indent: &str, text: &str) {
+    for line in text.lines() {
+        if line.is_empty() {
+            *out += &format!("{indent}#\n");
+        } else {
+            *out += &format!("{indent}# {line}\n");
+        }
+    }
+}
+
+// Writes a value on a single line, which is valid TOML or YAML depending on the key-value separator
+// `sep`. JSON is valid YAML, and only differs from TOML in how tables are written.
+fn inline(value: &Value, sep: &str) -> String {
+    match value {
+        Value::Object(o) => {
+            let items = o
+                .iter()
+                .map(|(k, v)| format!("{}{sep}{}", json!(k), inline(v, sep)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{{ {items} }}")
+        }
+        Value::Array(a) => {
+            format!(
+                "[{}]",
+                a.iter()
+                    .map(|v| inline(v, sep))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        }
+        v => v.to_string(),
+    }
+}
+
+// The values of the keys in FLIGHT_KEYS, which are null or empty when they're left out
+fn flight_values(flight: &FlightCtx) -> [Value; 10] {
+    [
+        json!(flight.name_id),
+        json!(flight.image.to_string()),
+        json!(flight.minimum),
+        json!(flight.maximum),
+        json!(flight.architecture),
+        json!(flight.api_permission),
+        json!(flight.env),
+        json!(flight.ports),
+        json!(flight.health_check),
+        json!(flight.resources),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_formats() {
+        let toml = r#"
+            name = "stubb"
+            gateway-flight = "flask"
+
+            [[flights]]
+            name = "flask"
+            image = "foo.com/flask:latest"
+
+            [[flights]]
+            name = "pequod"
+            image = "foo.com/pequod:latest"
+            minimum = 2
+            maximum = 5
+            architecture = ["amd64"]
+            api-permission = true
+            env = { LOG_LEVEL = "info", DB_PASS = { secret = "app/db-pass" } }
+        "#;
+        let json = r#"{
+            "name": "stubb",
+            "gateway-flight": "flask",
+            "flights": [
+                {"name": "flask", "image": "foo.com/flask:latest"},
+                {
+                    "name": "pequod",
+                    "image": "foo.com/pequod:latest",
+                    "minimum": 2,
+                    "maximum": 5,
+                    "architecture": ["amd64"],
+                    "api-permission": true,
+                    "env": {"LOG_LEVEL": "info", "DB_PASS": {"secret": "app/db-pass"}}
+                }
+            ]
+        }"#;
+        let yaml = "
+name: stubb
+gateway-flight: flask
+flights:
+  - name: flask
+    image: foo.com/flask:latest
+  - name: pequod
+    image: foo.com/pequod:latest
+    minimum: 2
+    maximum: 5
+    architecture: [amd64]
+    api-permission: true
+    env:
+      LOG_LEVEL: info
+      DB_PASS:
+        secret: app/db-pass
+";
+        let expected = FormationDocument::from_str(toml, DocumentFormat::Toml)
+            .unwrap()
+            .model()
+            .unwrap();
+        assert_eq!(expected.flights.len(), 2);
+        assert_eq!(expected.flights[1].maximum(), Some(5));
+        assert_eq!(expected.flights[1].secrets().count(), 1);
+        for (s, format) in [(json, DocumentFormat::Json), (yaml, DocumentFormat::Yaml)] {
+            let model = FormationDocument::from_str(s, format)
+                .unwrap()
+                .model()
+                .unwrap();
+            assert_eq!(model, expected);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let doc = |flights: &str| {
+            FormationDocument::from_str(
+                &format!(r#"{{"name": "stubb", "flights": [{flights}]}}"#),
+                DocumentFormat::Json,
+            )
+        };
+        assert!(doc(r#"{"name": "flask", "image": "foo.com/flask:latest"}"#).is_ok());
+        // Flight names are required and must be unique
+        assert!(doc(r#"{"image": "foo.com/flask:latest"}"#).is_err());
+        assert!(doc(r#"{"name": "flask", "image": "foo.com/flask:latest"},
+               {"name": "flask", "image": "foo.com/pequod:latest"}"#)
+        .is_err());
+        assert!(doc(r#"{"name": "flask", "image": "foo.com/flask:latest", "minimum": 0}"#).is_err());
+        assert!(doc("").is_err());
+        assert!(FormationDocument::from_str(
+            r#"{"name": "stubb", "gateway_flight": "flask", "flights": []}"#,
+            DocumentFormat::Json
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn template_round_trip() {
+        let doc = FormationDocument::from_str(
+            r#"{
+                "name": "stubb",
+                "gateway-flight": "flask",
+                "regions-allowed": ["xn", "XE"],
+                "providers-denied": ["aws"],
+                "flights": [
+                    {"name": "flask", "image": "foo.com/flask:latest"},
+                    {"name": "pequod", "image": "foo.com/pequod:latest", "maximum": 5,
+                     "architecture": ["amd64", "arm64"],
+                     "env": {"LOG_LEVEL": "info", "DB_PASS": {"secret": "app/db-pass"}},
+                     "ports": [{"port": 8080}, {"port": 5432, "protocol": "tcp"}],
+                     "health-check": {"path": "/healthz", "timeout": 2},
+                     "resources": {"memory-mib": 512}}
+                ]
+            }"#,
+            DocumentFormat::Json,
+        )
+        .unwrap();
+        let expected = doc.model().unwrap();
+        for format in [DocumentFormat::Toml, DocumentFormat::Json, DocumentFormat::Yaml] {
+            let template = doc.template(format);
+            let model = FormationDocument::from_str(&template, format)
+                .unwrap()
+                .model()
+                .unwrap();
+            assert_eq!(model, expected, "{format}:\n{template}");
+        }
+        // Keys which are left out are still shown as examples
+        assert!(doc
+            .template(DocumentFormat::Toml)
+            .contains("# maximum = 10\n"));
+        assert!(doc
+            .template(DocumentFormat::Yaml)
+            .contains("    # maximum: 10\n"));
+        assert!(doc.template(DocumentFormat::Toml).contains(
+            r#"# env = { "LOG_LEVEL" = "info", "DB_PASS" = { "secret" = "app/db-pass" } }"#
+        ));
+        assert!(doc
+            .template(DocumentFormat::Yaml)
+            .contains(r#"    # ports: [{ "port": 8080, "protocol": "http" }]"#));
+        assert!(doc
+            .template(DocumentFormat::Toml)
+            .contains("regions-allowed = [\"XE\", \"XN\"]\n"));
+        assert!(doc
+            .template(DocumentFormat::Toml)
+            .contains("# regions-denied = [\"XC\"]\n"));
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(DocumentFormat::from_path("stubb.toml"), Some(DocumentFormat::Toml));
+        assert_eq!(DocumentFormat::from_path("a/stubb.JSON"), Some(DocumentFormat::Json));
+        assert_eq!(DocumentFormat::from_path("stubb.yml"), Some(DocumentFormat::Yaml));
+        assert_eq!(DocumentFormat::from_path("stubb.yaml"), Some(DocumentFormat::Yaml));
+        assert_eq!(DocumentFormat::from_path("stubb"), None);
+        assert_eq!(DocumentFormat::from_path("-"), None);
+    }
+}
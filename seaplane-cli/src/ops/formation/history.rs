@@ -0,0 +1,127 @@
+//! The history module keeps the configurations remote Formation Instances had before they were
+//! updated, so that an Instance can be rolled back to a known good configuration with `seaplane
+//! formation rollback` even once the Compute API no longer keeps that revision active.
+use seaplane::api::compute::v2::{FlightStatus, Formation as FormationModel, FormationId};
+use serde::{Deserialize, Serialize};
+
+/// The most configurations kept for each remote Formation Instance, beyond which the oldest are
+/// dropped
+pub const MAX_REVISIONS: usize = 10;
+
+/// Earlier configurations of remote Formation Instances, oldest first
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(transparent)]
+pub struct FormationHistory {
+    inner: Vec<FormationModel>,
+}
+
+impl FormationHistory {
+    /// Records the configuration of a remote Formation Instance which is about to be replaced.
+    /// Configurations which have no ID or revision can't be rolled back to, so aren't recorded.
+    pub fn record(&mut self, model: &FormationModel) {
+        let (oid, revision) = match (model.oid, model.revision) {
+            (Some(oid), Some(revision)) => (oid, revision),
+            _ => return,
+        };
+        // The IDs and health of the Flights, and the traffic given to other revisions, aren't part
+        // of the configuration itself
+        let mut config = model.clone();
+        config.url = None;
+        config.active_revisions.clear();
+        for flight in config.flights.iter_mut() {
+            flight.oid = None;
+            flight.status = FlightStatus::default();
+        }
+
+        self.inner
+            .retain(|m| !(m.oid == Some(oid) && m.revision == Some(revision)));
+        self.inner.push(config);
+        let kept = self.of(oid).count();
+        if kept > MAX_REVISIONS {
+            let mut excess = kept - MAX_REVISIONS;
+            self.inner.retain(|m| {
+                if excess > 0 && m.oid == Some(oid) {
+                    excess -= 1;
+                    return false;
+                }
+                true
+            });
+        }
+    }
+
+    /// The recorded configurations of a remote Formation Instance, oldest first
+    pub fn of(&self, oid: FormationId) -> impl DoubleEndedIterator<Item = &FormationModel> {
+        self.inner.iter().filter(move |m| m.oid == Some(oid))
+    }
+
+    /// The recorded configuration of a remote Formation Instance at the given revision
+    pub fn get(&self, oid: FormationId, revision: u32) -> Option<&FormationModel> {
+        self.of(oid).find(|m| m.revision == Some(revision))
+    }
+
+    /// Forgets every configuration of a remote Formation Instance, such as once it's deleted
+    pub fn remove(&mut self, oid: FormationId) { self.inner.retain(|m| m.oid != Some(oid)); }
+}
+
+#[cfg(test)]
+mod tests {
+    use seaplane::api::compute::v2::Flight as FlightModel;
+
+    use super::*;
+
+    fn revision(oid: &str, revision: u32) -> FormationModel {
+        let mut model = FormationModel::builder()
+            .name("stubb")
+            .add_flight(
+                FlightModel::builder()
+                    .name("flask")
+                    .image(format!("foo.com/flask:v{revision}"))
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap();
+        model.oid = Some(oid.parse().unwrap());
+        model.revision = Some(revision);
+        model
+    }
+
+    #[test]
+    fn record() {
+        let oid = "frm-euqecs8n6h5l552ps6skal12lc";
+        let other = "frm-45rt0pqpo93q78t6au6dd8kn04";
+        let mut history = FormationHistory::default();
+        history.record(&revision(oid, 1));
+        history.record(&revision(other, 1));
+        history.record(&revision(oid, 2));
+        // Recording the same revision again replaces it
+        history.record(&revision(oid, 1));
+
+        let oid = oid.parse().unwrap();
+        let revisions: Vec<_> = history.of(oid).map(|m| m.revision.unwrap()).collect();
+        assert_eq!(revisions, vec![2, 1]);
+        assert_eq!(history.get(oid, 2).unwrap().flights[0].image_str(), "foo.com/flask:v2");
+        assert!(history.get(oid, 3).is_none());
+
+        history.remove(oid);
+        assert_eq!(history.of(oid).count(), 0);
+        assert_eq!(history.of(other.parse().unwrap()).count(), 1);
+    }
+
+    #[test]
+    fn record_drops_oldest() {
+        let oid = "frm-euqecs8n6h5l552ps6skal12lc";
+        let mut history = FormationHistory::default();
+        for i in 0..MAX_REVISIONS as u32 + 2 {
+            history.record(&revision(oid, i));
+        }
+        // Models without a revision are never recorded
+        let mut unassigned = revision(oid, 0);
+        unassigned.revision = None;
+        history.record(&unassigned);
+
+        let oid = oid.parse().unwrap();
+        assert_eq!(history.of(oid).count(), MAX_REVISIONS);
+        assert_eq!(history.of(oid).next().unwrap().revision, Some(2));
+    }
+}
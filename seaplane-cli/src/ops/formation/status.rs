@@ -3,18 +3,26 @@
 //! ```not_rust
 //! ◉ Formation stubb (frm-5wacbutjwbdexonddvdb2lnyxu): UNHEALTHY
 //! └─┐
-//!   │   FLIGHT    STATUS     OID
-//!   ├─◉ flask     HEALTHY    flt-4bjyoaoqhbaorip3izfrou3siu
-//!   └─◉ pequod    UNHEALTHY  flt-oqpr968hs53bb6egf1h4gcqsho
+//!   │   FLIGHT    STATUS       MIN    MAX     ARCH     API      OID
+//!   ├─◉ flask     HEALTHY      1      auto    any      false    flt-4bjyoaoqhbaorip3izfrou3siu
+//!   └─◉ pequod    UNHEALTHY    2      5       arm64    true     flt-oqpr968hs53bb6egf1h4gcqsho
 //! ```
 //!
 //! Chars we'll need from (<http://www.unicode.org/charts/PDF/U2500.pdf>): `│ ├ ─ └ ┐`
 use seaplane::api::compute::v2::{
-    Flight as FlightModel, FlightId, FlightStatus as FlightStatusModel, FormationId,
+    Architecture, Flight as FlightModel, FlightId, FlightStatus as FlightStatusModel, FormationId,
 };
 use serde::Serialize;
 
-use crate::{context::Ctx, error::Result, ops::formation::Formation, printer::Output};
+use crate::{
+    context::Ctx,
+    error::Result,
+    ops::{
+        flight::{flight_details, FLIGHT_DETAIL_HEADERS},
+        formation::Formation,
+    },
+    printer::Output,
+};
 
 // Possible Symbols?: ◯ ◉ ◍ ◐ ● ○ ◯
 const SYM: char = '◉';
@@ -46,13 +54,10 @@ pub struct FormationStatus {
 }
 
 impl FormationStatus {
-    pub fn update_status(&mut self) {
-        let mut status = FlightStatusModel::Healthy;
-        for flight in self.flights.inner.iter() {
-            worse_only(&mut status, flight.status);
-        }
-        self.status = status;
-    }
+    pub fn name(&self) -> &str { &self.name }
+
+    /// The overall health of the Formation, which is the worst status of any of its Flights
+    pub fn status(&self) -> FlightStatusModel { self.status }
 }
 
 impl<'a> From<&'a Formation> for FormationStatus {
@@ -60,7 +65,7 @@ impl<'a> From<&'a Formation> for FormationStatus {
         let mut fs = FormationStatus {
             name: value.model.name.clone(),
             oid: value.model.oid,
-            status: FlightStatusModel::Healthy,
+            status: value.model.health(),
             flights: FlightStatuses::with_capacity(value.model.flights.len()),
         };
 
@@ -68,8 +73,6 @@ impl<'a> From<&'a Formation> for FormationStatus {
             fs.flights.inner.push(flight.into());
         }
 
-        fs.update_status();
-
         fs
     }
 }
@@ -120,10 +123,18 @@ pub struct FlightStatus {
     name: String,
     oid: Option<FlightId>,
     status: FlightStatusModel,
+    minimum: u64,
+    maximum: Option<u64>,
+    architecture: Vec<Architecture>,
+    api_permission: bool,
 }
 
 impl FlightStatus {
-    pub fn print_pretty(&self, last: bool, flight_slot: usize, status_slot: usize) {
+    fn details(&self) -> [String; 4] {
+        flight_details(self.minimum, self.maximum, &self.architecture, self.api_permission)
+    }
+
+    pub fn print_pretty(&self, last: bool, flight_slot: usize, slots: &[usize; 5]) {
         if last {
             cli_print!("  └─");
         } else {
@@ -135,12 +146,22 @@ impl FlightStatus {
         let status = self.status;
 
         let s_after_name = nspaces!(flight_slot - name.len());
-        let s_after_status = nspaces!(status_slot - status_len(self.status));
+        let s_after_status = nspaces!(slots[0] - status_len(self.status));
 
         cli_print!(" {name}{s_after_name}");
         print_fsm(status);
+        cli_print!("{s_after_status}");
+        let details = self.details();
+        for (i, (detail, slot)) in details.iter().zip(&slots[1..]).enumerate() {
+            // Don't leave trailing whitespace when there is no OID to print
+            if i == details.len() - 1 && self.oid.is_none() {
+                cli_print!("{detail}");
+            } else {
+                cli_print!("{detail}{}", nspaces!(slot - detail.len()));
+            }
+        }
         if let Some(oid) = self.oid {
-            cli_println!("{s_after_status}{oid}");
+            cli_println!("{oid}");
         } else {
             cli_println!("");
         }
@@ -149,7 +170,15 @@ impl FlightStatus {
 
 impl<'a> From<&'a FlightModel> for FlightStatus {
     fn from(value: &'a FlightModel) -> Self {
-        FlightStatus { name: value.name.clone(), oid: value.oid, status: value.status }
+        FlightStatus {
+            name: value.name.clone(),
+            oid: value.oid,
+            status: value.status,
+            minimum: value.minimum,
+            maximum: value.maximum,
+            architecture: value.architecture.clone(),
+            api_permission: value.api_permission,
+        }
     }
 }
 
@@ -166,46 +195,31 @@ impl FlightStatuses {
             return;
         }
 
+        // Each slot is as wide as the longest value or header plus four spaces
+        let slot = |header: &str, longest: usize| std::cmp::max(longest, header.len()) + 4;
         let longest_flight_name = self.inner.iter().map(|f| f.name.len()).max().unwrap();
-        let total_flight_slot_size = std::cmp::max(longest_flight_name + 4, 10); // 10 = FLIGHT+4
-        let spaces_after_flight = total_flight_slot_size - 6; // 6 = FLIGHT
+        let flight_slot = slot("FLIGHT", longest_flight_name);
         let longest_status = self
             .inner
             .iter()
             .map(|f| status_len(f.status))
             .max()
             .unwrap();
-        let total_status_slot_size = std::cmp::max(longest_status + 4, 10); // 10 = STATUS+4
-        let spaces_after_status = total_status_slot_size - 6; // 6 = STATUS
-        cli_println!(
-            "  │   FLIGHT{}STATUS{}OID",
-            nspaces!(spaces_after_flight),
-            nspaces!(spaces_after_status)
-        );
-        for (i, flight) in self.inner.iter().enumerate() {
-            flight.print_pretty(
-                i == self.inner.len() - 1,
-                total_flight_slot_size,
-                total_status_slot_size,
-            );
+        let mut slots = [slot("STATUS", longest_status), 0, 0, 0, 0];
+        let details: Vec<_> = self.inner.iter().map(FlightStatus::details).collect();
+        for (i, header) in FLIGHT_DETAIL_HEADERS.iter().enumerate() {
+            let longest = details.iter().map(|d| d[i].len()).max().unwrap();
+            slots[i + 1] = slot(header, longest);
         }
-    }
-}
 
-pub fn worse_only(lhs: &mut FlightStatusModel, rhs: FlightStatusModel) {
-    use FlightStatusModel::*;
-    match lhs {
-        Healthy => match rhs {
-            Unhealthy => *lhs = Unhealthy,
-            Starting => *lhs = Starting,
-            _ => (),
-        },
-        Unhealthy => (),
-        Starting => match rhs {
-            Healthy => *lhs = Healthy,
-            Unhealthy => *lhs = Unhealthy,
-            _ => (),
-        },
+        cli_print!("  │   FLIGHT{}", nspaces!(flight_slot - 6)); // 6 = FLIGHT
+        for (header, slot) in ["STATUS"].iter().chain(&FLIGHT_DETAIL_HEADERS).zip(slots) {
+            cli_print!("{header}{}", nspaces!(slot - header.len()));
+        }
+        cli_println!("OID");
+        for (i, flight) in self.inner.iter().enumerate() {
+            flight.print_pretty(i == self.inner.len() - 1, flight_slot, &slots);
+        }
     }
 }
 
@@ -1,3 +1,5 @@
+pub mod archive;
+
 use std::io::Write;
 
 use base64::{
@@ -24,13 +26,20 @@ use crate::{
 pub struct KeyValue {
     pub key: EncodedString,
     pub value: EncodedString,
+    /// The version of the value as reported by the Metadata API, if it's known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
 }
 
 impl KeyValue {
     /// Creates a new KeyValue from an encoded key and value. You must pinky promise the key and
     /// value are URL safe base64 encoded or Bad Things may happen.
     pub fn new<S: Into<String>>(key: S, value: S) -> Self {
-        Self { key: EncodedString::new(key.into()), value: EncodedString::new(value.into()) }
+        Self {
+            key: EncodedString::new(key.into()),
+            value: EncodedString::new(value.into()),
+            version: None,
+        }
     }
 
     /// Creates a new KeyValue from an un-encoded key and value, encoding them along the way
@@ -68,7 +77,10 @@ impl KeyValues {
         Self {
             inner: model
                 .iter()
-                .map(|kv| KeyValue::new(kv.key.as_ref(), kv.value.as_ref()))
+                .map(|kv| KeyValue {
+                    version: kv.version,
+                    ..KeyValue::new(kv.key.as_ref(), kv.value.as_ref())
+                })
                 .collect(),
         }
     }
@@ -195,14 +207,17 @@ mod tests {
                 KeyValue {
                     key: EncodedString::new("a2V5MQ".into()),
                     value: EncodedString::new("dmFsdWUx".into()),
+                    version: None,
                 },
                 KeyValue {
                     key: EncodedString::new("a2V5Mg".into()),
                     value: EncodedString::new("dmFsdWUy".into()),
+                    version: None,
                 },
                 KeyValue {
                     key: EncodedString::new("a2V5Mw".into()),
                     value: EncodedString::new("dmFsdWUz".into()),
+                    version: None,
                 },
             ],
         }
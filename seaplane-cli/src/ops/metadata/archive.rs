@@ -0,0 +1,294 @@
+//! The archive module reads and writes the files of key-value pairs made by `seaplane metadata
+//! export` and replayed by `seaplane metadata import`.
+//!
+//! An archive is either JSON Lines, where each line is an object with a `key` and a `value`, or a
+//! tar file, where each key is the path of a file holding its value. The keys and values are URL
+//! safe base64 encoded unless the archive was made with `--decode`.
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use base64::{
+    alphabet::URL_SAFE,
+    engine::{general_purpose::NO_PAD, Engine, GeneralPurpose},
+};
+use seaplane::api::metadata::v1::KeyValue as KeyValueModel;
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+use crate::error::{CliError, Context, Result};
+
+/// The formats an archive of key-value pairs may be written in
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, clap::ValueEnum, Display, EnumString)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum ArchiveFormat {
+    #[default]
+    Jsonl,
+    Tar,
+}
+
+impl ArchiveFormat {
+    /// The format implied by the extension of `path`, if it has a known one
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path
+            .as_ref()
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "jsonl" | "ndjson" => Some(Self::Jsonl),
+            "tar" => Some(Self::Tar),
+            _ => None,
+        }
+    }
+}
+
+/// A single line of a JSON Lines archive
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Record {
+    key: String,
+    value: String,
+}
+
+/// A key-value pair read from an archive, decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+/// Writes key-value pairs to an archive
+#[allow(missing_debug_implementations)]
+pub struct ArchiveWriter<W: Write> {
+    inner: Writer<W>,
+    decode: bool,
+}
+
+enum Writer<W: Write> {
+    Jsonl(W),
+    Tar(tar::Builder<W>),
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Writes an archive of the given format to `out`, decoding the keys and values first when
+    /// `decode` is set
+    pub fn new(out: W, format: ArchiveFormat, decode: bool) -> Self {
+        let inner = match format {
+            ArchiveFormat::Jsonl => Writer::Jsonl(out),
+            ArchiveFormat::Tar => Writer::Tar(tar::Builder::new(out)),
+        };
+        Self { inner, decode }
+    }
+
+    pub fn append(&mut self, kv: &KeyValueModel) -> Result<()> {
+        let (key, value) = if self.decode {
+            (decoded_utf8(kv.key.decode(), "key", kv.key.encoded())?, kv.value.decode())
+        } else {
+            (kv.key.encoded().to_owned(), kv.value.encoded().as_bytes().to_vec())
+        };
+
+        match &mut self.inner {
+            Writer::Jsonl(out) => {
+                let value = if self.decode {
+                    decoded_utf8(value, "value", kv.key.encoded())?
+                } else {
+                    String::from_utf8(value)?
+                };
+                serde_json::to_writer(&mut *out, &Record { key, value })?;
+                out.write_all(b"\n")?;
+            }
+            Writer::Tar(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(value.len() as u64);
+                header.set_mode(0o644);
+                builder
+                    .append_data(&mut header, &key, value.as_slice())
+                    .map_err(CliError::from)
+                    .context("\n\tkey: ")
+                    .context(kv.key.encoded())
+                    .context("\n")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Completes the archive, returning the writer it was written to
+    pub fn finish(self) -> Result<W> {
+        match self.inner {
+            Writer::Jsonl(out) => Ok(out),
+            Writer::Tar(builder) => Ok(builder.into_inner()?),
+        }
+    }
+}
+
+// Decoded keys and values must be valid UTF-8 to be written as JSON strings or tar paths
+fn decoded_utf8(bytes: Vec<u8>, what: &str, key: &str) -> Result<String> {
+    String::from_utf8(bytes)
+        .map_err(CliError::from)
+        .context(format!("\n\tthe {what} of the key "))
+        .context(key)
+        .context(" isn't valid UTF-8 once decoded\n(hint: export it without --decode)\n")
+}
+
+/// Reads every key-value pair of an archive of the given format, where `decoded` says whether
+/// the archive was written with `--decode`
+pub fn read_archive<R: Read>(input: R, format: ArchiveFormat, decoded: bool) -> Result<Vec<Entry>> {
+    let engine = GeneralPurpose::new(&URL_SAFE, NO_PAD);
+    let entry = |key: Vec<u8>, value: Vec<u8>| -> Result<Entry> {
+        if decoded {
+            Ok(Entry { key, value })
+        } else {
+            Ok(Entry { key: engine.decode(key)?, value: engine.decode(value)? })
+        }
+    };
+
+    let mut entries = Vec::new();
+    match format {
+        ArchiveFormat::Jsonl => {
+            for (i, line) in BufReader::new(input).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: Record = serde_json::from_str(&line)
+                    .map_err(CliError::from)
+                    .context(format!("\n\tline: {}\n", i + 1))?;
+                entries.push(entry(record.key.into_bytes(), record.value.into_bytes())?);
+            }
+        }
+        ArchiveFormat::Tar => {
+            let mut archive = tar::Archive::new(input);
+            for file in archive.entries()? {
+                let mut file = file?;
+                // Directories only exist in the archive to hold the keys beneath them
+                if !file.header().entry_type().is_file() {
+                    continue;
+                }
+                let key = file.path_bytes().into_owned();
+                let mut value = Vec::new();
+                file.read_to_end(&mut value)?;
+                entries.push(entry(key, value)?);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Replaces the beginning of keys which start with `OLD` by `NEW`, such as to import an archive
+/// into a different directory than the one it was exported from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrefixRewrite {
+    old: Vec<u8>,
+    new: Vec<u8>,
+}
+
+impl PrefixRewrite {
+    /// The rewritten key, if it starts with the prefix
+    pub fn apply(&self, key: &[u8]) -> Option<Vec<u8>> {
+        key.strip_prefix(self.old.as_slice())
+            .map(|rest| [self.new.as_slice(), rest].concat())
+    }
+
+    /// Rewrites `key` with the first of `rewrites` which applies to it, if any
+    pub fn rewrite(rewrites: &[Self], key: Vec<u8>) -> Vec<u8> {
+        rewrites.iter().find_map(|r| r.apply(&key)).unwrap_or(key)
+    }
+}
+
+impl FromStr for PrefixRewrite {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (old, new) = s
+            .split_once('=')
+            .ok_or_else(|| format!("'{s}' isn't of the form OLD=NEW"))?;
+        Ok(Self { old: old.as_bytes().to_vec(), new: new.as_bytes().to_vec() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use seaplane::api::metadata::v1::{Key, Value};
+
+    use super::*;
+
+    fn kvs() -> Vec<KeyValueModel> {
+        vec![
+            KeyValueModel {
+                key: Key::from_unencoded("ships/pequod"),
+                value: Value::from_unencoded("whaler"),
+                version: None,
+            },
+            KeyValueModel {
+                key: Key::from_unencoded("ships/rachel"),
+                value: Value::from_unencoded([0xFF, 0x00]),
+                version: Some(3),
+            },
+        ]
+    }
+
+    fn round_trip(format: ArchiveFormat, decode: bool) -> Vec<Entry> {
+        let mut writer = ArchiveWriter::new(Vec::new(), format, decode);
+        for kv in &kvs() {
+            writer.append(kv).unwrap();
+        }
+        let archive = writer.finish().unwrap();
+        read_archive(archive.as_slice(), format, decode).unwrap()
+    }
+
+    #[test]
+    fn jsonl() {
+        let mut writer = ArchiveWriter::new(Vec::new(), ArchiveFormat::Jsonl, false);
+        writer.append(&kvs()[0]).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.finish().unwrap()).unwrap(),
+            "{\"key\":\"c2hpcHMvcGVxdW9k\",\"value\":\"d2hhbGVy\"}\n"
+        );
+
+        let expected = vec![
+            Entry { key: b"ships/pequod".to_vec(), value: b"whaler".to_vec() },
+            Entry { key: b"ships/rachel".to_vec(), value: vec![0xFF, 0x00] },
+        ];
+        assert_eq!(round_trip(ArchiveFormat::Jsonl, false), expected);
+        assert_eq!(round_trip(ArchiveFormat::Tar, false), expected);
+        assert_eq!(round_trip(ArchiveFormat::Tar, true), expected);
+    }
+
+    #[test]
+    fn decoded_jsonl_needs_utf8() {
+        let mut writer = ArchiveWriter::new(Vec::new(), ArchiveFormat::Jsonl, true);
+        writer.append(&kvs()[0]).unwrap();
+        assert!(writer.append(&kvs()[1]).is_err());
+
+        let entries = read_archive(
+            "{\"key\":\"ships/pequod\",\"value\":\"whaler\"}\n\n".as_bytes(),
+            ArchiveFormat::Jsonl,
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![Entry { key: b"ships/pequod".to_vec(), value: b"whaler".to_vec() }]
+        );
+    }
+
+    #[test]
+    fn prefix_rewrite() {
+        let rewrites: Vec<PrefixRewrite> =
+            vec!["ships/=fleet/".parse().unwrap(), "=imported/".parse().unwrap()];
+        assert_eq!(PrefixRewrite::rewrite(&rewrites, b"ships/pequod".to_vec()), b"fleet/pequod");
+        assert_eq!(PrefixRewrite::rewrite(&rewrites, b"crew/ahab".to_vec()), b"imported/crew/ahab");
+        assert!("ships".parse::<PrefixRewrite>().is_err());
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(ArchiveFormat::from_path("backup.tar"), Some(ArchiveFormat::Tar));
+        assert_eq!(ArchiveFormat::from_path("backup.JSONL"), Some(ArchiveFormat::Jsonl));
+        assert_eq!(ArchiveFormat::from_path("backup"), None);
+    }
+}
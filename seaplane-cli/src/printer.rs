@@ -43,6 +43,26 @@ impl Pb {
         }
     }
 
+    /// A progress bar of `len` steps, which are counted with [`Pb::inc`]
+    pub fn with_length(ctx: &Ctx, len: u64) -> Self {
+        let pb = Self::new(ctx);
+        if let Some(bar) = &pb.0 {
+            bar.set_length(len);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner:.green} {msg} [{bar:30}] {pos}/{len}")
+                    .expect("Invalid progress bar template"),
+            );
+        }
+        pb
+    }
+
+    pub fn inc(&self, delta: u64) {
+        if let Some(pb) = &self.0 {
+            pb.inc(delta);
+        }
+    }
+
     pub fn set_message(&self, msg: impl Into<Cow<'static, str>>) {
         if let Some(pb) = &self.0 {
             pb.set_message(msg);
@@ -3,10 +3,18 @@ mod fetch;
 #[macro_use]
 mod land; // uses fetch
 mod delete; // uses land, fetch
+mod diff;
+mod edit;
 #[macro_use]
 mod launch;
+mod list; // uses fetch
 mod plan; // uses launch, fetch
+mod rollback;
+mod rollout;
 mod status; // uses fetch
+mod update;
+
+use std::collections::BTreeSet;
 
 use seaplane::api::compute::v2::{
     Flight as FlightModel, FlightStatus, Formation as FormationModel,
@@ -43,6 +51,12 @@ fn local_formation(name: &str, f1: &str, f2: &str) -> FormationModel {
                 .unwrap(),
         ],
         gateway_flight: Some(f1.into()),
+        regions_allowed: BTreeSet::new(),
+        regions_denied: BTreeSet::new(),
+        providers_allowed: BTreeSet::new(),
+        providers_denied: BTreeSet::new(),
+        revision: None,
+        active_revisions: Vec::new(),
     }
 }
 fn default_local_formation() -> FormationModel { local_formation("stubb", "flask", "pequod") }
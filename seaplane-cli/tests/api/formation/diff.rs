@@ -0,0 +1,80 @@
+use httpmock::prelude::*;
+use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+
+use crate::api::{
+    formation::{ctx_with_formations, default_deployed_formation, default_local_formation},
+    then, when, MOCK_SERVER,
+};
+
+macro_rules! test_fn_diff {
+    ($name:ident, $argv:expr, $local:expr, $output:expr) => {
+        #[test]
+        fn $name() {
+            let remote = default_deployed_formation();
+            let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+            let mut mock = MOCK_SERVER.mock(|w, t| {
+                when(w, GET, &path);
+                then(t, &serde_json::to_value(&remote).unwrap());
+            });
+
+            let res = test_main_exec_with_ctx(&argv!($argv), ctx_with_formations(vec![$local]));
+            assert!(res.is_ok(), "{res:?}");
+            mock.assert();
+            assert_eq!(printer().as_string().trim(), $output.trim());
+
+            mock.delete();
+            printer().clear();
+        }
+    };
+}
+
+fn changed_formation() -> seaplane::api::compute::v2::Formation {
+    let mut local = default_deployed_formation();
+    local.flights[1].image = "foo.com/baz:v2".parse().unwrap();
+    local.flights[1].maximum = Some(3);
+    local.flights.remove(0);
+    local.gateway_flight = Some("pequod".into());
+    local
+}
+
+test_fn_diff!(
+    same,
+    "formation diff stubb",
+    default_deployed_formation(),
+    "Local Formation Plan stubb is the same as its remote Formation Instance"
+);
+
+test_fn_diff!(
+    changed,
+    "formation diff stubb",
+    changed_formation(),
+    "
+--- remote Formation Instance stubb (frm-euqecs8n6h5l552ps6skal12lc)
++++ local Formation Plan stubb
+- gateway-flight: flask
++ gateway-flight: pequod
+  flight pequod
+-     image: foo.com/baz:latest
++     image: foo.com/baz:v2
+-     maximum: auto
++     maximum: 3
+- flight flask
+-     image: foo.com/bar:latest"
+);
+
+test_fn_diff!(
+    changed_json,
+    "formation diff stubb --format json",
+    changed_formation(),
+    r#"[{"name":"stubb","oid":"frm-euqecs8n6h5l552ps6skal12lc","gateway-flight":{"local":"pequod","remote":"flask"},"flights":[{"change":"changed","name":"pequod","fields":[{"field":"image","local":"foo.com/baz:v2","remote":"foo.com/baz:latest"},{"field":"maximum","local":3,"remote":null}]},{"change":"removed","name":"flask","image":"foo.com/bar:latest"}]}]"#
+);
+
+#[test]
+fn not_launched() {
+    let res = test_main_exec_with_ctx(
+        &argv!("formation diff stubb"),
+        ctx_with_formations(vec![default_local_formation()]),
+    );
+    assert!(res.is_err());
+    printer().clear();
+}
@@ -0,0 +1,100 @@
+use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+
+use crate::api::formation::{
+    ctx_with_formations, default_deployed_formation, default_local_formation,
+};
+
+macro_rules! test_fn_edit {
+    ($name:ident, $argv:expr, $local:expr, $output:expr) => {
+        #[test]
+        fn $name() {
+            let res = test_main_exec_with_ctx(&argv!($argv), ctx_with_formations(vec![$local]));
+            assert!(res.is_ok(), "{res:?}");
+            assert_eq!(printer().as_string().trim(), $output.trim());
+            printer().clear();
+        }
+    };
+}
+
+test_fn_edit!(
+    set_image,
+    "formation edit stubb --set-image flask=foo.com/bar:v2 --gateway-flight pequod",
+    default_local_formation(),
+    "Successfully edited local Formation Plan stubb
+    Changed Flight flask"
+);
+
+test_fn_edit!(
+    add_remove,
+    "formation edit stubb --remove-flight pequod --add-flight name=ahab,image=foo.com/ahab:latest",
+    default_deployed_formation(),
+    "Successfully edited local Formation Plan stubb
+    Added Flight ahab
+    Removed Flight pequod
+(hint: update its remote Formation Instance with 'seaplane formation update stubb')"
+);
+
+test_fn_edit!(
+    // a Flight without a tag differs from the same image with one
+    add_tag,
+    "formation edit stubb --set-image pequod=foo.com/baz",
+    default_local_formation(),
+    "Successfully edited local Formation Plan stubb
+    Changed Flight pequod"
+);
+
+test_fn_edit!(
+    unchanged,
+    "formation edit stubb --set-image flask=foo.com/bar:latest --gateway-flight flask",
+    default_deployed_formation(),
+    "No changes made to local Formation Plan stubb"
+);
+
+#[cfg(target_os = "linux")]
+#[test]
+fn editor() {
+    let mut ctx = ctx_with_formations(vec![default_local_formation()]);
+    ctx.editor = "sed -i s/baz:latest/baz:v2/".into();
+    let res = test_main_exec_with_ctx(&argv!("formation edit stubb"), ctx);
+    assert!(res.is_ok(), "{res:?}");
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully edited local Formation Plan stubb
+    Changed Flight pequod"
+    );
+    printer().clear();
+}
+
+// An invalid edit is kept, so that it isn't lost
+#[cfg(target_os = "linux")]
+#[test]
+fn editor_invalid() {
+    let mut ctx = ctx_with_formations(vec![default_local_formation()]);
+    ctx.editor = "sed -i s/flask/pequod/".into();
+    let err = test_main_exec_with_ctx(&argv!("formation edit stubb"), ctx).unwrap_err();
+    let err = format!("{err:?}");
+    let kept = err
+        .split('"')
+        .find(|s| s.ends_with(".toml"))
+        .unwrap_or_else(|| panic!("no kept file in {err}"));
+    assert!(std::fs::read_to_string(kept).unwrap().contains("pequod"));
+    std::fs::remove_file(kept).unwrap();
+    printer().clear();
+}
+
+#[test]
+fn invalid() {
+    for argv in [
+        argv!("formation edit stubb --remove-flight ahab"),
+        argv!("formation edit stubb --set-image ahab=foo.com/bar:v2"),
+        argv!("formation edit stubb --remove-flight flask,pequod"),
+        argv!("formation edit stubb --gateway-flight ahab"),
+        argv!("formation edit stubb --add-flight name=flask,image=foo.com/bar:v2"),
+        argv!("formation edit ahab --gateway-flight pequod"),
+    ] {
+        let res =
+            test_main_exec_with_ctx(&argv, ctx_with_formations(vec![default_local_formation()]));
+        assert!(res.is_err());
+    }
+    printer().clear();
+}
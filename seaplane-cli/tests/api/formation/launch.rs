@@ -1,9 +1,11 @@
 use httpmock::prelude::*;
+use seaplane::api::compute::v2::{EnvValue, Formation as FormationModel};
 use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+use serde_json::json;
 
 use crate::api::{
     formation::{ctx_with_formations, default_deployed_formation, default_local_formation},
-    when, MOCK_SERVER,
+    then, when, MOCK_SERVER,
 };
 
 macro_rules! mock_launch {
@@ -41,3 +43,44 @@ fn name() {
     );
     printer().clear();
 }
+
+#[test]
+fn secret() {
+    let with_db_pass = |mut model: FormationModel, value: EnvValue| {
+        model.flights[0].env.insert("DB_PASS".into(), value);
+        model
+    };
+    let local = with_db_pass(
+        default_local_formation(),
+        EnvValue::Secret { secret: "stubb/db-pass".into() },
+    );
+
+    // Only the request to the Compute API carries the value of the secret
+    let mut mock_secret = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:c3R1YmIvZGItcGFzcw");
+        then(t, &json!({"key": "c3R1YmIvZGItcGFzcw", "value": "aHVudGVyMg"}));
+    });
+    let mut mock_launch = MOCK_SERVER.mock(|w, then| {
+        when(w, POST, "/v2beta/formations").json_body(
+            serde_json::to_value(with_db_pass(default_local_formation(), "hunter2".into()))
+                .unwrap(),
+        );
+        then.status(201).json_body(
+            serde_json::to_value(with_db_pass(default_deployed_formation(), "hunter2".into()))
+                .unwrap(),
+        );
+    });
+
+    let res =
+        test_main_exec_with_ctx(&argv!("formation launch stubb"), ctx_with_formations(vec![local]));
+    assert!(res.is_ok(), "{res:?}");
+    mock_secret.assert();
+    mock_launch.assert();
+    assert!(printer()
+        .as_string()
+        .starts_with("Successfully Launched remote Formation Instance stubb"));
+
+    mock_secret.delete();
+    mock_launch.delete();
+    printer().clear();
+}
@@ -0,0 +1,38 @@
+use httpmock::prelude::*;
+use seaplane::api::compute::v2::{Architecture, PageMetadata, PagedResponse};
+use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+
+use crate::api::{
+    formation::{ctx_with_formations, default_deployed_formation},
+    when, MOCK_SERVER,
+};
+
+#[test]
+fn flight_details() {
+    let mut formation = default_deployed_formation();
+    formation.flights[1].minimum = 2;
+    formation.flights[1].maximum = Some(5);
+    formation.flights[1].architecture = vec![Architecture::Amd64, Architecture::Arm64];
+    formation.flights[1].api_permission = true;
+    let pr = PagedResponse {
+        objects: vec![formation],
+        meta: PageMetadata { total: 1, next: None, prev: None },
+    };
+    let mut mock = mock_fetch!(@impl "/v2beta/formations", pr);
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation list --fetch"),
+        ctx_with_formations(vec![default_deployed_formation()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "NAME   LOCAL  DEPLOYED  OID                             FLIGHT  MIN  MAX   ARCH         API
+stubb  true   true      frm-euqecs8n6h5l552ps6skal12lc  flask   1    auto  any          false
+                                                        pequod  2    5     amd64,arm64  true"
+    );
+
+    mock.delete();
+    printer().clear();
+}
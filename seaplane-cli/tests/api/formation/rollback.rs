@@ -0,0 +1,120 @@
+use httpmock::prelude::*;
+use seaplane::api::compute::v2::{FlightStatus, Formation as FormationModel, FormationRevision};
+use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+
+use crate::api::{
+    formation::{ctx_with_formations, default_deployed_formation},
+    then, when, MOCK_SERVER,
+};
+
+// The default deployed Formation at the given revision, with a new image for pequod if `changed`
+fn remote_at(revision: u32, changed: bool) -> FormationModel {
+    let mut remote = default_deployed_formation();
+    remote.revision = Some(revision);
+    if changed {
+        remote.flights[1].image = "foo.com/baz:v2".parse().unwrap();
+    }
+    remote
+}
+
+#[test]
+fn undo_rollout() {
+    let mut remote = remote_at(4, true);
+    remote
+        .active_revisions
+        .push(FormationRevision::of(&remote_at(3, false), 90).unwrap());
+    let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+    // Revision 3 is restored as the current configuration
+    let expected = remote_at(4, false);
+    let mut resp = remote_at(4, false);
+    resp.revision = Some(5);
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path).json_body_obj(&expected);
+        then(t, &serde_json::to_value(&resp).unwrap());
+    });
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation rollback stubb"),
+        ctx_with_formations(vec![remote.clone()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully Rolled Back remote Formation Instance stubb to revision 3
+    Changed Flight pequod"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn from_history() {
+    let remote = remote_at(4, true);
+    let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+    // The recorded configuration doesn't include the IDs of its Flights, so the changed Flight is
+    // recreated
+    let mut expected = remote_at(4, false);
+    expected.flights[1].oid = None;
+    expected.flights[1].status = FlightStatus::default();
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path).json_body_obj(&expected);
+        then(t, &serde_json::to_value(&expected).unwrap());
+    });
+
+    let mut ctx = ctx_with_formations(vec![remote.clone()]);
+    ctx.db.formation_history.record(&remote_at(2, true));
+    ctx.db.formation_history.record(&remote_at(3, false));
+    let res = test_main_exec_with_ctx(&argv!("formation rollback stubb --revision 3"), ctx);
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully Rolled Back remote Formation Instance stubb to revision 3
+    Changed Flight pequod"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn unknown_revision() {
+    let remote = remote_at(4, true);
+    let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+
+    let mut ctx = ctx_with_formations(vec![remote.clone()]);
+    ctx.db.formation_history.record(&remote_at(3, false));
+    let res = test_main_exec_with_ctx(&argv!("formation rollback stubb --revision 2"), ctx);
+    assert!(res.is_err());
+    mock_get.assert();
+    mock_update.assert_hits(0);
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
@@ -0,0 +1,141 @@
+use httpmock::prelude::*;
+use seaplane::api::compute::v2::{Formation as FormationModel, FormationRevision};
+use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+
+use crate::api::{
+    formation::{ctx_with_formations, default_deployed_formation},
+    then, when, MOCK_SERVER,
+};
+
+// The default deployed Formation at the given revision
+fn remote_at(revision: u32) -> FormationModel {
+    let mut remote = default_deployed_formation();
+    remote.revision = Some(revision);
+    remote
+}
+
+// The default deployed Formation with a new image for pequod
+fn changed() -> FormationModel {
+    let mut local = remote_at(3);
+    local.flights[1].image = "foo.com/baz:v2".parse().unwrap();
+    local
+}
+
+#[test]
+fn start() {
+    let remote = remote_at(3);
+    let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+    let mut expected = changed();
+    expected
+        .active_revisions
+        .push(FormationRevision::of(&remote, 90).unwrap());
+    let mut resp = expected.clone();
+    resp.revision = Some(4);
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path).json_body_obj(&expected);
+        then(t, &serde_json::to_value(&resp).unwrap());
+    });
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation rollout stubb --to-weight 10"),
+        ctx_with_formations(vec![changed()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully Rolled Out revision 4 of remote Formation Instance stubb to 10% of its \
+        traffic
+    Changed Flight pequod
+    Revision 3 receives 90% of the traffic
+(hint: finish the rollout with 'seaplane formation rollout stubb --to-weight 100' or undo it with \
+        'seaplane formation rollback stubb')"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn finish() {
+    let mut remote = changed();
+    remote.revision = Some(4);
+    remote
+        .active_revisions
+        .push(FormationRevision::of(&remote_at(3), 50).unwrap());
+    let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+    let mut expected = remote.clone();
+    expected.active_revisions.clear();
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path).json_body_obj(&expected);
+        then(t, &serde_json::to_value(&expected).unwrap());
+    });
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation rollout stubb --to-weight 100"),
+        ctx_with_formations(vec![remote.clone()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully Rolled Out revision 4 of remote Formation Instance stubb to 100% of its \
+        traffic"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn already_in_progress() {
+    let mut remote = remote_at(4);
+    remote
+        .active_revisions
+        .push(FormationRevision::of(&remote_at(3), 50).unwrap());
+    let path = format!("/v2beta/formations/{}", remote.oid.unwrap());
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path);
+        then(t, &serde_json::to_value(&remote).unwrap());
+    });
+
+    // A different change can't be rolled out until the current rollout is finished
+    let res = test_main_exec_with_ctx(
+        &argv!("formation rollout stubb --to-weight 10"),
+        ctx_with_formations(vec![changed()]),
+    );
+    assert!(res.is_err());
+    mock_get.assert();
+    mock_update.assert_hits(0);
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn invalid_weight() {
+    for argv in ["--to-weight 0", "--to-weight 101", ""] {
+        let argv = format!("seaplane formation rollout stubb {argv}");
+        assert!(seaplane_cli::test_cli(argv.split_whitespace()).is_err(), "{argv}");
+    }
+}
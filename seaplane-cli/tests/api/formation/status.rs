@@ -1,5 +1,7 @@
 use httpmock::prelude::*;
-use seaplane::api::compute::v2::{PageMetadata, PagedResponse};
+use seaplane::api::compute::v2::{
+    FlightStatus, Formation as FormationModel, PageMetadata, PagedResponse,
+};
 use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
 
 use crate::api::{
@@ -12,26 +14,26 @@ use crate::api::{
 
 const STARTING: &str = r#"◉ Formation stubb: Starting
 └─┐
-  │   FLIGHT    STATUS      OID
-  ├─◉ flask     Starting
-  └─◉ pequod    Starting"#;
+  │   FLIGHT    STATUS      MIN    MAX     ARCH    API      OID
+  ├─◉ flask     Starting    1      auto    any     false
+  └─◉ pequod    Starting    1      auto    any     false"#;
 
 const ONE: &str = r#"◉ Formation stubb (frm-euqecs8n6h5l552ps6skal12lc): Healthy
 └─┐
-  │   FLIGHT    STATUS     OID
-  ├─◉ flask     Healthy    flt-dkk055g7qp5cdfr1ui9rc363dc
-  └─◉ pequod    Healthy    flt-va5g8orh1503ja6r9mqf1gvvj8"#;
+  │   FLIGHT    STATUS     MIN    MAX     ARCH    API      OID
+  ├─◉ flask     Healthy    1      auto    any     false    flt-dkk055g7qp5cdfr1ui9rc363dc
+  └─◉ pequod    Healthy    1      auto    any     false    flt-va5g8orh1503ja6r9mqf1gvvj8"#;
 
 const MULTI: &str = r#"◉ Formation stubb (frm-euqecs8n6h5l552ps6skal12lc): Healthy
 └─┐
-  │   FLIGHT    STATUS     OID
-  ├─◉ flask     Healthy    flt-dkk055g7qp5cdfr1ui9rc363dc
-  └─◉ pequod    Healthy    flt-va5g8orh1503ja6r9mqf1gvvj8
+  │   FLIGHT    STATUS     MIN    MAX     ARCH    API      OID
+  ├─◉ flask     Healthy    1      auto    any     false    flt-dkk055g7qp5cdfr1ui9rc363dc
+  └─◉ pequod    Healthy    1      auto    any     false    flt-va5g8orh1503ja6r9mqf1gvvj8
 ◉ Formation stubb2 (frm-45rt0pqpo93q78t6au6dd8kn04): Healthy
 └─┐
-  │   FLIGHT     STATUS     OID
-  ├─◉ flask2     Healthy    flt-r5mrglbl0d4qjeopdh8vli89oo
-  └─◉ pequod2    Healthy    flt-90mifnt75d4i3a6eevt4po2f7k"#;
+  │   FLIGHT     STATUS     MIN    MAX     ARCH    API      OID
+  ├─◉ flask2     Healthy    1      auto    any     false    flt-r5mrglbl0d4qjeopdh8vli89oo
+  └─◉ pequod2    Healthy    1      auto    any     false    flt-90mifnt75d4i3a6eevt4po2f7k"#;
 
 macro_rules! test_fn_status {
     (@impl $test_fn:ident, $argv:expr, $ctx:expr, $correct_out:expr, $MOCK:expr) => {
@@ -60,6 +62,15 @@ macro_rules! test_fn_status {
                         $correct_out,
                         mock_fetch!());
     };
+    // Only the matching Formation is fetched by its ID
+    (@oid $test_fn:ident, $argv:expr, $local_db:expr, $correct_out:expr) => {
+        test_fn_status!(@impl
+                        $test_fn,
+                        $argv,
+                        ctx_with_formations(vec![$local_db]),
+                        $correct_out,
+                        mock_fetch!("frm-euqecs8n6h5l552ps6skal12lc"));
+    };
     (@all $test_fn:ident, $argv:expr, $local_db:expr, $correct_out:expr) => {
         test_fn_status!(@impl
                         $test_fn,
@@ -71,7 +82,13 @@ macro_rules! test_fn_status {
 }
 
 // Ask for single formation by name
-test_fn_status!(one_name_has_oid, "formation status stubb", default_deployed_formation(), ONE);
+test_fn_status!(
+    @oid
+    one_name_has_oid,
+    "formation status stubb",
+    default_deployed_formation(),
+    ONE
+);
 test_fn_status!(
     one_name_has_oid_no_fetch,
     "formation status stubb --no-fetch",
@@ -88,6 +105,7 @@ test_fn_status!(
 
 // Ask for single formation by OID
 test_fn_status!(
+    @oid
     one_oid_has_oid,
     "formation status frm-euqecs8n6h5l552ps6skal12lc",
     default_deployed_formation(),
@@ -100,18 +118,104 @@ test_fn_status!(
     ONE
 );
 test_fn_status!(
+    @oid
     one_oid_no_local_oid,
     "formation status frm-euqecs8n6h5l552ps6skal12lc",
     default_local_formation(),
     ONE
 );
+// Without fetching there is no Formation with that ID
+#[test]
+fn one_oid_no_local_oid_no_fetch() {
+    let res = test_main_exec_with_ctx(
+        &argv!("formation status frm-euqecs8n6h5l552ps6skal12lc --no-fetch"),
+        ctx_with_formations(vec![default_local_formation()]),
+    );
+    assert!(res.is_err());
+    printer().clear();
+}
+
+// Only the matching Formations are displayed
+const STUBB2: &str = r#"◉ Formation stubb2 (frm-45rt0pqpo93q78t6au6dd8kn04): Healthy
+└─┐
+  │   FLIGHT     STATUS     MIN    MAX     ARCH    API      OID
+  ├─◉ flask2     Healthy    1      auto    any     false    flt-r5mrglbl0d4qjeopdh8vli89oo
+  └─◉ pequod2    Healthy    1      auto    any     false    flt-90mifnt75d4i3a6eevt4po2f7k"#;
+
 test_fn_status!(
-    one_oid_no_local_oid_no_fetch,
-    "formation status frm-euqecs8n6h5l552ps6skal12lc --no-fetch",
-    default_local_formation(),
-    STARTING
+    @all
+    filter_exact,
+    "formation status stubb --no-fetch",
+    multi_deployed_formations(),
+    ONE
+);
+test_fn_status!(
+    @all
+    filter_exact_oid,
+    "formation status frm-45rt0pqpo93q78t6au6dd8kn04 --no-fetch",
+    multi_deployed_formations(),
+    STUBB2
+);
+test_fn_status!(
+    @all
+    filter_partial,
+    "formation status ubb2 --no-fetch",
+    multi_deployed_formations(),
+    STUBB2
+);
+test_fn_status!(
+    @all
+    filter_partial_oid,
+    "formation status 45rt0pqpo93q --no-fetch",
+    multi_deployed_formations(),
+    STUBB2
 );
 
+// A NAME matching no local Formation is looked for among all remote Instances
+test_fn_status!(
+    @impl
+    filter_fetch_unknown_name,
+    "formation status stubb2",
+    ctx_with_formations(vec![default_deployed_formation()]),
+    STUBB2,
+    mock_fetch!()
+);
+
+#[test]
+fn filter_no_match() {
+    let mut mock = mock_fetch!();
+    let res = test_main_exec_with_ctx(
+        &argv!("formation status nope"),
+        ctx_with_formations(multi_deployed_formations()),
+    );
+    assert!(res.is_err());
+    mock.assert();
+    mock.delete();
+    printer().clear();
+}
+
+// The matching Formation is fetched by its ID, without fetching the others
+#[test]
+fn filter_fetch_one() {
+    let mut mock_all = mock_fetch!();
+    let mut mock_one = mock_fetch!(@impl "/v2beta/formations/frm-45rt0pqpo93q78t6au6dd8kn04", {
+        let mut formations = multi_deployed_formations();
+        formations.pop().unwrap()
+    });
+    let res = test_main_exec_with_ctx(
+        &argv!("formation status stubb2"),
+        ctx_with_formations(multi_deployed_formations()),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_one.assert();
+    mock_all.assert_hits(0);
+    assert_eq!(printer().as_string().trim(), STUBB2);
+
+    mock_all.delete();
+    mock_one.delete();
+    printer().clear();
+}
+
 // Ask for multiple formations by name
 test_fn_status!(
     @all
@@ -127,3 +231,91 @@ test_fn_status!(
     multi_deployed_formations(),
     MULTI
 );
+
+// A single remote Formation with the given Flight statuses
+fn mock_fetch_statuses(statuses: [FlightStatus; 2]) -> httpmock::Mock<'static> {
+    let mut formation = default_deployed_formation();
+    for (flight, status) in formation.flights.iter_mut().zip(statuses) {
+        flight.status = status;
+    }
+    let pr = PagedResponse {
+        objects: vec![formation],
+        meta: PageMetadata { total: 1, next: None, prev: None },
+    };
+    mock_fetch!(@impl "/v2beta/formations", pr)
+}
+
+macro_rules! test_fn_wait_healthy {
+    ($test_fn:ident, $argv:expr, $statuses:expr, $ok:expr, $correct_out:expr) => {
+        #[test]
+        fn $test_fn() {
+            let mut mock = mock_fetch_statuses($statuses);
+            let res = test_main_exec_with_ctx(
+                &argv!($argv),
+                ctx_with_formations(vec![default_deployed_formation()]),
+            );
+            assert_eq!(res.is_ok(), $ok, "{res:?}");
+            mock.assert();
+            let actual_out: String = printer().as_string().trim().to_string();
+            assert_eq!($correct_out, actual_out);
+
+            printer().clear();
+            mock.delete();
+        }
+    };
+}
+
+test_fn_wait_healthy!(
+    wait_healthy,
+    "formation status --wait-healthy",
+    [FlightStatus::Healthy, FlightStatus::Healthy],
+    true,
+    ONE
+);
+test_fn_wait_healthy!(
+    watch_wait_healthy,
+    "formation status --watch --wait-healthy --interval 1",
+    [FlightStatus::Healthy, FlightStatus::Healthy],
+    true,
+    ONE
+);
+test_fn_wait_healthy!(
+    wait_healthy_unhealthy,
+    "formation status --wait-healthy",
+    [FlightStatus::Healthy, FlightStatus::Unhealthy],
+    false,
+    r#"◉ Formation stubb (frm-euqecs8n6h5l552ps6skal12lc): Unhealthy
+└─┐
+  │   FLIGHT    STATUS       MIN    MAX     ARCH    API      OID
+  ├─◉ flask     Healthy      1      auto    any     false    flt-dkk055g7qp5cdfr1ui9rc363dc
+  └─◉ pequod    Unhealthy    1      auto    any     false    flt-va5g8orh1503ja6r9mqf1gvvj8"#
+);
+test_fn_wait_healthy!(
+    wait_healthy_timeout,
+    "formation status --wait-healthy --timeout 0",
+    [FlightStatus::Starting, FlightStatus::Healthy],
+    false,
+    r#"◉ Formation stubb (frm-euqecs8n6h5l552ps6skal12lc): Starting
+└─┐
+  │   FLIGHT    STATUS      MIN    MAX     ARCH    API      OID
+  ├─◉ flask     Starting    1      auto    any     false    flt-dkk055g7qp5cdfr1ui9rc363dc
+  └─◉ pequod    Healthy     1      auto    any     false    flt-va5g8orh1503ja6r9mqf1gvvj8"#
+);
+
+// Waiting for no Formations at all is not the same as them all being Healthy
+#[test]
+fn wait_healthy_none() {
+    let mut mock = mock_fetch!(@impl "/v2beta/formations", PagedResponse::<FormationModel> {
+        objects: Vec::new(),
+        meta: PageMetadata { total: 0, next: None, prev: None },
+    });
+    let res = test_main_exec_with_ctx(
+        &argv!("formation status --wait-healthy"),
+        ctx_with_formations(Vec::new()),
+    );
+    assert!(res.is_err());
+    mock.assert();
+
+    printer().clear();
+    mock.delete();
+}
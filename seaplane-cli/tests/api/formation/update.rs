@@ -0,0 +1,118 @@
+use httpmock::prelude::*;
+use seaplane_cli::{printer::printer, test_main_exec_with_ctx};
+
+use crate::api::{
+    formation::{ctx_with_formations, default_deployed_formation, default_local_formation},
+    then, when, MOCK_SERVER,
+};
+
+#[test]
+fn changed_flight() {
+    let mut local = default_deployed_formation();
+    local.flights[1].image = "foo.com/baz:v2".parse().unwrap();
+    let path = format!("/v2beta/formations/{}", local.oid.unwrap());
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(default_deployed_formation()).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path).json_body_obj(&local);
+        then(t, &serde_json::to_value(&local).unwrap());
+    });
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation update stubb"),
+        ctx_with_formations(vec![local.clone()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully Updated remote Formation Instance stubb (frm-euqecs8n6h5l552ps6skal12lc)\n    \
+        Changed Flight pequod"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn pinned_image() {
+    let mut local = default_deployed_formation();
+    // An image without a digest matches any digest, but pinning one is still a change
+    local.flights[1].image =
+        "foo.com/baz:latest@sha256:9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+            .parse()
+            .unwrap();
+    let path = format!("/v2beta/formations/{}", local.oid.unwrap());
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(default_deployed_formation()).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path).json_body_obj(&local);
+        then(t, &serde_json::to_value(&local).unwrap());
+    });
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation update stubb"),
+        ctx_with_formations(vec![local.clone()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert();
+    assert_eq!(
+        printer().as_string().trim(),
+        "Successfully Updated remote Formation Instance stubb (frm-euqecs8n6h5l552ps6skal12lc)\n    \
+        Changed Flight pequod"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn up_to_date() {
+    let local = default_deployed_formation();
+    let path = format!("/v2beta/formations/{}", local.oid.unwrap());
+
+    let mut mock_get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, &path);
+        then(t, &serde_json::to_value(&local).unwrap());
+    });
+    let mut mock_update = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, &path);
+        then(t, &serde_json::to_value(&local).unwrap());
+    });
+
+    let res = test_main_exec_with_ctx(
+        &argv!("formation redeploy stubb"),
+        ctx_with_formations(vec![local.clone()]),
+    );
+    assert!(res.is_ok(), "{res:?}");
+    mock_get.assert();
+    mock_update.assert_hits(0);
+    assert_eq!(
+        printer().as_string().trim(),
+        "Remote Formation Instance stubb is already up to date"
+    );
+
+    mock_get.delete();
+    mock_update.delete();
+    printer().clear();
+}
+
+#[test]
+fn not_launched() {
+    let res = test_main_exec_with_ctx(
+        &argv!("formation update stubb"),
+        ctx_with_formations(vec![default_local_formation()]),
+    );
+    assert!(res.is_err());
+    printer().clear();
+}
@@ -1,8 +1,10 @@
+use std::io::Write;
+
 use httpmock::prelude::*;
-use seaplane_cli::printer::printer;
+use seaplane_cli::{error::Result, printer::printer};
 use serde_json::json;
 
-use super::{then, when, when_json, MOCK_SERVER};
+use super::{ctx_from_url, then, when, when_json, MOCK_SERVER};
 
 fn multi_kv_resp() -> serde_json::Value {
     json!({
@@ -192,3 +194,258 @@ fn metadata_delete() {
     assert_eq!(printer().as_string().trim(), "Removed Zm9v\n\nSuccessfully removed 1 item");
     printer().clear();
 }
+
+#[test]
+fn metadata_delete_recursive() {
+    let mut list = MOCK_SERVER.mock(|w, t| {
+        when_json(w, GET, "/v1/config/base64:cmV0aXJlZA/");
+        then(
+            t,
+            &json!({"next_key": None::<String>, "kvs": [
+                {"key": "cmV0aXJlZC9h", "value": "YmFy"},
+                {"key": "cmV0aXJlZC9i", "value": "YnV6"}
+            ]}),
+        );
+    });
+    let mut removed = MOCK_SERVER.mock(|w, t| {
+        when_json(w, DELETE, "/v1/config/base64:cmV0aXJlZC9h");
+        then(t, &json!({"status": 200_i32, "title": "Ok"}));
+    });
+    let mut gone = MOCK_SERVER.mock(|w, t| {
+        when_json(w, DELETE, "/v1/config/base64:cmV0aXJlZC9i");
+        t.status(404)
+            .json_body_obj(&json!({"status": 404_i32, "title": "Not Found"}));
+    });
+
+    // Nothing is removed without confirmation
+    let res = run!("metadata delete retired --recursive");
+    assert!(res.is_ok());
+    list.assert_hits(1);
+    removed.assert_hits(0);
+    assert!(printer().as_string().is_empty());
+    printer().clear();
+
+    // A key which can't be removed doesn't stop the others
+    let res = run!("metadata delete retired --recursive --yes");
+    assert!(res.is_err());
+    list.assert_hits(2);
+    removed.assert_hits(1);
+    gone.assert_hits(1);
+    assert_eq!(printer().as_string().trim(), "Removed cmV0aXJlZC9h\n\nSuccessfully removed 1 item");
+    printer().clear();
+
+    list.delete();
+    removed.delete();
+    gone.delete();
+
+    // Confirmation is only skipped for recursive deletes
+    assert!(seaplane_cli::test_cli(
+        "seaplane --stateless metadata delete retired --yes".split(' ')
+    )
+    .is_err());
+}
+
+#[test]
+fn metadata_get_version() {
+    let resp = json!({"key": "bW9ieQ", "value": "YmFy", "version": 4});
+
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when_json(w, GET, "/v1/config/base64:bW9ieQ");
+        then(t, &resp);
+    });
+
+    let res = run!("metadata get moby --format json");
+    assert!(res.is_ok());
+    mock.assert_hits(1);
+    assert_eq!(
+        printer().as_string().trim(),
+        json!([{"key": "bW9ieQ", "value": "YmFy", "version": 4}]).to_string()
+    );
+    printer().clear();
+    mock.delete();
+}
+
+#[test]
+fn metadata_put_if_version() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:bW9ieQ")
+            .header("if-match", "\"4\"")
+            .body("YmFy");
+        then(t, &json!({"status": 200_i32, "title": "Ok"}));
+    });
+
+    let res = run!("metadata set moby bar --if-version 4");
+    assert!(res.is_ok());
+    mock.assert_hits(1);
+    assert_eq!(printer().as_string().trim(), "Success");
+    printer().clear();
+    mock.delete();
+}
+
+#[test]
+fn metadata_put_if_absent_conflict() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:bW9ieQ").header("if-none-match", "*");
+        t.status(412)
+            .json_body_obj(&json!({"status": 412_i32, "title": "Precondition Failed"}));
+    });
+
+    let res = run!("metadata set moby bar --if-absent");
+    assert!(res.is_err());
+    mock.assert_hits(1);
+    assert!(printer().as_string().is_empty());
+    printer().clear();
+    mock.delete();
+
+    // The conditions can't be combined
+    assert!(seaplane_cli::test_cli(
+        "seaplane --stateless --api-key abc123 metadata set moby bar --if-absent --if-version 4"
+            .split(' ')
+    )
+    .is_err());
+}
+
+#[test]
+fn metadata_export() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when_json(w, GET, "/v1/config/base64:ZXhwb3J0/");
+        then(t, &multi_kv_resp());
+    });
+
+    let res = run!("metadata export export");
+    assert!(res.is_ok());
+    mock.assert_hits(1);
+    assert_eq!(
+        printer().as_string(),
+        "{\"key\":\"Zm9v\",\"value\":\"YmFy\"}\n{\"key\":\"YmF6\",\"value\":\"YnV6\"}\n"
+    );
+    printer().clear();
+
+    let res = run!("metadata export export --decode");
+    assert!(res.is_ok());
+    mock.assert_hits(2);
+    assert_eq!(
+        printer().as_string(),
+        "{\"key\":\"foo\",\"value\":\"bar\"}\n{\"key\":\"baz\",\"value\":\"buz\"}\n"
+    );
+    printer().clear();
+    mock.delete();
+}
+
+// The archive path isn't known until runtime, so the arguments can't be built with run!
+fn run_import(archive: &tempfile::NamedTempFile, args: &[&str]) -> Result<()> {
+    let argv = ["seaplane", "--stateless", "--api-key", "abc123", "metadata", "import"]
+        .into_iter()
+        .chain(archive.path().to_str())
+        .chain(args.iter().copied());
+    seaplane_cli::test_main_exec_with_ctx(
+        &seaplane_cli::test_cli(argv).unwrap(),
+        ctx_from_url(MOCK_SERVER.base_url()),
+    )
+}
+
+#[test]
+fn metadata_import() {
+    let mut archive = tempfile::Builder::new()
+        .suffix(".jsonl")
+        .tempfile()
+        .unwrap();
+    writeln!(archive, "{}", json!({"key": "aW1wb3J0L25ldw", "value": "YmFy"})).unwrap();
+    writeln!(archive, "{}", json!({"key": "aW1wb3J0L3NhbWU", "value": "YmFy"})).unwrap();
+    writeln!(archive, "{}", json!({"key": "aW1wb3J0L2NoYW5nZWQ", "value": "YmFy"})).unwrap();
+
+    let mut get_new = MOCK_SERVER.mock(|w, t| {
+        when_json(w, GET, "/v1/config/base64:aW1wb3J0L25ldw");
+        t.status(404)
+            .json_body_obj(&json!({"status": 404_i32, "title": "Not Found"}));
+    });
+    let mut get_same = MOCK_SERVER.mock(|w, t| {
+        when_json(w, GET, "/v1/config/base64:aW1wb3J0L3NhbWU");
+        then(t, &json!({"key": "aW1wb3J0L3NhbWU", "value": "YmFy"}));
+    });
+    let mut get_changed = MOCK_SERVER.mock(|w, t| {
+        when_json(w, GET, "/v1/config/base64:aW1wb3J0L2NoYW5nZWQ");
+        then(t, &json!({"key": "aW1wb3J0L2NoYW5nZWQ", "value": "YnV6", "version": 7}));
+    });
+    // Keys are only written as they were read
+    let mut put_new = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:aW1wb3J0L25ldw")
+            .header("if-none-match", "*")
+            .body("YmFy");
+        then(t, &json!({"status": 200_i32, "title": "Ok"}));
+    });
+    let mut put_changed = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:aW1wb3J0L2NoYW5nZWQ")
+            .header("if-match", "\"7\"")
+            .body("YmFy");
+        then(t, &json!({"status": 200_i32, "title": "Ok"}));
+    });
+
+    let res = run_import(&archive, &["--dry-run"]);
+    assert!(res.is_ok());
+    get_new.assert_hits(1);
+    put_new.assert_hits(0);
+    put_changed.assert_hits(0);
+    assert_eq!(
+        printer().as_string().trim(),
+        "Would create aW1wb3J0L25ldw
+Would skip aW1wb3J0L3NhbWU (unchanged)
+Would update aW1wb3J0L2NoYW5nZWQ
+
+Dry run, nothing was written: 1 to create, 1 to update, 1 to skip"
+    );
+    printer().clear();
+
+    let res = run_import(&archive, &["--skip-existing"]);
+    assert!(res.is_ok());
+    put_new.assert_hits(1);
+    put_changed.assert_hits(0);
+    assert_eq!(
+        printer().as_string().trim(),
+        "Created aW1wb3J0L25ldw
+Skipped aW1wb3J0L3NhbWU (unchanged)
+Skipped aW1wb3J0L2NoYW5nZWQ (already exists)
+
+Successfully imported 3 keys: 1 created, 0 updated, 2 skipped"
+    );
+    printer().clear();
+
+    let res = run_import(&archive, &[]);
+    assert!(res.is_ok());
+    put_new.assert_hits(2);
+    put_changed.assert_hits(1);
+    assert_eq!(
+        printer().as_string().trim(),
+        "Created aW1wb3J0L25ldw
+Skipped aW1wb3J0L3NhbWU (unchanged)
+Updated aW1wb3J0L2NoYW5nZWQ
+
+Successfully imported 3 keys: 1 created, 1 updated, 1 skipped"
+    );
+    printer().clear();
+
+    // A key created by someone else after it was found missing is left as they wrote it
+    put_new.delete();
+    let mut put_new = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:aW1wb3J0L25ldw").header("if-none-match", "*");
+        t.status(412)
+            .json_body_obj(&json!({"status": 412_i32, "title": "Precondition Failed"}));
+    });
+    let res = run_import(&archive, &[]);
+    assert!(res.is_err());
+    put_new.assert_hits(1);
+    put_changed.assert_hits(2);
+    assert_eq!(
+        printer().as_string().trim(),
+        "Conflict aW1wb3J0L25ldw (changed while importing, left untouched)
+Skipped aW1wb3J0L3NhbWU (unchanged)
+Updated aW1wb3J0L2NoYW5nZWQ
+
+Imported 3 keys: 0 created, 1 updated, 1 skipped, 1 conflicting"
+    );
+    printer().clear();
+
+    for mock in [&mut get_new, &mut get_same, &mut get_changed, &mut put_new, &mut put_changed] {
+        mock.delete();
+    }
+}
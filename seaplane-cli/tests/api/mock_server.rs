@@ -0,0 +1,285 @@
+// Full workflows against the stateful stand-in for the APIs, rather than individual requests
+// against canned responses
+
+use std::io::Write;
+
+use seaplane_cli::printer::printer;
+use seaplane_mock::MockServer;
+
+use super::ctx_from_url;
+
+macro_rules! run_on {
+    ($server:expr, $argv:expr) => {{
+        seaplane_cli::test_main_exec_with_ctx(&argv!($argv), ctx_from_url($server.base_url()))
+    }};
+}
+
+#[test]
+fn metadata_workflow() {
+    let server = MockServer::start();
+
+    assert!(run_on!(server, "metadata set foo bar").is_ok());
+    printer().clear();
+    assert!(run_on!(server, "metadata get foo --decode").is_ok());
+    assert_eq!(printer().as_string().trim(), "bar");
+    printer().clear();
+
+    assert!(run_on!(server, "metadata delete foo").is_ok());
+    assert!(run_on!(server, "metadata get foo").is_err());
+    printer().clear();
+}
+
+#[test]
+fn locks_workflow() {
+    let server = MockServer::start();
+
+    assert!(run_on!(server, "locks acquire foo --client-id bar --ttl 30").is_ok());
+    // The lock is already held
+    assert!(run_on!(server, "locks acquire foo --client-id baz --ttl 30").is_err());
+    printer().clear();
+    assert!(run_on!(server, "locks list --decode --no-header").is_ok());
+    assert!(printer().as_string().contains("bar"));
+    printer().clear();
+}
+
+#[test]
+fn formation_apply_workflow() {
+    let server = MockServer::start();
+    let mut doc = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    let path = doc.path().to_path_buf();
+    let apply = |args: &str| {
+        let argv = format!(
+            "seaplane --stateless --api-key abc123 formation apply -f {} {args}",
+            path.display()
+        );
+        let res = seaplane_cli::test_main_exec_with_ctx(
+            &seaplane_cli::test_cli(argv.split_whitespace()).unwrap(),
+            ctx_from_url(server.base_url()),
+        );
+        assert!(res.is_ok(), "{res:?}");
+        let out = printer().as_string().to_string();
+        printer().clear();
+        out
+    };
+
+    write!(
+        doc,
+        r#"
+        name = "stubb"
+        gateway-flight = "flask"
+
+        [[flights]]
+        name = "flask"
+        image = "foo.com/flask:latest"
+        "#
+    )
+    .unwrap();
+    assert!(apply("--dry-run").starts_with("Would launch remote Formation Instance stubb\n"));
+    let out = apply("");
+    assert!(out.starts_with("Successfully Launched remote Formation Instance stubb (frm-"));
+    assert!(out.ends_with("    Added Flight flask\n"));
+    assert_eq!(apply(""), "Remote Formation Instance stubb is already up to date\n");
+
+    write!(
+        doc,
+        r#"
+        [[flights]]
+        name = "pequod"
+        image = "foo.com/pequod:latest"
+        minimum = 2
+        "#
+    )
+    .unwrap();
+    let out = apply("--dry-run");
+    assert!(out.starts_with("Would update remote Formation Instance stubb (frm-"));
+    assert!(out.ends_with("    Added Flight pequod\n"));
+    assert!(apply("").starts_with("Successfully Updated remote Formation Instance stubb (frm-"));
+    assert_eq!(apply(""), "Remote Formation Instance stubb is already up to date\n");
+
+    // Pinning an image to a digest is a change, even though an image without one matches any
+    let pinned = std::fs::read_to_string(&path).unwrap().replace(
+        "foo.com/pequod:latest",
+        "foo.com/pequod:latest@sha256:\
+        9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08",
+    );
+    std::fs::write(&path, pinned).unwrap();
+    let out = apply("");
+    assert!(out.starts_with("Successfully Updated remote Formation Instance stubb (frm-"));
+    assert!(out.ends_with("    Changed Flight pequod\n"));
+    assert_eq!(apply(""), "Remote Formation Instance stubb is already up to date\n");
+}
+
+// Instances which are on later pages are still found and updated, rather than launched again
+#[test]
+fn formation_apply_paged() {
+    let server = MockServer::builder().page_size(1).start();
+    let apply = |name: &str| {
+        let mut doc = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        write!(
+            doc,
+            r#"
+            name = "{name}"
+
+            [[flights]]
+            name = "flask"
+            image = "foo.com/flask:latest"
+            "#
+        )
+        .unwrap();
+        let argv = format!(
+            "seaplane --stateless --api-key abc123 formation apply -f {}",
+            doc.path().display()
+        );
+        let res = seaplane_cli::test_main_exec_with_ctx(
+            &seaplane_cli::test_cli(argv.split_whitespace()).unwrap(),
+            ctx_from_url(server.base_url()),
+        );
+        assert!(res.is_ok(), "{res:?}");
+        let out = printer().as_string().to_string();
+        printer().clear();
+        out
+    };
+
+    for name in ["ahab", "stubb", "flask"] {
+        let out = apply(name);
+        assert!(
+            out.starts_with(&format!("Successfully Launched remote Formation Instance {name}")),
+            "{out}"
+        );
+    }
+    for name in ["ahab", "stubb", "flask"] {
+        assert_eq!(
+            apply(name),
+            format!("Remote Formation Instance {name} is already up to date\n")
+        );
+    }
+}
+
+// Instances unknown to the local DB are looked for on every page, preferring exact matches
+#[test]
+fn formation_status_paged() {
+    let server = MockServer::builder().page_size(1).start();
+    for name in ["stubb", "stubb-two", "ahab"] {
+        let mut doc = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        write!(
+            doc,
+            r#"
+            name = "{name}"
+
+            [[flights]]
+            name = "flask"
+            image = "foo.com/flask:latest"
+            "#
+        )
+        .unwrap();
+        let argv = format!(
+            "seaplane --stateless --api-key abc123 formation apply -f {}",
+            doc.path().display()
+        );
+        let res = seaplane_cli::test_main_exec_with_ctx(
+            &seaplane_cli::test_cli(argv.split_whitespace()).unwrap(),
+            ctx_from_url(server.base_url()),
+        );
+        assert!(res.is_ok(), "{res:?}");
+    }
+    printer().clear();
+
+    let status = |name: &str| {
+        let argv = format!("seaplane --stateless --api-key abc123 formation status {name}");
+        let res = seaplane_cli::test_main_exec_with_ctx(
+            &seaplane_cli::test_cli(argv.split_whitespace()).unwrap(),
+            ctx_from_url(server.base_url()),
+        );
+        assert!(res.is_ok(), "{res:?}");
+        let out = printer().as_string().to_string();
+        printer().clear();
+        out
+    };
+
+    for (name, found, not_found) in
+        [("stubb", "stubb", "stubb-two"), ("ahab", "ahab", "stubb"), ("two", "stubb-two", "ahab")]
+    {
+        let out = status(name);
+        assert!(out.contains(&format!("Formation {found} (")), "{out}");
+        assert!(!out.contains(&format!("Formation {not_found} (")), "{out}");
+    }
+}
+
+#[test]
+fn formation_template_workflow() {
+    let server = MockServer::start();
+    let run = |args: &str| {
+        let argv = format!("seaplane --stateless --api-key abc123 formation {args}");
+        let res = seaplane_cli::test_main_exec_with_ctx(
+            &seaplane_cli::test_cli(argv.split_whitespace()).unwrap(),
+            ctx_from_url(server.base_url()),
+        );
+        assert!(res.is_ok(), "{res:?}");
+        let out = printer().as_string().to_string();
+        printer().clear();
+        out
+    };
+
+    for format in ["toml", "json", "yaml"] {
+        let mut doc = tempfile::Builder::new()
+            .suffix(&format!(".{format}"))
+            .tempfile()
+            .unwrap();
+        let template = run(&format!(
+            "template -n stubb-{format} --file-format {format} \
+            --image foo.com/flask:latest,foo.com/pequod:latest"
+        ));
+        write!(doc, "{template}").unwrap();
+
+        let out = run(&format!("apply -f {}", doc.path().display()));
+        assert!(
+            out.starts_with(&format!(
+                "Successfully Launched remote Formation Instance stubb-{format} (frm-"
+            )),
+            "{out}"
+        );
+        assert!(out.ends_with("    Added Flight flask\n    Added Flight pequod\n"), "{out}");
+    }
+}
+
+#[test]
+fn formation_secret_workflow() {
+    let server = MockServer::start();
+    let mut doc = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(
+        doc,
+        r#"
+        name = "stubb"
+
+        [[flights]]
+        name = "flask"
+        image = "foo.com/flask:latest"
+        env = {{ DB_PASS = {{ secret = "stubb/db-pass" }} }}
+        "#
+    )
+    .unwrap();
+    let apply = || {
+        let argv = format!(
+            "seaplane --stateless --api-key abc123 formation apply -f {}",
+            doc.path().display()
+        );
+        let res = seaplane_cli::test_main_exec_with_ctx(
+            &seaplane_cli::test_cli(argv.split_whitespace()).unwrap(),
+            ctx_from_url(server.base_url()),
+        );
+        let out = printer().as_string().to_string();
+        printer().clear();
+        res.map(|()| out)
+    };
+
+    // The Metadata key must exist before the Formation can be launched
+    assert!(apply().is_err());
+    assert!(run_on!(server, "metadata set stubb/db-pass hunter2").is_ok());
+    printer().clear();
+
+    assert!(apply()
+        .unwrap()
+        .starts_with("Successfully Launched remote Formation Instance stubb (frm-"));
+    // The Instance has the value of the secret, which is still the same as the reference to it
+    assert_eq!(apply().unwrap(), "Remote Formation Instance stubb is already up to date\n");
+}
@@ -29,6 +29,7 @@ mod account;
 mod formation;
 mod locks;
 mod metadata;
+mod mock_server;
 mod restrict;
 
 pub fn ctx_from_url(url: String) -> Ctx {
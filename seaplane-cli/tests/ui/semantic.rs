@@ -159,6 +159,11 @@ fn seaplane_formation_plan() {
     let res = cli!("formation plan --flight -");
     assert!(res.is_ok(), "{res:?}");
     assert!(cli!("formation plan -F-").is_ok());
+    // document
+    assert!(cli!("formation plan --file stubb.toml").is_ok());
+    assert!(cli!("formation plan -f- --file-format yaml --launch").is_ok());
+    assert!(cli!("formation plan --file stubb.toml -F foo.json").is_err());
+    assert!(cli!("formation plan --file stubb.toml --name stubb").is_err());
     // multiples
     // Technically VAL2;VAL2 is the correct way to pass two flights, however when _not_ using the
     // INLINE flight spec people probably expect VAL1,VAL2. So the way our validation works we
@@ -269,6 +274,34 @@ fn seaplane_formation_land() {
     assert!(cli!("formation stop foo").is_ok());
 }
 
+#[test]
+fn seaplane_formation_edit() {
+    // requires a NAME|ID
+    assert!(cli!("formation edit").is_err());
+    // opens an editor
+    assert!(cli!("formation edit foo").is_ok());
+    // options
+    assert!(cli!("formation edit foo --add-flight name=bar,image=demos.com/nginx:latest").is_ok());
+    assert!(cli!("formation edit foo --remove-flight bar,baz --remove-flight qux").is_ok());
+    assert!(cli!("formation edit foo --set-image bar=demos.com/nginx:v2 -G bar").is_ok());
+    // --set-image requires FLIGHT=IMAGE
+    assert!(cli!("formation edit foo --set-image demos.com/nginx:v2").is_err());
+}
+
+#[test]
+fn seaplane_formation_status() {
+    // NAME|ID is optional
+    assert!(cli!("formation status").is_ok());
+    assert!(cli!("formation status foo --watch --interval 10").is_ok());
+    assert!(cli!("formation status foo --wait-healthy --timeout 60 --interval 10").is_ok());
+    // --interval requires --watch or --wait-healthy
+    assert!(cli!("formation status foo --interval 10").is_err());
+    // --timeout requires --wait-healthy
+    assert!(cli!("formation status foo --watch --timeout 60").is_err());
+    // watching and waiting fetch the remote Instances
+    assert!(cli!("formation status foo --wait-healthy --no-fetch").is_err());
+}
+
 #[test]
 fn seaplane_md() {
     // requires a subcmd
@@ -1,12 +1,57 @@
 //! Interacting with our REST API endpoints
 
+// Without either the `blocking` or `async` feature there are no request types, leaving the shared
+// request building code unused
+#![cfg_attr(not(any(feature = "blocking", feature = "async")), allow(dead_code, unused_imports))]
+
+// Applies the configuration shared by all request builders (default headers, HTTPS enforcement and
+// certificate validation) to either a blocking or async `reqwest` `ClientBuilder` and builds the
+// client
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    any(
+        feature = "compute_api_v2",
+        feature = "identity_api_v1",
+        feature = "locks_api_v1",
+        feature = "metadata_api_v1",
+        feature = "restrict_api_v1",
+    )
+))]
+macro_rules! build_client {
+    ($builder:expr, $headers:expr, $this:ident) => {{
+        #[cfg_attr(
+            not(any(
+                feature = "api_tests",
+                feature = "allow_insecure_urls",
+                feature = "danger_zone"
+            )),
+            allow(unused_mut)
+        )]
+        let mut builder = $builder.default_headers($headers).https_only(true);
+
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "api_tests")] {
+                builder = builder.https_only(false);
+            } else if #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))] {
+                builder = builder.https_only(!$this.allow_http);
+            }
+        }
+        #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
+        {
+            builder = builder.danger_accept_invalid_certs($this.allow_invalid_certs);
+        }
+
+        builder.build()?
+    }};
+}
+
 pub mod compute;
 pub mod identity;
 pub mod locks;
 pub mod metadata;
 pub mod restrict;
 #[cfg(any(
-    feature = "compute_api_v1",
+    feature = "compute_api_v2",
     feature = "locks_api_v1",
     feature = "metadata_api_v1",
     feature = "restrict_api_v1"
@@ -17,18 +62,44 @@ pub mod shared;
 pub mod error;
 pub use error::*;
 
+// Retrying requests which fail due to transient errors
+mod retry;
+pub use retry::RetryPolicy;
+
 /// Request builder base structs that handle token reuse
 #[cfg(any(
-    feature = "compute_api_v1",
+    feature = "compute_api_v2",
     feature = "locks_api_v1",
     feature = "metadata_api_v1",
     feature = "restrict_api_v1",
 ))]
 mod request;
 #[cfg(any(
-    feature = "compute_api_v1",
+    feature = "compute_api_v2",
     feature = "locks_api_v1",
     feature = "metadata_api_v1",
     feature = "restrict_api_v1",
 ))]
 pub(crate) use request::*;
+
+/// A single entry point sharing a connection pool, configuration and credentials between requests
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    any(
+        feature = "compute_api_v2",
+        feature = "locks_api_v1",
+        feature = "metadata_api_v1",
+        feature = "restrict_api_v1",
+    )
+))]
+mod client;
+#[cfg(all(
+    any(feature = "blocking", feature = "async"),
+    any(
+        feature = "compute_api_v2",
+        feature = "locks_api_v1",
+        feature = "metadata_api_v1",
+        feature = "restrict_api_v1",
+    )
+))]
+pub use client::*;
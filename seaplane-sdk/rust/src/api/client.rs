@@ -0,0 +1,264 @@
+//! A single entry point to the APIs which shares one connection pool, configuration and set of
+//! credentials between all requests
+
+use reqwest::Url;
+
+#[cfg(feature = "compute_api_v2")]
+use crate::api::compute::v2::FormationsRequestBuilder;
+#[cfg(feature = "identity_api_v1")]
+use crate::api::identity::v1::{TokenProvider, TokenRequestBuilder};
+#[cfg(feature = "locks_api_v1")]
+use crate::api::locks::v1::LocksRequestBuilder;
+#[cfg(feature = "metadata_api_v1")]
+use crate::api::metadata::v1::MetadataRequestBuilder;
+#[cfg(feature = "restrict_api_v1")]
+use crate::api::restrict::v1::RestrictRequestBuilder;
+use crate::{
+    api::{default_headers, Auth, RetryPolicy},
+    error::Result,
+};
+
+/// A builder struct for creating a [`SeaplaneClient`]
+#[derive(Debug, Default)]
+pub struct SeaplaneClientBuilder {
+    // Used to request and refresh access tokens
+    #[cfg(feature = "identity_api_v1")]
+    api_key: Option<String>,
+    // Used for Bearer Auth when no API key is set
+    auth: Option<Auth>,
+    retry: RetryPolicy,
+    compute_url: Option<Url>,
+    identity_url: Option<Url>,
+    metadata_url: Option<Url>,
+    locks_url: Option<Url>,
+    restrict_url: Option<Url>,
+    // Used to allow HTTP endpoints
+    #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
+    allow_http: bool,
+    // Used to allow invalid TLS certs
+    #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
+    allow_invalid_certs: bool,
+}
+
+impl SeaplaneClientBuilder {
+    /// Create a new `Default` builder
+    pub fn new() -> Self { Self::default() }
+
+    /// Set the API Key used to request access tokens, which are then cached and refreshed as
+    /// needed by a [`TokenProvider`] shared by all requests
+    ///
+    /// **NOTE:** Either this, `token` or `token_provider` is required
+    #[cfg(feature = "identity_api_v1")]
+    #[must_use]
+    pub fn api_key<S: Into<String>>(mut self, key: S) -> Self {
+        self.api_key = Some(key.into());
+        self.auth = None;
+        self
+    }
+
+    /// Set the token used in Bearer Authorization
+    ///
+    /// **NOTE:** Either this, `api_key` or `token_provider` is required
+    #[must_use]
+    pub fn token<S: Into<String>>(mut self, token: S) -> Self {
+        self.auth = Some(Auth::Token(token.into()));
+        #[cfg(feature = "identity_api_v1")]
+        {
+            self.api_key = None;
+        }
+        self
+    }
+
+    /// Set the provider of tokens used in Bearer Authorization
+    ///
+    /// **NOTE:** Either this, `api_key` or `token` is required
+    #[cfg(feature = "identity_api_v1")]
+    #[must_use]
+    pub fn token_provider(mut self, provider: TokenProvider) -> Self {
+        self.auth = Some(Auth::Provider(provider));
+        self.api_key = None;
+        self
+    }
+
+    /// Set how requests which fail due to transient errors are retried (default:
+    /// [`RetryPolicy::default`])
+    #[must_use]
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Use a different URL for the Compute API
+    #[must_use]
+    pub fn compute_url(mut self, url: Url) -> Self {
+        self.compute_url = Some(url);
+        self
+    }
+
+    /// Use a different URL for the Identity API, which is used to request access tokens
+    #[must_use]
+    pub fn identity_url(mut self, url: Url) -> Self {
+        self.identity_url = Some(url);
+        self
+    }
+
+    /// Use a different URL for the Metadata API
+    #[must_use]
+    pub fn metadata_url(mut self, url: Url) -> Self {
+        self.metadata_url = Some(url);
+        self
+    }
+
+    /// Use a different URL for the Locks API
+    #[must_use]
+    pub fn locks_url(mut self, url: Url) -> Self {
+        self.locks_url = Some(url);
+        self
+    }
+
+    /// Use a different URL for the Restrict API
+    #[must_use]
+    pub fn restrict_url(mut self, url: Url) -> Self {
+        self.restrict_url = Some(url);
+        self
+    }
+
+    /// Allow non-HTTPS endpoints for all requests (default: `false`)
+    #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))))]
+    pub fn allow_http(mut self, yes: bool) -> Self {
+        self.allow_http = yes;
+        self
+    }
+
+    /// Allow invalid TLS certificates for all requests (default: `false`)
+    #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))))]
+    pub fn allow_invalid_certs(mut self, yes: bool) -> Self {
+        self.allow_invalid_certs = yes;
+        self
+    }
+
+    /// Build a SeaplaneClient from the given parameters
+    pub fn build(self) -> Result<SeaplaneClient> {
+        #[cfg(feature = "identity_api_v1")]
+        let auth = match &self.api_key {
+            Some(key) => Some(Auth::Provider(TokenProvider::new(self.token_request(key))?)),
+            None => self.auth.clone(),
+        };
+        #[cfg(not(feature = "identity_api_v1"))]
+        let auth = self.auth.clone();
+
+        Ok(SeaplaneClient {
+            #[cfg(feature = "blocking")]
+            client: build_client!(reqwest::blocking::Client::builder(), default_headers(), self),
+            #[cfg(feature = "async")]
+            async_client: build_client!(reqwest::Client::builder(), default_headers(), self),
+            auth,
+            retry: self.retry,
+            compute_url: self.compute_url,
+            metadata_url: self.metadata_url,
+            locks_url: self.locks_url,
+            restrict_url: self.restrict_url,
+        })
+    }
+
+    // The builder of the requests made by the `TokenProvider` when using an API key
+    #[cfg(feature = "identity_api_v1")]
+    fn token_request(&self, key: &str) -> TokenRequestBuilder {
+        #[cfg_attr(
+            not(any(
+                feature = "allow_insecure_urls",
+                feature = "allow_invalid_certs",
+                feature = "danger_zone"
+            )),
+            allow(unused_mut)
+        )]
+        let mut builder = TokenRequestBuilder::new().api_key(key).retry(self.retry);
+        #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
+        {
+            builder = builder.allow_http(self.allow_http);
+        }
+        #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
+        {
+            builder = builder.allow_invalid_certs(self.allow_invalid_certs);
+        }
+        match &self.identity_url {
+            Some(url) => builder.base_url(url),
+            None => builder,
+        }
+    }
+}
+
+/// A single entry point to the APIs which holds one connection pool, along with the endpoint URLs
+/// and credentials, shared by all the requests it hands out.
+///
+/// Cloning a `SeaplaneClient` is cheap and clones share the same connection pool and cached access
+/// token.
+///
+/// # Examples
+///
+/// ```no_run
+/// use seaplane::api::SeaplaneClient;
+///
+/// let client = SeaplaneClient::builder().api_key("abc123").build().unwrap();
+///
+/// let formations = client.formations().build().unwrap().get_all().unwrap();
+/// dbg!(formations);
+///
+/// let value = client
+///     .metadata()
+///     .encoded_key("bW9ieQo")
+///     .build()
+///     .unwrap()
+///     .get_value()
+///     .unwrap();
+/// dbg!(value);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeaplaneClient {
+    #[cfg(feature = "blocking")]
+    pub(crate) client: reqwest::blocking::Client,
+    #[cfg(feature = "async")]
+    pub(crate) async_client: reqwest::Client,
+    pub(crate) auth: Option<Auth>,
+    pub(crate) retry: RetryPolicy,
+    // Each URL is only read by the builder of its API's requests
+    #[cfg_attr(not(feature = "compute_api_v2"), allow(dead_code))]
+    compute_url: Option<Url>,
+    #[cfg_attr(not(feature = "metadata_api_v1"), allow(dead_code))]
+    metadata_url: Option<Url>,
+    #[cfg_attr(not(feature = "locks_api_v1"), allow(dead_code))]
+    locks_url: Option<Url>,
+    #[cfg_attr(not(feature = "restrict_api_v1"), allow(dead_code))]
+    restrict_url: Option<Url>,
+}
+
+impl SeaplaneClient {
+    /// Create a new client builder
+    pub fn builder() -> SeaplaneClientBuilder { SeaplaneClientBuilder::new() }
+
+    /// A builder for requests against the `/formations` APIs which uses this client
+    #[cfg(feature = "compute_api_v2")]
+    pub fn formations(&self) -> FormationsRequestBuilder {
+        FormationsRequestBuilder::new().with_client(self, self.compute_url.as_ref())
+    }
+
+    /// A builder for requests against the `/config` APIs which uses this client
+    #[cfg(feature = "metadata_api_v1")]
+    pub fn metadata(&self) -> MetadataRequestBuilder {
+        MetadataRequestBuilder::new().with_client(self, self.metadata_url.as_ref())
+    }
+
+    /// A builder for requests against the `/locks` APIs which uses this client
+    #[cfg(feature = "locks_api_v1")]
+    pub fn locks(&self) -> LocksRequestBuilder {
+        LocksRequestBuilder::new().with_client(self, self.locks_url.as_ref())
+    }
+
+    /// A builder for requests against the `/restrict` APIs which uses this client
+    #[cfg(feature = "restrict_api_v1")]
+    pub fn restrict(&self) -> RestrictRequestBuilder {
+        RestrictRequestBuilder::new().with_client(self, self.restrict_url.as_ref())
+    }
+}
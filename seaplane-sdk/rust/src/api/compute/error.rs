@@ -1,8 +1,10 @@
 use thiserror::Error as ThisError;
 
-use crate::rexports::seaplane_oid::error::Error as OidError;
+use crate::rexports::{
+    container_image_ref::ImageReferenceError, seaplane_oid::error::Error as OidError,
+};
 
-#[derive(ThisError, Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(ThisError, Debug, Clone, PartialEq, Eq)]
 pub enum FormationValidation {
     #[error("Formation name cannot be empty")]
     NameEmpty,
@@ -16,6 +18,20 @@ pub enum FormationValidation {
     NameConsecutiveHyphens,
     #[error("Formation names may not start or end with a hyphen ('-')")]
     NameLeadingOrTrailingHyphen,
+    #[error("Flight name cannot be empty")]
+    FlightNameEmpty,
+    #[error("Flight name too long, must be <= 63 in length")]
+    FlightNameLength,
+    #[error("illegal character in Flight name; must only contain ASCII lowercase, digit, or hyphen ('-')")]
+    FlightNameInvalidChar,
+    #[error("no more than three hyphens ('-') allowed in Flight name")]
+    FlightNameTooManyHyphens,
+    #[error("consecutive hyphens ('--') not allowed in Flight name")]
+    FlightNameConsecutiveHyphens,
+    #[error("Flight names may not start or end with a hyphen ('-')")]
+    FlightNameLeadingOrTrailingHyphen,
+    #[error("Flight names must be unique within a Formation")]
+    DuplicateFlightName,
     #[error("request did not include the required formation name")]
     MissingFormationName,
     #[error("flights cannot be empty")]
@@ -26,6 +42,48 @@ pub enum FormationValidation {
     MissingFlightName,
     #[error("missing required Flight image reference")]
     MissingFlightImageReference,
+    #[error("invalid Flight image reference: {0}")]
+    InvalidImageReference(ImageReferenceError),
+    #[error("Flight minimum must be at least 1")]
+    FlightMinimumZero,
+    #[error("Flight minimum cannot be greater than its maximum")]
+    FlightMinimumExceedsMaximum,
+    #[error("invalid Flight environment variable name '{0}'; must only contain ASCII letters, digits, or underscore ('_') and not start with a digit")]
+    InvalidEnvName(String),
+    #[error("the secret of Flight environment variable '{0}' does not reference a Metadata key")]
+    EmptySecretKey(String),
+    #[error("invalid Flight port '{0}', must be PORT or PORT/PROTOCOL where PROTOCOL is http, tcp or udp")]
+    InvalidPort(String),
+    #[error("Flight ports must be between 1 and 65535")]
+    PortZero,
+    #[error("Flight ports must be unique for each protocol")]
+    DuplicatePort,
+    #[error("Flight health check path must start with a '/'")]
+    HealthCheckInvalidPath,
+    #[error(
+        "Flight health checks are HTTP requests, so the first port of the Flight must be HTTP"
+    )]
+    HealthCheckNotHttp,
+    #[error("Flight health check interval, timeout and thresholds must be at least 1")]
+    HealthCheckZero,
+    #[error("Flight health check timeout cannot be greater than its interval")]
+    HealthCheckTimeoutExceedsInterval,
+    #[error("Flight CPU and memory resources must be greater than 0")]
+    ResourcesZero,
+    #[error("the same region cannot be both allowed and denied for a Formation")]
+    ConflictingRegions,
+    #[error("the same provider cannot be both allowed and denied for a Formation")]
+    ConflictingProviders,
+    #[error("a Formation cannot deny every region without allowing any")]
+    NoAllowedRegions,
+    #[error("a Formation cannot deny every provider without allowing any")]
+    NoAllowedProviders,
+    #[error("the traffic weight of an active Formation revision must be between 1 and 100")]
+    InvalidTrafficWeight,
+    #[error("the traffic weights of a Formation's active revisions cannot total more than 100")]
+    TrafficWeightsExceedTotal,
+    #[error("the same Formation revision cannot be active more than once")]
+    DuplicateRevision,
     #[error("Request requires a valid Formation ID but none was provided")]
     MissingFormationId,
     #[error("a gateway flight was not specified or could not be implied because the number of flights is > 1")]
@@ -36,6 +94,12 @@ pub enum FormationValidation {
 pub enum ComputeError {
     #[error("{0}")]
     FormationValidation(#[from] FormationValidation),
+    #[error("the following Flights of the Formation are unhealthy: {}", .0.join(", "))]
+    FormationUnhealthy(Vec<String>),
+    #[error("timed out waiting for the Formation to become healthy")]
+    HealthTimeout,
+    #[error("the secret environment variables of Flight {0} must be resolved before it is sent")]
+    UnresolvedSecret(String),
     #[error("Object ID error: {0}")]
     Oid(#[from] OidError),
 }
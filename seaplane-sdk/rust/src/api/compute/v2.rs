@@ -1,21 +1,36 @@
 //! The `/formations` endpoint APIs which allows working with [`Formation`]s,
 //! [`Flight`]s, and the underlying containers
 
+#[cfg(feature = "async")]
+mod asynchronous;
 mod models;
 pub mod response;
 mod validate;
+#[cfg(feature = "blocking")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "async")]
+pub use asynchronous::*;
 pub use models::*;
+use reqwest::Url;
 pub use response::*;
 pub use validate::*;
 
+#[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+use crate::api::identity::v1::TokenProvider;
+#[cfg(feature = "blocking")]
+use crate::api::shared::v1::{Entries, Pages};
+#[cfg(feature = "blocking")]
+use crate::api::ApiRequest;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::api::SeaplaneClient;
 use crate::{
     api::{
         compute::{
             error::{ComputeError, FormationValidation},
             COMPUTE_API_URL,
         },
-        error::map_api_error,
-        ApiRequest, RequestBuilder,
+        Request, RequestBuilder, RetryPolicy,
     },
     error::Result,
 };
@@ -41,6 +56,8 @@ impl FormationsRequestBuilder {
     pub fn new() -> Self { RequestBuilder::new(COMPUTE_API_URL, COMPUTE_API_ROUTE).into() }
 
     /// Builds a FormationsRequest from the given parameters
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
     pub fn build(self) -> Result<FormationsRequest> { Ok(self.builder.build()?.into()) }
 
     /// Set the token used in Bearer Authorization
@@ -49,6 +66,27 @@ impl FormationsRequestBuilder {
     #[must_use]
     pub fn token<U: Into<String>>(self, token: U) -> Self { self.builder.token(token).into() }
 
+    /// Set the provider of tokens used in Bearer Authorization, in place of a fixed token
+    ///
+    /// The access token is cached and refreshed as needed, and requests rejected with HTTP 401
+    /// UNAUTHORIZED are retried once with a new token.
+    #[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+    #[must_use]
+    pub fn token_provider(self, provider: TokenProvider) -> Self {
+        self.builder.token_provider(provider).into()
+    }
+
+    /// Set how requests which fail due to transient errors are retried (default:
+    /// [`RetryPolicy::default`])
+    #[must_use]
+    pub fn retry(self, policy: RetryPolicy) -> Self { self.builder.retry(policy).into() }
+
+    // Shares the connection pool, credentials and configuration of the client
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn with_client(self, client: &SeaplaneClient, url: Option<&Url>) -> Self {
+        self.builder.with_client(client, url).into()
+    }
+
     /// Allow non-HTTPS endpoints for this request (default: `false`)
     #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))))]
@@ -74,21 +112,27 @@ impl FormationsRequestBuilder {
 }
 
 /// For making requests against the `/formations` APIs.
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
 #[derive(Debug)]
 pub struct FormationsRequest {
     request: ApiRequest<FormationId>,
 }
 
+#[cfg(feature = "blocking")]
 impl From<ApiRequest<FormationId>> for FormationsRequest {
     fn from(request: ApiRequest<FormationId>) -> Self { Self { request } }
 }
 
+#[cfg(feature = "blocking")]
 impl FormationsRequest {
     /// Create a new request builder
     pub fn builder() -> FormationsRequestBuilder { FormationsRequestBuilder::new() }
 
     /// Create a new Formation and returns the IDs of the created Formation.
     ///
+    /// Secret references must have been resolved first, see [`EnvValue::Secret`].
+    ///
     /// Uses `POST /formations`
     ///
     /// # Examples
@@ -115,14 +159,14 @@ impl FormationsRequest {
     /// dbg!(resp);
     /// ```
     pub fn create(&self, formation: &Formation) -> Result<CreateFormationResponse> {
+        ensure_resolved(formation)?;
         let req = self
             .request
             .client
             .post(self.request.endpoint_url.clone())
-            .bearer_auth(&self.request.token)
             .json(formation);
-        let resp = req.send()?;
-        map_api_error(resp)?
+        self.request
+            .send(req)?
             .json::<CreateFormationResponse>()
             .map_err(Into::into)
     }
@@ -144,30 +188,58 @@ impl FormationsRequest {
     /// assert!(req.delete().is_ok());
     /// ```
     pub fn delete(&self) -> Result<DeleteFormationResponse> {
-        use FormationValidation::*;
-        if self.request.target.is_none() {
-            Err(ComputeError::FormationValidation(MissingFormationId))?;
-        }
-        let url = self
-            .request
-            .endpoint_url
-            .join(&format!("formations/{}", self.oid()))?;
-        let resp = self
-            .request
-            .client
-            .delete(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        map_api_error(resp)?;
+        let url = formation_url(&self.request)?;
+        self.request.send(self.request.client.delete(url))?;
         Ok(())
     }
 
-    // @TODO: a paging iterator may be more appropriate here in the future
-    /// Returns a list of all the Formations you have access to
+    /// Replaces the configuration of an existing Formation with `formation`, returning the updated
+    /// Formation including the IDs of any newly added Flights.
+    ///
+    /// Unlike landing and launching the Formation again, Flights which are unchanged keep running.
+    ///
+    /// Secret references must have been resolved first, see [`EnvValue::Secret`].
+    ///
+    /// Uses `PUT /formations/ID`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use seaplane::api::compute::v2::{FormationsRequest, Formation, Flight};
+    /// let req = FormationsRequest::builder()
+    ///     .token("abc123")
+    ///     .formation_id("frm-agc6amh7z527vijkv2cutplwaa".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let mut formation = req.get().unwrap();
+    /// formation.add_flight(
+    ///     Flight::builder()
+    ///         .name("another-flight")
+    ///         .image("my/image:latest")
+    ///         .build()
+    ///         .unwrap(),
+    /// );
+    /// let resp = req.update(&formation).unwrap();
+    /// dbg!(resp);
+    /// ```
+    pub fn update(&self, formation: &Formation) -> Result<UpdateFormationResponse> {
+        ensure_resolved(formation)?;
+        let url = formation_url(&self.request)?;
+        self.request
+            .send(self.request.client.put(url).json(formation))?
+            .json::<UpdateFormationResponse>()
+            .map_err(Into::into)
+    }
+
+    /// Returns the first page of the Formations you have access to. Use [`pages`] or [`entries`]
+    /// to follow the links to any further pages.
     ///
     /// Uses `GET /formations`
     ///
+    /// [`pages`]: FormationsRequest::pages
+    /// [`entries`]: FormationsRequest::entries
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -181,17 +253,59 @@ impl FormationsRequest {
     /// dbg!(resp);
     /// ```
     pub fn get_all(&self) -> Result<GetFormationsResponse> {
-        let client = reqwest::blocking::Client::new();
-        let resp = client
-            .get(self.request.endpoint_url.clone())
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        map_api_error(resp)?
+        let req = self.request.client.get(self.request.endpoint_url.clone());
+        self.request
+            .send(req)?
             .json::<GetFormationsResponse>()
             .map_err(Into::into)
     }
 
+    /// Returns an iterator over the pages of the Formations you have access to, following the
+    /// `next` link of each page. Each page is only requested once the previous one has been
+    /// consumed.
+    ///
+    /// Uses `GET /formations`
+    pub fn pages(&self) -> Pages<'_, GetFormationsResponse> {
+        let mut url = self.request.endpoint_url.clone();
+        Pages::new(move || {
+            let page = self
+                .request
+                .send(self.request.client.get(url.clone()))?
+                .json::<GetFormationsResponse>()?;
+            let more = match &page.meta.next {
+                Some(next) => {
+                    url = next.clone();
+                    true
+                }
+                None => false,
+            };
+            Ok((page, more))
+        })
+    }
+
+    /// Returns an iterator over all the Formations you have access to. Each page is only requested
+    /// once the Formations of the previous one have been consumed, so iteration may stop early
+    /// without requesting the remaining pages.
+    ///
+    /// Uses `GET /formations`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use seaplane::api::compute::v2::{FormationsRequest};
+    /// let req = FormationsRequest::builder()
+    ///     .token("abc123_token")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// for formation in req.entries() {
+    ///     dbg!(formation.unwrap().name);
+    /// }
+    /// ```
+    pub fn entries(&self) -> Entries<'_, Formation> {
+        Entries::new(self.pages(), |page| page.objects)
+    }
+
     /// Returns a single Formation's metadata
     ///
     /// Uses `GET /formations/ID`
@@ -210,30 +324,81 @@ impl FormationsRequest {
     /// dbg!(resp);
     /// ```
     pub fn get(&self) -> Result<GetFormationResponse> {
-        if self.request.target.is_none() {
-            Err(ComputeError::FormationValidation(FormationValidation::MissingFormationId))?
-        }
-        let url = self
-            .request
-            .endpoint_url
-            .join(&format!("formations/{}", self.oid()))?;
-        let resp = self
-            .request
-            .client
-            .get(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        map_api_error(resp)?
+        let url = formation_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))?
             .json::<GetFormationResponse>()
             .map_err(Into::into)
     }
 
-    // Internal; gets the OID of the target formation
-    //
-    // # Panics
-    //
-    // If FormationId isn't `Some`
-    #[inline]
-    fn oid(&self) -> &FormationId { self.request.target.as_ref().unwrap() }
+    /// Blocks until all of the Formation's Flights are `Healthy`, checking with
+    /// [`FormationsRequest::get`] every `interval`, and returns the healthy Formation.
+    ///
+    /// Returns [`ComputeError::FormationUnhealthy`] as soon as any Flight is `Unhealthy`, or
+    /// [`ComputeError::HealthTimeout`] if the Formation is still starting once `timeout` has
+    /// passed.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use seaplane::api::compute::v2::{FormationsRequest};
+    /// let req = FormationsRequest::builder()
+    ///     .token("abc123_token")
+    ///     .formation_id("frm-agc6amh7z527vijkv2cutplwaa".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let resp = req
+    ///     .wait_until_healthy(Duration::from_secs(5), Duration::from_secs(300))
+    ///     .unwrap();
+    /// dbg!(resp);
+    /// ```
+    pub fn wait_until_healthy(&self, interval: Duration, timeout: Duration) -> Result<Formation> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(res) = health_check(self.get()?) {
+                return res;
+            }
+            match deadline.checked_duration_since(Instant::now()) {
+                Some(left) if !left.is_zero() => std::thread::sleep(interval.min(left)),
+                _ => return Err(ComputeError::HealthTimeout.into()),
+            }
+        }
+    }
+}
+
+// Internal; the outcome of waiting for a Formation to become healthy, or `None` if its Flights
+// are still starting
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn health_check(formation: Formation) -> Option<Result<Formation>> {
+    match formation.health() {
+        FlightStatus::Healthy => Some(Ok(formation)),
+        FlightStatus::Unhealthy => Some(Err(ComputeError::FormationUnhealthy(
+            formation
+                .flights
+                .into_iter()
+                .filter(|f| f.status == FlightStatus::Unhealthy)
+                .map(|f| f.name)
+                .collect(),
+        )
+        .into())),
+        FlightStatus::Starting => None,
+    }
+}
+
+// Internal; the Compute API only accepts the values of secrets, not references to them
+fn ensure_resolved(formation: &Formation) -> Result<()> {
+    match formation.unresolved_secret() {
+        Some(flight) => Err(ComputeError::UnresolvedSecret(flight.name().into()))?,
+        None => Ok(()),
+    }
+}
+
+// Internal; creates the URL of the target formation for all single Formation endpoints
+fn formation_url<C>(request: &Request<FormationId, C>) -> Result<Url> {
+    match &request.target {
+        Some(oid) => Ok(request.endpoint_url.join(&format!("formations/{oid}"))?),
+        None => Err(ComputeError::FormationValidation(FormationValidation::MissingFormationId))?,
+    }
 }
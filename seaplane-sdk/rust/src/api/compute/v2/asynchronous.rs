@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use super::{ensure_resolved, formation_url, health_check, FormationsRequestBuilder};
+use crate::{
+    api::{
+        compute::{
+            error::ComputeError,
+            v2::{
+                CreateFormationResponse, DeleteFormationResponse, Formation, FormationId,
+                GetFormationResponse, GetFormationsResponse, UpdateFormationResponse,
+            },
+        },
+        AsyncApiRequest,
+    },
+    error::Result,
+};
+
+impl FormationsRequestBuilder {
+    /// Builds an AsyncFormationsRequest from the given parameters
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async(self) -> Result<AsyncFormationsRequest> {
+        Ok(self.builder.build_async()?.into())
+    }
+}
+
+/// For making async requests against the `/formations` APIs.
+///
+/// The async equivalent of [`FormationsRequest`](super::FormationsRequest), requires a `tokio`
+/// runtime.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug)]
+pub struct AsyncFormationsRequest {
+    request: AsyncApiRequest<FormationId>,
+}
+
+impl From<AsyncApiRequest<FormationId>> for AsyncFormationsRequest {
+    fn from(request: AsyncApiRequest<FormationId>) -> Self { Self { request } }
+}
+
+impl AsyncFormationsRequest {
+    /// Create a new request builder
+    pub fn builder() -> FormationsRequestBuilder { FormationsRequestBuilder::new() }
+
+    /// Create a new Formation and returns the IDs of the created Formation.
+    ///
+    /// Secret references must have been resolved first, see
+    /// [`EnvValue::Secret`](super::EnvValue::Secret).
+    ///
+    /// Uses `POST /formations`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use seaplane::api::compute::v2::{AsyncFormationsRequest, Formation, Flight};
+    /// # async fn example() {
+    /// let req = AsyncFormationsRequest::builder()
+    ///     .token("abc123")
+    ///     .build_async()
+    ///     .unwrap();
+    ///
+    /// let formation = Formation::builder()
+    ///     .name("exmample-formation")
+    ///     .add_flight(
+    ///         Flight::builder()
+    ///             .name("myflight")
+    ///             .image("my/image:latest")
+    ///             .build()
+    ///             .unwrap(),
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// let resp = req.create(&formation).await.unwrap();
+    /// dbg!(resp);
+    /// # }
+    /// ```
+    pub async fn create(&self, formation: &Formation) -> Result<CreateFormationResponse> {
+        ensure_resolved(formation)?;
+        let req = self
+            .request
+            .client
+            .post(self.request.endpoint_url.clone())
+            .json(formation);
+        self.request
+            .send(req)
+            .await?
+            .json::<CreateFormationResponse>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Deletes a formation
+    ///
+    /// Uses `DELETE /formations/ID`
+    pub async fn delete(&self) -> Result<DeleteFormationResponse> {
+        let url = formation_url(&self.request)?;
+        self.request.send(self.request.client.delete(url)).await?;
+        Ok(())
+    }
+
+    /// Replaces the configuration of an existing Formation with `formation`, returning the updated
+    /// Formation
+    ///
+    /// Secret references must have been resolved first, see
+    /// [`EnvValue::Secret`](super::EnvValue::Secret).
+    ///
+    /// Uses `PUT /formations/ID`
+    pub async fn update(&self, formation: &Formation) -> Result<UpdateFormationResponse> {
+        ensure_resolved(formation)?;
+        let url = formation_url(&self.request)?;
+        self.request
+            .send(self.request.client.put(url).json(formation))
+            .await?
+            .json::<UpdateFormationResponse>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns a list of all the Formations you have access to
+    ///
+    /// Uses `GET /formations`
+    pub async fn get_all(&self) -> Result<GetFormationsResponse> {
+        let req = self.request.client.get(self.request.endpoint_url.clone());
+        self.request
+            .send(req)
+            .await?
+            .json::<GetFormationsResponse>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns a single Formation's metadata
+    ///
+    /// Uses `GET /formations/ID`
+    pub async fn get(&self) -> Result<GetFormationResponse> {
+        let url = formation_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<GetFormationResponse>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Waits until all of the Formation's Flights are `Healthy`, checking every `interval`, and
+    /// returns the healthy Formation.
+    ///
+    /// See [`FormationsRequest::wait_until_healthy`](super::FormationsRequest::wait_until_healthy)
+    /// for the errors returned.
+    pub async fn wait_until_healthy(
+        &self,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<Formation> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(res) = health_check(self.get().await?) {
+                return res;
+            }
+            match deadline.checked_duration_since(Instant::now()) {
+                Some(left) if !left.is_zero() => tokio::time::sleep(interval.min(left)).await,
+                _ => return Err(ComputeError::HealthTimeout.into()),
+            }
+        }
+    }
+}
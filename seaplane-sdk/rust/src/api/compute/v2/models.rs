@@ -1,17 +1,26 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    result::Result as StdResult,
+    str::FromStr,
+};
+
 use serde::{Deserialize, Serialize};
-use strum::{Display, EnumString};
+use strum::{Display, EnumString, VariantNames};
 use url::Url;
 
 #[cfg(doc)]
 use crate::api::compute::v2::FormationsRequest;
 use crate::{
-    api::compute::{
-        error::{ComputeError, FormationValidation},
-        v2::validate_formation_name,
+    api::{
+        compute::{
+            error::{ComputeError, FormationValidation},
+            v2::{validate_env_name, validate_flight_name, validate_formation_name},
+        },
+        shared::v1::{Provider, Region},
     },
     error::Result,
     rexports::{
-        container_image_ref::ImageReference,
+        container_image_ref::{ImageReference, ImageReferenceError},
         seaplane_oid::{OidPrefix, TypedOid},
     },
 };
@@ -49,6 +58,20 @@ pub enum FlightStatus {
 
 impl FlightStatus {
     pub fn is_starting(&self) -> bool { self == &FlightStatus::Starting }
+
+    /// The worst of `statuses`. That is, `Unhealthy` if any are unhealthy, otherwise `Starting` if
+    /// any are still starting, otherwise (including when there are none) `Healthy`.
+    pub fn worst<I: IntoIterator<Item = FlightStatus>>(statuses: I) -> Self {
+        statuses
+            .into_iter()
+            .fold(FlightStatus::Healthy, |worst, status| match (worst, status) {
+                (FlightStatus::Unhealthy, _) | (_, FlightStatus::Unhealthy) => {
+                    FlightStatus::Unhealthy
+                }
+                (FlightStatus::Starting, _) | (_, FlightStatus::Starting) => FlightStatus::Starting,
+                _ => FlightStatus::Healthy,
+            })
+    }
 }
 
 impl_serde_str!(FlightStatus);
@@ -72,6 +95,15 @@ mod flight_health_status_tests {
         assert_eq!(FlightStatus::Starting, "STARTING".parse().unwrap());
     }
 
+    #[test]
+    fn worst() {
+        use FlightStatus::*;
+        assert_eq!(FlightStatus::worst([]), Healthy);
+        assert_eq!(FlightStatus::worst([Healthy, Healthy]), Healthy);
+        assert_eq!(FlightStatus::worst([Healthy, Starting]), Starting);
+        assert_eq!(FlightStatus::worst([Unhealthy, Starting, Healthy]), Unhealthy);
+    }
+
     #[test]
     fn ser() {
         assert_eq!(FlightStatus::Healthy.to_string(), "healthy".to_string());
@@ -80,6 +112,262 @@ mod flight_health_status_tests {
     }
 }
 
+/// The CPU architecture a [`Flight`]'s container image can run on
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum Architecture {
+    /// x86-64
+    Amd64,
+
+    /// 64-bit ARM
+    Arm64,
+}
+
+impl_serde_str!(Architecture);
+
+#[cfg(test)]
+mod architecture_tests {
+    use super::*;
+
+    #[test]
+    fn deser() {
+        assert_eq!(Architecture::Amd64, "amd64".parse().unwrap());
+        assert_eq!(Architecture::Amd64, "AMD64".parse().unwrap());
+        assert_eq!(Architecture::Arm64, "arm64".parse().unwrap());
+        assert_eq!(Architecture::Arm64, "Arm64".parse().unwrap());
+        assert!("x86".parse::<Architecture>().is_err());
+    }
+
+    #[test]
+    fn ser() {
+        assert_eq!(Architecture::Amd64.to_string(), "amd64".to_string());
+        assert_eq!(Architecture::Arm64.to_string(), "arm64".to_string());
+    }
+}
+
+/// The value of one of a [`Flight`]'s environment variables
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum EnvValue {
+    /// The value itself, in clear text
+    Value(String),
+
+    /// A reference to the (unencoded) Metadata key that holds the value. The Compute API only
+    /// accepts values, so the references must be replaced with the values they reference using
+    /// [`Formation::resolve_secrets`] before the Formation is sent, and restored with
+    /// [`Formation::restore_secrets`] in what the Compute API returns to keep the values out of
+    /// anything stored. [`FormationsRequest::create`] and [`FormationsRequest::update`] refuse to
+    /// send a reference.
+    Secret { secret: String },
+}
+
+impl EnvValue {
+    /// Returns the Metadata key holding the value, if this is a secret reference
+    pub fn secret(&self) -> Option<&str> {
+        match self {
+            EnvValue::Value(_) => None,
+            EnvValue::Secret { secret } => Some(secret),
+        }
+    }
+}
+
+impl From<String> for EnvValue {
+    fn from(value: String) -> Self { EnvValue::Value(value) }
+}
+
+impl From<&str> for EnvValue {
+    fn from(value: &str) -> Self { EnvValue::Value(value.into()) }
+}
+
+#[cfg(test)]
+mod env_value_tests {
+    use super::*;
+
+    #[test]
+    fn deser() {
+        assert_eq!(EnvValue::from("bar"), serde_json::from_str(r#""bar""#).unwrap());
+        assert_eq!(
+            EnvValue::Secret { secret: "app/db-pass".into() },
+            serde_json::from_str(r#"{"secret":"app/db-pass"}"#).unwrap()
+        );
+        assert!(serde_json::from_str::<EnvValue>("1").is_err());
+    }
+
+    #[test]
+    fn ser() {
+        assert_eq!(serde_json::to_string(&EnvValue::from("bar")).unwrap(), r#""bar""#);
+        assert_eq!(
+            serde_json::to_string(&EnvValue::Secret { secret: "app/db-pass".into() }).unwrap(),
+            r#"{"secret":"app/db-pass"}"#
+        );
+    }
+}
+
+/// The protocol of traffic to one of a [`Flight`]'s [`Port`]s
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Display, Default,
+)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum Protocol {
+    /// HTTP(S) traffic
+    #[default]
+    Http,
+
+    /// Any TCP traffic
+    Tcp,
+
+    /// Any UDP traffic
+    Udp,
+}
+
+impl_serde_str!(Protocol);
+
+/// A port that a [`Flight`]'s containers listen on
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Port {
+    /// The port number
+    pub port: u16,
+
+    /// The protocol of traffic to the port
+    #[serde(default)]
+    pub protocol: Protocol,
+}
+
+impl Port {
+    /// Creates a new [`Port`] with the given port number and protocol
+    pub fn new(port: u16, protocol: Protocol) -> Self { Self { port, protocol } }
+}
+
+impl FromStr for Port {
+    type Err = FormationValidation;
+
+    /// Parses a port in the form `PORT[/PROTOCOL]`, where the protocol defaults to HTTP
+    fn from_str(s: &str) -> StdResult<Self, Self::Err> {
+        let invalid = || FormationValidation::InvalidPort(s.into());
+        let (port, protocol) = match s.split_once('/') {
+            Some((port, protocol)) => (port, protocol.parse().map_err(|_| invalid())?),
+            None => (s, Protocol::default()),
+        };
+        Ok(Port { port: port.parse().map_err(|_| invalid())?, protocol })
+    }
+}
+
+/// How the runtime checks whether a [`Flight`]'s containers are healthy, by making HTTP requests to
+/// a path of the Flight's first [`Port`], which must therefore take HTTP traffic
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct HealthCheck {
+    /// The path requests are made to, which must start with a `/`
+    pub path: String,
+
+    /// The number of seconds between requests (default: `10`)
+    pub interval: u64,
+
+    /// The number of seconds to wait for a response before the request fails (default: `5`)
+    pub timeout: u64,
+
+    /// The number of consecutive successful requests after which a container is healthy (default:
+    /// `1`)
+    pub healthy_threshold: u32,
+
+    /// The number of consecutive failed requests after which a container is unhealthy (default:
+    /// `3`)
+    pub unhealthy_threshold: u32,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            path: "/".into(),
+            interval: 10,
+            timeout: 5,
+            healthy_threshold: 1,
+            unhealthy_threshold: 3,
+        }
+    }
+}
+
+impl HealthCheck {
+    /// Creates a new [`HealthCheck`] of the given path, using the defaults for everything else
+    pub fn new<S: Into<String>>(path: S) -> Self { Self { path: path.into(), ..Self::default() } }
+
+    // Health checks are made to the first of `ports`, so it must take HTTP traffic
+    fn validate(&self, ports: &[Port]) -> StdResult<(), FormationValidation> {
+        use FormationValidation::*;
+        if !self.path.starts_with('/') {
+            return Err(HealthCheckInvalidPath);
+        }
+        if ports
+            .first()
+            .map_or(false, |p| p.protocol != Protocol::Http)
+        {
+            return Err(HealthCheckNotHttp);
+        }
+        if self.interval == 0
+            || self.timeout == 0
+            || self.healthy_threshold == 0
+            || self.unhealthy_threshold == 0
+        {
+            return Err(HealthCheckZero);
+        }
+        if self.timeout > self.interval {
+            return Err(HealthCheckTimeoutExceedsInterval);
+        }
+        Ok(())
+    }
+}
+
+/// The CPU and memory each of a [`Flight`]'s containers needs
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Resources {
+    /// Thousandths of a CPU, or `None` for the default of the runtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub millicpu: Option<u64>,
+
+    /// Mebibytes of memory, or `None` for the default of the runtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mib: Option<u64>,
+}
+
+impl Resources {
+    fn validate(&self) -> StdResult<(), FormationValidation> {
+        if self.millicpu == Some(0) || self.memory_mib == Some(0) {
+            return Err(FormationValidation::ResourcesZero);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod port_tests {
+    use super::*;
+
+    #[test]
+    fn from_str() {
+        assert_eq!("8080".parse::<Port>().unwrap(), Port::new(8080, Protocol::Http));
+        assert_eq!("53/udp".parse::<Port>().unwrap(), Port::new(53, Protocol::Udp));
+        assert_eq!("5432/TCP".parse::<Port>().unwrap(), Port::new(5432, Protocol::Tcp));
+        assert_eq!(
+            "65536".parse::<Port>().unwrap_err(),
+            FormationValidation::InvalidPort("65536".into())
+        );
+        assert!("53/sctp".parse::<Port>().is_err());
+        assert!("http".parse::<Port>().is_err());
+    }
+
+    #[test]
+    fn serde() {
+        let json = r#"{"port":53,"protocol":"udp"}"#;
+        assert_eq!(serde_json::to_string(&Port::new(53, Protocol::Udp)).unwrap(), json);
+        assert_eq!(Port::new(53, Protocol::Udp), serde_json::from_str(json).unwrap());
+        assert_eq!(
+            Port::new(8080, Protocol::Http),
+            serde_json::from_str(r#"{"port":8080}"#).unwrap()
+        );
+    }
+}
+
 /// A builder for creating a [`Formation`] which is the primary way to describe a
 /// valid configuration for a Formation.
 #[derive(Debug, Default)]
@@ -87,6 +375,11 @@ pub struct FormationBuilder {
     flights: Vec<Flight>,
     name: String,
     gateway_flight: Option<String>,
+    regions_allowed: BTreeSet<Region>,
+    regions_denied: BTreeSet<Region>,
+    providers_allowed: BTreeSet<Provider>,
+    providers_denied: BTreeSet<Provider>,
+    active_revisions: Vec<FormationRevision>,
 }
 
 impl FormationBuilder {
@@ -109,12 +402,81 @@ impl FormationBuilder {
         self
     }
 
+    /// The name of the [`Flight`] which receives all public traffic arriving on the Formation URL.
+    /// When the Formation has exactly one Flight it is implied as the gateway Flight, otherwise
+    /// one must be given.
+    ///
+    /// **NOTE:** The Flight will be validated on the call to [`FormationBuilder::build`]
+    #[must_use]
     pub fn gateway_flight(mut self, flight: impl Into<String>) -> Self {
-        // @TODO validate flight name
         self.gateway_flight = Some(flight.into());
         self
     }
 
+    /// Add a [`Region`] the Formation's [`Flight`]s may run in. When none are added the Flights may
+    /// run in any Region which isn't denied.
+    ///
+    /// If this conflicts with `regions_denied` ([`FormationBuilder::add_denied_region`]) (e.g.
+    /// [`Region::XN`] is both allowed and denied) the configuration is invalid and will be
+    /// rejected.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized.
+    #[must_use]
+    pub fn add_allowed_region<R: Into<Region>>(mut self, region: R) -> Self {
+        self.regions_allowed.insert(region.into());
+        self
+    }
+
+    /// The inverse of [`FormationBuilder::add_allowed_region`] which specifies a [`Region`] the
+    /// Formation's [`Flight`]s may not run in.
+    ///
+    /// By default no [`Region`]s are denied.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized.
+    #[must_use]
+    pub fn add_denied_region<R: Into<Region>>(mut self, region: R) -> Self {
+        self.regions_denied.insert(region.into());
+        self
+    }
+
+    /// Add a [`Provider`] the Formation's [`Flight`]s may run on. When none are added the Flights
+    /// may run on any Provider which isn't denied.
+    ///
+    /// If this conflicts with `providers_denied` ([`FormationBuilder::add_denied_provider`]) (e.g.
+    /// [`Provider::GCP`] is both allowed and denied) the configuration is invalid and will be
+    /// rejected.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized.
+    #[must_use]
+    pub fn add_allowed_provider<P: Into<Provider>>(mut self, provider: P) -> Self {
+        self.providers_allowed.insert(provider.into());
+        self
+    }
+
+    /// The inverse of [`FormationBuilder::add_allowed_provider`] which specifies a [`Provider`]
+    /// the Formation's [`Flight`]s may not run on.
+    ///
+    /// By default no [`Provider`]s are denied.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized.
+    #[must_use]
+    pub fn add_denied_provider<P: Into<Provider>>(mut self, provider: P) -> Self {
+        self.providers_denied.insert(provider.into());
+        self
+    }
+
+    /// Keep an earlier [`FormationRevision`] active alongside this configuration, receiving its
+    /// weight as a percentage of the Formation's public traffic. This configuration receives
+    /// whatever share the active revisions leave.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized. The
+    /// weights will be validated on the call to [`FormationBuilder::build`]
+    #[must_use]
+    pub fn add_active_revision(mut self, revision: FormationRevision) -> Self {
+        self.active_revisions.push(revision);
+        self
+    }
+
     /// Removes all [`Flight`]s from this Formation Configuration
     pub fn clear_flights(&mut self) { self.flights.clear(); }
 
@@ -125,24 +487,82 @@ impl FormationBuilder {
             return Err(ComputeError::FormationValidation(EmptyFlights).into());
         }
 
-        // Ensure gateway_flight was defined
+        for (i, flight) in self.flights.iter().enumerate() {
+            validate_flight_name(&flight.name).map_err(ComputeError::FormationValidation)?;
+            if self.flights[..i].iter().any(|f| f.name == flight.name) {
+                return Err(ComputeError::FormationValidation(DuplicateFlightName).into());
+            }
+        }
+
+        // Ensure gateway_flight was defined, or can be implied from the only Flight
+        let gateway_flight = match self.gateway_flight {
+            Some(gw_f) if self.flights.iter().any(|f| f.name == gw_f) => gw_f,
+            Some(_) => return Err(ComputeError::FormationValidation(InvalidGatewayFlight).into()),
+            None if self.flights.len() == 1 => self.flights[0].name.clone(),
+            None => return Err(ComputeError::FormationValidation(NoGatewayFlight).into()),
+        };
+
+        validate_formation_name(&self.name).map_err(ComputeError::FormationValidation)?;
+
         if self
-            .gateway_flight
-            .as_ref()
-            .map(|gw_f| self.flights.iter().any(|f| &f.name == gw_f))
-            == Some(false)
+            .regions_allowed
+            .intersection(&self.regions_denied)
+            .next()
+            .is_some()
         {
-            return Err(ComputeError::FormationValidation(InvalidGatewayFlight).into());
+            return Err(ComputeError::FormationValidation(ConflictingRegions).into());
+        }
+        if self
+            .providers_allowed
+            .intersection(&self.providers_denied)
+            .next()
+            .is_some()
+        {
+            return Err(ComputeError::FormationValidation(ConflictingProviders).into());
+        }
+        // With nothing allowed, denying everything leaves nowhere for the Flights to run
+        if self.regions_allowed.is_empty() && self.regions_denied.len() == Region::VARIANTS.len() {
+            return Err(ComputeError::FormationValidation(NoAllowedRegions).into());
+        }
+        if self.providers_allowed.is_empty()
+            && self.providers_denied.len() == Provider::VARIANTS.len()
+        {
+            return Err(ComputeError::FormationValidation(NoAllowedProviders).into());
         }
 
-        validate_formation_name(&self.name).map_err(ComputeError::FormationValidation)?;
+        for (i, rev) in self.active_revisions.iter().enumerate() {
+            if !(1..=100).contains(&rev.weight) {
+                return Err(ComputeError::FormationValidation(InvalidTrafficWeight).into());
+            }
+            if self.active_revisions[..i]
+                .iter()
+                .any(|r| r.revision == rev.revision)
+            {
+                return Err(ComputeError::FormationValidation(DuplicateRevision).into());
+            }
+        }
+        if self
+            .active_revisions
+            .iter()
+            .map(|r| u32::from(r.weight))
+            .sum::<u32>()
+            > 100
+        {
+            return Err(ComputeError::FormationValidation(TrafficWeightsExceedTotal).into());
+        }
 
         Ok(Formation {
             name: self.name,
             oid: None,
             url: None,
             flights: self.flights,
-            gateway_flight: self.gateway_flight,
+            gateway_flight: Some(gateway_flight),
+            regions_allowed: self.regions_allowed,
+            regions_denied: self.regions_denied,
+            providers_allowed: self.providers_allowed,
+            providers_denied: self.providers_denied,
+            revision: None,
+            active_revisions: self.active_revisions,
         })
     }
 }
@@ -168,14 +588,49 @@ pub struct Formation {
     /// The Flight who will receive all the public HTTP(s) traffic that arrives on the public
     /// Formation URL
     pub gateway_flight: Option<String>,
+
+    /// The regions the Flights may run in, or empty for any region which isn't denied
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub regions_allowed: BTreeSet<Region>,
+
+    /// The regions the Flights may not run in
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub regions_denied: BTreeSet<Region>,
+
+    /// The cloud providers the Flights may run on, or empty for any provider which isn't denied
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub providers_allowed: BTreeSet<Provider>,
+
+    /// The cloud providers the Flights may not run on
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub providers_denied: BTreeSet<Provider>,
+
+    /// The revision of this configuration, which is assigned by the Compute API and incremented
+    /// each time the Formation is updated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u32>,
+
+    /// Earlier revisions of the Formation which are kept active alongside this configuration,
+    /// each receiving a share of the public traffic
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub active_revisions: Vec<FormationRevision>,
 }
 
 impl Formation {
     /// Create a [`FormationBuilder`] to build a new configuration
     pub fn builder() -> FormationBuilder { FormationBuilder::default() }
 
-    /// Add a [`Flight`] to the makeup of this Formation Configuration.
-    pub fn add_flight(&mut self, flight: Flight) { self.flights.push(flight); }
+    /// Add a [`Flight`] to the makeup of this Formation Configuration, replacing any Flight with
+    /// the same name. The replaced Flight is returned.
+    pub fn add_flight(&mut self, flight: Flight) -> Option<Flight> {
+        match self.flights.iter_mut().find(|f| f.name == flight.name) {
+            Some(existing) => Some(std::mem::replace(existing, flight)),
+            None => {
+                self.flights.push(flight);
+                None
+            }
+        }
+    }
 
     /// Remove a [`Flight`] from the makeup of this Formation Configuration.
     pub fn remove_flight(&mut self, name: &str) -> Option<Flight> {
@@ -196,6 +651,122 @@ impl Formation {
 
     /// Set the [`Flight`]s that makeup this Formation Configuration.
     pub fn flights(&self) -> &[Flight] { &self.flights }
+
+    /// The percentage of public traffic this configuration receives, which is whatever share its
+    /// [`FormationRevision`]s leave
+    pub fn weight(&self) -> u8 {
+        let active: u32 = self
+            .active_revisions
+            .iter()
+            .map(|r| u32::from(r.weight))
+            .sum();
+        100u32.saturating_sub(active) as u8
+    }
+
+    /// The overall health of the Formation, which is the worst status of any of its [`Flight`]s.
+    /// That is, `Unhealthy` if any Flight is unhealthy, otherwise `Starting` if any Flight is
+    /// still starting, otherwise `Healthy`.
+    pub fn health(&self) -> FlightStatus {
+        FlightStatus::worst(self.flights.iter().map(|f| f.status))
+    }
+
+    /// Replaces each secret reference among the environment variables of the [`Flight`]s,
+    /// including those of the active revisions, with the value `resolve` returns for it.
+    /// `resolve` is called with the name of the Flight, the name of the environment variable and
+    /// the Metadata key it references.
+    ///
+    /// See [`EnvValue::Secret`].
+    pub fn resolve_secrets<F, E>(&mut self, mut resolve: F) -> StdResult<(), E>
+    where
+        F: FnMut(&str, &str, &str) -> StdResult<String, E>,
+    {
+        let flights = self.flights.iter_mut().chain(
+            self.active_revisions
+                .iter_mut()
+                .flat_map(|r| &mut r.flights),
+        );
+        for flight in flights {
+            for (var, value) in flight.env.iter_mut() {
+                if let EnvValue::Secret { secret } = value {
+                    *value = EnvValue::Value(resolve(&flight.name, var, secret)?);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets each environment variable which is a secret reference in the same named [`Flight`] of
+    /// `from` back to that reference, such as to keep the values resolved with
+    /// [`Formation::resolve_secrets`] out of a Formation returned by the Compute API. The Flights
+    /// of active revisions are matched to those of the same revision in `from`.
+    ///
+    /// Note that a restored secret compares equal to the reference whatever its value was.
+    pub fn restore_secrets(&mut self, from: &Formation) {
+        restore_secrets(&mut self.flights, &from.flights);
+        for rev in &mut self.active_revisions {
+            if let Some(from) = from
+                .active_revisions
+                .iter()
+                .find(|r| r.revision == rev.revision)
+            {
+                restore_secrets(&mut rev.flights, &from.flights);
+            }
+        }
+    }
+
+    // The first Flight, including those of the active revisions, with a secret reference
+    pub(crate) fn unresolved_secret(&self) -> Option<&Flight> {
+        self.flights
+            .iter()
+            .chain(self.active_revisions.iter().flat_map(|r| &r.flights))
+            .find(|f| f.secrets().next().is_some())
+    }
+}
+
+// Sets the environment variables of `flights` which are secret references in the same named
+// Flight of `from` back to those references
+fn restore_secrets(flights: &mut [Flight], from: &[Flight]) {
+    for flight in flights {
+        if let Some(from) = from.iter().find(|f| f.name == flight.name) {
+            for (var, secret) in from.secrets() {
+                if let Some(value) = flight.env.get_mut(var) {
+                    *value = EnvValue::Secret { secret: secret.into() };
+                }
+            }
+        }
+    }
+}
+
+/// An earlier configuration of a [`Formation`] which is kept active alongside its current one,
+/// receiving a share of the public traffic arriving on the Formation URL. Keeping an earlier
+/// configuration active allows a new one to be rolled out gradually, and rolled back if needed.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct FormationRevision {
+    /// The revision of the Formation this configuration was
+    pub revision: u32,
+
+    /// The percentage of public traffic this configuration receives, from 1 to 100
+    pub weight: u8,
+
+    /// The Flights that make up this configuration
+    pub flights: Vec<Flight>,
+
+    /// The Flight who will receive this configuration's share of the public traffic
+    pub gateway_flight: Option<String>,
+}
+
+impl FormationRevision {
+    /// The current configuration of `formation` as a revision receiving `weight` percent of the
+    /// public traffic, or `None` if the Compute API has not assigned it a revision
+    pub fn of(formation: &Formation, weight: u8) -> Option<Self> {
+        Some(Self {
+            revision: formation.revision?,
+            weight,
+            flights: formation.flights.clone(),
+            gateway_flight: formation.gateway_flight.clone(),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -228,8 +799,22 @@ mod formation_tests {
                 oid: Some("flt-6f9asfo8ql0ar3mihb0ruv14i0".parse().unwrap()),
                 image: "foo.com/bar:latest".parse::<ImageReference>().unwrap(),
                 status: FlightStatus::Starting,
+                minimum: 1,
+                maximum: None,
+                architecture: Vec::new(),
+                api_permission: false,
+                env: BTreeMap::new(),
+                ports: Vec::new(),
+                health_check: None,
+                resources: None,
             }],
             gateway_flight: Some("example-flight".into()),
+            regions_allowed: BTreeSet::new(),
+            regions_denied: BTreeSet::new(),
+            providers_allowed: BTreeSet::new(),
+            providers_denied: BTreeSet::new(),
+            revision: None,
+            active_revisions: Vec::new(),
         };
 
         assert_eq!(model, serde_json::from_str(json).unwrap());
@@ -247,13 +832,291 @@ mod formation_tests {
                 oid: Some("flt-6f9asfo8ql0ar3mihb0ruv14i0".parse().unwrap()),
                 image: "foo.com/bar:latest".parse::<ImageReference>().unwrap(),
                 status: FlightStatus::Starting,
+                minimum: 1,
+                maximum: None,
+                architecture: Vec::new(),
+                api_permission: false,
+                env: BTreeMap::new(),
+                ports: Vec::new(),
+                health_check: None,
+                resources: None,
             }],
             gateway_flight: Some("example-flight".into()),
+            regions_allowed: BTreeSet::new(),
+            regions_denied: BTreeSet::new(),
+            providers_allowed: BTreeSet::new(),
+            providers_denied: BTreeSet::new(),
+            revision: None,
+            active_revisions: Vec::new(),
         };
 
         assert_eq!(json.to_string(), serde_json::to_string(&model).unwrap());
     }
 
+    #[test]
+    fn health() {
+        let flight = |name: &str, status| {
+            let mut flight = Flight::new(name, "foo.com/bar:latest");
+            flight.status = status;
+            flight
+        };
+        let mut model = Formation::builder()
+            .name("example-formation")
+            .add_flight(flight("a", FlightStatus::Healthy))
+            .add_flight(flight("b", FlightStatus::Healthy))
+            .gateway_flight("a")
+            .build()
+            .unwrap();
+        assert_eq!(model.health(), FlightStatus::Healthy);
+        model.flights[0].status = FlightStatus::Starting;
+        assert_eq!(model.health(), FlightStatus::Starting);
+        model.flights[1].status = FlightStatus::Unhealthy;
+        assert_eq!(model.health(), FlightStatus::Unhealthy);
+    }
+
+    #[test]
+    fn build_gateway_flight() {
+        use FormationValidation::*;
+        let builder = |flights: &[&str]| {
+            let mut builder = Formation::builder().name("example-formation");
+            for name in flights {
+                builder = builder.add_flight(Flight::new(*name, "foo.com/bar:latest"));
+            }
+            builder
+        };
+        let err = |v| ComputeError::FormationValidation(v).into();
+
+        // the only Flight is implied as the gateway
+        let model = builder(&["a"]).build().unwrap();
+        assert_eq!(model.gateway_flight.as_deref(), Some("a"));
+        let model = builder(&["a", "b"]).gateway_flight("b").build().unwrap();
+        assert_eq!(model.gateway_flight.as_deref(), Some("b"));
+        assert_eq!(builder(&["a", "b"]).build().unwrap_err(), err(NoGatewayFlight));
+        assert_eq!(
+            builder(&["a", "b"])
+                .gateway_flight("c")
+                .build()
+                .unwrap_err(),
+            err(InvalidGatewayFlight)
+        );
+    }
+
+    #[test]
+    fn placement() {
+        let json = r#"{"name":"example-formation","flights":[{"name":"example-flight","image":"foo.com/bar:latest"}],"gateway-flight":"example-flight","regions-allowed":["XE","XN"],"providers-denied":["GCP"]}"#;
+        let model = Formation::builder()
+            .name("example-formation")
+            .add_flight(Flight::new("example-flight", "foo.com/bar:latest"))
+            .add_allowed_region(Region::XN)
+            .add_allowed_region(Region::XE)
+            .add_denied_provider(Provider::GCP)
+            .build()
+            .unwrap();
+
+        assert_eq!(json, serde_json::to_string(&model).unwrap());
+        assert_eq!(model, serde_json::from_str(json).unwrap());
+    }
+
+    #[test]
+    fn revisions() {
+        let json = r#"{
+            "name": "example-formation",
+            "flights": [{"name": "example-flight", "image": "foo.com/bar:v2"}],
+            "gateway-flight": "example-flight",
+            "revision": 3,
+            "active-revisions": [{
+                "revision": 2,
+                "weight": 90,
+                "flights": [{"name": "example-flight", "image": "foo.com/bar:v1"}],
+                "gateway-flight": "example-flight"
+            }]
+        }"#;
+        let model: Formation = serde_json::from_str(json).unwrap();
+        assert_eq!(model.revision, Some(3));
+        assert_eq!(model.active_revisions[0].revision, 2);
+        assert_eq!(model.weight(), 10);
+
+        let rev = FormationRevision::of(&model, 50).unwrap();
+        assert_eq!(rev.revision, 3);
+        assert_eq!(rev.flights, model.flights);
+        let built = Formation::builder()
+            .name("example-formation")
+            .add_flight(Flight::new("example-flight", "foo.com/bar:v3"))
+            .add_active_revision(rev)
+            .build()
+            .unwrap();
+        assert_eq!(built.revision, None);
+        assert_eq!(built.weight(), 50);
+        assert_eq!(FormationRevision::of(&built, 50), None);
+    }
+
+    #[test]
+    fn build_invalid_revisions() {
+        use FormationValidation::*;
+        let rev = |revision, weight| FormationRevision {
+            revision,
+            weight,
+            flights: vec![Flight::new("example-flight", "foo.com/bar:v1")],
+            gateway_flight: Some("example-flight".into()),
+        };
+        let build = |revs: Vec<FormationRevision>| {
+            revs.into_iter()
+                .fold(
+                    Formation::builder()
+                        .name("example-formation")
+                        .add_flight(Flight::new("example-flight", "foo.com/bar:v2")),
+                    FormationBuilder::add_active_revision,
+                )
+                .build()
+        };
+        let err = |v| ComputeError::FormationValidation(v).into();
+
+        assert!(build(vec![rev(1, 100)]).is_ok());
+        assert!(build(vec![rev(1, 60), rev(2, 40)]).is_ok());
+        assert_eq!(build(vec![rev(1, 0)]).unwrap_err(), err(InvalidTrafficWeight));
+        assert_eq!(build(vec![rev(1, 101)]).unwrap_err(), err(InvalidTrafficWeight));
+        assert_eq!(build(vec![rev(1, 10), rev(1, 20)]).unwrap_err(), err(DuplicateRevision));
+        assert_eq!(
+            build(vec![rev(1, 60), rev(2, 50)]).unwrap_err(),
+            err(TrafficWeightsExceedTotal)
+        );
+    }
+
+    #[test]
+    fn build_conflicting_placement() {
+        use FormationValidation::*;
+        let builder = || {
+            Formation::builder()
+                .name("example-formation")
+                .add_flight(Flight::new("example-flight", "foo.com/bar:latest"))
+        };
+        let err = |v| ComputeError::FormationValidation(v).into();
+
+        assert!(builder()
+            .add_allowed_region(Region::XN)
+            .add_denied_region(Region::XE)
+            .build()
+            .is_ok());
+        assert_eq!(
+            builder()
+                .add_allowed_region(Region::XN)
+                .add_denied_region(Region::XN)
+                .build()
+                .unwrap_err(),
+            err(ConflictingRegions)
+        );
+        assert_eq!(
+            builder()
+                .add_allowed_provider(Provider::AWS)
+                .add_denied_provider(Provider::AWS)
+                .build()
+                .unwrap_err(),
+            err(ConflictingProviders)
+        );
+
+        let all_denied = |mut builder: FormationBuilder| {
+            for region in Region::VARIANTS {
+                builder = builder.add_denied_region(region.parse::<Region>().unwrap());
+            }
+            builder
+        };
+        assert_eq!(all_denied(builder()).build().unwrap_err(), err(NoAllowedRegions));
+        // Allowing any of them conflicts instead
+        assert_eq!(
+            all_denied(builder().add_allowed_region(Region::XN))
+                .build()
+                .unwrap_err(),
+            err(ConflictingRegions)
+        );
+        let mut builder = builder();
+        for provider in Provider::VARIANTS {
+            builder = builder.add_denied_provider(provider.parse::<Provider>().unwrap());
+        }
+        assert_eq!(builder.build().unwrap_err(), err(NoAllowedProviders));
+    }
+
+    #[test]
+    fn add_flight_replaces() {
+        let mut model = Formation::builder()
+            .name("example-formation")
+            .add_flight(Flight::new("example-flight", "foo.com/bar:latest"))
+            .build()
+            .unwrap();
+
+        assert!(model
+            .add_flight(Flight::new("other-flight", "foo.com/baz:latest"))
+            .is_none());
+        let replaced = model
+            .add_flight(Flight::new("example-flight", "foo.com/bar:v2"))
+            .unwrap();
+        assert_eq!(replaced.image_str(), "foo.com/bar:latest");
+        assert_eq!(model.flights.len(), 2);
+        assert_eq!(model.flights[0].image_str(), "foo.com/bar:v2");
+    }
+
+    #[test]
+    fn resolve_and_restore_secrets() {
+        let flight = Flight::builder()
+            .name("example-flight")
+            .image("foo.com/bar:latest")
+            .add_env("MODE", "production")
+            .add_secret_env("DB_PASS", "app/db-pass")
+            .build()
+            .unwrap();
+        let mut model = Formation::builder()
+            .name("example-formation")
+            .add_flight(flight.clone())
+            .build()
+            .unwrap();
+        model.active_revisions.push(FormationRevision {
+            revision: 1,
+            weight: 50,
+            flights: vec![flight],
+            gateway_flight: Some("example-flight".into()),
+        });
+        let planned = model.clone();
+
+        let mut resolved = Vec::new();
+        model
+            .resolve_secrets(|flight, var, key| {
+                resolved.push(format!("{flight} {var} {key}"));
+                Ok::<_, ()>(format!("value of {key}"))
+            })
+            .unwrap();
+        assert_eq!(resolved, ["example-flight DB_PASS app/db-pass"; 2]);
+        assert!(model.unresolved_secret().is_none());
+        assert_eq!(model.flights[0].env()["DB_PASS"], EnvValue::from("value of app/db-pass"));
+        assert_eq!(model.flights[0].env()["MODE"], EnvValue::from("production"));
+
+        model.restore_secrets(&planned);
+        assert_eq!(model, planned);
+        assert!(model.unresolved_secret().is_some());
+    }
+
+    #[test]
+    fn build_flight_names() {
+        use FormationValidation::*;
+        let builder = |flights: &[&str]| {
+            let mut builder = Formation::builder()
+                .name("example-formation")
+                .gateway_flight("a");
+            for name in flights {
+                // skip the validation of Flight::new to check the Formation validates names
+                let mut flight = Flight::new("a", "foo.com/bar:latest");
+                flight.name = name.to_string();
+                builder = builder.add_flight(flight);
+            }
+            builder
+        };
+        let err = |v| ComputeError::FormationValidation(v).into();
+
+        assert!(builder(&["a", "b-c"]).build().is_ok());
+        assert_eq!(builder(&["a", "a"]).build().unwrap_err(), err(DuplicateFlightName));
+        assert_eq!(builder(&["a", "B"]).build().unwrap_err(), err(FlightNameInvalidChar));
+        assert_eq!(builder(&["a", "b--c"]).build().unwrap_err(), err(FlightNameConsecutiveHyphens));
+        assert_eq!(builder(&["a", ""]).build().unwrap_err(), err(FlightNameEmpty));
+    }
+
     #[test]
     fn ser_no_oid() {
         let json = r#"{"name":"example-formation","flights":[{"name":"example-flight","image":"foo.com/bar:latest","status":"healthy"}],"gateway-flight":"example-flight"}"#;
@@ -266,8 +1129,22 @@ mod formation_tests {
                 oid: None,
                 image: "foo.com/bar:latest".parse::<ImageReference>().unwrap(),
                 status: FlightStatus::Healthy,
+                minimum: 1,
+                maximum: None,
+                architecture: Vec::new(),
+                api_permission: false,
+                env: BTreeMap::new(),
+                ports: Vec::new(),
+                health_check: None,
+                resources: None,
             }],
             gateway_flight: Some("example-flight".into()),
+            regions_allowed: BTreeSet::new(),
+            regions_denied: BTreeSet::new(),
+            providers_allowed: BTreeSet::new(),
+            providers_denied: BTreeSet::new(),
+            revision: None,
+            active_revisions: Vec::new(),
         };
 
         assert_eq!(json.to_string(), serde_json::to_string(&model).unwrap());
@@ -278,7 +1155,16 @@ mod formation_tests {
 #[derive(Debug, Default)]
 pub struct FlightBuilder {
     name: Option<String>,
-    image: Option<ImageReference>,
+    // An image which failed to parse is kept until the Flight is built, to be reported then
+    image: Option<StdResult<ImageReference, ImageReferenceError>>,
+    minimum: Option<u64>,
+    maximum: Option<u64>,
+    architecture: Vec<Architecture>,
+    api_permission: bool,
+    env: BTreeMap<String, EnvValue>,
+    ports: Vec<Port>,
+    health_check: Option<HealthCheck>,
+    resources: Option<Resources>,
 }
 
 impl FlightBuilder {
@@ -295,46 +1181,186 @@ impl FlightBuilder {
     /// A container image registry reference which points to the container image this [`Flight`]
     /// should uses
     ///
-    /// # Panics
-    ///
-    /// This method `panic!`s if the `image_ref` provided cannot be parsed into a valid
-    /// [`ImageReference`]
+    /// **NOTE:** The value will be parsed into an [`ImageReference`] on the call to
+    /// [`FlightBuilder::build`], which returns [`FormationValidation::InvalidImageReference`] if
+    /// it isn't valid
     #[must_use]
     pub fn image<R: AsRef<str>>(mut self, image_ref: R) -> Self {
-        self.image = Some(
-            image_ref
-                .as_ref()
-                .parse::<ImageReference>()
-                .expect("Failed to parse image reference"),
-        );
+        self.image = Some(image_ref.as_ref().parse::<ImageReference>());
         self
     }
 
     /// A container image registry reference which points to the container image this [`Flight`]
     /// should uses.
     ///
-    /// This method allows providing a pre-parsed [`ImageReference`] instead of a string which can
-    /// `panic!` on parsing in [`FlightBuilder::image`].
+    /// This method allows providing a pre-parsed [`ImageReference`] instead of a string as in
+    /// [`FlightBuilder::image`].
     #[must_use]
     pub fn image_reference(mut self, image_ref: ImageReference) -> Self {
-        self.image = Some(image_ref);
+        self.image = Some(Ok(image_ref));
+        self
+    }
+
+    /// The minimum number of container instances that should ever be running (default: `1`)
+    ///
+    /// **NOTE:** The value will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn minimum(mut self, minimum: u64) -> Self {
+        self.minimum = Some(minimum);
+        self
+    }
+
+    /// The maximum number of container instances that should ever be running (default: autoscale
+    /// as needed)
+    ///
+    /// **NOTE:** The value will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn maximum(mut self, maximum: u64) -> Self {
+        self.maximum = Some(maximum);
+        self
+    }
+
+    /// Add an [`Architecture`] the container image of this [`Flight`] can run on. When none are
+    /// added the Flight may be placed on any architecture.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized.
+    #[must_use]
+    pub fn add_architecture(mut self, architecture: Architecture) -> Self {
+        if !self.architecture.contains(&architecture) {
+            self.architecture.push(architecture);
+        }
+        self
+    }
+
+    /// Whether or not the [`Flight`] is allowed to access the Seaplane APIs (default: `false`)
+    #[must_use]
+    pub fn api_permission(mut self, permission: bool) -> Self {
+        self.api_permission = permission;
+        self
+    }
+
+    /// Set the environment variable `key` of the [`Flight`] to `value`, replacing any previous
+    /// value of `key`
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized. The name
+    /// will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn add_env<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.env.insert(key.into(), EnvValue::Value(value.into()));
+        self
+    }
+
+    /// Set the environment variable `key` of the [`Flight`] to the value of the (unencoded)
+    /// Metadata key `metadata_key`, replacing any previous value of `key`. See
+    /// [`EnvValue::Secret`].
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized. The name
+    /// will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn add_secret_env<K: Into<String>, M: Into<String>>(
+        mut self,
+        key: K,
+        metadata_key: M,
+    ) -> Self {
+        self.env
+            .insert(key.into(), EnvValue::Secret { secret: metadata_key.into() });
+        self
+    }
+
+    /// Add a [`Port`] the containers of this [`Flight`] listen on. When none are added the Flight
+    /// receives HTTP traffic on the default port of the runtime.
+    ///
+    /// **NOTE:** This method can be called multiple times. All values will be utilized. The ports
+    /// will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn add_port(mut self, port: Port) -> Self {
+        self.ports.push(port);
+        self
+    }
+
+    /// How the containers of this [`Flight`] are health checked (default: as decided by the
+    /// runtime)
+    ///
+    /// **NOTE:** The value will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn health_check(mut self, health_check: HealthCheck) -> Self {
+        self.health_check = Some(health_check);
+        self
+    }
+
+    /// The CPU and memory each container of this [`Flight`] needs (default: as decided by the
+    /// runtime)
+    ///
+    /// **NOTE:** The value will be validated on the call to [`FlightBuilder::build`]
+    #[must_use]
+    pub fn resources(mut self, resources: Resources) -> Self {
+        self.resources = Some(resources);
         self
     }
 
     /// Perform validation checks and construct a [`Flight`]
     pub fn build(self) -> Result<Flight> {
         use FormationValidation::*;
-        if self.name.is_none() {
-            return Err(ComputeError::FormationValidation(MissingFlightName).into());
-        } else if self.image.is_none() {
-            return Err(ComputeError::FormationValidation(MissingFlightImageReference).into());
+        match &self.name {
+            None => return Err(ComputeError::FormationValidation(MissingFlightName).into()),
+            Some(name) => validate_flight_name(name).map_err(ComputeError::FormationValidation)?,
+        }
+        let image = match self.image {
+            None => {
+                return Err(ComputeError::FormationValidation(MissingFlightImageReference).into())
+            }
+            Some(Err(e)) => {
+                return Err(ComputeError::FormationValidation(InvalidImageReference(e)).into())
+            }
+            Some(Ok(image)) => image,
+        };
+
+        let minimum = self.minimum.unwrap_or_else(default_minimum);
+        if minimum == 0 {
+            return Err(ComputeError::FormationValidation(FlightMinimumZero).into());
+        } else if matches!(self.maximum, Some(max) if max < minimum) {
+            return Err(ComputeError::FormationValidation(FlightMinimumExceedsMaximum).into());
+        }
+
+        for (key, value) in &self.env {
+            validate_env_name(key).map_err(ComputeError::FormationValidation)?;
+            if value.secret() == Some("") {
+                return Err(ComputeError::FormationValidation(EmptySecretKey(key.clone())).into());
+            }
+        }
+
+        for (i, port) in self.ports.iter().enumerate() {
+            if port.port == 0 {
+                return Err(ComputeError::FormationValidation(PortZero).into());
+            }
+            if self.ports[..i].contains(port) {
+                return Err(ComputeError::FormationValidation(DuplicatePort).into());
+            }
+        }
+        if let Some(health_check) = &self.health_check {
+            health_check
+                .validate(&self.ports)
+                .map_err(ComputeError::FormationValidation)?;
+        }
+        if let Some(resources) = &self.resources {
+            resources
+                .validate()
+                .map_err(ComputeError::FormationValidation)?;
         }
 
         Ok(Flight {
             name: self.name.unwrap(),
             oid: None,
-            image: self.image.unwrap(),
+            image,
             status: FlightStatus::default(),
+            minimum,
+            maximum: self.maximum,
+            architecture: self.architecture,
+            api_permission: self.api_permission,
+            env: self.env,
+            ports: self.ports,
+            health_check: self.health_check,
+            resources: self.resources,
         })
     }
 }
@@ -361,8 +1387,47 @@ pub struct Flight {
     /// The status of this Flight
     #[serde(default, skip_serializing_if = "FlightStatus::is_starting")]
     pub status: FlightStatus,
+
+    /// The minimum number of container instances that should ever be running
+    #[serde(default = "default_minimum", skip_serializing_if = "is_default_minimum")]
+    pub minimum: u64,
+
+    /// The maximum number of container instances that should ever be running, or `None` to
+    /// autoscale as needed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<u64>,
+
+    /// The architectures the container image can run on, or empty for any architecture
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub architecture: Vec<Architecture>,
+
+    /// Whether or not the Flight is allowed to access the Seaplane APIs
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub api_permission: bool,
+
+    /// The environment variables of the Flight's containers, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, EnvValue>,
+
+    /// The ports the Flight's containers listen on, or empty for HTTP on the runtime's default
+    /// port
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<Port>,
+
+    /// How the Flight's containers are health checked, or `None` for the runtime's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+
+    /// The CPU and memory each of the Flight's containers needs, or `None` for the runtime's
+    /// default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<Resources>,
 }
 
+fn default_minimum() -> u64 { 1 }
+
+fn is_default_minimum(minimum: &u64) -> bool { *minimum == default_minimum() }
+
 impl Flight {
     /// Create a new [`FlightBuilder`] in order to construct a new [`Flight`]
     pub fn builder() -> FlightBuilder { FlightBuilder::new() }
@@ -373,18 +1438,25 @@ impl Flight {
     ///
     /// # Panics
     ///
-    /// This method `panic!`s if the `image_ref` provided cannot be parsed into a valid
-    /// [`ImageReference`]
+    /// This method `panic!`s if the `name` is not a valid Flight name (see
+    /// [`validate_flight_name`]), or the `image_ref` provided cannot be parsed into a valid
+    /// [`ImageReference`]. Use [`Flight::try_new`] for input which may not be valid.
     pub fn new<S, R>(name: S, image_ref: R) -> Flight
     where
         S: Into<String>,
         R: AsRef<str>,
     {
-        FlightBuilder::new()
-            .name(name)
-            .image(image_ref)
-            .build()
-            .unwrap()
+        Self::try_new(name, image_ref).unwrap()
+    }
+
+    /// Creates a new [`Flight`] in the same way as [`Flight::new`], but returns an error instead
+    /// of panicking when the `name` or `image_ref` are not valid.
+    pub fn try_new<S, R>(name: S, image_ref: R) -> Result<Flight>
+    where
+        S: Into<String>,
+        R: AsRef<str>,
+    {
+        FlightBuilder::new().name(name).image(image_ref).build()
     }
 
     /// Returns the human readable [`Flight`] name, which is unique within the Formation
@@ -398,6 +1470,49 @@ impl Flight {
     /// Returns the container image reference this [`Flight`] uses, as an [`ImageReference`]
     #[inline]
     pub fn image(&self) -> &ImageReference { &self.image }
+
+    /// Returns the minimum number of container instances that should ever be running
+    #[inline]
+    pub fn minimum(&self) -> u64 { self.minimum }
+
+    /// Returns the maximum number of container instances that should ever be running, or `None`
+    /// to autoscale as needed
+    #[inline]
+    pub fn maximum(&self) -> Option<u64> { self.maximum }
+
+    /// Returns the architectures the container image can run on, which is empty for any
+    #[inline]
+    pub fn architecture(&self) -> &[Architecture] { &self.architecture }
+
+    /// Returns whether or not the [`Flight`] is allowed to access the Seaplane APIs
+    #[inline]
+    pub fn api_permission(&self) -> bool { self.api_permission }
+
+    /// Returns the environment variables of the [`Flight`]'s containers, by name
+    #[inline]
+    pub fn env(&self) -> &BTreeMap<String, EnvValue> { &self.env }
+
+    /// Returns the ports the [`Flight`]'s containers listen on, which is empty for HTTP on the
+    /// runtime's default port
+    #[inline]
+    pub fn ports(&self) -> &[Port] { &self.ports }
+
+    /// Returns how the [`Flight`]'s containers are health checked, if not the runtime's default
+    #[inline]
+    pub fn health_check(&self) -> Option<&HealthCheck> { self.health_check.as_ref() }
+
+    /// Returns the CPU and memory each of the [`Flight`]'s containers needs, if not the runtime's
+    /// default
+    #[inline]
+    pub fn resources(&self) -> Option<&Resources> { self.resources.as_ref() }
+
+    /// Returns the names of the environment variables which are secret references, along with
+    /// the Metadata key each references
+    pub fn secrets(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.env
+            .iter()
+            .filter_map(|(k, v)| v.secret().map(|secret| (k.as_str(), secret)))
+    }
 }
 
 #[cfg(test)]
@@ -416,11 +1531,258 @@ mod flight_tests {
             oid: Some("flt-6f9asfo8ql0ar3mihb0ruv14i0".parse().unwrap()),
             image: "foo.com/bar:latest".parse::<ImageReference>().unwrap(),
             status: FlightStatus::Starting,
+            minimum: 1,
+            maximum: None,
+            architecture: Vec::new(),
+            api_permission: false,
+            env: BTreeMap::new(),
+            ports: Vec::new(),
+            health_check: None,
+            resources: None,
         };
 
         assert_eq!(model, serde_json::from_str(json).unwrap());
     }
 
+    #[test]
+    fn deser_scale() {
+        let json = r#"{
+            "name":"example-flight",
+            "image":"foo.com/bar:latest",
+            "minimum":2,
+            "maximum":5,
+            "architecture":["amd64","ARM64"],
+            "api-permission":true
+        }"#;
+        let model = Flight::builder()
+            .name("example-flight")
+            .image("foo.com/bar:latest")
+            .minimum(2)
+            .maximum(5)
+            .add_architecture(Architecture::Amd64)
+            .add_architecture(Architecture::Arm64)
+            .api_permission(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(model, serde_json::from_str(json).unwrap());
+        assert!(serde_json::from_str::<Flight>(
+            r#"{"name":"example-flight","image":"foo.com/bar:latest","architecture":["x86"]}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ser_scale() {
+        let json = r#"{"name":"example-flight","image":"foo.com/bar:latest","minimum":2,"maximum":5,"architecture":["arm64"],"api-permission":true}"#;
+        let model = Flight::builder()
+            .name("example-flight")
+            .image("foo.com/bar:latest")
+            .minimum(2)
+            .maximum(5)
+            .add_architecture(Architecture::Arm64)
+            .api_permission(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(json, serde_json::to_string(&model).unwrap());
+    }
+
+    #[test]
+    fn build_invalid_scale() {
+        let builder = || {
+            Flight::builder()
+                .name("example-flight")
+                .image("foo.com/bar:latest")
+        };
+        assert!(builder().minimum(5).maximum(5).build().is_ok());
+        assert!(builder().maximum(1).build().is_ok());
+        assert_eq!(
+            builder().minimum(6).maximum(5).build().unwrap_err(),
+            ComputeError::FormationValidation(FormationValidation::FlightMinimumExceedsMaximum)
+                .into()
+        );
+        assert_eq!(
+            builder().minimum(0).build().unwrap_err(),
+            ComputeError::FormationValidation(FormationValidation::FlightMinimumZero).into()
+        );
+    }
+
+    #[test]
+    fn env() {
+        let json = r#"{"name":"example-flight","image":"foo.com/bar:latest","env":{"DB_HOST":"db.example.com","DB_PASS":{"secret":"app/db-pass"}}}"#;
+        let model = Flight::builder()
+            .name("example-flight")
+            .image("foo.com/bar:latest")
+            .add_env("DB_HOST", "db.example.com")
+            .add_secret_env("DB_PASS", "app/db-pass")
+            .build()
+            .unwrap();
+
+        assert_eq!(json, serde_json::to_string(&model).unwrap());
+        assert_eq!(model, serde_json::from_str(json).unwrap());
+        assert_eq!(model.secrets().collect::<Vec<_>>(), vec![("DB_PASS", "app/db-pass")]);
+    }
+
+    #[test]
+    fn workload() {
+        let json = r#"{"name":"example-flight","image":"foo.com/bar:latest","ports":[{"port":9090,"protocol":"http"},{"port":5432,"protocol":"tcp"}],"health-check":{"path":"/healthz","interval":30,"timeout":5,"healthy-threshold":1,"unhealthy-threshold":3},"resources":{"millicpu":500,"memory-mib":1024}}"#;
+        let model = Flight::builder()
+            .name("example-flight")
+            .image("foo.com/bar:latest")
+            .add_port(Port::new(9090, Protocol::Http))
+            .add_port(Port::new(5432, Protocol::Tcp))
+            .health_check(HealthCheck { interval: 30, ..HealthCheck::new("/healthz") })
+            .resources(Resources { millicpu: Some(500), memory_mib: Some(1024) })
+            .build()
+            .unwrap();
+
+        assert_eq!(json, serde_json::to_string(&model).unwrap());
+        assert_eq!(model, serde_json::from_str(json).unwrap());
+        // Anything left out of a health check uses the defaults
+        let model: Flight = serde_json::from_str(
+            r#"{"name":"example-flight","image":"foo.com/bar:latest","health-check":{"path":"/healthz"}}"#,
+        )
+        .unwrap();
+        assert_eq!(model.health_check(), Some(&HealthCheck::new("/healthz")));
+    }
+
+    #[test]
+    fn build_invalid_workload() {
+        use FormationValidation::*;
+        let builder = || {
+            Flight::builder()
+                .name("example-flight")
+                .image("foo.com/bar:latest")
+        };
+        let err = |v| ComputeError::FormationValidation(v).into();
+        assert_eq!(
+            builder()
+                .add_port(Port::new(0, Protocol::Tcp))
+                .build()
+                .unwrap_err(),
+            err(PortZero)
+        );
+        assert_eq!(
+            builder()
+                .add_port(Port::new(53, Protocol::Udp))
+                .add_port(Port::new(53, Protocol::Tcp))
+                .add_port(Port::new(53, Protocol::Udp))
+                .build()
+                .unwrap_err(),
+            err(DuplicatePort)
+        );
+        assert_eq!(
+            builder()
+                .health_check(HealthCheck::new("healthz"))
+                .build()
+                .unwrap_err(),
+            err(HealthCheckInvalidPath)
+        );
+        assert_eq!(
+            builder()
+                .add_port(Port::new(5432, Protocol::Tcp))
+                .add_port(Port::new(8080, Protocol::Http))
+                .health_check(HealthCheck::new("/healthz"))
+                .build()
+                .unwrap_err(),
+            err(HealthCheckNotHttp)
+        );
+        assert!(builder()
+            .add_port(Port::new(8080, Protocol::Http))
+            .add_port(Port::new(5432, Protocol::Tcp))
+            .health_check(HealthCheck::new("/healthz"))
+            .build()
+            .is_ok());
+        assert_eq!(
+            builder()
+                .health_check(HealthCheck { unhealthy_threshold: 0, ..Default::default() })
+                .build()
+                .unwrap_err(),
+            err(HealthCheckZero)
+        );
+        assert_eq!(
+            builder()
+                .health_check(HealthCheck { interval: 5, timeout: 10, ..Default::default() })
+                .build()
+                .unwrap_err(),
+            err(HealthCheckTimeoutExceedsInterval)
+        );
+        assert_eq!(
+            builder()
+                .resources(Resources { millicpu: Some(0), memory_mib: None })
+                .build()
+                .unwrap_err(),
+            err(ResourcesZero)
+        );
+    }
+
+    #[test]
+    fn build_invalid_env() {
+        use FormationValidation::*;
+        let builder = || {
+            Flight::builder()
+                .name("example-flight")
+                .image("foo.com/bar:latest")
+        };
+        let err = |v| ComputeError::FormationValidation(v).into();
+        assert!(builder().add_env("_FOO_1", "").build().is_ok());
+        assert_eq!(
+            builder().add_env("1FOO", "bar").build().unwrap_err(),
+            err(InvalidEnvName("1FOO".into()))
+        );
+        assert_eq!(
+            builder().add_env("FOO-BAR", "bar").build().unwrap_err(),
+            err(InvalidEnvName("FOO-BAR".into()))
+        );
+        assert_eq!(
+            builder().add_env("", "bar").build().unwrap_err(),
+            err(InvalidEnvName("".into()))
+        );
+        assert_eq!(
+            builder().add_secret_env("FOO", "").build().unwrap_err(),
+            err(EmptySecretKey("FOO".into()))
+        );
+    }
+
+    #[test]
+    fn build_invalid_name() {
+        let builder = || Flight::builder().image("foo.com/bar:latest");
+        assert_eq!(
+            builder().build().unwrap_err(),
+            ComputeError::FormationValidation(FormationValidation::MissingFlightName).into()
+        );
+        assert_eq!(
+            builder().name("example_flight").build().unwrap_err(),
+            ComputeError::FormationValidation(FormationValidation::FlightNameInvalidChar).into()
+        );
+        assert_eq!(
+            builder().name("-example-flight").build().unwrap_err(),
+            ComputeError::FormationValidation(
+                FormationValidation::FlightNameLeadingOrTrailingHyphen
+            )
+            .into()
+        );
+    }
+
+    #[test]
+    fn build_invalid_image() {
+        let res = Flight::builder()
+            .name("example-flight")
+            .image("foo.com/bar::latest")
+            .build();
+        assert!(matches!(
+            res,
+            Err(crate::error::SeaplaneError::ComputeRequest(ComputeError::FormationValidation(
+                FormationValidation::InvalidImageReference(_)
+            )))
+        ));
+
+        assert!(Flight::try_new("example-flight", "foo.com/bar:latest").is_ok());
+        assert!(Flight::try_new("example-flight", "foo.com/bar::latest").is_err());
+        assert!(Flight::try_new("example_flight", "foo.com/bar:latest").is_err());
+    }
+
     #[test]
     fn ser() {
         let json = r#"{"name":"example-flight","oid":"flt-6f9asfo8ql0ar3mihb0ruv14i0","image":"foo.com/bar:latest","status":"healthy"}"#;
@@ -429,6 +1791,14 @@ mod flight_tests {
             oid: Some("flt-6f9asfo8ql0ar3mihb0ruv14i0".parse().unwrap()),
             image: "foo.com/bar:latest".parse::<ImageReference>().unwrap(),
             status: FlightStatus::Healthy,
+            minimum: 1,
+            maximum: None,
+            architecture: Vec::new(),
+            api_permission: false,
+            env: BTreeMap::new(),
+            ports: Vec::new(),
+            health_check: None,
+            resources: None,
         };
 
         assert_eq!(json, serde_json::to_string(&model).unwrap());
@@ -442,6 +1812,14 @@ mod flight_tests {
             oid: None,
             image: "foo.com/bar:latest".parse::<ImageReference>().unwrap(),
             status: FlightStatus::Healthy,
+            minimum: 1,
+            maximum: None,
+            architecture: Vec::new(),
+            api_permission: false,
+            env: BTreeMap::new(),
+            ports: Vec::new(),
+            health_check: None,
+            resources: None,
         };
 
         assert_eq!(json, serde_json::to_string(&model).unwrap());
@@ -27,4 +27,5 @@ pub struct PageMetadata {
 pub type GetFormationsResponse = PagedResponse<Formation>;
 pub type CreateFormationResponse = Formation;
 pub type GetFormationResponse = Formation;
+pub type UpdateFormationResponse = Formation;
 pub type DeleteFormationResponse = ();
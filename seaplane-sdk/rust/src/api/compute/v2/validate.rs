@@ -2,7 +2,7 @@ use std::result::Result as StdResult;
 
 use crate::api::compute::error::FormationValidation;
 #[cfg(doc)]
-use crate::api::compute::v2::Formation;
+use crate::api::compute::v2::{Flight, Formation};
 
 /// Determine if a [`Formation`] name (slug) is valid or not.
 ///
@@ -13,12 +13,68 @@ use crate::api::compute::v2::Formation;
 ///  - no more than three (3) total hyphens
 ///  - may not start, or end with a hyphen
 pub fn validate_formation_name(name: impl AsRef<str>) -> StdResult<(), FormationValidation> {
+    use FormationValidation::*;
+    validate_name(
+        name.as_ref(),
+        [
+            NameEmpty,
+            NameLength,
+            NameInvalidChar,
+            NameTooManyHyphens,
+            NameConsecutiveHyphens,
+            NameLeadingOrTrailingHyphen,
+        ],
+    )
+}
+
+/// Determine if a [`Flight`] name is valid or not.
+///
+/// Flight names follow the same rules as [`Formation`] names, see [`validate_formation_name`].
+pub fn validate_flight_name(name: impl AsRef<str>) -> StdResult<(), FormationValidation> {
+    use FormationValidation::*;
+    validate_name(
+        name.as_ref(),
+        [
+            FlightNameEmpty,
+            FlightNameLength,
+            FlightNameInvalidChar,
+            FlightNameTooManyHyphens,
+            FlightNameConsecutiveHyphens,
+            FlightNameLeadingOrTrailingHyphen,
+        ],
+    )
+}
+
+/// Determine if the name of a [`Flight`]'s environment variable is valid or not.
+///
+/// The name must be made of ASCII letters, digits, or underscores ('_'), and may not start with a
+/// digit.
+pub fn validate_env_name(name: impl AsRef<str>) -> StdResult<(), FormationValidation> {
     let name = name.as_ref();
+    let valid = matches!(name.as_bytes().first(), Some(c) if !c.is_ascii_digit())
+        && name
+            .as_bytes()
+            .iter()
+            .all(|&c| c.is_ascii_alphanumeric() || c == b'_');
+    if !valid {
+        return Err(FormationValidation::InvalidEnvName(name.into()));
+    }
+    Ok(())
+}
+
+// Checks the rules shared by Formation and Flight names, returning the given error for whichever
+// rule is broken first
+fn validate_name(
+    name: &str,
+    errors: [FormationValidation; 6],
+) -> StdResult<(), FormationValidation> {
+    let [empty, length, invalid_char, too_many_hyphens, consecutive_hyphens, leading_trailing] =
+        errors;
     if name.is_empty() {
-        return Err(FormationValidation::NameEmpty);
+        return Err(empty);
     }
     if name.len() > 63 {
-        return Err(FormationValidation::NameLength);
+        return Err(length);
     }
     let mut hyphen_count = 0;
     if !name.as_bytes().iter().all(|&c| {
@@ -31,16 +87,16 @@ pub fn validate_formation_name(name: impl AsRef<str>) -> StdResult<(), Formation
             }
         }
     }) {
-        return Err(FormationValidation::NameInvalidChar);
+        return Err(invalid_char);
     }
     if hyphen_count > 3 {
-        return Err(FormationValidation::NameTooManyHyphens);
+        return Err(too_many_hyphens);
     }
     if name.contains("--") {
-        return Err(FormationValidation::NameConsecutiveHyphens);
+        return Err(consecutive_hyphens);
     }
     if name.ends_with('-') || name.starts_with('-') {
-        return Err(FormationValidation::NameLeadingOrTrailingHyphen);
+        return Err(leading_trailing);
     }
 
     Ok(())
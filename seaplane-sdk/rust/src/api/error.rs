@@ -2,10 +2,10 @@
 
 use std::{error::Error, fmt, result::Result as StdResult};
 
-use reqwest::blocking::Response;
 use serde::{de, Deserialize};
 use serde_json::{Map, Value};
 
+#[cfg(any(feature = "blocking", feature = "async"))]
 use crate::error::Result;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -73,13 +73,26 @@ mod api_response_error_tests {
 }
 
 /// Maps a response error for all of the coordination services that use a JSON response type
-pub fn map_api_error(resp: Response) -> Result<Response> {
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub fn map_api_error(resp: reqwest::blocking::Response) -> Result<reqwest::blocking::Response> {
     if let Err(source) = resp.error_for_status_ref() {
         return Err(ApiError { response: resp.json::<ApiResponse>()?, source }.into());
     }
     Ok(resp)
 }
 
+/// Maps a response error for all of the coordination services that use a JSON response type, the
+/// async equivalent of [`map_api_error`]
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub async fn map_api_error_async(resp: reqwest::Response) -> Result<reqwest::Response> {
+    if let Err(source) = resp.error_for_status_ref() {
+        return Err(ApiError { response: resp.json::<ApiResponse>().await?, source }.into());
+    }
+    Ok(resp)
+}
+
 /// A generic Server Side error response derived from errors following [RFC 7807][rfc_7807]
 ///
 /// [rfc_7807]: https://www.rfc-editor.org/rfc/rfc7807
@@ -100,6 +113,11 @@ impl ApiError {
     pub fn is_http_unauthorized(&self) -> bool {
         self.source.status() == Some(reqwest::StatusCode::UNAUTHORIZED)
     }
+
+    /// A convenience method for seeing if the error came from an HTTP 412 PRECONDITION FAILED
+    pub fn is_http_precondition_failed(&self) -> bool {
+        self.source.status() == Some(reqwest::StatusCode::PRECONDITION_FAILED)
+    }
 }
 
 impl PartialEq for ApiError {
@@ -1,14 +1,26 @@
 //! The API endpoints related to Tokens and Authentication
 
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod provider;
+
+#[cfg(feature = "blocking")]
+use reqwest::header::ACCEPT;
 use reqwest::{
-    blocking,
-    header::{HeaderMap, HeaderValue, ACCEPT, CONTENT_LENGTH, CONTENT_TYPE},
+    header::{HeaderMap, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
     Url,
 };
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "async")]
+pub use self::asynchronous::*;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use self::provider::*;
+#[cfg(feature = "blocking")]
+use crate::api::retry::send_with_retry;
 use crate::{
-    api::{identity::IDENTITY_API_URL, map_api_error},
+    api::{identity::IDENTITY_API_URL, RetryPolicy},
     error::{Result, SeaplaneError},
 };
 
@@ -26,10 +38,12 @@ pub struct AccessToken {
     pub subdomain: String,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct TokenRequestBuilder {
     // Required for Bearer Auth
     api_key: Option<String>,
+    // How requests which fail due to transient errors are retried
+    retry: RetryPolicy,
     // Used for testing
     #[doc(hidden)]
     base_url: Option<Url>,
@@ -54,6 +68,17 @@ impl TokenRequestBuilder {
         self
     }
 
+    /// Set how requests which fail due to transient errors are retried (default:
+    /// [`RetryPolicy::default`])
+    ///
+    /// **NOTE:** Requesting an access token has no side effects, so these requests are always
+    /// treated as idempotent
+    #[must_use]
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
     /// Allow non-HTTPS endpoints for this request (default: `false`)
     #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))))]
@@ -71,51 +96,42 @@ impl TokenRequestBuilder {
     }
 
     /// Build a TokenRequest from the given parameters
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
     pub fn build(self) -> Result<TokenRequest> {
-        if self.api_key.is_none() {
-            return Err(SeaplaneError::MissingRequestApiKey);
-        }
+        let client = build_client!(reqwest::blocking::Client::builder(), self.headers(), self);
+        Ok(TokenRequest {
+            endpoint_url: self.endpoint_url()?,
+            retry: self.retry,
+            api_key: self.into_api_key()?,
+            client,
+        })
+    }
 
+    // Consumes the builder returning the API key, or an error if one was not provided
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn into_api_key(self) -> Result<String> {
+        self.api_key.ok_or(SeaplaneError::MissingRequestApiKey)
+    }
+
+    // The default headers sent with every request
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
         headers.insert(CONTENT_LENGTH, HeaderValue::from_static("0"));
+        headers
+    }
 
-        #[cfg_attr(
-            not(any(
-                feature = "api_tests",
-                feature = "allow_insecure_urls",
-                feature = "danger_zone"
-            )),
-            allow(unused_mut)
-        )]
-        let mut builder = blocking::Client::builder()
-            .default_headers(headers)
-            .https_only(true);
-
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "api_tests")] {
-                builder = builder.https_only(false);
-            } else if #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))] {
-                builder = builder.https_only(!self.allow_http);
-            }
-        }
-        #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
-        {
-            builder = builder.danger_accept_invalid_certs(self.allow_invalid_certs);
-        }
-
-        let url = if let Some(url) = self.base_url {
+    // The URL of the token endpoint
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn endpoint_url(&self) -> Result<Url> {
+        Ok(if let Some(url) = &self.base_url {
             url.join(TOKEN_API_BASE_PATH)?
         } else {
             let mut url: Url = IDENTITY_API_URL.parse()?;
             url.set_path(TOKEN_API_BASE_PATH);
             url
-        };
-
-        Ok(TokenRequest {
-            api_key: self.api_key.unwrap(),
-            client: builder.build()?,
-            endpoint_url: url,
         })
     }
 
@@ -128,15 +144,19 @@ impl TokenRequestBuilder {
 }
 
 /// For making requests against the `/v1/token` APIs.
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
 #[derive(Debug)]
 pub struct TokenRequest {
     api_key: String,
+    retry: RetryPolicy,
     #[doc(hidden)]
     client: reqwest::blocking::Client,
     #[doc(hidden)]
     endpoint_url: Url,
 }
 
+#[cfg(feature = "blocking")]
 impl TokenRequest {
     /// Create a new request builder
     pub fn builder() -> TokenRequestBuilder { TokenRequestBuilder::new() }
@@ -153,12 +173,11 @@ impl TokenRequest {
     /// dbg!(resp);
     /// ```
     pub fn access_token(&self) -> Result<String> {
-        let resp = self
+        let req = self
             .client
             .post(self.endpoint_url.clone())
-            .bearer_auth(&self.api_key)
-            .send()?;
-        map_api_error(resp)?.text().map_err(Into::into)
+            .bearer_auth(&self.api_key);
+        self.send(req)?.text().map_err(Into::into)
     }
 
     /// Returns a JSON response of an `AccessToken` which contains the short lived JWT used to
@@ -175,14 +194,16 @@ impl TokenRequest {
     /// dbg!(resp);
     /// ```
     pub fn access_token_json(&self) -> Result<AccessToken> {
-        let resp = self
+        let req = self
             .client
             .post(self.endpoint_url.clone())
             .bearer_auth(&self.api_key)
-            .header(ACCEPT, HeaderValue::from_static("application/json"))
-            .send()?;
-        map_api_error(resp)?
-            .json::<AccessToken>()
-            .map_err(Into::into)
+            .header(ACCEPT, HeaderValue::from_static("application/json"));
+        self.send(req)?.json::<AccessToken>().map_err(Into::into)
+    }
+
+    // Sends the request, retrying transient failures and mapping any error responses
+    fn send(&self, req: reqwest::blocking::RequestBuilder) -> Result<reqwest::blocking::Response> {
+        send_with_retry(&self.retry, true, req, |req| Ok(req.send()?))
     }
 }
@@ -0,0 +1,88 @@
+use reqwest::{
+    header::{HeaderValue, ACCEPT},
+    Url,
+};
+
+use super::{AccessToken, TokenRequestBuilder};
+use crate::{
+    api::{retry::send_with_retry_async, RetryPolicy},
+    error::Result,
+};
+
+impl TokenRequestBuilder {
+    /// Build an AsyncTokenRequest from the given parameters
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async(self) -> Result<AsyncTokenRequest> {
+        let client = build_client!(reqwest::Client::builder(), self.headers(), self);
+        Ok(AsyncTokenRequest {
+            endpoint_url: self.endpoint_url()?,
+            retry: self.retry,
+            api_key: self.into_api_key()?,
+            client,
+        })
+    }
+}
+
+/// For making async requests against the `/v1/token` APIs.
+///
+/// The async equivalent of [`TokenRequest`](super::TokenRequest), requires a `tokio` runtime.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug)]
+pub struct AsyncTokenRequest {
+    api_key: String,
+    retry: RetryPolicy,
+    #[doc(hidden)]
+    client: reqwest::Client,
+    #[doc(hidden)]
+    endpoint_url: Url,
+}
+
+impl AsyncTokenRequest {
+    /// Create a new request builder
+    pub fn builder() -> TokenRequestBuilder { TokenRequestBuilder::new() }
+
+    /// Returns a short lived JWT that can be used to authenticate to other API endpoints
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use seaplane::api::identity::v1::AsyncTokenRequest;
+    /// # async fn example() {
+    /// let req = AsyncTokenRequest::builder()
+    ///     .api_key("abc123")
+    ///     .build_async()
+    ///     .unwrap();
+    ///
+    /// let resp = req.access_token().await.unwrap();
+    /// dbg!(resp);
+    /// # }
+    /// ```
+    pub async fn access_token(&self) -> Result<String> {
+        let req = self
+            .client
+            .post(self.endpoint_url.clone())
+            .bearer_auth(&self.api_key);
+        self.send(req).await?.text().await.map_err(Into::into)
+    }
+
+    /// Returns a JSON response of an `AccessToken` which contains the short lived JWT used to
+    /// authenticate to other public API endpoints, along with addition fields for tenant ID and
+    /// subdomain
+    pub async fn access_token_json(&self) -> Result<AccessToken> {
+        let req = self
+            .client
+            .post(self.endpoint_url.clone())
+            .bearer_auth(&self.api_key)
+            .header(ACCEPT, HeaderValue::from_static("application/json"));
+        self.send(req)
+            .await?
+            .json::<AccessToken>()
+            .await
+            .map_err(Into::into)
+    }
+
+    // Sends the request, retrying transient failures and mapping any error responses
+    async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        send_with_retry_async(&self.retry, true, req, |req| async { Ok(req.send().await?) }).await
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+
+use super::{AccessToken, TokenRequestBuilder};
+use crate::error::{Result, SeaplaneError};
+
+/// How long before the JWT expires that a new access token will be requested
+const REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+/// Provides access tokens to the request builders in place of a fixed token.
+///
+/// The `AccessToken` is requested via [`TokenRequest::access_token_json`] the first time it's
+/// needed, and then cached and shared by all clones of the provider until shortly before the JWT
+/// expires. Requests built with a `TokenProvider` which are rejected with HTTP 401 UNAUTHORIZED
+/// are retried once with a freshly requested token.
+///
+/// [`TokenRequest::access_token_json`]: super::TokenRequest::access_token_json
+///
+/// # Examples
+///
+/// ```no_run
+/// use seaplane::api::{
+///     identity::v1::{TokenProvider, TokenRequest},
+///     metadata::v1::MetadataRequest,
+/// };
+///
+/// let provider = TokenProvider::new(TokenRequest::builder().api_key("abc123")).unwrap();
+///
+/// let req = MetadataRequest::builder()
+///     .token_provider(provider.clone())
+///     .encoded_key("bW9ieQo")
+///     .build()
+///     .unwrap();
+///
+/// let resp = req.get_value().unwrap();
+/// dbg!(resp);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenProvider {
+    inner: Arc<ProviderInner>,
+}
+
+#[derive(Debug)]
+struct ProviderInner {
+    builder: TokenRequestBuilder,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedToken {
+    access_token: AccessToken,
+    // `None` if the expiration could not be read from the JWT, in which case the token is used
+    // until it is rejected
+    expires_at: Option<SystemTime>,
+}
+
+impl CachedToken {
+    fn new(access_token: AccessToken) -> Self {
+        Self { expires_at: jwt_expiration(&access_token.token), access_token }
+    }
+
+    fn is_fresh(&self) -> bool {
+        self.expires_at
+            .map_or(true, |exp| SystemTime::now() + REFRESH_MARGIN < exp)
+    }
+}
+
+impl TokenProvider {
+    /// Create a new provider which requests access tokens using the given builder
+    ///
+    /// **NOTE:** The builder must have an API key set
+    pub fn new(builder: TokenRequestBuilder) -> Result<Self> {
+        if builder.api_key.is_none() {
+            return Err(SeaplaneError::MissingRequestApiKey);
+        }
+        Ok(Self { inner: Arc::new(ProviderInner { builder, cached: Mutex::new(None) }) })
+    }
+
+    /// Returns the cached `AccessToken`, requesting a new one if there is none or it's about to
+    /// expire
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+    pub fn access_token(&self) -> Result<AccessToken> {
+        if let Some(cached) = self.fresh() {
+            return Ok(cached);
+        }
+        let access_token = self.inner.builder.clone().build()?.access_token_json()?;
+        Ok(self.store(access_token))
+    }
+
+    /// Returns the cached JWT, requesting a new one if there is none or it's about to expire
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+    pub fn token(&self) -> Result<String> { self.access_token().map(|t| t.token) }
+
+    /// Returns the cached `AccessToken`, requesting a new one if there is none or it's about to
+    /// expire
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub async fn access_token_async(&self) -> Result<AccessToken> {
+        if let Some(cached) = self.fresh() {
+            return Ok(cached);
+        }
+        let access_token = self
+            .inner
+            .builder
+            .clone()
+            .build_async()?
+            .access_token_json()
+            .await?;
+        Ok(self.store(access_token))
+    }
+
+    /// Returns the cached JWT, requesting a new one if there is none or it's about to expire
+    #[cfg(feature = "async")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub async fn token_async(&self) -> Result<String> {
+        self.access_token_async().await.map(|t| t.token)
+    }
+
+    /// Drops the cached access token so that the next request will use a new one
+    pub fn invalidate(&self) { *self.inner.cached.lock().unwrap() = None; }
+
+    /// Drops the cached access token only if it is still `token`, so that when several requests
+    /// are rejected at once only the first causes the token to be requested again
+    #[cfg(any(
+        feature = "compute_api_v2",
+        feature = "locks_api_v1",
+        feature = "metadata_api_v1",
+        feature = "restrict_api_v1",
+    ))]
+    pub(crate) fn invalidate_token(&self, token: &str) {
+        let mut cached = self.inner.cached.lock().unwrap();
+        if matches!(&*cached, Some(c) if c.access_token.token == token) {
+            *cached = None;
+        }
+    }
+
+    fn fresh(&self) -> Option<AccessToken> {
+        self.inner
+            .cached
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|c| c.is_fresh())
+            .map(|c| c.access_token.clone())
+    }
+
+    fn store(&self, access_token: AccessToken) -> AccessToken {
+        *self.inner.cached.lock().unwrap() = Some(CachedToken::new(access_token.clone()));
+        access_token
+    }
+}
+
+// Reads the `exp` claim from the JWT payload without verifying the signature, since the token is
+// only being inspected to know when to refresh it
+fn jwt_expiration(jwt: &str) -> Option<SystemTime> {
+    #[derive(Deserialize)]
+    struct Claims {
+        exp: u64,
+    }
+
+    let payload = jwt.split('.').nth(1)?;
+    let decoded = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Claims = serde_json::from_slice(&decoded).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(claims.exp))
+}
+
+#[cfg(test)]
+mod token_provider_tests {
+    use super::*;
+
+    fn access_token(jwt: &str) -> AccessToken {
+        AccessToken {
+            token: jwt.into(),
+            tenant: "tnt-abcdef1234567890".into(),
+            subdomain: "pequod".into(),
+        }
+    }
+
+    #[test]
+    fn jwt_exp() {
+        // {"exp":4102444800}
+        assert_eq!(
+            jwt_expiration("abc.eyJleHAiOjQxMDI0NDQ4MDB9.def"),
+            Some(UNIX_EPOCH + Duration::from_secs(4102444800))
+        );
+        // padded
+        assert_eq!(
+            jwt_expiration("abc.eyJleHAiOjF9.def"),
+            jwt_expiration("abc.eyJleHAiOjF9==.def")
+        );
+        assert_eq!(jwt_expiration("abc.123.def"), None);
+        assert_eq!(jwt_expiration("abc"), None);
+    }
+
+    #[test]
+    fn freshness() {
+        assert!(CachedToken::new(access_token("abc.eyJleHAiOjQxMDI0NDQ4MDB9.def")).is_fresh());
+        // {"exp":1}
+        assert!(!CachedToken::new(access_token("abc.eyJleHAiOjF9.def")).is_fresh());
+        // Unknown expiration is used until rejected
+        assert!(CachedToken::new(access_token("abc.123.def")).is_fresh());
+    }
+
+    #[test]
+    fn requires_api_key() {
+        assert_eq!(
+            TokenProvider::new(TokenRequestBuilder::new()).unwrap_err(),
+            SeaplaneError::MissingRequestApiKey
+        );
+    }
+}
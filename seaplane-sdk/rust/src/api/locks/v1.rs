@@ -1,16 +1,27 @@
 //! The `/locks` endpoint APIs which allows working with [`HeldLock`]s
+#[cfg(feature = "async")]
+mod asynchronous;
 mod models;
 
 use reqwest::Url;
 use serde::Deserialize;
 
+#[cfg(feature = "async")]
+pub use self::asynchronous::*;
 pub use self::models::*;
+#[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+use crate::api::identity::v1::TokenProvider;
+#[cfg(feature = "blocking")]
+use crate::api::shared::v1::{Entries, Pages};
+#[cfg(feature = "blocking")]
+use crate::api::ApiRequest;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::api::SeaplaneClient;
 use crate::{
     api::{
         locks::{error::LocksError, LOCKS_API_URL},
-        map_api_error,
         shared::v1::RangeQueryContext,
-        ApiRequest, RequestBuilder,
+        Request, RequestBuilder, RetryPolicy,
     },
     base64::add_base64_path_segment,
     error::Result,
@@ -26,6 +37,8 @@ pub struct LocksRequestBuilder {
 }
 
 /// For making requests against the `/locks` APIs.
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
 #[derive(Debug)]
 pub struct LocksRequest {
     request: ApiRequest<RequestTarget>,
@@ -43,8 +56,21 @@ impl LocksRequestBuilder {
     pub fn new() -> Self { RequestBuilder::new(LOCKS_API_URL, LOCKS_API_BASE_PATH).into() }
 
     /// Build a LocksRequest from the given parameters
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
     pub fn build(self) -> Result<LocksRequest> { Ok(self.builder.build()?.into()) }
 
+    /// Set how requests which fail due to transient errors are retried (default:
+    /// [`RetryPolicy::default`])
+    #[must_use]
+    pub fn retry(self, policy: RetryPolicy) -> Self { self.builder.retry(policy).into() }
+
+    // Shares the connection pool, credentials and configuration of the client
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn with_client(self, client: &SeaplaneClient, url: Option<&Url>) -> Self {
+        self.builder.with_client(client, url).into()
+    }
+
     /// Allow non-HTTPS endpoints for this request (default: `false`)
     #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))))]
@@ -63,6 +89,16 @@ impl LocksRequestBuilder {
     #[must_use]
     pub fn token<U: Into<String>>(self, token: U) -> Self { self.builder.token(token).into() }
 
+    /// Set the provider of tokens used in Bearer Authorization, in place of a fixed token
+    ///
+    /// The access token is cached and refreshed as needed, and requests rejected with HTTP 401
+    /// UNAUTHORIZED are retried once with a new token.
+    #[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+    #[must_use]
+    pub fn token_provider(self, provider: TokenProvider) -> Self {
+        self.builder.token_provider(provider).into()
+    }
+
     // Used in testing and development to manually set the URL
     #[doc(hidden)]
     pub fn base_url<U: AsRef<str>>(self, url: U) -> Self { self.builder.base_url(url).into() }
@@ -105,76 +141,16 @@ impl LocksRequestBuilder {
     }
 }
 
+#[cfg(feature = "blocking")]
 impl From<ApiRequest<RequestTarget>> for LocksRequest {
     fn from(request: ApiRequest<RequestTarget>) -> Self { Self { request } }
 }
 
+#[cfg(feature = "blocking")]
 impl LocksRequest {
     /// Create a new request builder
     pub fn builder() -> LocksRequestBuilder { LocksRequestBuilder::new() }
 
-    // Internal method creating the URL for all single lock endpoints
-    fn single_lock_url(&self) -> Result<Url> {
-        match &self.request.target {
-            None | Some(RequestTarget::HeldLock(_) | RequestTarget::Range(_)) => {
-                Err(LocksError::IncorrectLocksRequestTarget)?
-            }
-            Some(RequestTarget::SingleLock(l)) => {
-                Ok(add_base64_path_segment(self.request.endpoint_url.clone(), l.encoded()))
-            }
-        }
-    }
-
-    // Internal method for creating the URL for held lock endpoints
-    fn held_lock_url(&self) -> Result<Url> {
-        match &self.request.target {
-            None | Some(RequestTarget::SingleLock(_) | RequestTarget::Range(_)) => {
-                Err(LocksError::IncorrectLocksRequestTarget)?
-            }
-
-            Some(RequestTarget::HeldLock(HeldLock { name, id, .. })) => {
-                let mut url =
-                    add_base64_path_segment(self.request.endpoint_url.clone(), name.encoded());
-                url.set_query(Some(&format!("id={}", id.encoded())));
-                Ok(url)
-            }
-        }
-    }
-
-    // Internal method for creating the URL for range endpoints
-    fn range_url(&self) -> Result<Url> {
-        match &self.request.target {
-            None | Some(RequestTarget::SingleLock(_) | RequestTarget::HeldLock(_)) => {
-                Err(LocksError::IncorrectLocksRequestTarget)?
-            }
-            Some(RequestTarget::Range(context)) => {
-                let mut url = self.request.endpoint_url.clone();
-
-                if let Some(encoded_dir) = context.directory() {
-                    url = add_base64_path_segment(url, encoded_dir.encoded());
-                    // A directory is distinguished from a key by the trailing slash
-                    url.set_path(&format!("{}/", url.path()));
-                }
-
-                if let Some(from) = context.from() {
-                    url.set_query(Some(&format!("from=base64:{}", from.encoded())));
-                }
-
-                Ok(url)
-            }
-        }
-    }
-
-    // Internal method for getting the lock name
-    fn lock_name(&self) -> Result<LockName> {
-        match &self.request.target {
-            None | Some(RequestTarget::HeldLock(_) | RequestTarget::Range(_)) => {
-                Err(LocksError::IncorrectLocksRequestTarget)?
-            }
-            Some(RequestTarget::SingleLock(l)) => Ok(l.clone()),
-        }
-    }
-
     /// Attempts to acquire the lock with the given lock name with the given TTL.
     /// Client-ID should identify the client making the request for debugging purposes.
     ///
@@ -194,23 +170,10 @@ impl LocksRequest {
     /// dbg!(resp);
     /// ```
     pub fn acquire(&self, ttl: u32, client_id: &str) -> Result<HeldLock> {
-        let mut url = self.single_lock_url()?;
-        url.set_query(Some(&format!("ttl={ttl}&client-id={client_id}")));
-        let resp = self
-            .request
-            .client
-            .post(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        #[derive(Deserialize)]
-        struct AcquireResponse {
-            id: LockId,
-            sequencer: u32,
-        }
-
-        let name = self.lock_name()?;
-        map_api_error(resp)?
+        let url = acquire_url(&self.request, ttl, client_id)?;
+        let name = lock_name(&self.request)?;
+        self.request
+            .send(self.request.client.post(url))?
             .json::<AcquireResponse>()
             .map(|AcquireResponse { id, sequencer }| HeldLock { name, id, sequencer })
             .map_err(Into::into)
@@ -243,16 +206,9 @@ impl LocksRequest {
     /// dbg!(resp)
     /// ```
     pub fn release(&self) -> Result<()> {
-        let url = self.held_lock_url()?;
-
-        let resp = self
-            .request
-            .client
-            .delete(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        map_api_error(resp)?
+        let url = held_lock_url(&self.request)?;
+        self.request
+            .send(self.request.client.delete(url))?
             .text()
             .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
             .map_err(Into::into)
@@ -284,18 +240,9 @@ impl LocksRequest {
     /// dbg!(resp)
     /// ```
     pub fn renew(&self, ttl: u32) -> Result<()> {
-        let mut url = self.held_lock_url()?;
-
-        url.query_pairs_mut().append_pair("ttl", &ttl.to_string());
-
-        let resp = self
-            .request
-            .client
-            .patch(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        map_api_error(resp)?
+        let url = renew_url(&self.request, ttl)?;
+        self.request
+            .send(self.request.client.patch(url))?
             .text()
             .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
             .map_err(Into::into)
@@ -319,16 +266,11 @@ impl LocksRequest {
     /// dbg!(resp);
     /// ```
     pub fn get_lock_info(&self) -> Result<LockInfo> {
-        let url = self.single_lock_url()?;
-
-        let resp = self
-            .request
-            .client
-            .get(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-
-        map_api_error(resp)?.json::<LockInfo>().map_err(Into::into)
+        let url = single_lock_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))?
+            .json::<LockInfo>()
+            .map_err(Into::into)
     }
 
     /// Returns a single page of lock information for the given directory, beginning with the `from`
@@ -373,24 +315,54 @@ impl LocksRequest {
     /// }
     /// ```
     pub fn get_page(&self) -> Result<LockInfoRange> {
-        match &self.request.target {
-            None | Some(RequestTarget::SingleLock(_) | RequestTarget::HeldLock(_)) => {
-                Err(LocksError::IncorrectLocksRequestTarget)?
-            }
-            Some(RequestTarget::Range(_)) => {
-                let url = self.range_url()?;
-
-                let resp = self
-                    .request
-                    .client
-                    .get(url)
-                    .bearer_auth(&self.request.token)
-                    .send()?;
-                map_api_error(resp)?
-                    .json::<LockInfoRange>()
-                    .map_err(Into::into)
-            }
-        }
+        let url = range_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))?
+            .json::<LockInfoRange>()
+            .map_err(Into::into)
+    }
+
+    /// Returns an iterator over the pages of held locks for the given directory, from the `from`
+    /// key onwards. Each page is only requested once the previous one has been consumed.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub fn pages(&mut self) -> Pages<'_, LockInfoRange> {
+        Pages::new(move || {
+            let page = self.get_page()?;
+            let more = match &page.next {
+                Some(next_key) => {
+                    set_next_key(&mut self.request, next_key.clone())?;
+                    true
+                }
+                None => false,
+            };
+            Ok((page, more))
+        })
+    }
+
+    /// Returns an iterator over the individual held locks for the given directory, from the `from`
+    /// key onwards. Each page is only requested once the entries of the previous one have been
+    /// consumed, so iteration may stop early without requesting the remaining pages.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::{locks::v1::LocksRequestBuilder, shared::v1::RangeQueryContext};
+    ///
+    /// let mut req = LocksRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .range(RangeQueryContext::new())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// for lock in req.entries() {
+    ///     let lock = lock.unwrap();
+    ///     dbg!(lock.name);
+    /// }
+    /// ```
+    pub fn entries(&mut self) -> Entries<'_, LockInfo> {
+        Entries::new(self.pages(), |page| page.locks)
     }
 
     /// Returns all held lock information for the given directory, from the `from` key onwards. May
@@ -418,24 +390,99 @@ impl LocksRequest {
     /// let resp = req.get_all_pages().unwrap();
     /// dbg!(resp);
     /// ```
-    // TODO: Replace this with a collect on a Pages/Entries iterator
-    pub fn get_all_pages(&mut self) -> Result<Vec<LockInfo>> {
-        let mut pages = Vec::new();
-        loop {
-            let mut lir = self.get_page()?;
-            pages.append(&mut lir.locks);
-            if let Some(next_key) = lir.next {
-                // TODO: Regrettable duplication here suggests that there should be a
-                // ConfigKeyRequest and a ConfigRangeRequest
-                if let Some(RequestTarget::Range(ref mut context)) = self.request.target {
-                    context.set_from(next_key);
-                } else {
-                    Err(LocksError::IncorrectLocksRequestTarget)?
-                }
-            } else {
-                break;
+    pub fn get_all_pages(&mut self) -> Result<Vec<LockInfo>> { self.entries().collect() }
+}
+
+// The response body of a successful acquire
+#[derive(Deserialize)]
+struct AcquireResponse {
+    id: LockId,
+    sequencer: u32,
+}
+
+// Internal function creating the URL for all single lock endpoints
+fn single_lock_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        None | Some(RequestTarget::HeldLock(_) | RequestTarget::Range(_)) => {
+            Err(LocksError::IncorrectLocksRequestTarget)?
+        }
+        Some(RequestTarget::SingleLock(l)) => {
+            Ok(add_base64_path_segment(request.endpoint_url.clone(), l.encoded()))
+        }
+    }
+}
+
+// Internal function creating the URL for acquiring a lock
+fn acquire_url<C>(request: &Request<RequestTarget, C>, ttl: u32, client_id: &str) -> Result<Url> {
+    let mut url = single_lock_url(request)?;
+    url.set_query(Some(&format!("ttl={ttl}&client-id={client_id}")));
+    Ok(url)
+}
+
+// Internal function for creating the URL for held lock endpoints
+fn held_lock_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        None | Some(RequestTarget::SingleLock(_) | RequestTarget::Range(_)) => {
+            Err(LocksError::IncorrectLocksRequestTarget)?
+        }
+
+        Some(RequestTarget::HeldLock(HeldLock { name, id, .. })) => {
+            let mut url = add_base64_path_segment(request.endpoint_url.clone(), name.encoded());
+            url.set_query(Some(&format!("id={}", id.encoded())));
+            Ok(url)
+        }
+    }
+}
+
+// Internal function creating the URL for renewing a held lock
+fn renew_url<C>(request: &Request<RequestTarget, C>, ttl: u32) -> Result<Url> {
+    let mut url = held_lock_url(request)?;
+    url.query_pairs_mut().append_pair("ttl", &ttl.to_string());
+    Ok(url)
+}
+
+// Internal function for creating the URL for range endpoints
+fn range_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        None | Some(RequestTarget::SingleLock(_) | RequestTarget::HeldLock(_)) => {
+            Err(LocksError::IncorrectLocksRequestTarget)?
+        }
+        Some(RequestTarget::Range(context)) => {
+            let mut url = request.endpoint_url.clone();
+
+            if let Some(encoded_dir) = context.directory() {
+                url = add_base64_path_segment(url, encoded_dir.encoded());
+                // A directory is distinguished from a key by the trailing slash
+                url.set_path(&format!("{}/", url.path()));
+            }
+
+            if let Some(from) = context.from() {
+                url.set_query(Some(&format!("from=base64:{}", from.encoded())));
             }
+
+            Ok(url)
+        }
+    }
+}
+
+// Internal function for getting the lock name
+fn lock_name<C>(request: &Request<RequestTarget, C>) -> Result<LockName> {
+    match &request.target {
+        None | Some(RequestTarget::HeldLock(_) | RequestTarget::Range(_)) => {
+            Err(LocksError::IncorrectLocksRequestTarget)?
         }
-        Ok(pages)
+        Some(RequestTarget::SingleLock(l)) => Ok(l.clone()),
+    }
+}
+
+// Internal function moving a range request on to the page beginning at `next_key`
+fn set_next_key<C>(request: &mut Request<RequestTarget, C>, next_key: LockName) -> Result<()> {
+    // TODO: Regrettable duplication here suggests that there should be a
+    // ConfigKeyRequest and a ConfigRangeRequest
+    if let Some(RequestTarget::Range(ref mut context)) = request.target {
+        context.set_from(next_key);
+        Ok(())
+    } else {
+        Err(LocksError::IncorrectLocksRequestTarget)?
     }
 }
@@ -0,0 +1,140 @@
+use super::{
+    acquire_url, held_lock_url, lock_name, range_url, renew_url, set_next_key, single_lock_url,
+    AcquireResponse, LocksRequestBuilder,
+};
+use crate::{
+    api::{
+        locks::v1::{HeldLock, LockInfo, LockInfoRange, RequestTarget},
+        AsyncApiRequest,
+    },
+    error::Result,
+};
+
+impl LocksRequestBuilder {
+    /// Build an AsyncLocksRequest from the given parameters
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async(self) -> Result<AsyncLocksRequest> { Ok(self.builder.build_async()?.into()) }
+}
+
+/// For making async requests against the `/locks` APIs.
+///
+/// The async equivalent of [`LocksRequest`](super::LocksRequest), requires a `tokio` runtime.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug)]
+pub struct AsyncLocksRequest {
+    request: AsyncApiRequest<RequestTarget>,
+}
+
+impl From<AsyncApiRequest<RequestTarget>> for AsyncLocksRequest {
+    fn from(request: AsyncApiRequest<RequestTarget>) -> Self { Self { request } }
+}
+
+impl AsyncLocksRequest {
+    /// Create a new request builder
+    pub fn builder() -> LocksRequestBuilder { LocksRequestBuilder::new() }
+
+    /// Attempts to acquire the lock with the given lock name with the given TTL.
+    /// Client-ID should identify the client making the request for debugging purposes.
+    ///
+    /// **NOTE:** This endpoints requires the `RequestTarget` be a `SingleLock`
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::locks::v1::LocksRequestBuilder;
+    ///
+    /// # async fn example() {
+    /// let req = LocksRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .encoded_lock_name("bW9ieQo")
+    ///     .build_async()
+    ///     .unwrap();
+    ///
+    /// let resp = req.acquire(15, "test-client").await.unwrap();
+    /// dbg!(resp);
+    /// # }
+    /// ```
+    pub async fn acquire(&self, ttl: u32, client_id: &str) -> Result<HeldLock> {
+        let url = acquire_url(&self.request, ttl, client_id)?;
+        let name = lock_name(&self.request)?;
+        self.request
+            .send(self.request.client.post(url))
+            .await?
+            .json::<AcquireResponse>()
+            .await
+            .map(|AcquireResponse { id, sequencer }| HeldLock { name, id, sequencer })
+            .map_err(Into::into)
+    }
+
+    /// Attempts to release the given lock.
+    ///
+    /// **NOTE:** This endpoints requires the `RequestTarget` be a `HeldLock`
+    pub async fn release(&self) -> Result<()> {
+        let url = held_lock_url(&self.request)?;
+        self.request
+            .send(self.request.client.delete(url))
+            .await?
+            .text()
+            .await
+            .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
+            .map_err(Into::into)
+    }
+
+    /// Attempts to renew the given lock, setting the TTL to the given `ttl`
+    ///
+    /// **NOTE:** This endpoints requires the `RequestTarget` be a `HeldLock`
+    pub async fn renew(&self, ttl: u32) -> Result<()> {
+        let url = renew_url(&self.request, ttl)?;
+        self.request
+            .send(self.request.client.patch(url))
+            .await?
+            .text()
+            .await
+            .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
+            .map_err(Into::into)
+    }
+
+    /// Gets information about a single lock.
+    ///
+    /// **NOTE:** This endpoints requires the `RequestTarget` be a `SingleLock`
+    pub async fn get_lock_info(&self) -> Result<LockInfo> {
+        let url = single_lock_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<LockInfo>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns a single page of lock information for the given directory, beginning with the `from`
+    /// key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub async fn get_page(&self) -> Result<LockInfoRange> {
+        let url = range_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<LockInfoRange>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns all held lock information for the given directory, from the `from` key onwards. May
+    /// perform multiple requests.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub async fn get_all_pages(&mut self) -> Result<Vec<LockInfo>> {
+        let mut pages = Vec::new();
+        loop {
+            let mut lir = self.get_page().await?;
+            pages.append(&mut lir.locks);
+            if let Some(next_key) = lir.next {
+                set_next_key(&mut self.request, next_key)?;
+            } else {
+                break;
+            }
+        }
+        Ok(pages)
+    }
+}
@@ -7,4 +7,8 @@ pub enum MetadataError {
     MissingMetadataKey,
     #[error("request must target either key or range")]
     IncorrectMetadataRequestTarget,
+    #[error("the key was changed since it was read, or its existence didn't match the request")]
+    VersionConflict,
+    #[error("the key value pair has no version to compare against")]
+    MissingVersion,
 }
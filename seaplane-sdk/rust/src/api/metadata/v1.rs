@@ -1,18 +1,39 @@
 //! The `/config` endpoint APIs which allows working with [`KeyValue`]s
+#[cfg(feature = "async")]
+mod asynchronous;
 mod models;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod watch;
 
-use reqwest::{
-    header::{self, CONTENT_TYPE},
-    Url,
-};
+#[cfg(feature = "blocking")]
+use std::thread;
+
+#[cfg(feature = "blocking")]
+use reqwest::header::{self, CONTENT_TYPE};
+#[cfg(any(feature = "blocking", feature = "async"))]
+use reqwest::header::{HeaderName, HeaderValue, IF_MATCH, IF_NONE_MATCH};
+use reqwest::Url;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use serde::{de::DeserializeOwned, Serialize};
 
+#[cfg(feature = "async")]
+pub use self::asynchronous::*;
 pub use self::models::*;
+#[cfg(feature = "blocking")]
+pub use self::watch::*;
+#[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+use crate::api::identity::v1::TokenProvider;
+#[cfg(feature = "blocking")]
+use crate::api::shared::v1::{Entries, Pages};
+#[cfg(feature = "blocking")]
+use crate::api::ApiRequest;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::{api::SeaplaneClient, error::SeaplaneError};
 use crate::{
     api::{
-        map_api_error,
         metadata::{error::MetadataError, METADATA_API_URL},
         shared::v1::RangeQueryContext,
-        ApiRequest, RequestBuilder,
+        Request, RequestBuilder, RetryPolicy,
     },
     base64::add_base64_path_segment,
     error::Result,
@@ -20,6 +41,16 @@ use crate::{
 
 const METADATA_API_BASE_PATH: &str = "v1/config/";
 
+/// The most times [`MetadataRequest::update_value`] reads and writes a key which keeps being
+/// changed by others before giving up
+pub const MAX_UPDATE_ATTEMPTS: usize = 10;
+
+/// The most keys [`MetadataRequest::delete_keys`] deletes at the same time
+pub const DELETE_BATCH_SIZE: usize = 16;
+
+/// The most checks in a row which may fail before [`MetadataWatcher::watch`] gives up
+pub const MAX_WATCH_FAILURES: usize = 3;
+
 /// A builder struct for creating a [`MetadataRequest`] which will then be used for making a
 /// request against the `/config` APIs
 #[derive(Debug)]
@@ -40,6 +71,8 @@ impl MetadataRequestBuilder {
     pub fn new() -> Self { RequestBuilder::new(METADATA_API_URL, METADATA_API_BASE_PATH).into() }
 
     /// Build an MetadataRequest from the given parameters
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
     pub fn build(self) -> Result<MetadataRequest> { Ok(self.builder.build()?.into()) }
 
     /// Set the token used in Bearer Authorization
@@ -48,6 +81,27 @@ impl MetadataRequestBuilder {
     #[must_use]
     pub fn token<U: Into<String>>(self, token: U) -> Self { self.builder.token(token).into() }
 
+    /// Set the provider of tokens used in Bearer Authorization, in place of a fixed token
+    ///
+    /// The access token is cached and refreshed as needed, and requests rejected with HTTP 401
+    /// UNAUTHORIZED are retried once with a new token.
+    #[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+    #[must_use]
+    pub fn token_provider(self, provider: TokenProvider) -> Self {
+        self.builder.token_provider(provider).into()
+    }
+
+    /// Set how requests which fail due to transient errors are retried (default:
+    /// [`RetryPolicy::default`])
+    #[must_use]
+    pub fn retry(self, policy: RetryPolicy) -> Self { self.builder.retry(policy).into() }
+
+    // Shares the connection pool, credentials and configuration of the client
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn with_client(self, client: &SeaplaneClient, url: Option<&Url>) -> Self {
+        self.builder.with_client(client, url).into()
+    }
+
     /// Allow non-HTTPS endpoints for this request (default: `false`)
     #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))))]
@@ -84,55 +138,23 @@ impl MetadataRequestBuilder {
 }
 
 /// For making requests against the `/config` APIs.
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
 #[derive(Debug)]
 pub struct MetadataRequest {
     request: ApiRequest<RequestTarget>,
 }
 
+#[cfg(feature = "blocking")]
 impl From<ApiRequest<RequestTarget>> for MetadataRequest {
     fn from(request: ApiRequest<RequestTarget>) -> Self { Self { request } }
 }
 
+#[cfg(feature = "blocking")]
 impl MetadataRequest {
     /// Create a new request builder
     pub fn builder() -> MetadataRequestBuilder { MetadataRequestBuilder::new() }
 
-    // Internal method creating the URL for all single key endpoints
-    fn single_key_url(&self) -> Result<Url> {
-        match &self.request.target {
-            None | Some(RequestTarget::Range(_)) => {
-                Err(MetadataError::IncorrectMetadataRequestTarget)?
-            }
-            Some(RequestTarget::Key(k)) => {
-                Ok(add_base64_path_segment(self.request.endpoint_url.clone(), k.encoded()))
-            }
-        }
-    }
-
-    // Internal method creating the URL for range endpoints
-    fn range_url(&self) -> Result<Url> {
-        match &self.request.target {
-            None | Some(RequestTarget::Key(_)) => {
-                Err(MetadataError::IncorrectMetadataRequestTarget)?
-            }
-            Some(RequestTarget::Range(context)) => {
-                let mut url = self.request.endpoint_url.clone();
-
-                if let Some(encoded_dir) = context.directory() {
-                    url = add_base64_path_segment(url, encoded_dir.encoded());
-                    // A directory is distinguished from a key by the trailing slash
-                    url.set_path(&format!("{}/", url.path()));
-                }
-
-                if let Some(from) = context.from() {
-                    url.set_query(Some(&format!("from=base64:{}", from.encoded())));
-                }
-
-                Ok(url)
-            }
-        }
-    }
-
     /// Returns the key value pair associated with the set key.
     ///
     /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
@@ -151,17 +173,22 @@ impl MetadataRequest {
     /// let resp = req.get_value().unwrap();
     /// dbg!(resp);
     /// ```
-    pub fn get_value(&self) -> Result<Value> {
-        let url = self.single_key_url()?;
-        let resp = self
-            .request
-            .client
-            .get(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-        map_api_error(resp)?
+    pub fn get_value(&self) -> Result<Value> { self.get_key_value().map(KeyValue::into_value) }
+
+    /// Returns the key value pair associated with the set key, including the version of the
+    /// value which can be given to [`MetadataRequest::put_value_if`] or
+    /// [`MetadataRequest::delete_value_if`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub fn get_key_value(&self) -> Result<KeyValue> {
+        self.get_key_value_at(single_key_url(&self.request)?)
+    }
+
+    // Returns the key value pair at `url`
+    fn get_key_value_at(&self, url: Url) -> Result<KeyValue> {
+        self.request
+            .send(self.request.client.get(url))?
             .json::<KeyValue>()
-            .map(|kv| kv.value)
             .map_err(Into::into)
     }
 
@@ -204,17 +231,112 @@ impl MetadataRequest {
     /// let resp = req.put_value(Value::from_encoded("YWhhYgo")).unwrap();
     /// dbg!(resp);
     /// ```
-    pub fn put_value(&self, value: Value) -> Result<()> {
-        let url = self.single_key_url()?;
-        let resp = self
+    pub fn put_value(&self, value: Value) -> Result<()> { self.put(value, None) }
+
+    /// Adds a base64 encoded value to the store at the given key, only if the value there is
+    /// still at `version`, such as when it was read with [`MetadataRequest::get_key_value`].
+    ///
+    /// Fails with [`MetadataError::VersionConflict`] if the key was changed or deleted since.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::metadata::v1::{MetadataRequest, MetadataRequestBuilder, Value};
+    ///
+    /// let req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .encoded_key("bW9ieQo")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let kv = req.get_key_value().unwrap();
+    /// let resp = req.put_value_if(Value::from_encoded("YWhhYgo"), kv.version.unwrap());
+    /// dbg!(resp);
+    /// ```
+    pub fn put_value_if(&self, value: Value, version: u64) -> Result<()> {
+        self.put(value, Some(Precondition::Version(version)))
+    }
+
+    /// Adds a base64 encoded value to the store at the given key, only if the key doesn't exist
+    /// yet.
+    ///
+    /// Fails with [`MetadataError::VersionConflict`] if the key already exists.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub fn put_value_if_absent(&self, value: Value) -> Result<()> {
+        self.put(value, Some(Precondition::Absent))
+    }
+
+    /// Replaces the value at the given key with the one returned by `f`, which is given the
+    /// current value, or `None` when the key doesn't exist. Returns the value which was written.
+    ///
+    /// The write only succeeds if the key wasn't changed since it was read, otherwise the new
+    /// value is read and `f` is called again, up to [`MAX_UPDATE_ATTEMPTS`] times before failing
+    /// with [`MetadataError::VersionConflict`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::metadata::v1::{MetadataRequest, MetadataRequestBuilder, Value};
+    ///
+    /// let req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .encoded_key("Y291bnRlcg")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let resp = req.update_value(|current| {
+    ///     let count: u64 = current
+    ///         .and_then(|v| String::from_utf8(v.decode()).ok()?.parse().ok())
+    ///         .unwrap_or(0);
+    ///     Value::from_unencoded((count + 1).to_string())
+    /// });
+    /// dbg!(resp);
+    /// ```
+    pub fn update_value<F>(&self, mut f: F) -> Result<Value>
+    where
+        F: FnMut(Option<&Value>) -> Value,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let current = existing(self.get_key_value())?;
+            let value = f(current.as_ref().map(|kv| &kv.value));
+            let res = match current {
+                Some(kv) => self
+                    .put_value_if(value.clone(), kv.version.ok_or(MetadataError::MissingVersion)?),
+                None => self.put_value_if_absent(value.clone()),
+            };
+            match res {
+                Err(SeaplaneError::MetadataRequest(MetadataError::VersionConflict))
+                    if attempts < MAX_UPDATE_ATTEMPTS => {}
+                res => return res.map(|_| value),
+            }
+        }
+    }
+
+    // Sends the value, only if the precondition holds when one is given
+    fn put(&self, value: Value, precondition: Option<Precondition>) -> Result<()> {
+        self.put_at(single_key_url(&self.request)?, value, precondition)
+    }
+
+    // Sends the value to `url`, only if the precondition holds when one is given
+    fn put_at(&self, url: Url, value: Value, precondition: Option<Precondition>) -> Result<()> {
+        let mut req = self
             .request
             .client
             .put(url)
-            .bearer_auth(&self.request.token)
             .header(CONTENT_TYPE, header::HeaderValue::from_static("application/octet-stream"))
-            .body(value.to_string())
-            .send()?;
-        map_api_error(resp)?
+            .body(value.to_string());
+        if let Some(precondition) = precondition {
+            let (name, value) = precondition.header();
+            req = req.header(name, value);
+        }
+        self.request
+            .send(req)
+            .map_err(map_conflict)?
             .text()
             .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
             .map_err(Into::into)
@@ -237,20 +359,177 @@ impl MetadataRequest {
     /// let resp = req.delete_value().unwrap();
     /// dbg!(resp);
     /// ```
-    pub fn delete_value(&self) -> Result<()> {
-        let url = self.single_key_url()?;
-        let resp = self
-            .request
-            .client
-            .delete(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-        map_api_error(resp)?
+    pub fn delete_value(&self) -> Result<()> { self.delete(None) }
+
+    /// Deletes the key value pair at the given key, only if the value there is still at
+    /// `version`, such as when it was read with [`MetadataRequest::get_key_value`].
+    ///
+    /// Fails with [`MetadataError::VersionConflict`] if the key was changed or deleted since.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub fn delete_value_if(&self, version: u64) -> Result<()> {
+        self.delete(Some(Precondition::Version(version)))
+    }
+
+    /// Deletes every key-value pair in the given directory, from the `from` key onwards. All of
+    /// the keys are listed before any of them are deleted, which happens as with
+    /// [`MetadataRequest::delete_keys`].
+    ///
+    /// Only listing the keys can fail as a whole, failures to delete individual keys are found in
+    /// the returned [`DeleteReport`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::{
+    ///     metadata::v1::MetadataRequestBuilder,
+    ///     shared::v1::{Directory, RangeQueryContext},
+    /// };
+    ///
+    /// let mut range = RangeQueryContext::new();
+    /// range.set_directory(Directory::from_unencoded("feature-flags"));
+    ///
+    /// let mut req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .range(range)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let report = req.delete_directory().unwrap();
+    /// for (key, err) in &report.failed {
+    ///     eprintln!("failed to delete {key}: {err}");
+    /// }
+    /// ```
+    pub fn delete_directory(&mut self) -> Result<DeleteReport> {
+        let keys = self
+            .entries()
+            .map(|kv| kv.map(|kv| kv.key))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(self.delete_keys(keys))
+    }
+
+    /// Deletes each of `keys`, up to [`DELETE_BATCH_SIZE`] of them at the same time. A key which
+    /// can't be deleted doesn't stop the others from being deleted, and is listed in the failures
+    /// of the returned [`DeleteReport`].
+    ///
+    /// The `RequestTarget` is ignored.
+    pub fn delete_keys(&self, keys: Vec<Key>) -> DeleteReport {
+        let mut report = DeleteReport::default();
+        for batch in keys.chunks(DELETE_BATCH_SIZE) {
+            let results: Vec<_> = thread::scope(|s| {
+                let handles: Vec<_> = batch
+                    .iter()
+                    .map(|key| s.spawn(move || self.delete_at(key_url(&self.request, key), None)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|h| h.join().expect("deleting a key panicked"))
+                    .collect()
+            });
+            for (key, res) in batch.iter().zip(results) {
+                report.record(key.clone(), res);
+            }
+        }
+        report
+    }
+
+    // Deletes the key, only if the precondition holds when one is given
+    fn delete(&self, precondition: Option<Precondition>) -> Result<()> {
+        self.delete_at(single_key_url(&self.request)?, precondition)
+    }
+
+    // Deletes the key at `url`, only if the precondition holds when one is given
+    fn delete_at(&self, url: Url, precondition: Option<Precondition>) -> Result<()> {
+        let mut req = self.request.client.delete(url);
+        if let Some(precondition) = precondition {
+            let (name, value) = precondition.header();
+            req = req.header(name, value);
+        }
+        self.request
+            .send(req)
+            .map_err(map_conflict)?
             .text()
             .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
             .map_err(Into::into)
     }
 
+    /// Returns the value associated with the set key, decoded from JSON into a `T`.
+    ///
+    /// A value which isn't valid JSON for `T` fails with [`SeaplaneError::Json`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use seaplane::api::metadata::v1::MetadataRequestBuilder;
+    ///
+    /// let req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .encoded_key("cmVwbGljYXM")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let replicas: u32 = req.get_json().unwrap();
+    /// dbg!(replicas);
+    /// ```
+    pub fn get_json<T: DeserializeOwned>(&self) -> Result<T> { from_json(&self.get_value()?) }
+
+    /// Adds the JSON encoding of `value` to the store at the given key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use seaplane::api::metadata::v1::MetadataRequestBuilder;
+    ///
+    /// let req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .encoded_key("cmVwbGljYXM")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// req.put_json(&3).unwrap();
+    /// ```
+    pub fn put_json<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        self.put_value(to_json(value)?)
+    }
+
+    /// Returns the value of `key`, decoded from JSON into the key's type. The key the request
+    /// targets, if any, is left as it is.
+    ///
+    /// A value which isn't valid JSON for `T` fails with [`SeaplaneError::Json`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use seaplane::api::metadata::v1::{MetadataRequestBuilder, TypedKey};
+    ///
+    /// let replicas = TypedKey::<u32>::from_unencoded("replicas");
+    /// let req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let count = req.get_typed(&replicas).unwrap();
+    /// req.put_typed(&replicas, &(count + 1)).unwrap();
+    /// ```
+    pub fn get_typed<T: DeserializeOwned>(&self, key: &TypedKey<T>) -> Result<T> {
+        from_json(
+            &self
+                .get_key_value_at(key_url(&self.request, key.key()))?
+                .value,
+        )
+    }
+
+    /// Adds the JSON encoding of `value` to the store at `key`. The key the request targets, if
+    /// any, is left as it is.
+    pub fn put_typed<T: Serialize>(&self, key: &TypedKey<T>, value: &T) -> Result<()> {
+        self.put_at(key_url(&self.request, key.key()), to_json(value)?, None)
+    }
+
     /// Returns a single page of key value pairs for the given directory, beginning with the `from`
     /// key.
     ///
@@ -293,24 +572,54 @@ impl MetadataRequest {
     /// }
     /// ```
     pub fn get_page(&self) -> Result<KeyValueRange> {
-        match &self.request.target {
-            None | Some(RequestTarget::Key(_)) => {
-                Err(MetadataError::IncorrectMetadataRequestTarget)?
-            }
-            Some(RequestTarget::Range(_)) => {
-                let url = self.range_url()?;
-
-                let resp = self
-                    .request
-                    .client
-                    .get(url)
-                    .bearer_auth(&self.request.token)
-                    .send()?;
-                map_api_error(resp)?
-                    .json::<KeyValueRange>()
-                    .map_err(Into::into)
-            }
-        }
+        let url = range_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))?
+            .json::<KeyValueRange>()
+            .map_err(Into::into)
+    }
+
+    /// Returns an iterator over the pages of key-value pairs for the given directory, from the
+    /// `from` key onwards. Each page is only requested once the previous one has been consumed.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub fn pages(&mut self) -> Pages<'_, KeyValueRange> {
+        Pages::new(move || {
+            let page = self.get_page()?;
+            let more = match &page.next_key {
+                Some(next_key) => {
+                    set_next_key(&mut self.request, next_key.clone())?;
+                    true
+                }
+                None => false,
+            };
+            Ok((page, more))
+        })
+    }
+
+    /// Returns an iterator over the individual key-value pairs for the given directory, from the
+    /// `from` key onwards. Each page is only requested once the entries of the previous one
+    /// have been consumed, so iteration may stop early without requesting the remaining pages.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::{metadata::v1::MetadataRequestBuilder, shared::v1::RangeQueryContext};
+    ///
+    /// let mut req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .range(RangeQueryContext::new())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// for kv in req.entries() {
+    ///     let kv = kv.unwrap();
+    ///     dbg!(kv.key);
+    /// }
+    /// ```
+    pub fn entries(&mut self) -> Entries<'_, KeyValue> {
+        Entries::new(self.pages(), |page| page.kvs)
     }
 
     /// Returns all key-value pairs for the given directory, from the `from` key onwards. May
@@ -338,24 +647,109 @@ impl MetadataRequest {
     /// let resp = req.get_all_pages().unwrap();
     /// dbg!(resp);
     /// ```
-    // TODO: Replace this with a collect on a Pages/Entries iterator
-    pub fn get_all_pages(&mut self) -> Result<Vec<KeyValue>> {
-        let mut pages = Vec::new();
-        loop {
-            let mut kvr = self.get_page()?;
-            pages.append(&mut kvr.kvs);
-            if let Some(next_key) = kvr.next_key {
-                // TODO: Regrettable duplication here suggests that there should
-                // be a MetadataKeyRequest and a MetadataRangeRequest
-                if let Some(RequestTarget::Range(ref mut context)) = self.request.target {
-                    context.set_from(next_key);
-                } else {
-                    Err(MetadataError::IncorrectMetadataRequestTarget)?
-                }
-            } else {
-                break;
+    pub fn get_all_pages(&mut self) -> Result<Vec<KeyValue>> { self.entries().collect() }
+}
+
+// A condition on the current state of a key which must hold for a write to it to be applied
+#[cfg(any(feature = "blocking", feature = "async"))]
+#[derive(Debug, Copy, Clone)]
+enum Precondition {
+    // The value is at the given version
+    Version(u64),
+    // The key doesn't exist
+    Absent,
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl Precondition {
+    fn header(self) -> (HeaderName, HeaderValue) {
+        match self {
+            Precondition::Version(version) => (
+                IF_MATCH,
+                HeaderValue::from_str(&format!("\"{version}\"")).expect("valid entity tag"),
+            ),
+            Precondition::Absent => (IF_NONE_MATCH, HeaderValue::from_static("*")),
+        }
+    }
+}
+
+// Internal function surfacing a failed precondition as a MetadataError::VersionConflict
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn map_conflict(err: SeaplaneError) -> SeaplaneError {
+    match err {
+        SeaplaneError::ApiResponse(ae) if ae.is_http_precondition_failed() => {
+            MetadataError::VersionConflict.into()
+        }
+        err => err,
+    }
+}
+
+// Internal function treating a key which doesn't exist as `None` rather than an error
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn existing(res: Result<KeyValue>) -> Result<Option<KeyValue>> {
+    match res {
+        Ok(kv) => Ok(Some(kv)),
+        Err(SeaplaneError::ApiResponse(ae)) if ae.is_http_not_found() => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+// Internal function decoding a value which holds JSON
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn from_json<T: DeserializeOwned>(value: &Value) -> Result<T> {
+    Ok(serde_json::from_slice(&value.decode())?)
+}
+
+// Internal function encoding a value as JSON
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    Ok(Value::from_unencoded(serde_json::to_vec(value)?))
+}
+
+// Internal function creating the URL for all single key endpoints
+fn single_key_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        None | Some(RequestTarget::Range(_)) => Err(MetadataError::IncorrectMetadataRequestTarget)?,
+        Some(RequestTarget::Key(k)) => Ok(key_url(request, k)),
+    }
+}
+
+// Internal function creating the URL of `key`, regardless of the target of the request
+fn key_url<C>(request: &Request<RequestTarget, C>, key: &Key) -> Url {
+    add_base64_path_segment(request.endpoint_url.clone(), key.encoded())
+}
+
+// Internal function creating the URL for range endpoints
+fn range_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        None | Some(RequestTarget::Key(_)) => Err(MetadataError::IncorrectMetadataRequestTarget)?,
+        Some(RequestTarget::Range(context)) => {
+            let mut url = request.endpoint_url.clone();
+
+            if let Some(encoded_dir) = context.directory() {
+                url = add_base64_path_segment(url, encoded_dir.encoded());
+                // A directory is distinguished from a key by the trailing slash
+                url.set_path(&format!("{}/", url.path()));
             }
+
+            if let Some(from) = context.from() {
+                url.set_query(Some(&format!("from=base64:{}", from.encoded())));
+            }
+
+            Ok(url)
         }
-        Ok(pages)
+    }
+}
+
+// Internal function moving a range request on to the page beginning at `next_key`
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn set_next_key<C>(request: &mut Request<RequestTarget, C>, next_key: Key) -> Result<()> {
+    // TODO: Regrettable duplication here suggests that there should
+    // be a MetadataKeyRequest and a MetadataRangeRequest
+    if let Some(RequestTarget::Range(ref mut context)) = request.target {
+        context.set_from(next_key);
+        Ok(())
+    } else {
+        Err(MetadataError::IncorrectMetadataRequestTarget)?
     }
 }
@@ -0,0 +1,512 @@
+use std::{
+    future::{poll_fn, Future},
+    ops::ControlFlow,
+    task::Poll,
+    time::Duration,
+};
+
+use reqwest::{
+    header::{self, CONTENT_TYPE},
+    Url,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use super::{
+    existing, from_json, key_url, map_conflict, range_url, set_next_key, single_key_url, to_json,
+    watch::{Failures, Snapshot},
+    MetadataRequestBuilder, Precondition, DELETE_BATCH_SIZE, MAX_UPDATE_ATTEMPTS,
+};
+use crate::{
+    api::{
+        metadata::{
+            error::MetadataError,
+            v1::{
+                DeleteReport, Key, KeyValue, KeyValueRange, MetadataEvent, RequestTarget, TypedKey,
+                Value,
+            },
+        },
+        AsyncApiRequest,
+    },
+    error::{Result, SeaplaneError},
+};
+
+impl MetadataRequestBuilder {
+    /// Build an AsyncMetadataRequest from the given parameters
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async(self) -> Result<AsyncMetadataRequest> {
+        Ok(self.builder.build_async()?.into())
+    }
+}
+
+/// For making async requests against the `/config` APIs.
+///
+/// The async equivalent of [`MetadataRequest`](super::MetadataRequest), requires a `tokio`
+/// runtime.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug)]
+pub struct AsyncMetadataRequest {
+    request: AsyncApiRequest<RequestTarget>,
+}
+
+impl From<AsyncApiRequest<RequestTarget>> for AsyncMetadataRequest {
+    fn from(request: AsyncApiRequest<RequestTarget>) -> Self { Self { request } }
+}
+
+impl AsyncMetadataRequest {
+    /// Create a new request builder
+    pub fn builder() -> MetadataRequestBuilder { MetadataRequestBuilder::new() }
+
+    /// Returns the key value pair associated with the set key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use seaplane::api::metadata::v1::MetadataRequestBuilder;
+    ///
+    /// # async fn example() {
+    /// let req = MetadataRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .encoded_key("bW9ieQo")
+    ///     .build_async()
+    ///     .unwrap();
+    ///
+    /// let resp = req.get_value().await.unwrap();
+    /// dbg!(resp);
+    /// # }
+    /// ```
+    pub async fn get_value(&self) -> Result<Value> {
+        self.get_key_value().await.map(KeyValue::into_value)
+    }
+
+    /// Returns the key value pair associated with the set key, including the version of the
+    /// value.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn get_key_value(&self) -> Result<KeyValue> {
+        self.get_key_value_at(single_key_url(&self.request)?).await
+    }
+
+    // Returns the key value pair at `url`
+    async fn get_key_value_at(&self, url: Url) -> Result<KeyValue> {
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<KeyValue>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Adds an unencoded value to the store at the given key performing the encoding before
+    /// sending the request.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn put_value_unencoded<S: AsRef<[u8]>>(&self, value: S) -> Result<()> {
+        self.put_value(Value::from_unencoded(value)).await
+    }
+
+    /// Adds a base64 encoded value to the store at the given key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn put_value(&self, value: Value) -> Result<()> { self.put(value, None).await }
+
+    /// Adds a base64 encoded value to the store at the given key, only if the value there is
+    /// still at `version`.
+    ///
+    /// Fails with [`MetadataError::VersionConflict`] if the key was changed or deleted since.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn put_value_if(&self, value: Value, version: u64) -> Result<()> {
+        self.put(value, Some(Precondition::Version(version))).await
+    }
+
+    /// Adds a base64 encoded value to the store at the given key, only if the key doesn't exist
+    /// yet.
+    ///
+    /// Fails with [`MetadataError::VersionConflict`] if the key already exists.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn put_value_if_absent(&self, value: Value) -> Result<()> {
+        self.put(value, Some(Precondition::Absent)).await
+    }
+
+    /// Replaces the value at the given key with the one returned by `f`, which is given the
+    /// current value, or `None` when the key doesn't exist. Returns the value which was written.
+    ///
+    /// The write only succeeds if the key wasn't changed since it was read, otherwise the new
+    /// value is read and `f` is called again, up to [`MAX_UPDATE_ATTEMPTS`] times before failing
+    /// with [`MetadataError::VersionConflict`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn update_value<F>(&self, mut f: F) -> Result<Value>
+    where
+        F: FnMut(Option<&Value>) -> Value,
+    {
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let current = existing(self.get_key_value().await)?;
+            let value = f(current.as_ref().map(|kv| &kv.value));
+            let res = match current {
+                Some(kv) => {
+                    let version = kv.version.ok_or(MetadataError::MissingVersion)?;
+                    self.put_value_if(value.clone(), version).await
+                }
+                None => self.put_value_if_absent(value.clone()).await,
+            };
+            match res {
+                Err(SeaplaneError::MetadataRequest(MetadataError::VersionConflict))
+                    if attempts < MAX_UPDATE_ATTEMPTS => {}
+                res => return res.map(|_| value),
+            }
+        }
+    }
+
+    /// Deletes the key value pair at from a given base64 encoded key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn delete_value(&self) -> Result<()> { self.delete(None).await }
+
+    /// Deletes the key value pair at the given key, only if the value there is still at
+    /// `version`.
+    ///
+    /// Fails with [`MetadataError::VersionConflict`] if the key was changed or deleted since.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn delete_value_if(&self, version: u64) -> Result<()> {
+        self.delete(Some(Precondition::Version(version))).await
+    }
+
+    // Sends the value, only if the precondition holds when one is given
+    async fn put(&self, value: Value, precondition: Option<Precondition>) -> Result<()> {
+        self.put_at(single_key_url(&self.request)?, value, precondition)
+            .await
+    }
+
+    // Sends the value to `url`, only if the precondition holds when one is given
+    async fn put_at(
+        &self,
+        url: Url,
+        value: Value,
+        precondition: Option<Precondition>,
+    ) -> Result<()> {
+        let mut req = self
+            .request
+            .client
+            .put(url)
+            .header(CONTENT_TYPE, header::HeaderValue::from_static("application/octet-stream"))
+            .body(value.to_string());
+        if let Some(precondition) = precondition {
+            let (name, value) = precondition.header();
+            req = req.header(name, value);
+        }
+        self.request
+            .send(req)
+            .await
+            .map_err(map_conflict)?
+            .text()
+            .await
+            .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
+            .map_err(Into::into)
+    }
+
+    /// Deletes every key-value pair in the given directory, from the `from` key onwards. All of
+    /// the keys are listed before any of them are deleted, which happens as with
+    /// [`AsyncMetadataRequest::delete_keys`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub async fn delete_directory(&mut self) -> Result<DeleteReport> {
+        let keys = self
+            .get_all_pages()
+            .await?
+            .into_iter()
+            .map(|kv| kv.key)
+            .collect();
+        Ok(self.delete_keys(keys).await)
+    }
+
+    /// Deletes each of `keys`, up to [`DELETE_BATCH_SIZE`] of them at the same time. A key which
+    /// can't be deleted doesn't stop the others from being deleted, and is listed in the failures
+    /// of the returned [`DeleteReport`].
+    ///
+    /// The `RequestTarget` is ignored.
+    pub async fn delete_keys(&self, keys: Vec<Key>) -> DeleteReport {
+        let mut report = DeleteReport::default();
+        let results = buffered(
+            keys.iter()
+                .map(|key| self.delete_at(key_url(&self.request, key), None)),
+            DELETE_BATCH_SIZE,
+        )
+        .await;
+        for (key, res) in keys.into_iter().zip(results) {
+            report.record(key, res);
+        }
+        report
+    }
+
+    // Deletes the key, only if the precondition holds when one is given
+    async fn delete(&self, precondition: Option<Precondition>) -> Result<()> {
+        self.delete_at(single_key_url(&self.request)?, precondition)
+            .await
+    }
+
+    // Deletes the key at `url`, only if the precondition holds when one is given
+    async fn delete_at(&self, url: Url, precondition: Option<Precondition>) -> Result<()> {
+        let mut req = self.request.client.delete(url);
+        if let Some(precondition) = precondition {
+            let (name, value) = precondition.header();
+            req = req.header(name, value);
+        }
+        self.request
+            .send(req)
+            .await
+            .map_err(map_conflict)?
+            .text()
+            .await
+            .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
+            .map_err(Into::into)
+    }
+
+    /// Returns the value associated with the set key, decoded from JSON into a `T`.
+    ///
+    /// A value which isn't valid JSON for `T` fails with [`SeaplaneError::Json`].
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn get_json<T: DeserializeOwned>(&self) -> Result<T> {
+        from_json(&self.get_value().await?)
+    }
+
+    /// Adds the JSON encoding of `value` to the store at the given key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Key`.
+    pub async fn put_json<T: Serialize + ?Sized>(&self, value: &T) -> Result<()> {
+        self.put_value(to_json(value)?).await
+    }
+
+    /// Returns the value of `key`, decoded from JSON into the key's type. The key the request
+    /// targets, if any, is left as it is.
+    ///
+    /// A value which isn't valid JSON for `T` fails with [`SeaplaneError::Json`].
+    pub async fn get_typed<T: DeserializeOwned>(&self, key: &TypedKey<T>) -> Result<T> {
+        let url = key_url(&self.request, key.key());
+        from_json(&self.get_key_value_at(url).await?.value)
+    }
+
+    /// Adds the JSON encoding of `value` to the store at `key`. The key the request targets, if
+    /// any, is left as it is.
+    pub async fn put_typed<T: Serialize>(&self, key: &TypedKey<T>, value: &T) -> Result<()> {
+        self.put_at(key_url(&self.request, key.key()), to_json(value)?, None)
+            .await
+    }
+
+    /// Returns a single page of key value pairs for the given directory, beginning with the `from`
+    /// key.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub async fn get_page(&self) -> Result<KeyValueRange> {
+        let url = range_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<KeyValueRange>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns all key-value pairs for the given directory, from the `from` key onwards. May
+    /// perform multiple requests.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub async fn get_all_pages(&mut self) -> Result<Vec<KeyValue>> {
+        let mut pages = Vec::new();
+        loop {
+            let mut kvr = self.get_page().await?;
+            pages.append(&mut kvr.kvs);
+            if let Some(next_key) = kvr.next_key {
+                set_next_key(&mut self.request, next_key)?;
+            } else {
+                break;
+            }
+        }
+        Ok(pages)
+    }
+}
+
+/// Watches a key or a directory of key-value pairs for changes, by checking it every interval
+/// and comparing it against what was seen the last time.
+///
+/// The async equivalent of [`MetadataWatcher`](super::MetadataWatcher), requires a `tokio`
+/// runtime. A check which fails is tried again at the next interval, until
+/// [`MAX_WATCH_FAILURES`](super::MAX_WATCH_FAILURES) checks in a row have failed.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug)]
+pub struct AsyncMetadataWatcher {
+    request: AsyncMetadataRequest,
+    // The target of the request as it was given, since reading a range moves its `from` key
+    target: RequestTarget,
+    interval: Duration,
+    snapshot: Snapshot,
+}
+
+impl AsyncMetadataWatcher {
+    /// Watches the key or directory targeted by `request`, checking it every `interval`
+    pub fn new(request: AsyncMetadataRequest, interval: Duration) -> Result<Self> {
+        let target = request
+            .request
+            .target
+            .clone()
+            .ok_or(MetadataError::IncorrectMetadataRequestTarget)?;
+        Ok(Self { request, target, interval, snapshot: Snapshot::default() })
+    }
+
+    /// Checks the watched key-value pairs once, returning how they changed since the last check
+    pub async fn poll(&mut self) -> Result<Vec<MetadataEvent>> {
+        self.request.request.target = Some(self.target.clone());
+        let kvs = match &self.target {
+            RequestTarget::Key(_) => existing(self.request.get_key_value().await)?
+                .into_iter()
+                .collect(),
+            RequestTarget::Range(_) => self.request.get_all_pages().await?,
+        };
+        Ok(self.snapshot.update(kvs))
+    }
+
+    /// Checks the watched key-value pairs every interval, calling `f` with each change, until `f`
+    /// returns [`ControlFlow::Break`] or [`MAX_WATCH_FAILURES`](super::MAX_WATCH_FAILURES) checks
+    /// in a row have failed, in which case the error of the last one is returned.
+    pub async fn watch<F>(&mut self, mut f: F) -> Result<()>
+    where
+        F: FnMut(MetadataEvent) -> ControlFlow<()>,
+    {
+        let mut failures = Failures::default();
+        loop {
+            for event in failures.check(self.poll().await)? {
+                if f(event).is_break() {
+                    return Ok(());
+                }
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+
+    /// Watches on a new task, which sends each change to the returned channel.
+    ///
+    /// The task stops after sending the error which ended the watch, or once it finds the receiver
+    /// was dropped the next time it has a change to send.
+    pub fn spawn(mut self) -> UnboundedReceiver<Result<MetadataEvent>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let res = self
+                .watch(|event| match tx.send(Ok(event)) {
+                    Ok(()) => ControlFlow::Continue(()),
+                    Err(_) => ControlFlow::Break(()),
+                })
+                .await;
+            if let Err(e) = res {
+                let _ = tx.send(Err(e));
+            }
+        });
+        rx
+    }
+}
+
+// Internal function driving up to `limit` of `futures` at the same time, starting the next one as
+// soon as any finishes, and returning all of their outputs in order
+async fn buffered<I>(futures: I, limit: usize) -> Vec<<I::Item as Future>::Output>
+where
+    I: IntoIterator,
+    I::Item: Future,
+{
+    let limit = limit.max(1);
+    let mut waiting = futures.into_iter().enumerate();
+    let mut running = Vec::with_capacity(limit);
+    let mut outputs = Vec::new();
+    poll_fn(|cx| loop {
+        while running.len() < limit {
+            let Some((i, f)) = waiting.next() else { break };
+            outputs.push(None);
+            running.push((i, Box::pin(f)));
+        }
+        if running.is_empty() {
+            return Poll::Ready(());
+        }
+        let before = running.len();
+        running.retain_mut(|(i, f)| match f.as_mut().poll(cx) {
+            Poll::Ready(out) => {
+                outputs[*i] = Some(out);
+                false
+            }
+            Poll::Pending => true,
+        });
+        // Only poll again when a finished future made room for another to start
+        if running.len() == before {
+            return Poll::Pending;
+        }
+    })
+    .await;
+    outputs
+        .into_iter()
+        .map(|out| out.expect("future completed"))
+        .collect()
+}
+
+#[cfg(test)]
+mod buffered_tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn block_on<F: Future>(f: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(f)
+    }
+
+    // Returns pending once before finishing, to let the other futures run
+    async fn yield_once() {
+        let mut yielded = false;
+        poll_fn(|cx| {
+            if yielded {
+                Poll::Ready(())
+            } else {
+                yielded = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    #[test]
+    fn buffered_bounds_and_orders() {
+        let running = Cell::new(0);
+        let most = Cell::new(0);
+        let futures = (0..10).map(|i| {
+            let (running, most) = (&running, &most);
+            async move {
+                running.set(running.get() + 1);
+                most.set(most.get().max(running.get()));
+                // Finish out of order
+                for _ in 0..(10 - i) % 4 {
+                    yield_once().await;
+                }
+                running.set(running.get() - 1);
+                i * 2
+            }
+        });
+
+        let outputs = block_on(buffered(futures, 3));
+        assert_eq!(outputs, (0..10).map(|i| i * 2).collect::<Vec<_>>());
+        assert_eq!(most.get(), 3);
+        assert_eq!(running.get(), 0);
+    }
+
+    #[test]
+    fn buffered_empty() {
+        let outputs = block_on(buffered(Vec::<std::future::Ready<()>>::new(), 3));
+        assert!(outputs.is_empty());
+    }
+}
@@ -1,14 +1,21 @@
-use std::fmt;
+use std::{fmt, marker::PhantomData};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{api::shared::v1::RangeQueryContext, base64::Base64Encoded, impl_base64};
+use crate::{
+    api::shared::v1::RangeQueryContext, base64::Base64Encoded, error::SeaplaneError, impl_base64,
+};
 
 /// A single key value pair, encoded in url-safe base64.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 pub struct KeyValue {
     pub key: Key,
     pub value: Value,
+    /// The version of the value, which changes every time the value is written. Used to only
+    /// write or delete the key if it hasn't changed since it was read, such as with
+    /// [`MetadataRequest::put_value_if`](super::MetadataRequest::put_value_if).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
 }
 
 impl KeyValue {
@@ -31,6 +38,44 @@ impl fmt::Display for Key {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { write!(f, "{}", self.inner) }
 }
 
+/// A [`Key`] whose value is always the JSON encoding of a `T`, so that reading or writing the value
+/// as the wrong type is caught at compile time.
+///
+/// Used with [`MetadataRequest::get_typed`](super::MetadataRequest::get_typed) and
+/// [`MetadataRequest::put_typed`](super::MetadataRequest::put_typed).
+pub struct TypedKey<T> {
+    key: Key,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<T> TypedKey<T> {
+    /// Constructs from an unencoded byte array, encoding with URL-safe base64 in the process
+    pub fn from_unencoded(unencoded: impl AsRef<[u8]>) -> Self {
+        Key::from_unencoded(unencoded).into()
+    }
+
+    /// Constructs a `TypedKey`, assuming the input is already encoded.
+    pub fn from_encoded(encoded: impl Into<String>) -> Self { Key::from_encoded(encoded).into() }
+
+    /// The untyped key
+    pub fn key(&self) -> &Key { &self.key }
+}
+
+impl<T> From<Key> for TypedKey<T> {
+    fn from(key: Key) -> Self { Self { key, value: PhantomData } }
+}
+
+// Implemented by hand as deriving would needlessly require `T` to implement the traits too
+impl<T> Clone for TypedKey<T> {
+    fn clone(&self) -> Self { self.key.clone().into() }
+}
+
+impl<T> fmt::Debug for TypedKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TypedKey").field(&self.key).finish()
+    }
+}
+
 /// The raw bytes stored at a given key, encoded in url-safe base64.
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(transparent)]
@@ -48,7 +93,7 @@ impl fmt::Display for Value {
 }
 
 /// The target of a request, representing either a single key or a range of keys.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RequestTarget {
     Key(Key),
     Range(RangeQueryContext<Key>),
@@ -63,6 +108,53 @@ pub struct KeyValueRange {
     pub kvs: Vec<KeyValue>,
 }
 
+/// A change to a watched key-value pair, found by a
+/// [`MetadataWatcher`](super::MetadataWatcher)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MetadataEvent {
+    /// The key didn't exist when last checked
+    Added(KeyValue),
+    /// The key holds a different value than when last checked
+    Changed(KeyValue),
+    /// The key no longer exists, along with the value it held when last checked
+    Removed(KeyValue),
+}
+
+impl MetadataEvent {
+    /// The key-value pair which changed, which for [`MetadataEvent::Removed`] is the one last seen
+    pub fn key_value(&self) -> &KeyValue {
+        match self {
+            MetadataEvent::Added(kv) | MetadataEvent::Changed(kv) | MetadataEvent::Removed(kv) => {
+                kv
+            }
+        }
+    }
+}
+
+/// The outcome of deleting many keys with
+/// [`MetadataRequest::delete_keys`](super::MetadataRequest::delete_keys), where failing to delete
+/// some of them doesn't stop the others from being deleted
+#[derive(Debug, Default)]
+pub struct DeleteReport {
+    /// The keys which were deleted
+    pub deleted: Vec<Key>,
+    /// The keys which couldn't be deleted, along with why
+    pub failed: Vec<(Key, SeaplaneError)>,
+}
+
+impl DeleteReport {
+    /// Whether every key was deleted
+    pub fn is_complete(&self) -> bool { self.failed.is_empty() }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn record(&mut self, key: Key, res: Result<(), SeaplaneError>) {
+        match res {
+            Ok(()) => self.deleted.push(key),
+            Err(e) => self.failed.push((key, e)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod metadata_models_test {
     use super::*;
@@ -80,6 +172,7 @@ mod metadata_models_test {
                 kvs: vec![KeyValue {
                     key: Key::from_encoded("aGVsbG8"),
                     value: Value::from_encoded("dmFsdWU"),
+                    version: None,
                 },]
             },
             deserialzied
@@ -93,6 +186,7 @@ mod metadata_models_test {
             kvs: vec![KeyValue {
                 key: Key::from_encoded("aGVsbG8"),
                 value: Value::from_encoded("dmFsdWU"),
+                version: None,
             }],
         })
         .unwrap();
@@ -102,4 +196,16 @@ mod metadata_models_test {
             serialized
         );
     }
+
+    #[test]
+    fn key_value_version() {
+        let kv: KeyValue =
+            serde_json::from_str("{\"key\":\"aGVsbG8\",\"value\":\"dmFsdWU\",\"version\":7}")
+                .unwrap();
+        assert_eq!(kv.version, Some(7));
+        assert_eq!(
+            "{\"key\":\"aGVsbG8\",\"value\":\"dmFsdWU\",\"version\":7}",
+            serde_json::to_string(&kv).unwrap()
+        );
+    }
 }
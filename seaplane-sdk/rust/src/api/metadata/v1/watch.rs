@@ -0,0 +1,241 @@
+//! Watching a key or a directory of key-value pairs for changes by checking it periodically
+use std::{collections::BTreeMap, mem};
+#[cfg(feature = "blocking")]
+use std::{
+    ops::ControlFlow,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "blocking")]
+use super::{existing, MetadataRequest, RequestTarget};
+use super::{KeyValue, MetadataEvent, MAX_WATCH_FAILURES};
+#[cfg(feature = "blocking")]
+use crate::api::metadata::error::MetadataError;
+use crate::error::Result;
+
+/// Watches a key or a directory of key-value pairs for changes, by checking it every interval
+/// and comparing it against what was seen the last time.
+///
+/// The first check reports every key-value pair which exists as added. Directories are read page
+/// by page from the `from` key of the request each time they're checked. A check which fails is
+/// tried again at the next interval, until [`MAX_WATCH_FAILURES`] checks in a row have failed.
+///
+/// Checks are authorized as the request is, so build it with an API key or a
+/// [`TokenProvider`](crate::api::identity::v1::TokenProvider) to have access tokens which expire
+/// during a long watch be refreshed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{ops::ControlFlow, time::Duration};
+///
+/// use seaplane::api::{
+///     metadata::v1::{MetadataEvent, MetadataRequestBuilder, MetadataWatcher},
+///     shared::v1::{Directory, RangeQueryContext},
+/// };
+///
+/// let mut range = RangeQueryContext::new();
+/// range.set_directory(Directory::from_unencoded("feature-flags"));
+///
+/// let req = MetadataRequestBuilder::new()
+///     .token("abc123_token")
+///     .range(range)
+///     .build()
+///     .unwrap();
+///
+/// let mut watcher = MetadataWatcher::new(req, Duration::from_secs(5)).unwrap();
+/// watcher
+///     .watch(|event| {
+///         if let MetadataEvent::Changed(kv) = event {
+///             dbg!(kv);
+///         }
+///         ControlFlow::Continue(())
+///     })
+///     .unwrap();
+/// ```
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+#[derive(Debug)]
+pub struct MetadataWatcher {
+    request: MetadataRequest,
+    // The target of the request as it was given, since reading a range moves its `from` key
+    target: RequestTarget,
+    interval: Duration,
+    snapshot: Snapshot,
+}
+
+#[cfg(feature = "blocking")]
+impl MetadataWatcher {
+    /// Watches the key or directory targeted by `request`, checking it every `interval`
+    pub fn new(request: MetadataRequest, interval: Duration) -> Result<Self> {
+        let target = request
+            .request
+            .target
+            .clone()
+            .ok_or(MetadataError::IncorrectMetadataRequestTarget)?;
+        Ok(Self { request, target, interval, snapshot: Snapshot::default() })
+    }
+
+    /// Checks the watched key-value pairs once, returning how they changed since the last check
+    pub fn poll(&mut self) -> Result<Vec<MetadataEvent>> {
+        self.request.request.target = Some(self.target.clone());
+        let kvs = match &self.target {
+            RequestTarget::Key(_) => existing(self.request.get_key_value())?
+                .into_iter()
+                .collect(),
+            RequestTarget::Range(_) => self.request.get_all_pages()?,
+        };
+        Ok(self.snapshot.update(kvs))
+    }
+
+    /// Checks the watched key-value pairs every interval, calling `f` with each change, until `f`
+    /// returns [`ControlFlow::Break`] or [`MAX_WATCH_FAILURES`] checks in a row have failed, in
+    /// which case the error of the last one is returned.
+    pub fn watch<F>(&mut self, mut f: F) -> Result<()>
+    where
+        F: FnMut(MetadataEvent) -> ControlFlow<()>,
+    {
+        let mut failures = Failures::default();
+        loop {
+            for event in failures.check(self.poll())? {
+                if f(event).is_break() {
+                    return Ok(());
+                }
+            }
+            thread::sleep(self.interval);
+        }
+    }
+
+    /// Watches on a new thread, which sends each change to the returned channel.
+    ///
+    /// The thread stops after sending the error which ended the watch, or once it finds the
+    /// receiver was dropped the next time it has a change to send.
+    pub fn spawn(mut self) -> Receiver<Result<MetadataEvent>> {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let res = self.watch(|event| match tx.send(Ok(event)) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            });
+            if let Err(e) = res {
+                let _ = tx.send(Err(e));
+            }
+        });
+        rx
+    }
+}
+
+// The key-value pairs seen by the last check, which the next check is compared against
+#[derive(Debug, Default)]
+pub(crate) struct Snapshot {
+    seen: BTreeMap<String, KeyValue>,
+}
+
+impl Snapshot {
+    // Replaces the key-value pairs seen with `kvs`, returning how they changed. Keys which were
+    // removed come last, in the order of their encoding.
+    pub(crate) fn update(&mut self, kvs: Vec<KeyValue>) -> Vec<MetadataEvent> {
+        let mut events = Vec::new();
+        let mut current = BTreeMap::new();
+        for kv in kvs {
+            match self.seen.remove(kv.key.encoded()) {
+                None => events.push(MetadataEvent::Added(kv.clone())),
+                Some(old) if old.value != kv.value => {
+                    events.push(MetadataEvent::Changed(kv.clone()))
+                }
+                Some(_) => (),
+            }
+            current.insert(kv.key.encoded().to_owned(), kv);
+        }
+        let removed = mem::replace(&mut self.seen, current);
+        events.extend(removed.into_values().map(MetadataEvent::Removed));
+        events
+    }
+}
+
+// The checks which failed in a row, which a watch tolerates up to `MAX_WATCH_FAILURES` of
+#[derive(Debug, Default)]
+pub(crate) struct Failures(usize);
+
+impl Failures {
+    // Passes on the changes found by a check, treating a failed check as finding none unless too
+    // many checks in a row have failed, in which case its error is passed on instead
+    pub(crate) fn check(&mut self, res: Result<Vec<MetadataEvent>>) -> Result<Vec<MetadataEvent>> {
+        match res {
+            Ok(events) => {
+                self.0 = 0;
+                Ok(events)
+            }
+            Err(e) => {
+                self.0 += 1;
+                if self.0 >= MAX_WATCH_FAILURES {
+                    return Err(e);
+                }
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+    use crate::api::metadata::{
+        error::MetadataError,
+        v1::{Key, Value},
+    };
+
+    fn kv(key: &str, value: &str, version: u64) -> KeyValue {
+        KeyValue {
+            key: Key::from_unencoded(key),
+            value: Value::from_unencoded(value),
+            version: Some(version),
+        }
+    }
+
+    #[test]
+    fn snapshot_update() {
+        let mut snapshot = Snapshot::default();
+        assert_eq!(
+            snapshot.update(vec![kv("ahab", "captain", 1), kv("ishmael", "sailor", 1)]),
+            vec![
+                MetadataEvent::Added(kv("ahab", "captain", 1)),
+                MetadataEvent::Added(kv("ishmael", "sailor", 1))
+            ]
+        );
+
+        // Rewriting the same value isn't a change
+        assert_eq!(
+            snapshot.update(vec![kv("ahab", "captain", 2), kv("ishmael", "sailor", 1)]),
+            vec![]
+        );
+
+        assert_eq!(
+            snapshot.update(vec![kv("ishmael", "narrator", 2), kv("queequeg", "harpooneer", 1)]),
+            vec![
+                MetadataEvent::Changed(kv("ishmael", "narrator", 2)),
+                MetadataEvent::Added(kv("queequeg", "harpooneer", 1)),
+                MetadataEvent::Removed(kv("ahab", "captain", 2))
+            ]
+        );
+    }
+
+    #[test]
+    fn failures_check() {
+        let failed = || Err(MetadataError::MissingVersion.into());
+        let mut failures = Failures::default();
+        for _ in 1..MAX_WATCH_FAILURES {
+            assert_eq!(failures.check(failed()).unwrap(), vec![]);
+        }
+
+        // A successful check starts the count again
+        let events = vec![MetadataEvent::Added(kv("ahab", "captain", 1))];
+        assert_eq!(failures.check(Ok(events.clone())).unwrap(), events);
+        for _ in 1..MAX_WATCH_FAILURES {
+            assert!(failures.check(failed()).is_ok());
+        }
+        assert!(failures.check(failed()).is_err());
+    }
+}
@@ -1,13 +1,37 @@
 //! Purpose of those structs is basically just to allow token re-use/retry so
 //! that we don't have to request a new API token on each and every call
 
+#[cfg(any(feature = "blocking", feature = "async"))]
+use reqwest::header::{HeaderMap, IF_MATCH, IF_NONE_MATCH};
+#[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+use reqwest::StatusCode;
 use reqwest::{
-    blocking,
     header::{self, CONTENT_TYPE},
-    Url,
+    Method, Url,
 };
 
-use crate::error::{Result, SeaplaneError};
+#[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+use crate::api::identity::v1::TokenProvider;
+#[cfg(feature = "blocking")]
+use crate::api::retry::send_with_retry;
+#[cfg(feature = "async")]
+use crate::api::retry::send_with_retry_async;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::api::SeaplaneClient;
+use crate::{
+    api::RetryPolicy,
+    error::{Result, SeaplaneError},
+};
+
+/// How requests are authorized
+#[derive(Debug, Clone)]
+pub(crate) enum Auth {
+    /// A fixed token used for Bearer Authorization
+    Token(String),
+    /// Tokens used for Bearer Authorization are requested, cached and refreshed by the provider
+    #[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+    Provider(TokenProvider),
+}
 
 /// A builder struct for creating a ApiRequest which will then be used for
 /// making a request against the APIs
@@ -16,7 +40,9 @@ pub(crate) struct RequestBuilder<T> {
     // Target resource of this request
     pub target: Option<T>,
     // Required for Bearer Auth
-    pub token: Option<String>,
+    pub auth: Option<Auth>,
+    // How requests which fail due to transient errors are retried
+    pub retry: RetryPolicy,
     // API URL
     pub api_url: String,
     // Base path for the api
@@ -30,6 +56,11 @@ pub(crate) struct RequestBuilder<T> {
     // Used for testing
     #[doc(hidden)]
     pub base_url: Option<Url>,
+    // A client shared with other requests, used in place of building a new one
+    #[cfg(feature = "blocking")]
+    pub client: Option<reqwest::blocking::Client>,
+    #[cfg(feature = "async")]
+    pub async_client: Option<reqwest::Client>,
 }
 
 impl<T> RequestBuilder<T> {
@@ -37,10 +68,15 @@ impl<T> RequestBuilder<T> {
     pub(crate) fn new<S: Into<String>>(api_url: S, base_path: S) -> Self {
         Self {
             target: None,
-            token: None,
+            auth: None,
+            retry: RetryPolicy::default(),
             api_url: api_url.into(),
             base_path: base_path.into(),
             base_url: None,
+            #[cfg(feature = "blocking")]
+            client: None,
+            #[cfg(feature = "async")]
+            async_client: None,
             #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
             allow_http: false,
             #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
@@ -52,7 +88,20 @@ impl<T> RequestBuilder<T> {
     ///
     /// **NOTE:** This is required for all endpoints
     pub(crate) fn token<U: Into<String>>(mut self, token: U) -> Self {
-        self.token = Some(token.into());
+        self.auth = Some(Auth::Token(token.into()));
+        self
+    }
+
+    /// Set the provider of tokens used in Bearer Authorization, in place of a fixed token
+    #[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+    pub(crate) fn token_provider(mut self, provider: TokenProvider) -> Self {
+        self.auth = Some(Auth::Provider(provider));
+        self
+    }
+
+    /// Set how requests which fail due to transient errors are retried
+    pub(crate) fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
         self
     }
 
@@ -81,38 +130,50 @@ impl<T> RequestBuilder<T> {
         self
     }
 
-    /// Build an APIRequest from the given parameters
-    pub(crate) fn build(self) -> Result<ApiRequest<T>> {
-        if self.token.is_none() {
-            return Err(SeaplaneError::MissingRequestAuthToken);
+    /// Use the connection pool, credentials and retry policy of the given client, along with its
+    /// URL for this API if it has one
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn with_client(mut self, client: &SeaplaneClient, base_url: Option<&Url>) -> Self {
+        self.auth = client.auth.clone();
+        self.retry = client.retry;
+        if let Some(url) = base_url {
+            self.base_url = Some(url.clone());
         }
-
-        let mut headers = header::HeaderMap::new();
-        headers.insert(CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
-
-        #[cfg_attr(
-            not(any(
-                feature = "api_tests",
-                feature = "allow_insecure_urls",
-                feature = "danger_zone"
-            )),
-            allow(unused_mut)
-        )]
-        let mut builder = blocking::Client::builder()
-            .default_headers(headers)
-            .https_only(true);
-
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "api_tests")] {
-                builder = builder.https_only(false);
-            } else if #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))] {
-                builder = builder.https_only(!self.allow_http);
-            }
+        #[cfg(feature = "blocking")]
+        {
+            self.client = Some(client.client.clone());
         }
-        #[cfg(any(feature = "allow_invalid_certs", feature = "danger_zone"))]
+        #[cfg(feature = "async")]
         {
-            builder = builder.danger_accept_invalid_certs(self.allow_invalid_certs);
+            self.async_client = Some(client.async_client.clone());
         }
+        self
+    }
+
+    /// Build a blocking APIRequest from the given parameters
+    #[cfg(feature = "blocking")]
+    pub(crate) fn build(mut self) -> Result<ApiRequest<T>> {
+        let client = match self.client.take() {
+            Some(client) => client,
+            None => build_client!(reqwest::blocking::Client::builder(), default_headers(), self),
+        };
+        self.into_request(client)
+    }
+
+    /// Build an async APIRequest from the given parameters
+    #[cfg(feature = "async")]
+    pub(crate) fn build_async(mut self) -> Result<AsyncApiRequest<T>> {
+        let client = match self.async_client.take() {
+            Some(client) => client,
+            None => build_client!(reqwest::Client::builder(), default_headers(), self),
+        };
+        self.into_request(client)
+    }
+
+    // Consumes the builder pairing the target, token and endpoint URL with the given client
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    fn into_request<C>(self, client: C) -> Result<Request<T, C>> {
+        let auth = self.auth.ok_or(SeaplaneError::MissingRequestAuthToken)?;
 
         let url = if let Some(url) = &self.base_url {
             url.join(&self.base_path)?
@@ -122,12 +183,7 @@ impl<T> RequestBuilder<T> {
             url
         };
 
-        Ok(ApiRequest::<T> {
-            target: self.target,
-            token: self.token.unwrap(),
-            client: builder.build()?,
-            endpoint_url: url,
-        })
+        Ok(Request { target: self.target, auth, retry: self.retry, client, endpoint_url: url })
     }
 
     // Used in testing and development to manually set the URL
@@ -138,13 +194,119 @@ impl<T> RequestBuilder<T> {
     }
 }
 
+/// The default headers sent with every request
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub(crate) fn default_headers() -> header::HeaderMap {
+    let mut headers = header::HeaderMap::new();
+    headers.insert(CONTENT_TYPE, header::HeaderValue::from_static("application/json"));
+    headers
+}
+
+/// A built request against the APIs generic over the underlying HTTP client so the URL building
+/// of each API can be shared between the blocking and async variants
 #[derive(Debug)]
-pub(crate) struct ApiRequest<T> {
+pub(crate) struct Request<T, C> {
     /// The target resource
     pub(crate) target: Option<T>,
-    pub(crate) token: String,
+    pub(crate) auth: Auth,
+    pub(crate) retry: RetryPolicy,
     #[doc(hidden)]
-    pub(crate) client: reqwest::blocking::Client,
+    pub(crate) client: C,
     #[doc(hidden)]
     pub(crate) endpoint_url: Url,
 }
+
+/// A request using the blocking HTTP client
+#[cfg(feature = "blocking")]
+pub(crate) type ApiRequest<T> = Request<T, reqwest::blocking::Client>;
+
+/// A request using the async HTTP client
+#[cfg(feature = "async")]
+pub(crate) type AsyncApiRequest<T> = Request<T, reqwest::Client>;
+
+#[cfg(feature = "blocking")]
+impl<T> ApiRequest<T> {
+    /// Sends the request using Bearer Authorization, retrying transient failures and mapping any
+    /// error responses
+    pub(crate) fn send(
+        &self,
+        req: reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response> {
+        let idempotent = req
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .map_or(false, |r| is_idempotent(r.method(), r.headers()));
+        send_with_retry(&self.retry, idempotent, req, |req| self.send_once(req))
+    }
+
+    // A single attempt at sending the request, which when using a `TokenProvider` is sent again if
+    // the token is rejected
+    fn send_once(
+        &self,
+        req: reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response> {
+        match &self.auth {
+            Auth::Token(token) => Ok(req.bearer_auth(token).send()?),
+            #[cfg(feature = "identity_api_v1")]
+            Auth::Provider(provider) => {
+                // Keep a copy of the request in case the token is rejected and it must be retried
+                let retry = req.try_clone();
+                let token = provider.token()?;
+                let resp = req.bearer_auth(&token).send()?;
+                match retry {
+                    Some(retry) if resp.status() == StatusCode::UNAUTHORIZED => {
+                        provider.invalidate_token(&token);
+                        Ok(retry.bearer_auth(provider.token()?).send()?)
+                    }
+                    _ => Ok(resp),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> AsyncApiRequest<T> {
+    /// Sends the request using Bearer Authorization, retrying transient failures and mapping any
+    /// error responses
+    pub(crate) async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let idempotent = req
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .map_or(false, |r| is_idempotent(r.method(), r.headers()));
+        send_with_retry_async(&self.retry, idempotent, req, |req| self.send_once(req)).await
+    }
+
+    // A single attempt at sending the request, which when using a `TokenProvider` is sent again if
+    // the token is rejected
+    async fn send_once(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        match &self.auth {
+            Auth::Token(token) => Ok(req.bearer_auth(token).send().await?),
+            #[cfg(feature = "identity_api_v1")]
+            Auth::Provider(provider) => {
+                // Keep a copy of the request in case the token is rejected and it must be retried
+                let retry = req.try_clone();
+                let token = provider.token_async().await?;
+                let resp = req.bearer_auth(&token).send().await?;
+                match retry {
+                    Some(retry) if resp.status() == StatusCode::UNAUTHORIZED => {
+                        provider.invalidate_token(&token);
+                        let token = provider.token_async().await?;
+                        Ok(retry.bearer_auth(token).send().await?)
+                    }
+                    _ => Ok(resp),
+                }
+            }
+        }
+    }
+}
+
+// Whether requests with the given method and headers may be safely retried by default. A
+// conditional request is not, since its first attempt may have succeeded and changed the condition
+// even though its response was lost.
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn is_idempotent(method: &Method, headers: &HeaderMap) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS)
+        && !headers.contains_key(IF_MATCH)
+        && !headers.contains_key(IF_NONE_MATCH)
+}
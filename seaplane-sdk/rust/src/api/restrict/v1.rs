@@ -1,20 +1,30 @@
 //! The `/restrict` endpoint APIs which allows working with [`Restriction`]s
+#[cfg(feature = "async")]
+mod asynchronous;
 pub mod models;
 
 use std::str::FromStr;
 
-use reqwest::{
-    header::{self, CONTENT_TYPE},
-    Url,
-};
+#[cfg(feature = "blocking")]
+use reqwest::header::{self, CONTENT_TYPE};
+use reqwest::Url;
 
+#[cfg(feature = "async")]
+pub use self::asynchronous::*;
 pub use self::models::*;
+#[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+use crate::api::identity::v1::TokenProvider;
+#[cfg(feature = "blocking")]
+use crate::api::shared::v1::{Entries, Pages};
+#[cfg(feature = "blocking")]
+use crate::api::ApiRequest;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use crate::api::SeaplaneClient;
 use crate::{
     api::{
-        map_api_error,
         restrict::{error::RestrictError, RESTRICT_API_URL},
         shared::v1::RangeQueryContext,
-        ApiRequest, RequestBuilder,
+        Request, RequestBuilder, RetryPolicy,
     },
     error::Result,
 };
@@ -41,6 +51,8 @@ impl RestrictRequestBuilder {
     pub fn new() -> Self { RequestBuilder::new(RESTRICT_API_URL, RESTRICT_API_BASE_PATH).into() }
 
     /// Build a RestrictRequest from the given parameters
+    #[cfg(feature = "blocking")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
     pub fn build(self) -> Result<RestrictRequest> { Ok(self.builder.build()?.into()) }
 
     /// Set the token used in Bearer Authorization
@@ -49,6 +61,27 @@ impl RestrictRequestBuilder {
     #[must_use]
     pub fn token<U: Into<String>>(self, token: U) -> Self { self.builder.token(token).into() }
 
+    /// Set the provider of tokens used in Bearer Authorization, in place of a fixed token
+    ///
+    /// The access token is cached and refreshed as needed, and requests rejected with HTTP 401
+    /// UNAUTHORIZED are retried once with a new token.
+    #[cfg(all(feature = "identity_api_v1", any(feature = "blocking", feature = "async")))]
+    #[must_use]
+    pub fn token_provider(self, provider: TokenProvider) -> Self {
+        self.builder.token_provider(provider).into()
+    }
+
+    /// Set how requests which fail due to transient errors are retried (default:
+    /// [`RetryPolicy::default`])
+    #[must_use]
+    pub fn retry(self, policy: RetryPolicy) -> Self { self.builder.retry(policy).into() }
+
+    // Shares the connection pool, credentials and configuration of the client
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn with_client(self, client: &SeaplaneClient, url: Option<&Url>) -> Self {
+        self.builder.with_client(client, url).into()
+    }
+
     /// Allow non-HTTPS endpoints for this request (default: `false`)
     #[cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))]
     #[cfg_attr(docsrs, doc(cfg(any(feature = "allow_insecure_urls", feature = "danger_zone"))))]
@@ -106,68 +139,23 @@ impl RestrictRequestBuilder {
 }
 
 /// For making requests against the `/request` APIs.
+#[cfg(feature = "blocking")]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
 #[derive(Debug)]
 pub struct RestrictRequest {
     request: ApiRequest<RequestTarget>,
 }
 
+#[cfg(feature = "blocking")]
 impl From<ApiRequest<RequestTarget>> for RestrictRequest {
     fn from(request: ApiRequest<RequestTarget>) -> Self { Self { request } }
 }
 
+#[cfg(feature = "blocking")]
 impl RestrictRequest {
     /// Create a new request builder
     pub fn builder() -> RestrictRequestBuilder { RestrictRequestBuilder::new() }
 
-    // Internal method creating the URL for single key endpoints
-    fn single_url(&self) -> Result<Url> {
-        match &self.request.target {
-            Some(RequestTarget::Single { api, directory }) => Ok(self
-                .request
-                .endpoint_url
-                .join(&format!("{}/base64:{}/", api, directory.encoded()))?),
-            _ => Err(RestrictError::IncorrectRestrictRequestTarget)?,
-        }
-    }
-
-    // Internal method creating the URL for all range endpoints
-    fn range_url(&self) -> Result<Url> {
-        match &self.request.target {
-            Some(RequestTarget::AllRange { from_api, context }) => {
-                let mut url = self.request.endpoint_url.clone();
-
-                match (from_api, context.from()) {
-                    (None, None) => Ok(url),
-                    (Some(api), Some(from)) => {
-                        url.set_query(Some(&format!(
-                            "from_api={}&from=base64:{}",
-                            api,
-                            from.encoded()
-                        )));
-                        Ok(url)
-                    }
-                    (..) => Err(RestrictError::IncorrectRestrictRequestTarget)?,
-                }
-            }
-
-            Some(RequestTarget::ApiRange { api, context }) => {
-                let api = Api::from_str(api)
-                    .map_err(|_| RestrictError::IncorrectRestrictRequestTarget)?;
-
-                let mut url = self.request.endpoint_url.join(&format!("{api}/"))?;
-
-                match context.from() {
-                    None => Ok(url),
-                    Some(from) => {
-                        url.set_query(Some(&format!("from=base64:{}", from.encoded())));
-                        Ok(url)
-                    }
-                }
-            }
-            _ => Err(RestrictError::IncorrectRestrictRequestTarget)?,
-        }
-    }
-
     /// Returns restriction details for an API-directory combination
     ///
     /// **NOTE:** This endpoint requires the `RequestTarget` be a `Single`.
@@ -187,14 +175,9 @@ impl RestrictRequest {
     /// dbg!(resp);
     /// ```
     pub fn get_restriction(&self) -> Result<Restriction> {
-        let url = self.single_url()?;
-        let resp = self
-            .request
-            .client
-            .get(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-        map_api_error(resp)?
+        let url = single_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))?
             .json::<Restriction>()
             .map_err(Into::into)
     }
@@ -284,37 +267,54 @@ impl RestrictRequest {
 
     /// ```
     pub fn get_page(&self) -> Result<RestrictionRange> {
-        match &self.request.target {
-            None | Some(RequestTarget::Single { .. }) => {
-                Err(RestrictError::IncorrectRestrictRequestTarget)?
-            }
-            Some(RequestTarget::ApiRange { .. }) => {
-                let url = self.range_url()?;
-
-                let resp = self
-                    .request
-                    .client
-                    .get(url)
-                    .bearer_auth(&self.request.token)
-                    .send()?;
-                map_api_error(resp)?
-                    .json::<RestrictionRange>()
-                    .map_err(Into::into)
-            }
-            Some(RequestTarget::AllRange { .. }) => {
-                let url = self.range_url()?;
-
-                let resp = self
-                    .request
-                    .client
-                    .get(url)
-                    .bearer_auth(&self.request.token)
-                    .send()?;
-                map_api_error(resp)?
-                    .json::<RestrictionRange>()
-                    .map_err(Into::into)
-            }
-        }
+        let url = range_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))?
+            .json::<RestrictionRange>()
+            .map_err(Into::into)
+    }
+
+    /// Returns an iterator over the pages of restrictions for the given directory, from the `from`
+    /// key onwards. Each page is only requested once the previous one has been consumed.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    pub fn pages(&mut self) -> Pages<'_, RestrictionRange> {
+        Pages::new(move || {
+            let page = self.get_page()?;
+            let more = match &page.next_key {
+                Some(next_key) => {
+                    set_next_page(&mut self.request, page.next_api, next_key.clone())?;
+                    true
+                }
+                None => false,
+            };
+            Ok((page, more))
+        })
+    }
+
+    /// Returns an iterator over the individual restrictions for the given directory, from the
+    /// `from` key onwards. Each page is only requested once the entries of the previous one
+    /// have been consumed, so iteration may stop early without requesting the remaining pages.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Range`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use seaplane::api::{restrict::v1::RestrictRequestBuilder, shared::v1::RangeQueryContext};
+    ///
+    /// let mut req = RestrictRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .api_range("config", RangeQueryContext::new())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// for restriction in req.entries() {
+    ///     let restriction = restriction.unwrap();
+    ///     dbg!(restriction.directory);
+    /// }
+    /// ```
+    pub fn entries(&mut self) -> Entries<'_, Restriction> {
+        Entries::new(self.pages(), |page| page.restrictions)
     }
 
     /// Returns all restrictions within for a tenant or API.
@@ -365,35 +365,7 @@ impl RestrictRequest {
     /// let resp = req.get_all_pages().unwrap();
     /// dbg!(resp);
     /// ```
-
-    // TODO: Replace this with a collect on a Pages/Entries iterator
-    pub fn get_all_pages(&mut self) -> Result<Vec<Restriction>> {
-        let mut pages = Vec::new();
-        loop {
-            let mut rr = self.get_page()?;
-            pages.append(&mut rr.restrictions);
-            if let Some(next_key) = rr.next_key {
-                match &mut self.request.target {
-                    None | Some(RequestTarget::Single { .. }) => {
-                        Err(RestrictError::IncorrectRestrictRequestTarget)?
-                    }
-                    Some(RequestTarget::ApiRange { api: _, context }) => {
-                        context.set_from(next_key);
-                    }
-                    Some(RequestTarget::AllRange { from_api: _, context }) => {
-                        context.set_from(next_key);
-                        self.request.target = Some(RequestTarget::AllRange {
-                            from_api: rr.next_api.map(|a| a.to_string()),
-                            context: context.to_owned(),
-                        });
-                    }
-                }
-            } else {
-                break;
-            }
-        }
-        Ok(pages)
-    }
+    pub fn get_all_pages(&mut self) -> Result<Vec<Restriction>> { self.entries().collect() }
 
     /// Sets a restriction for an API-directory combination
     ///
@@ -424,16 +396,15 @@ impl RestrictRequest {
     /// dbg!(resp);
     /// ```
     pub fn set_restriction(&self, details: RestrictionDetails) -> Result<()> {
-        let url = self.single_url()?;
-        let resp = self
+        let url = single_url(&self.request)?;
+        let req = self
             .request
             .client
             .put(url)
-            .bearer_auth(&self.request.token)
             .header(CONTENT_TYPE, header::HeaderValue::from_static("application/json"))
-            .body(serde_json::to_string(&details)?)
-            .send()?;
-        map_api_error(resp)?
+            .body(serde_json::to_string(&details)?);
+        self.request
+            .send(req)?
             .text()
             .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
             .map_err(Into::into)
@@ -458,16 +429,83 @@ impl RestrictRequest {
     /// dbg!(resp);
     /// ```
     pub fn delete_restriction(&self) -> Result<()> {
-        let url = self.single_url()?;
-        let resp = self
-            .request
-            .client
-            .delete(url)
-            .bearer_auth(&self.request.token)
-            .send()?;
-        map_api_error(resp)?
+        let url = single_url(&self.request)?;
+        self.request
+            .send(self.request.client.delete(url))?
             .text()
             .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
             .map_err(Into::into)
     }
 }
+
+// Internal function creating the URL for single key endpoints
+fn single_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        Some(RequestTarget::Single { api, directory }) => Ok(request
+            .endpoint_url
+            .join(&format!("{}/base64:{}/", api, directory.encoded()))?),
+        _ => Err(RestrictError::IncorrectRestrictRequestTarget)?,
+    }
+}
+
+// Internal function creating the URL for all range endpoints
+fn range_url<C>(request: &Request<RequestTarget, C>) -> Result<Url> {
+    match &request.target {
+        Some(RequestTarget::AllRange { from_api, context }) => {
+            let mut url = request.endpoint_url.clone();
+
+            match (from_api, context.from()) {
+                (None, None) => Ok(url),
+                (Some(api), Some(from)) => {
+                    url.set_query(Some(&format!(
+                        "from_api={}&from=base64:{}",
+                        api,
+                        from.encoded()
+                    )));
+                    Ok(url)
+                }
+                (..) => Err(RestrictError::IncorrectRestrictRequestTarget)?,
+            }
+        }
+
+        Some(RequestTarget::ApiRange { api, context }) => {
+            let api =
+                Api::from_str(api).map_err(|_| RestrictError::IncorrectRestrictRequestTarget)?;
+
+            let mut url = request.endpoint_url.join(&format!("{api}/"))?;
+
+            match context.from() {
+                None => Ok(url),
+                Some(from) => {
+                    url.set_query(Some(&format!("from=base64:{}", from.encoded())));
+                    Ok(url)
+                }
+            }
+        }
+        _ => Err(RestrictError::IncorrectRestrictRequestTarget)?,
+    }
+}
+
+// Internal function moving a range request on to the page beginning at `next_api` and `next_key`
+fn set_next_page<C>(
+    request: &mut Request<RequestTarget, C>,
+    next_api: Option<Api>,
+    next_key: RestrictedDirectory,
+) -> Result<()> {
+    match &mut request.target {
+        None | Some(RequestTarget::Single { .. }) => {
+            Err(RestrictError::IncorrectRestrictRequestTarget)?
+        }
+        Some(RequestTarget::ApiRange { api: _, context }) => {
+            context.set_from(next_key);
+        }
+        Some(RequestTarget::AllRange { from_api: _, context }) => {
+            context.set_from(next_key);
+            request.target = Some(RequestTarget::AllRange {
+                from_api: next_api.map(|a| a.to_string()),
+                context: context.to_owned(),
+            });
+        }
+    }
+    Ok(())
+}
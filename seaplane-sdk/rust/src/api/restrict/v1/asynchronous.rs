@@ -0,0 +1,135 @@
+use reqwest::header::{self, CONTENT_TYPE};
+
+use super::{range_url, set_next_page, single_url, RestrictRequestBuilder};
+use crate::{
+    api::{
+        restrict::v1::{RequestTarget, Restriction, RestrictionDetails, RestrictionRange},
+        AsyncApiRequest,
+    },
+    error::Result,
+};
+
+impl RestrictRequestBuilder {
+    /// Build an AsyncRestrictRequest from the given parameters
+    #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+    pub fn build_async(self) -> Result<AsyncRestrictRequest> {
+        Ok(self.builder.build_async()?.into())
+    }
+}
+
+/// For making async requests against the `/restrict` APIs.
+///
+/// The async equivalent of [`RestrictRequest`](super::RestrictRequest), requires a `tokio`
+/// runtime.
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[derive(Debug)]
+pub struct AsyncRestrictRequest {
+    request: AsyncApiRequest<RequestTarget>,
+}
+
+impl From<AsyncApiRequest<RequestTarget>> for AsyncRestrictRequest {
+    fn from(request: AsyncApiRequest<RequestTarget>) -> Self { Self { request } }
+}
+
+impl AsyncRestrictRequest {
+    /// Create a new request builder
+    pub fn builder() -> RestrictRequestBuilder { RestrictRequestBuilder::new() }
+
+    /// Returns restriction details for an API-directory combination
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Single`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use seaplane::api::restrict::v1::RestrictRequestBuilder;
+    ///
+    /// # async fn example() {
+    /// let req = RestrictRequestBuilder::new()
+    ///     .token("abc123_token")
+    ///     .single_restriction("config", "bW9ieQo")
+    ///     .build_async()
+    ///     .unwrap();
+    ///
+    /// let resp = req.get_restriction().await.unwrap();
+    /// dbg!(resp);
+    /// # }
+    /// ```
+    pub async fn get_restriction(&self) -> Result<Restriction> {
+        let url = single_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<Restriction>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns a single page of restrictions, starting from `from_api` and
+    /// `from_key` combination.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be an `ApiRange` or
+    /// `AllRange`.
+    pub async fn get_page(&self) -> Result<RestrictionRange> {
+        let url = range_url(&self.request)?;
+        self.request
+            .send(self.request.client.get(url))
+            .await?
+            .json::<RestrictionRange>()
+            .await
+            .map_err(Into::into)
+    }
+
+    /// Returns all restrictions within for a tenant or API.
+    /// May perform multiple requests.
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `ApiRange` or
+    /// `AllRange`.
+    pub async fn get_all_pages(&mut self) -> Result<Vec<Restriction>> {
+        let mut pages = Vec::new();
+        loop {
+            let mut rr = self.get_page().await?;
+            pages.append(&mut rr.restrictions);
+            if let Some(next_key) = rr.next_key {
+                set_next_page(&mut self.request, rr.next_api, next_key)?;
+            } else {
+                break;
+            }
+        }
+        Ok(pages)
+    }
+
+    /// Sets a restriction for an API-directory combination
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Single`.
+    pub async fn set_restriction(&self, details: RestrictionDetails) -> Result<()> {
+        let url = single_url(&self.request)?;
+        let req = self
+            .request
+            .client
+            .put(url)
+            .header(CONTENT_TYPE, header::HeaderValue::from_static("application/json"))
+            .body(serde_json::to_string(&details)?);
+        self.request
+            .send(req)
+            .await?
+            .text()
+            .await
+            .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
+            .map_err(Into::into)
+    }
+
+    /// Removes a restriction for an API-directory combination
+    ///
+    /// **NOTE:** This endpoint requires the `RequestTarget` be a `Single`.
+    pub async fn delete_restriction(&self) -> Result<()> {
+        let url = single_url(&self.request)?;
+        self.request
+            .send(self.request.client.delete(url))
+            .await?
+            .text()
+            .await
+            .map(|_| ()) // TODO: for now we drop the "success" message to control it ourselves
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,348 @@
+//! Retrying requests which failed due to transient errors
+
+// Without any of the API features there are no requests to retry, leaving all but the policy
+// itself unused
+#![cfg_attr(
+    not(any(
+        feature = "compute_api_v2",
+        feature = "locks_api_v1",
+        feature = "metadata_api_v1",
+        feature = "restrict_api_v1",
+    )),
+    allow(dead_code)
+)]
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+
+#[cfg(feature = "blocking")]
+use crate::api::map_api_error;
+#[cfg(feature = "async")]
+use crate::api::map_api_error_async;
+use crate::error::{Result, SeaplaneError};
+
+/// Controls how requests which fail due to transient errors are retried.
+///
+/// A request is retried when the connection fails or times out, or when the server responds with
+/// HTTP 429 TOO MANY REQUESTS or a 5xx status (other than 501 NOT IMPLEMENTED). Between attempts
+/// the delay starts at `base_delay` and doubles each time up to `max_delay`. When the response
+/// includes a `Retry-After` header that delay is used instead, unless it is longer than
+/// `max_delay` in which case the request is not retried.
+///
+/// By default only idempotent requests (`GET`, `PUT` and `DELETE`) are retried, so for example
+/// creating a Formation or acquiring a Lock is attempted only once unless `retry_non_idempotent`
+/// is set. Conditional requests, which have an `If-Match` or `If-None-Match` header such as the
+/// conditional writes of the Metadata API, are not idempotent either.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use seaplane::api::{metadata::v1::MetadataRequest, RetryPolicy};
+///
+/// let policy = RetryPolicy::new()
+///     .max_attempts(5)
+///     .base_delay(Duration::from_millis(500))
+///     .max_delay(Duration::from_secs(30));
+///
+/// let req = MetadataRequest::builder()
+///     .token("abc123")
+///     .encoded_key("bW9ieQo")
+///     .retry(policy);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    jitter: bool,
+    retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a new `Default` policy which makes up to 3 attempts of idempotent requests
+    pub fn new() -> Self { Self::default() }
+
+    /// A policy which never retries requests
+    pub fn none() -> Self { Self::default().max_attempts(1) }
+
+    /// The maximum number of times a request is attempted, including the first (default: `3`)
+    ///
+    /// **NOTE:** A value of `0` is treated as `1`
+    #[must_use]
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// The delay before the first retry, which doubles with each subsequent retry (default:
+    /// `250ms`)
+    #[must_use]
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// The longest delay between attempts (default: `10s`)
+    #[must_use]
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Randomize each delay to between half and all of its value so that many clients failing
+    /// at once don't retry in lockstep (default: `true`)
+    #[must_use]
+    pub fn jitter(mut self, yes: bool) -> Self {
+        self.jitter = yes;
+        self
+    }
+
+    /// Also retry requests which are not idempotent, such as `POST` and `PATCH` (default:
+    /// `false`)
+    ///
+    /// **NOTE:** A retried request may be applied more than once if the original request reached
+    /// the server but the response did not reach the client
+    #[must_use]
+    pub fn retry_non_idempotent(mut self, yes: bool) -> Self {
+        self.retry_non_idempotent = yes;
+        self
+    }
+
+    // Whether a request which has been attempted `attempt` times may be attempted again
+    fn may_retry(&self, attempt: u32, idempotent: bool) -> bool {
+        attempt < self.max_attempts && (idempotent || self.retry_non_idempotent)
+    }
+
+    // How long to wait before retrying a request which received the given response, or `None` if
+    // it should not be retried
+    fn response_delay(
+        &self,
+        attempt: u32,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        if !is_transient(status) {
+            return None;
+        }
+        match retry_after(headers) {
+            Some(delay) if delay > self.max_delay => None,
+            Some(delay) => Some(delay),
+            None => Some(self.backoff(attempt)),
+        }
+    }
+
+    // How long to wait before retrying a request which failed with the given error, or `None` if
+    // it should not be retried
+    fn error_delay(&self, attempt: u32, err: &SeaplaneError) -> Option<Duration> {
+        match err {
+            SeaplaneError::UnknownHttp(e) if e.is_request() || e.is_timeout() => {
+                Some(self.backoff(attempt))
+            }
+            _ => None,
+        }
+    }
+
+    // The exponential backoff after the given attempt
+    fn backoff(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(random_fraction())
+        } else {
+            delay
+        }
+    }
+}
+
+// Whether the status indicates a failure that may succeed if retried
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+}
+
+// Reads a `Retry-After` header given as either a number of seconds or an HTTP date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(SystemTime::now()).unwrap_or_default())
+}
+
+// A pseudo-random number in `[0, 1)`, which is plenty for spreading out retries
+fn random_fraction() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+/// Sends the request with `send`, retrying according to the policy and mapping any error
+/// responses
+#[cfg(feature = "blocking")]
+pub(crate) fn send_with_retry<F>(
+    policy: &RetryPolicy,
+    idempotent: bool,
+    mut req: reqwest::blocking::RequestBuilder,
+    send: F,
+) -> Result<reqwest::blocking::Response>
+where
+    F: Fn(reqwest::blocking::RequestBuilder) -> Result<reqwest::blocking::Response>,
+{
+    let mut attempt = 1;
+    loop {
+        let retry = if policy.may_retry(attempt, idempotent) { req.try_clone() } else { None };
+        let res = send(req);
+        let delay = match (&res, &retry) {
+            (_, None) => None,
+            (Ok(resp), _) => policy.response_delay(attempt, resp.status(), resp.headers()),
+            (Err(e), _) => policy.error_delay(attempt, e),
+        };
+        match (delay, retry) {
+            (Some(delay), Some(retry)) => {
+                std::thread::sleep(delay);
+                req = retry;
+                attempt += 1;
+            }
+            _ => return map_api_error(res?),
+        }
+    }
+}
+
+/// Sends the request with `send`, retrying according to the policy and mapping any error
+/// responses
+#[cfg(feature = "async")]
+pub(crate) async fn send_with_retry_async<F, Fut>(
+    policy: &RetryPolicy,
+    idempotent: bool,
+    mut req: reqwest::RequestBuilder,
+    send: F,
+) -> Result<reqwest::Response>
+where
+    F: Fn(reqwest::RequestBuilder) -> Fut,
+    Fut: std::future::Future<Output = Result<reqwest::Response>>,
+{
+    let mut attempt = 1;
+    loop {
+        let retry = if policy.may_retry(attempt, idempotent) { req.try_clone() } else { None };
+        let res = send(req).await;
+        let delay = match (&res, &retry) {
+            (_, None) => None,
+            (Ok(resp), _) => policy.response_delay(attempt, resp.status(), resp.headers()),
+            (Err(e), _) => policy.error_delay(attempt, e),
+        };
+        match (delay, retry) {
+            (Some(delay), Some(retry)) => {
+                tokio::time::sleep(delay).await;
+                req = retry;
+                attempt += 1;
+            }
+            _ => return map_api_error_async(res?).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(retry_after: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(retry_after).unwrap());
+        headers
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_max() {
+        let policy = RetryPolicy::new()
+            .base_delay(Duration::from_secs(1))
+            .max_delay(Duration::from_secs(5))
+            .jitter(false);
+        assert_eq!(policy.backoff(1), Duration::from_secs(1));
+        assert_eq!(policy.backoff(2), Duration::from_secs(2));
+        assert_eq!(policy.backoff(3), Duration::from_secs(4));
+        assert_eq!(policy.backoff(4), Duration::from_secs(5));
+        assert_eq!(policy.backoff(100), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn backoff_jitter() {
+        let policy = RetryPolicy::new().base_delay(Duration::from_secs(4));
+        for _ in 0..100 {
+            let delay = policy.backoff(1);
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+    }
+
+    #[test]
+    fn may_retry() {
+        let policy = RetryPolicy::new();
+        assert!(policy.may_retry(1, true));
+        assert!(policy.may_retry(2, true));
+        assert!(!policy.may_retry(3, true));
+        assert!(!policy.may_retry(1, false));
+        assert!(policy.retry_non_idempotent(true).may_retry(1, false));
+        assert!(!RetryPolicy::none().may_retry(1, true));
+        assert!(!RetryPolicy::new().max_attempts(0).may_retry(1, true));
+    }
+
+    #[test]
+    fn response_delay() {
+        let policy = RetryPolicy::new().max_delay(Duration::from_secs(60));
+        let empty = HeaderMap::new();
+        assert!(policy
+            .response_delay(1, StatusCode::SERVICE_UNAVAILABLE, &empty)
+            .is_some());
+        assert!(policy
+            .response_delay(1, StatusCode::TOO_MANY_REQUESTS, &empty)
+            .is_some());
+        assert!(policy
+            .response_delay(1, StatusCode::NOT_IMPLEMENTED, &empty)
+            .is_none());
+        assert!(policy
+            .response_delay(1, StatusCode::NOT_FOUND, &empty)
+            .is_none());
+        assert_eq!(
+            policy.response_delay(1, StatusCode::TOO_MANY_REQUESTS, &headers("30")),
+            Some(Duration::from_secs(30))
+        );
+        // Longer than the max delay
+        assert_eq!(policy.response_delay(1, StatusCode::TOO_MANY_REQUESTS, &headers("90")), None);
+    }
+
+    #[test]
+    fn retry_after_http_date() {
+        let at = SystemTime::now() + Duration::from_secs(120);
+        let delay = retry_after(&headers(&httpdate::fmt_http_date(at))).unwrap();
+        assert!(delay > Duration::from_secs(110) && delay <= Duration::from_secs(120));
+        // Dates in the past mean retry immediately
+        assert_eq!(retry_after(&headers("Wed, 21 Oct 2015 07:28:00 GMT")), Some(Duration::ZERO));
+        assert_eq!(retry_after(&headers("soon")), None);
+    }
+}
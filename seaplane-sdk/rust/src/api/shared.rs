@@ -1,7 +1,7 @@
 #[cfg_attr(
     docsrs,
     doc(cfg(any(
-        feature = "compute_api_v1",
+        feature = "compute_api_v2",
         feature = "locks_api_v1",
         feature = "metadata_api_v1",
         feature = "restrict_api_v1"
@@ -1,4 +1,8 @@
 mod models;
 pub use models::*;
+#[cfg(feature = "blocking")]
+mod pages;
+#[cfg(feature = "blocking")]
+pub use pages::*;
 mod range_query;
 pub use range_query::*;
@@ -49,7 +49,18 @@ mod test_provider {
 
 /// A regulatory region used to restrict data placement
 #[derive(
-    strum::Display, EnumString, Debug, Serialize, Hash, Eq, PartialEq, PartialOrd, Ord, Copy, Clone,
+    strum::Display,
+    EnumString,
+    EnumVariantNames,
+    Debug,
+    Serialize,
+    Hash,
+    Eq,
+    PartialEq,
+    PartialOrd,
+    Ord,
+    Copy,
+    Clone,
 )]
 #[allow(clippy::upper_case_acronyms)]
 #[strum(ascii_case_insensitive)]
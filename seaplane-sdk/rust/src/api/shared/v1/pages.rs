@@ -0,0 +1,155 @@
+//! Lazily iterating over paged responses, requesting each page only once it's needed
+
+use std::fmt;
+
+use crate::error::Result;
+
+/// An iterator over the pages of a range request, requesting each page only once the previous one
+/// has been consumed.
+///
+/// If a page can't be retrieved the error is returned in place of the page and iteration ends.
+pub struct Pages<'a, P> {
+    // Requests the current page, moving on to the next and returning whether there is one
+    fetch: Box<dyn FnMut() -> Result<(P, bool)> + 'a>,
+    done: bool,
+}
+
+impl<'a, P> Pages<'a, P> {
+    pub(crate) fn new<F>(fetch: F) -> Self
+    where
+        F: FnMut() -> Result<(P, bool)> + 'a,
+    {
+        Self { fetch: Box::new(fetch), done: false }
+    }
+}
+
+impl<P> fmt::Debug for Pages<'_, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pages").field("done", &self.done).finish()
+    }
+}
+
+impl<P> Iterator for Pages<'_, P> {
+    type Item = Result<P>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match (self.fetch)() {
+            Ok((page, more)) => {
+                self.done = !more;
+                Some(Ok(page))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// An iterator over the individual entries of each page of a range request, requesting each page
+/// only once the entries of the previous one have been consumed.
+///
+/// If a page can't be retrieved the error is returned in place of its entries and iteration ends.
+pub struct Entries<'a, T> {
+    pages: Box<dyn Iterator<Item = Result<Vec<T>>> + 'a>,
+    page: std::vec::IntoIter<T>,
+}
+
+impl<'a, T> Entries<'a, T> {
+    pub(crate) fn new<P, F>(pages: Pages<'a, P>, mut entries: F) -> Self
+    where
+        P: 'a,
+        F: FnMut(P) -> Vec<T> + 'a,
+    {
+        Self {
+            pages: Box::new(pages.map(move |page| page.map(&mut entries))),
+            page: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Entries<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Entries").field("page", &self.page).finish()
+    }
+}
+
+impl<T> Iterator for Entries<'_, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(Ok(entry));
+            }
+            match self.pages.next()? {
+                Ok(page) => self.page = page.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod pages_tests {
+    use super::*;
+    use crate::error::SeaplaneError;
+
+    // Pages of two numbers, up to `last`
+    fn numbers(last: u32) -> Pages<'static, Vec<u32>> {
+        let mut start = 0;
+        Pages::new(move || {
+            let page: Vec<u32> = (start..(start + 2).min(last + 1)).collect();
+            start += 2;
+            Ok((page, start <= last))
+        })
+    }
+
+    #[test]
+    fn pages() {
+        assert_eq!(
+            numbers(4).collect::<Result<Vec<_>>>().unwrap(),
+            vec![vec![0, 1], vec![2, 3], vec![4]]
+        );
+    }
+
+    #[test]
+    fn entries() {
+        assert_eq!(
+            Entries::new(numbers(4), |p| p)
+                .collect::<Result<Vec<_>>>()
+                .unwrap(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[test]
+    fn lazy() {
+        let mut fetched = 0;
+        let pages = Pages::new(|| {
+            fetched += 1;
+            Ok((vec![fetched], true))
+        });
+        assert_eq!(Entries::new(pages, |p| p).take(3).count(), 3);
+        assert_eq!(fetched, 3);
+    }
+
+    #[test]
+    fn stops_after_error() {
+        let mut fetched = 0;
+        let mut pages = Pages::new(|| {
+            fetched += 1;
+            if fetched == 2 {
+                Err(SeaplaneError::MissingRequestAuthToken)
+            } else {
+                Ok((fetched, true))
+            }
+        });
+        assert_eq!(pages.next().unwrap().unwrap(), 1);
+        assert_eq!(pages.next().unwrap().unwrap_err(), SeaplaneError::MissingRequestAuthToken);
+        assert!(pages.next().is_none());
+    }
+}
@@ -21,12 +21,15 @@
 
 use thiserror::Error as ThisError;
 
-use crate::api::ApiError;
 #[cfg(feature = "compute_api_v2")]
-use crate::api::{
-    compute::error::ComputeError, locks::error::LocksError, metadata::error::MetadataError,
-    restrict::error::RestrictError,
-};
+use crate::api::compute::error::ComputeError;
+#[cfg(feature = "locks_api_v1")]
+use crate::api::locks::error::LocksError;
+#[cfg(feature = "metadata_api_v1")]
+use crate::api::metadata::error::MetadataError;
+#[cfg(feature = "restrict_api_v1")]
+use crate::api::restrict::error::RestrictError;
+use crate::api::ApiError;
 
 pub type Result<T> = std::result::Result<T, SeaplaneError>;
 
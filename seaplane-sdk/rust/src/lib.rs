@@ -19,7 +19,7 @@
 #![forbid(unsafe_code)]
 
 #[cfg(any(
-    feature = "compute_api_v1",
+    feature = "compute_api_v2",
     feature = "locks_api_v1",
     feature = "metadata_api_v1",
     feature = "restrict_api_v1"
@@ -0,0 +1,79 @@
+use httpmock::prelude::*;
+use seaplane::api::{
+    locks::v1::{LockId, LockInfo, LockInfoInner, LockName},
+    metadata::v1::Value,
+    SeaplaneClient,
+};
+use serde_json::json;
+
+use super::MOCK_SERVER;
+
+fn build_client(api_key: &str) -> SeaplaneClient {
+    let url: url::Url = MOCK_SERVER.base_url().parse().unwrap();
+    SeaplaneClient::builder()
+        .api_key(api_key)
+        .identity_url(url.clone())
+        .metadata_url(url.clone())
+        .locks_url(url)
+        .build()
+        .unwrap()
+}
+
+// Requests handed out by the client share its endpoints and access token
+#[test]
+fn shared_access_token() {
+    let mut token = MOCK_SERVER.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/token")
+            .header("authorization", "Bearer client-key");
+        then.status(201)
+            .json_body(json!({"token": "abc.Y2xpZW50.def", "tenant": "tnt-abcdef1234567890", "subdomain": "pequod"}));
+    });
+    let mut value = MOCK_SERVER.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/config/base64:Y2xpZW50")
+            .header("authorization", "Bearer abc.Y2xpZW50.def");
+        then.status(200)
+            .json_body(json!({"key": "Y2xpZW50", "value": "b2s"}));
+    });
+    let lock_info = LockInfo {
+        name: LockName::from_encoded("Y2xpZW50"),
+        id: LockId::from_encoded("D4lbVpdBE_U"),
+        info: LockInfoInner {
+            ttl: 5,
+            client_id: "test-client".to_string(),
+            ip: "192.0.2.137".to_string(),
+        },
+    };
+    let mut lock = MOCK_SERVER.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/locks/base64:Y2xpZW50")
+            .header("authorization", "Bearer abc.Y2xpZW50.def");
+        then.status(200).json_body(json!(lock_info));
+    });
+
+    let client = build_client("client-key");
+    let resp = client
+        .metadata()
+        .encoded_key("Y2xpZW50")
+        .build()
+        .unwrap()
+        .get_value()
+        .unwrap();
+    assert_eq!(resp, Value::from_encoded("b2s"));
+    let resp = client
+        .locks()
+        .encoded_lock_name("Y2xpZW50")
+        .build()
+        .unwrap()
+        .get_lock_info()
+        .unwrap();
+    assert_eq!(resp, lock_info);
+
+    token.assert_hits(1);
+    value.assert();
+    lock.assert();
+    token.delete();
+    value.delete();
+    lock.delete();
+}
@@ -1,5 +1,14 @@
+use std::time::Duration;
+
 use httpmock::{prelude::*, Method, Then, When};
-use seaplane::api::compute::v2::{response::*, Flight, Formation, FormationsRequest};
+use seaplane::{
+    api::compute::{
+        error::ComputeError,
+        v2::{response::*, Flight, FlightStatus, Formation, FormationsRequest},
+    },
+    error::SeaplaneError,
+};
+use serde_json::json;
 
 use super::MOCK_SERVER;
 
@@ -87,6 +96,50 @@ fn get_all_formations() {
     assert_eq!(resp, resp_t);
 }
 
+// GET /formations following `meta.next`
+#[test]
+fn get_formation_entries() {
+    let formation = |name: &str| {
+        json!({
+            "name": name,
+            "oid": "frm-sjt5inobm97i317b95uerqv080",
+            "flights": [],
+            "gateway-flight": null
+        })
+    };
+    let mut first = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/paged/v2beta/formations");
+        then(
+            t,
+            json!({
+                "objects": [formation("first")],
+                "meta": {"total": 2, "next": MOCK_SERVER.url("/paged/v2beta/formations/next"), "prev": null}
+            }),
+        );
+    });
+    let mut second = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/paged/v2beta/formations/next");
+        then(
+            t,
+            json!({"objects": [formation("second")], "meta": {"total": 2, "next": null, "prev": null}}),
+        );
+    });
+
+    let req = FormationsRequest::builder()
+        .token("abc123")
+        .base_url(MOCK_SERVER.url("/paged/"))
+        .build()
+        .unwrap();
+    let names: Vec<String> = req.entries().map(|f| f.unwrap().name).collect();
+
+    first.assert();
+    second.assert();
+    assert_eq!(names, vec!["first".to_string(), "second".to_string()]);
+
+    first.delete();
+    second.delete();
+}
+
 // GET /formations/OID
 #[test]
 fn get_formation() {
@@ -143,6 +196,48 @@ fn create_formation() {
     mock.assert();
 }
 
+#[test]
+fn create_formation_unresolved_secret() {
+    let mut frm = build_formation();
+    frm.add_flight(
+        Flight::builder()
+            .name("flask")
+            .image("registry.hub.docker.com/stubb/alpine:latest")
+            .add_secret_env("DB_PASS", "stubb/db-pass")
+            .build()
+            .unwrap(),
+    );
+
+    // Refused before anything is sent, so no mock is needed
+    assert_eq!(
+        build_req(false).create(&frm).unwrap_err(),
+        SeaplaneError::ComputeRequest(ComputeError::UnresolvedSecret("flask".into()))
+    );
+}
+
+// PUT /formations/ID
+#[test]
+fn update_formation() {
+    let mut frm = build_formation();
+    frm.oid = Some("frm-sjt5inobm97i317b95uerqv080".parse().unwrap());
+    let resp_body = serde_json::to_value(&frm).unwrap();
+
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v2beta/formations/frm-sjt5inobm97i317b95uerqv080")
+            .header("content-type", "application/json")
+            .json_body_obj(&build_formation());
+        then(t, resp_body);
+    });
+
+    let resp = build_req(true).update(&build_formation()).unwrap();
+
+    // Ensure the endpoint was hit
+    mock.assert();
+    mock.delete();
+
+    assert_eq!(resp, frm);
+}
+
 // DELETE /formations/ID
 #[test]
 fn delete_formation() {
@@ -158,3 +253,198 @@ fn delete_formation() {
     // Ensure the endpoint was hit
     mock.assert();
 }
+
+// A launched Formation whose Flights have the given statuses, along with a request for it
+fn formation_with_statuses(
+    oid: &str,
+    statuses: [FlightStatus; 2],
+) -> (Formation, FormationsRequest) {
+    let mut frm = build_formation();
+    frm.oid = Some(oid.parse().unwrap());
+    for (flight, status) in frm.flights.iter_mut().zip(statuses) {
+        flight.status = status;
+    }
+    let req = FormationsRequest::builder()
+        .token("abc123")
+        .base_url(MOCK_SERVER.base_url())
+        .formation_id(frm.oid.unwrap())
+        .build()
+        .unwrap();
+    (frm, req)
+}
+
+// GET /formations/OID until healthy
+#[test]
+fn wait_until_healthy() {
+    let (frm, req) = formation_with_statuses(
+        "frm-euqecs8n6h5l552ps6skal12lc",
+        [FlightStatus::Healthy, FlightStatus::Healthy],
+    );
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v2beta/formations/frm-euqecs8n6h5l552ps6skal12lc");
+        then(t, serde_json::to_value(&frm).unwrap());
+    });
+
+    let resp = req
+        .wait_until_healthy(Duration::from_millis(10), Duration::from_secs(1))
+        .unwrap();
+
+    mock.assert();
+    mock.delete();
+    assert_eq!(resp, frm);
+}
+
+#[test]
+fn wait_until_healthy_unhealthy() {
+    let (frm, req) = formation_with_statuses(
+        "frm-45rt0pqpo93q78t6au6dd8kn04",
+        [FlightStatus::Starting, FlightStatus::Unhealthy],
+    );
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v2beta/formations/frm-45rt0pqpo93q78t6au6dd8kn04");
+        then(t, serde_json::to_value(&frm).unwrap());
+    });
+
+    let err = req
+        .wait_until_healthy(Duration::from_millis(10), Duration::from_secs(1))
+        .unwrap_err();
+
+    mock.assert();
+    mock.delete();
+    assert_eq!(
+        err,
+        SeaplaneError::ComputeRequest(ComputeError::FormationUnhealthy(vec!["flask".into()]))
+    );
+}
+
+#[test]
+fn wait_until_healthy_timeout() {
+    let (frm, req) = formation_with_statuses(
+        "frm-90mifnt75d4i3a6eevt4po2f7k",
+        [FlightStatus::Healthy, FlightStatus::Starting],
+    );
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v2beta/formations/frm-90mifnt75d4i3a6eevt4po2f7k");
+        then(t, serde_json::to_value(&frm).unwrap());
+    });
+
+    let err = req
+        .wait_until_healthy(Duration::from_millis(10), Duration::from_millis(50))
+        .unwrap_err();
+
+    // The Formation was checked repeatedly before giving up
+    assert!(mock.hits() > 1);
+    mock.delete();
+    assert_eq!(err, SeaplaneError::ComputeRequest(ComputeError::HealthTimeout));
+}
+
+#[cfg(feature = "async")]
+mod async_tests {
+    use seaplane::api::compute::v2::AsyncFormationsRequest;
+
+    use super::{super::block_on, *};
+
+    fn build_req(incl_id: bool) -> AsyncFormationsRequest {
+        let mut bdr = AsyncFormationsRequest::builder()
+            .token("abc123")
+            .base_url(MOCK_SERVER.base_url());
+        if incl_id {
+            bdr = bdr.formation_id("frm-sjt5inobm97i317b95uerqv080".parse().unwrap());
+        }
+        bdr.build_async().unwrap()
+    }
+
+    // GET /formations/OID
+    #[test]
+    fn get_formation() {
+        let mut frm = build_formation();
+        frm.oid = Some("frm-sjt5inobm97i317b95uerqv080".parse().unwrap());
+        let resp_body = serde_json::to_value(&frm).unwrap();
+
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v2beta/formations/frm-sjt5inobm97i317b95uerqv080");
+            then(t, resp_body);
+        });
+
+        let resp = block_on(build_req(true).get()).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(resp, frm);
+    }
+
+    // PUT /formations/ID
+    #[test]
+    fn update_formation() {
+        let mut frm = build_formation();
+        frm.oid = Some("frm-sjt5inobm97i317b95uerqv080".parse().unwrap());
+        let resp_body = serde_json::to_value(&frm).unwrap();
+
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, PUT, "/v2beta/formations/frm-sjt5inobm97i317b95uerqv080")
+                .json_body_obj(&build_formation());
+            then(t, resp_body);
+        });
+
+        let resp = block_on(build_req(true).update(&build_formation())).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(resp, frm);
+    }
+
+    // POST /formations
+    #[test]
+    fn create_formation() {
+        let mut frm = build_formation();
+        frm.oid = Some("frm-sjt5inobm97i317b95uerqv080".parse().unwrap());
+        let resp_body = serde_json::to_value(&frm).unwrap();
+
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, POST, "/v2beta/formations").json_body_obj(&build_formation());
+            then(t.status(201), resp_body);
+        });
+
+        let resp = block_on(build_req(false).create(&build_formation())).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(resp, frm);
+    }
+
+    // GET /formations/OID until healthy
+    #[test]
+    fn wait_until_healthy() {
+        let (frm, _) = formation_with_statuses(
+            "frm-euqecs8n6h5l552ps6skal12lc",
+            [FlightStatus::Healthy, FlightStatus::Healthy],
+        );
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v2beta/formations/frm-euqecs8n6h5l552ps6skal12lc");
+            then(t, serde_json::to_value(&frm).unwrap());
+        });
+
+        let req = AsyncFormationsRequest::builder()
+            .token("abc123")
+            .base_url(MOCK_SERVER.base_url())
+            .formation_id(frm.oid.unwrap())
+            .build_async()
+            .unwrap();
+        let resp =
+            block_on(req.wait_until_healthy(Duration::from_millis(10), Duration::from_secs(1)))
+                .unwrap();
+
+        mock.assert();
+        mock.delete();
+        assert_eq!(resp, frm);
+    }
+}
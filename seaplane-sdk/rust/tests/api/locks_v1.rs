@@ -1,9 +1,12 @@
+use std::time::Duration;
+
 use httpmock::{prelude::*, Method, Then, When};
 use seaplane::api::{
     locks::v1::{
         HeldLock, LockId, LockInfo, LockInfoInner, LockInfoRange, LockName, LocksRequestBuilder,
     },
     shared::v1::{Directory, RangeQueryContext},
+    RetryPolicy,
 };
 use serde_json::json;
 
@@ -213,3 +216,151 @@ fn get_dir() {
 
     assert_eq!(resp_val, resp);
 }
+
+fn lock(name: &str) -> LockInfo {
+    LockInfo {
+        name: LockName::from_encoded(name),
+        id: LockId::from_encoded("D4lbVpdBE_U"),
+        info: LockInfoInner {
+            ttl: 5,
+            client_id: "test-client".to_string(),
+            ip: "192.0.2.137".to_string(),
+        },
+    }
+}
+
+// GET /locks/base64:{dir}/ following `next`
+#[test]
+fn get_entries() {
+    let mut first = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/locks/base64:cGFnZXM/")
+            .matches(|req| req.query_params.as_ref().map_or(true, |q| q.is_empty()));
+        then(
+            t,
+            json!(LockInfoRange {
+                next: Some(LockName::from_encoded("Yw")),
+                locks: vec![lock("YQ"), lock("Yg")]
+            }),
+        );
+    });
+    let mut second = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/locks/base64:cGFnZXM/").query_param("from", "base64:Yw");
+        then(t, json!(LockInfoRange { next: None, locks: vec![lock("Yw")] }));
+    });
+
+    let mut range = RangeQueryContext::new();
+    range.set_directory(Directory::from_encoded("cGFnZXM"));
+
+    // Only the pages which are consumed are requested
+    let mut req = partial_build().range(range.clone()).build().unwrap();
+    let locks: Vec<LockInfo> = req.entries().take(2).collect::<Result<_, _>>().unwrap();
+    assert_eq!(locks, vec![lock("YQ"), lock("Yg")]);
+    first.assert_hits(1);
+    second.assert_hits(0);
+
+    let mut req = partial_build().range(range).build().unwrap();
+    let locks: Vec<LockInfo> = req.entries().map(|l| l.unwrap()).collect();
+    assert_eq!(locks, vec![lock("YQ"), lock("Yg"), lock("Yw")]);
+    first.assert_hits(2);
+    second.assert_hits(1);
+
+    first.delete();
+    second.delete();
+}
+
+// A page which can't be read ends the iteration after its error
+#[test]
+fn get_entries_error() {
+    let mut first = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/locks/base64:YnJva2Vu/")
+            .matches(|req| req.query_params.as_ref().map_or(true, |q| q.is_empty()));
+        then(
+            t,
+            json!(LockInfoRange {
+                next: Some(LockName::from_encoded("Yg")),
+                locks: vec![lock("YQ")]
+            }),
+        );
+    });
+    let mut second = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/locks/base64:YnJva2Vu/").query_param("from", "base64:Yg");
+        t.status(404)
+            .header("content-type", "application/json")
+            .json_body(json!({"status": 404, "title": "Not Found"}));
+    });
+
+    let mut range = RangeQueryContext::new();
+    range.set_directory(Directory::from_encoded("YnJva2Vu"));
+    let mut req = partial_build().range(range).build().unwrap();
+    let mut locks = req.entries();
+    assert_eq!(locks.next().unwrap().unwrap(), lock("YQ"));
+    assert!(locks.next().unwrap().is_err());
+    assert!(locks.next().is_none());
+    drop(locks);
+    first.assert_hits(1);
+    second.assert_hits(1);
+
+    first.delete();
+    second.delete();
+}
+
+// Acquiring a lock is not idempotent so it is not retried by default
+#[test]
+fn acquire_lock_not_retried() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, POST, "/v1/locks/base64:dW5hdmFpbGFibGU");
+        t.status(503)
+            .header("content-type", "application/json")
+            .json_body(json!({"status": 503, "title": "Service Unavailable"}));
+    });
+
+    let req = partial_build()
+        .encoded_lock_name("dW5hdmFpbGFibGU")
+        .build()
+        .unwrap();
+    assert!(req.acquire(10, "test-client").is_err());
+    mock.assert_hits(1);
+
+    let policy = RetryPolicy::new()
+        .retry_non_idempotent(true)
+        .base_delay(Duration::ZERO);
+    let req = partial_build()
+        .encoded_lock_name("dW5hdmFpbGFibGU")
+        .retry(policy)
+        .build()
+        .unwrap();
+    assert!(req.acquire(10, "test-client").is_err());
+    mock.assert_hits(4);
+    mock.delete();
+}
+
+#[cfg(feature = "async")]
+mod async_tests {
+    use super::{super::block_on, *};
+
+    // POST /locks/base64:{key}?ttl={ttl}&client-id={client_id}
+    #[test]
+    fn acquire_lock() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, POST, "/v1/locks/base64:YmF6")
+                .query_param("ttl", "10")
+                .query_param("client-id", "test-client");
+            then(t, json!({"id": "E5mcWqeCF_V", "sequencer": 2}));
+        });
+
+        let req = partial_build()
+            .encoded_lock_name("YmF6")
+            .build_async()
+            .unwrap();
+        let resp = block_on(req.acquire(10, "test-client")).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        let lock =
+            HeldLock::new(LockName::from_encoded("YmF6"), LockId::from_encoded("E5mcWqeCF_V"), 2);
+        assert_eq!(lock, resp);
+    }
+}
@@ -1,8 +1,21 @@
+use std::{ops::ControlFlow, time::Duration};
+
 use httpmock::{prelude::*, Method, Then, When};
-use seaplane::api::{
-    metadata::v1::{Key, KeyValue, MetadataRequestBuilder, Value},
-    shared::v1::{Directory, RangeQueryContext},
+use seaplane::{
+    api::{
+        metadata::{
+            error::MetadataError,
+            v1::{
+                Key, KeyValue, MetadataEvent, MetadataRequestBuilder, MetadataWatcher, TypedKey,
+                Value, MAX_UPDATE_ATTEMPTS, MAX_WATCH_FAILURES,
+            },
+        },
+        shared::v1::{Directory, RangeQueryContext},
+        RetryPolicy,
+    },
+    error::SeaplaneError,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use super::MOCK_SERVER;
@@ -33,6 +46,7 @@ fn get_value() {
     let resp = KeyValue {
         key: Key::from_encoded("Zm9v".to_string()),
         value: Value::from_encoded("Zm9v".to_string()),
+        version: None,
     };
 
     let mock = MOCK_SERVER.mock(|w, t| {
@@ -91,6 +105,45 @@ fn get_values_from() {
     assert_eq!(resp, serde_json::from_value(resp_json).unwrap());
 }
 
+// GET /config/base64:{dir}/ following `next_key`
+#[test]
+fn get_entries() {
+    let mut first = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:cGFnZXM/")
+            .matches(|req| req.query_params.as_ref().map_or(true, |q| q.is_empty()));
+        then(
+            t,
+            json!({"next_key": "Yw", "kvs": [{"key": "YQ", "value": "MQ"}, {"key": "Yg", "value": "Mg"}]}),
+        );
+    });
+    let mut second = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:cGFnZXM/").query_param("from", "base64:Yw");
+        then(t, json!({"next_key": None::<String>, "kvs": [{"key": "Yw", "value": "Mw"}]}));
+    });
+
+    let mut range = RangeQueryContext::new();
+    range.set_directory(Directory::from_encoded("cGFnZXM"));
+
+    // Only the pages which are consumed are requested
+    let mut req = partial_build().range(range.clone()).build().unwrap();
+    let kvs: Vec<KeyValue> = req.entries().take(2).collect::<Result<_, _>>().unwrap();
+    assert_eq!(kvs.len(), 2);
+    first.assert_hits(1);
+    second.assert_hits(0);
+
+    let mut req = partial_build().range(range).build().unwrap();
+    let keys: Vec<Key> = req.entries().map(|kv| kv.unwrap().key).collect();
+    assert_eq!(
+        keys,
+        vec![Key::from_encoded("YQ"), Key::from_encoded("Yg"), Key::from_encoded("Yw")]
+    );
+    first.assert_hits(2);
+    second.assert_hits(1);
+
+    first.delete();
+    second.delete();
+}
+
 // PUT /config/base64:{key}
 #[test]
 fn put_value() {
@@ -147,3 +200,712 @@ fn delete_value() {
 
     assert!(resp.is_ok())
 }
+
+fn precondition_failed(then: Then) -> Then {
+    then.status(412)
+        .header("content-type", "application/json")
+        .json_body(json!({"status": 412, "title": "Precondition Failed"}))
+}
+
+// PUT /config/base64:{key} with If-Match
+#[test]
+fn put_value_if() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:aWZ2ZXJzaW9u")
+            .header("content-type", "application/octet-stream")
+            .header("if-match", "\"3\"")
+            .body("YmFy");
+        then(t, json!({"status": 200, "title": "Ok"}));
+    });
+
+    let req = partial_build().encoded_key("aWZ2ZXJzaW9u").build().unwrap();
+    let resp = req.put_value_if(Value::from_encoded("YmFy"), 3);
+
+    // Ensure the endpoint was hit
+    mock.assert();
+
+    assert!(resp.is_ok())
+}
+
+// A failed precondition is a conflict
+#[test]
+fn put_value_if_conflict() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:aWZjb25mbGljdA").header("if-match", "\"3\"");
+        precondition_failed(t);
+    });
+
+    let req = partial_build()
+        .encoded_key("aWZjb25mbGljdA")
+        .build()
+        .unwrap();
+    let resp = req.put_value_if(Value::from_encoded("YmFy"), 3);
+
+    // Ensure the endpoint was hit, and only once as a conflict isn't transient
+    mock.assert_hits(1);
+
+    assert_eq!(resp, Err(SeaplaneError::MetadataRequest(MetadataError::VersionConflict)));
+}
+
+// PUT /config/base64:{key} with If-None-Match
+#[test]
+fn put_value_if_absent() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:aWZhYnNlbnQ").header("if-none-match", "*");
+        precondition_failed(t);
+    });
+
+    let req = partial_build().encoded_key("aWZhYnNlbnQ").build().unwrap();
+    let resp = req.put_value_if_absent(Value::from_encoded("YmFy"));
+
+    // Ensure the endpoint was hit
+    mock.assert();
+
+    assert_eq!(resp, Err(SeaplaneError::MetadataRequest(MetadataError::VersionConflict)));
+}
+
+// DELETE /config/base64:{key} with If-Match
+#[test]
+fn delete_value_if() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, DELETE, "/v1/config/base64:ZGVsaWY").header("if-match", "\"7\"");
+        then(t, json!({"status": 200, "title": "Ok"}));
+    });
+
+    let req = partial_build().encoded_key("ZGVsaWY").build().unwrap();
+    let resp = req.delete_value_if(7);
+
+    // Ensure the endpoint was hit
+    mock.assert();
+
+    assert!(resp.is_ok())
+}
+
+fn not_found(then: Then) -> Then {
+    then.status(404)
+        .header("content-type", "application/json")
+        .json_body(json!({"status": 404, "title": "Not Found"}))
+}
+
+// GET /config/base64:{dir}/ then DELETE /config/base64:{key} of each key
+#[test]
+fn delete_directory() {
+    let mut list = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:ZG9vbWVk/");
+        then(
+            t,
+            json!({"next_key": None::<String>, "kvs": [
+                {"key": "ZG9vbWVkL2E", "value": "MQ"},
+                {"key": "ZG9vbWVkL2I", "value": "Mg"},
+                {"key": "ZG9vbWVkL2M", "value": "Mw"}
+            ]}),
+        );
+    });
+    let mut deletes: Vec<_> = ["ZG9vbWVkL2E", "ZG9vbWVkL2M"]
+        .into_iter()
+        .map(|key| {
+            MOCK_SERVER.mock(|w, t| {
+                when(w, DELETE, &format!("/v1/config/base64:{key}"));
+                then(t, json!({"status": 200, "title": "Ok"}));
+            })
+        })
+        .collect();
+    // A key which fails to be deleted doesn't stop the others
+    let mut gone = MOCK_SERVER.mock(|w, t| {
+        when(w, DELETE, "/v1/config/base64:ZG9vbWVkL2I");
+        not_found(t);
+    });
+
+    let mut range = RangeQueryContext::new();
+    range.set_directory(Directory::from_encoded("ZG9vbWVk"));
+    let mut req = partial_build().range(range).build().unwrap();
+    let report = req.delete_directory().unwrap();
+
+    list.assert_hits(1);
+    gone.assert_hits(1);
+    assert!(!report.is_complete());
+    assert_eq!(
+        report.deleted,
+        vec![Key::from_encoded("ZG9vbWVkL2E"), Key::from_encoded("ZG9vbWVkL2M")]
+    );
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].0, Key::from_encoded("ZG9vbWVkL2I"));
+    assert!(
+        matches!(&report.failed[0].1, SeaplaneError::ApiResponse(ae) if ae.is_http_not_found())
+    );
+
+    for mock in &mut deletes {
+        mock.assert_hits(1);
+        mock.delete();
+    }
+    list.delete();
+    gone.delete();
+}
+
+fn kv(key: &str, value: &str) -> KeyValue {
+    KeyValue { key: Key::from_encoded(key), value: Value::from_encoded(value), version: None }
+}
+
+// GET /config/base64:{dir}/ every check, comparing it against the last one
+#[test]
+fn watch_directory() {
+    let mut before = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:d2F0Y2hlZA/");
+        then(
+            t,
+            json!({"next_key": None::<String>, "kvs": [
+                {"key": "d2F0Y2hlZC9h", "value": "MQ"},
+                {"key": "d2F0Y2hlZC9i", "value": "Mg"}
+            ]}),
+        );
+    });
+
+    let mut range = RangeQueryContext::new();
+    range.set_directory(Directory::from_encoded("d2F0Y2hlZA"));
+    let req = partial_build().range(range).build().unwrap();
+    let mut watcher = MetadataWatcher::new(req, Duration::from_millis(10)).unwrap();
+
+    // Everything is new to the first check
+    assert_eq!(
+        watcher.poll().unwrap(),
+        vec![
+            MetadataEvent::Added(kv("d2F0Y2hlZC9h", "MQ")),
+            MetadataEvent::Added(kv("d2F0Y2hlZC9i", "Mg"))
+        ]
+    );
+    assert_eq!(watcher.poll().unwrap(), vec![]);
+    before.assert_hits(2);
+    before.delete();
+
+    let mut after = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:d2F0Y2hlZA/");
+        then(
+            t,
+            json!({"next_key": None::<String>, "kvs": [
+                {"key": "d2F0Y2hlZC9i", "value": "Mw"},
+                {"key": "d2F0Y2hlZC9j", "value": "NA"}
+            ]}),
+        );
+    });
+
+    let mut events = Vec::new();
+    watcher
+        .watch(|event| {
+            events.push(event);
+            if events.len() < 3 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        })
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            MetadataEvent::Changed(kv("d2F0Y2hlZC9i", "Mw")),
+            MetadataEvent::Added(kv("d2F0Y2hlZC9j", "NA")),
+            MetadataEvent::Removed(kv("d2F0Y2hlZC9h", "MQ"))
+        ]
+    );
+    after.assert_hits(1);
+    after.delete();
+}
+
+// GET /config/base64:{key} every check, where a key which doesn't exist was removed
+#[test]
+fn watch_key() {
+    let mut before = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:bG9va291dA");
+        then(t, json!({"key": "bG9va291dA", "value": "MQ"}));
+    });
+
+    let build = || partial_build().encoded_key("bG9va291dA").build().unwrap();
+    let mut watcher = MetadataWatcher::new(build(), Duration::from_millis(10)).unwrap();
+    assert_eq!(watcher.poll().unwrap(), vec![MetadataEvent::Added(kv("bG9va291dA", "MQ"))]);
+
+    // Changes are sent from another thread until the receiver is dropped
+    let events = MetadataWatcher::new(build(), Duration::from_millis(10))
+        .unwrap()
+        .spawn();
+    assert_eq!(events.recv().unwrap().unwrap(), MetadataEvent::Added(kv("bG9va291dA", "MQ")));
+    drop(events);
+    before.delete();
+
+    let mut after = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:bG9va291dA");
+        not_found(t);
+    });
+    assert_eq!(watcher.poll().unwrap(), vec![MetadataEvent::Removed(kv("bG9va291dA", "MQ"))]);
+    after.delete();
+}
+
+// A check which fails is tried again, until too many in a row have failed
+#[test]
+fn watch_gives_up() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:Z2l2ZXVw");
+        t.status(501)
+            .header("content-type", "application/json")
+            .json_body(json!({"status": 501, "title": "Not Implemented"}));
+    });
+
+    let req = partial_build().encoded_key("Z2l2ZXVw").build().unwrap();
+    let mut watcher = MetadataWatcher::new(req, Duration::from_millis(10)).unwrap();
+    assert!(watcher.watch(|_| ControlFlow::Continue(())).is_err());
+
+    mock.assert_hits(MAX_WATCH_FAILURES);
+    mock.delete();
+}
+
+// A key which doesn't exist yet is created only if it's still absent
+#[test]
+fn update_value_absent() {
+    let get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:Y291bnRlcg");
+        t.status(404)
+            .header("content-type", "application/json")
+            .json_body(json!({"status": 404, "title": "Not Found"}));
+    });
+    let put = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:Y291bnRlcg")
+            .header("if-none-match", "*")
+            .body("MQ");
+        then(t, json!({"status": 200, "title": "Ok"}));
+    });
+
+    let req = partial_build().encoded_key("Y291bnRlcg").build().unwrap();
+    let written = req
+        .update_value(|current| {
+            assert!(current.is_none());
+            Value::from_unencoded("1")
+        })
+        .unwrap();
+
+    // Ensure the endpoints were hit
+    get.assert();
+    put.assert();
+
+    assert_eq!(written, Value::from_encoded("MQ"));
+}
+
+// A key which keeps changing is read and written again until the attempts run out
+#[test]
+fn update_value_contended() {
+    let get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:Y29udGVuZGVk");
+        then(t, json!({"key": "Y29udGVuZGVk", "value": "MQ", "version": 1}));
+    });
+    let put = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:Y29udGVuZGVk")
+            .header("if-match", "\"1\"")
+            .body("Mg");
+        precondition_failed(t);
+    });
+
+    let req = partial_build().encoded_key("Y29udGVuZGVk").build().unwrap();
+    let mut calls = 0;
+    let resp = req.update_value(|current| {
+        calls += 1;
+        assert_eq!(current, Some(&Value::from_encoded("MQ")));
+        Value::from_unencoded("2")
+    });
+
+    get.assert_hits(MAX_UPDATE_ATTEMPTS);
+    put.assert_hits(MAX_UPDATE_ATTEMPTS);
+    assert_eq!(calls, MAX_UPDATE_ATTEMPTS);
+    assert_eq!(resp, Err(SeaplaneError::MetadataRequest(MetadataError::VersionConflict)));
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Ship {
+    name: String,
+    crew: u32,
+}
+
+// PUT /config/base64:{key} with a JSON value
+#[test]
+fn put_json() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:anNvbg")
+            .header("content-type", "application/octet-stream")
+            .body("eyJuYW1lIjoicGVxdW9kIiwiY3JldyI6MzB9");
+        then(t, json!({"status": 200, "title": "Ok"}));
+    });
+
+    let req = partial_build().encoded_key("anNvbg").build().unwrap();
+    let resp = req.put_json(&Ship { name: "pequod".into(), crew: 30 });
+
+    // Ensure the endpoint was hit
+    mock.assert();
+
+    assert!(resp.is_ok())
+}
+
+// GET /config/base64:{key} with a JSON value
+#[test]
+fn get_json() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:anNvbg");
+        then(t, json!({"key": "anNvbg", "value": "eyJuYW1lIjoicGVxdW9kIiwiY3JldyI6MzB9"}));
+    });
+
+    let req = partial_build().encoded_key("anNvbg").build().unwrap();
+    let ship: Ship = req.get_json().unwrap();
+
+    // Ensure the endpoint was hit
+    mock.assert();
+
+    assert_eq!(ship, Ship { name: "pequod".into(), crew: 30 });
+}
+
+// A value which isn't JSON is a decode error
+#[test]
+fn get_json_invalid() {
+    let mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:bm90anNvbg");
+        then(t, json!({"key": "bm90anNvbg", "value": "bm90IGpzb24"}));
+    });
+
+    let req = partial_build().encoded_key("bm90anNvbg").build().unwrap();
+    let resp = req.get_json::<Ship>();
+
+    // Ensure the endpoint was hit
+    mock.assert();
+
+    assert!(matches!(resp, Err(SeaplaneError::Json(_))));
+}
+
+// Typed keys are read and written without changing the key the request targets
+#[test]
+fn get_put_typed() {
+    let get = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:cmVwbGljYXM");
+        then(t, json!({"key": "cmVwbGljYXM", "value": "Mw"}));
+    });
+    let put = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:cmVwbGljYXM").body("NA");
+        then(t, json!({"status": 200, "title": "Ok"}));
+    });
+
+    let target = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:dGFyZ2V0");
+        then(t, json!({"key": "dGFyZ2V0", "value": "Mw"}));
+    });
+
+    let replicas = TypedKey::<u32>::from_unencoded("replicas");
+    let req = partial_build().encoded_key("dGFyZ2V0").build().unwrap();
+    let count = req.get_typed(&replicas).unwrap();
+    req.put_typed(&replicas, &(count + 1)).unwrap();
+    assert_eq!(req.get_json::<u32>().unwrap(), 3);
+
+    // Ensure the endpoints were hit
+    get.assert();
+    put.assert();
+    target.assert();
+
+    assert_eq!(count, 3);
+}
+
+fn unavailable(then: Then, retry_after: &str) -> Then {
+    then.status(503)
+        .header("content-type", "application/json")
+        .header("retry-after", retry_after)
+        .json_body(json!({"status": 503, "title": "Service Unavailable"}))
+}
+
+// Transient errors are retried up to the max attempts
+#[test]
+fn get_value_retries_unavailable() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:dW5hdmFpbGFibGU");
+        unavailable(t, "0");
+    });
+
+    let req = partial_build()
+        .encoded_key("dW5hdmFpbGFibGU")
+        .retry(RetryPolicy::new().max_attempts(4))
+        .build()
+        .unwrap();
+    assert!(req.get_value().is_err());
+
+    mock.assert_hits(4);
+    mock.delete();
+}
+
+// A conditional write may have succeeded even though its response was lost, so it is not retried
+#[test]
+fn put_value_if_not_retried() {
+    let mut put = MOCK_SERVER.mock(|w, t| {
+        when(w, PUT, "/v1/config/base64:Y29uZGl0aW9uYWw");
+        unavailable(t, "0");
+    });
+    let mut delete = MOCK_SERVER.mock(|w, t| {
+        when(w, DELETE, "/v1/config/base64:Y29uZGl0aW9uYWw");
+        unavailable(t, "0");
+    });
+
+    let req = partial_build()
+        .encoded_key("Y29uZGl0aW9uYWw")
+        .retry(RetryPolicy::new().max_attempts(4))
+        .build()
+        .unwrap();
+    assert!(req.put_value_if(Value::from_encoded("YmFy"), 3).is_err());
+    put.assert_hits(1);
+    assert!(req
+        .put_value_if_absent(Value::from_encoded("YmFy"))
+        .is_err());
+    put.assert_hits(2);
+    assert!(req.delete_value_if(3).is_err());
+    delete.assert_hits(1);
+
+    // Unconditional writes are still retried
+    assert!(req.put_value(Value::from_encoded("YmFy")).is_err());
+    put.assert_hits(6);
+    assert!(req.delete_value().is_err());
+    delete.assert_hits(5);
+    put.delete();
+    delete.delete();
+}
+
+// A Retry-After longer than the max delay is not waited for
+#[test]
+fn get_value_retry_after_too_long() {
+    let mut mock = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/v1/config/base64:dG9vbG9uZw");
+        unavailable(t, "120");
+    });
+
+    let req = partial_build()
+        .encoded_key("dG9vbG9uZw")
+        .retry(RetryPolicy::new().max_delay(Duration::from_secs(1)))
+        .build()
+        .unwrap();
+    assert!(req.get_value().is_err());
+
+    mock.assert_hits(1);
+    mock.delete();
+}
+
+#[cfg(feature = "async")]
+mod async_tests {
+    use seaplane::api::metadata::v1::AsyncMetadataWatcher;
+
+    use super::{super::block_on, *};
+
+    // GET /config/base64:{key}
+    #[test]
+    fn get_value() {
+        let resp = KeyValue {
+            key: Key::from_encoded("Zm9v".to_string()),
+            value: Value::from_encoded("Zm9v".to_string()),
+            version: None,
+        };
+
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:Zm9v");
+            then(t, json!(resp));
+        });
+
+        let req = partial_build().encoded_key("Zm9v").build_async().unwrap();
+        let resp_val = block_on(req.get_value()).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(resp_val, resp.value);
+    }
+
+    // PUT /config/base64:{key}
+    #[test]
+    fn put_value() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, PUT, "/v1/config/base64:Zm9v")
+                .header("content-type", "application/octet-stream")
+                .body("YmFy");
+            then(t, json!({"status": 200, "title": "Ok"}));
+        });
+
+        let req = partial_build().encoded_key("Zm9v").build_async().unwrap();
+        block_on(req.put_value(Value::from_encoded("YmFy"))).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+    }
+
+    // A failed precondition is a conflict
+    #[test]
+    fn put_value_if_conflict() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, PUT, "/v1/config/base64:YXN5bmNjb25mbGljdA").header("if-match", "\"3\"");
+            precondition_failed(t);
+        });
+
+        let req = partial_build()
+            .encoded_key("YXN5bmNjb25mbGljdA")
+            .build_async()
+            .unwrap();
+        let resp = block_on(req.put_value_if(Value::from_encoded("YmFy"), 3));
+
+        mock.assert_hits(1);
+        mock.delete();
+
+        assert_eq!(resp, Err(SeaplaneError::MetadataRequest(MetadataError::VersionConflict)));
+    }
+
+    // GET /config/base64:{dir}/ then DELETE /config/base64:{key} of each key
+    #[test]
+    fn delete_directory() {
+        let mut list = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:YXN5bmNkb29tZWQ/");
+            then(
+                t,
+                json!({"next_key": None::<String>, "kvs": [
+                    {"key": "YXN5bmNkb29tZWQvYQ", "value": "MQ"},
+                    {"key": "YXN5bmNkb29tZWQvYg", "value": "Mg"}
+                ]}),
+            );
+        });
+        let mut deleted = MOCK_SERVER.mock(|w, t| {
+            when(w, DELETE, "/v1/config/base64:YXN5bmNkb29tZWQvYQ");
+            then(t, json!({"status": 200, "title": "Ok"}));
+        });
+        let mut gone = MOCK_SERVER.mock(|w, t| {
+            when(w, DELETE, "/v1/config/base64:YXN5bmNkb29tZWQvYg");
+            not_found(t);
+        });
+
+        let mut range = RangeQueryContext::new();
+        range.set_directory(Directory::from_encoded("YXN5bmNkb29tZWQ"));
+        let mut req = partial_build().range(range).build_async().unwrap();
+        let report = block_on(req.delete_directory()).unwrap();
+
+        list.assert_hits(1);
+        deleted.assert_hits(1);
+        gone.assert_hits(1);
+        list.delete();
+        deleted.delete();
+        gone.delete();
+
+        assert_eq!(report.deleted, vec![Key::from_encoded("YXN5bmNkb29tZWQvYQ")]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, Key::from_encoded("YXN5bmNkb29tZWQvYg"));
+    }
+
+    // GET /config/base64:{key} with a JSON value
+    #[test]
+    fn get_json() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:YXN5bmNqc29u");
+            then(
+                t,
+                json!({"key": "YXN5bmNqc29u", "value": "eyJuYW1lIjoicGVxdW9kIiwiY3JldyI6MzB9"}),
+            );
+        });
+
+        let req = partial_build()
+            .encoded_key("YXN5bmNqc29u")
+            .build_async()
+            .unwrap();
+        let ship: Ship = block_on(req.get_json()).unwrap();
+
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(ship, Ship { name: "pequod".into(), crew: 30 });
+    }
+
+    // Transient errors are retried up to the max attempts
+    #[test]
+    fn get_value_retries_unavailable() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:YXN5bmMtdW5hdmFpbGFibGU");
+            unavailable(t, "0");
+        });
+
+        let req = partial_build()
+            .encoded_key("YXN5bmMtdW5hdmFpbGFibGU")
+            .build_async()
+            .unwrap();
+        assert!(block_on(req.get_value()).is_err());
+
+        mock.assert_hits(3);
+        mock.delete();
+    }
+
+    // 404 responses map to API errors
+    #[test]
+    fn get_value_not_found() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:bWlzc2luZw");
+            t.status(404)
+                .header("content-type", "application/json")
+                .json_body(json!({"status": 404, "title": "Not Found"}));
+        });
+
+        let req = partial_build()
+            .encoded_key("bWlzc2luZw")
+            .build_async()
+            .unwrap();
+        let err = block_on(req.get_value()).unwrap_err();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        match err {
+            seaplane::error::SeaplaneError::ApiResponse(ae) => assert!(ae.is_http_not_found()),
+            e => panic!("unexpected error {e:?}"),
+        }
+    }
+
+    // Changes are sent from another task until too many checks in a row have failed
+    #[test]
+    fn watch_spawn() {
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:YXN5bmMtd2F0Y2g");
+            then(t, json!({"key": "YXN5bmMtd2F0Y2g", "value": "MQ"}));
+        });
+
+        let req = partial_build()
+            .encoded_key("YXN5bmMtd2F0Y2g")
+            .build_async()
+            .unwrap();
+        block_on(async {
+            let mut events = AsyncMetadataWatcher::new(req, Duration::from_millis(10))
+                .unwrap()
+                .spawn();
+            assert_eq!(
+                events.recv().await.unwrap().unwrap(),
+                MetadataEvent::Added(kv("YXN5bmMtd2F0Y2g", "MQ"))
+            );
+        });
+        mock.assert();
+        mock.delete();
+
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/config/base64:YXN5bmMtd2F0Y2g");
+            t.status(501)
+                .header("content-type", "application/json")
+                .json_body(json!({"status": 501, "title": "Not Implemented"}));
+        });
+
+        let req = partial_build()
+            .encoded_key("YXN5bmMtd2F0Y2g")
+            .build_async()
+            .unwrap();
+        block_on(async {
+            let mut events = AsyncMetadataWatcher::new(req, Duration::from_millis(10))
+                .unwrap()
+                .spawn();
+            assert!(events.recv().await.unwrap().is_err());
+            assert!(events.recv().await.is_none());
+        });
+        mock.assert_hits(MAX_WATCH_FAILURES);
+        mock.delete();
+    }
+}
@@ -0,0 +1,209 @@
+// Full workflows against the stateful stand-in for the APIs, rather than individual requests
+// against canned responses. Each test starts its own server so none of them share any state.
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+use seaplane::{
+    api::{
+        compute::v2::{Flight, Formation},
+        metadata::v1::{Key, Value},
+        restrict::v1::{Api, RestrictionDetails},
+        shared::v1::{Directory, RangeQueryContext, Region},
+        SeaplaneClient,
+    },
+    error::SeaplaneError,
+};
+use seaplane_mock::MockServer;
+
+fn build_client(server: &MockServer) -> SeaplaneClient {
+    let url: url::Url = server.base_url().parse().unwrap();
+    SeaplaneClient::builder()
+        .api_key("abc123")
+        .identity_url(url.clone())
+        .compute_url(url.clone())
+        .metadata_url(url.clone())
+        .locks_url(url.clone())
+        .restrict_url(url)
+        .build()
+        .unwrap()
+}
+
+// The HTTP status of an error response from the API
+fn status(e: SeaplaneError) -> Option<StatusCode> {
+    match e {
+        SeaplaneError::ApiResponse(e) => e.source.status(),
+        _ => None,
+    }
+}
+
+#[test]
+fn metadata_workflow() {
+    let server = MockServer::builder().page_size(2).start();
+    let client = build_client(&server);
+    let key = |k: &str| client.metadata().encoded_key(k).build().unwrap();
+
+    for k in ["dir/a", "dir/b", "dir/c", "other"] {
+        key(Key::from_unencoded(k).encoded())
+            .put_value_unencoded(k.to_uppercase())
+            .unwrap();
+    }
+    assert_eq!(
+        key(Key::from_unencoded("dir/b").encoded())
+            .get_value()
+            .unwrap(),
+        Value::from_unencoded("DIR/B")
+    );
+
+    // The directory spans two pages
+    let mut range = RangeQueryContext::new();
+    range.set_directory(Directory::from_unencoded("dir"));
+    let mut req = client.metadata().range(range).build().unwrap();
+    let keys: Vec<_> = req.entries().map(|kv| kv.unwrap().key.decode()).collect();
+    assert_eq!(keys, vec![b"dir/a".to_vec(), b"dir/b".to_vec(), b"dir/c".to_vec()]);
+
+    let other = key(Key::from_unencoded("other").encoded());
+    other.delete_value().unwrap();
+    assert_eq!(status(other.get_value().unwrap_err()), Some(StatusCode::NOT_FOUND));
+    assert_eq!(status(other.delete_value().unwrap_err()), Some(StatusCode::NOT_FOUND));
+}
+
+#[test]
+fn locks_workflow() {
+    let server = MockServer::start();
+    let client = build_client(&server);
+    let lock = || client.locks().encoded_lock_name("bG9jaw").build().unwrap();
+
+    let held = lock().acquire(10, "first").unwrap();
+    assert_eq!(held.sequencer(), 1);
+    assert_eq!(status(lock().acquire(10, "second").unwrap_err()), Some(StatusCode::CONFLICT));
+    assert_eq!(lock().get_lock_info().unwrap().info.client_id, "first");
+
+    let req = client.locks().held_lock(held).build().unwrap();
+    req.renew(20).unwrap();
+    assert_eq!(lock().get_lock_info().unwrap().info.ttl, 20);
+    req.release().unwrap();
+    assert_eq!(status(req.release().unwrap_err()), Some(StatusCode::NOT_FOUND));
+
+    // Every acquisition of the same lock gets a higher sequencer, and it lapses after its TTL
+    let held = lock().acquire(10, "second").unwrap();
+    assert_eq!(held.sequencer(), 2);
+    server.advance(Duration::from_secs(11));
+    assert_eq!(status(lock().get_lock_info().unwrap_err()), Some(StatusCode::NOT_FOUND));
+    assert_eq!(lock().acquire(10, "third").unwrap().sequencer(), 3);
+}
+
+#[test]
+fn formations_workflow() {
+    let server = MockServer::builder().page_size(1).start();
+    let client = build_client(&server);
+
+    let formation = |name: &str| {
+        Formation::builder()
+            .name(name)
+            .add_flight(
+                Flight::builder()
+                    .name("flight")
+                    .image("foo.com/bar:latest")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    };
+    let req = client.formations().build().unwrap();
+    let created = req.create(&formation("first")).unwrap();
+    req.create(&formation("second")).unwrap();
+    assert_eq!(status(req.create(&formation("first")).unwrap_err()), Some(StatusCode::CONFLICT));
+
+    // Each Formation is on its own page
+    let names: Vec<_> = req.entries().map(|f| f.unwrap().name).collect();
+    assert_eq!(names, vec!["first".to_string(), "second".to_string()]);
+
+    let req = client
+        .formations()
+        .formation_id(created.oid.unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(req.get().unwrap(), created);
+
+    // Flights which are kept by an update keep their IDs
+    let mut changed = formation("first");
+    changed.add_flight(
+        Flight::builder()
+            .name("other")
+            .image("foo.com/baz:latest")
+            .build()
+            .unwrap(),
+    );
+    let updated = req.update(&changed).unwrap();
+    assert_eq!(updated.oid, created.oid);
+    assert_eq!(updated.flights[0].oid, created.flights[0].oid);
+    assert!(updated.flights[1].oid.is_some());
+    assert_eq!(req.get().unwrap(), updated);
+
+    req.delete().unwrap();
+    assert_eq!(status(req.get().unwrap_err()), Some(StatusCode::NOT_FOUND));
+}
+
+#[test]
+fn restrict_workflow() {
+    let server = MockServer::builder().page_size(1).start();
+    let client = build_client(&server);
+
+    let details = RestrictionDetails::builder()
+        .add_allowed_region(Region::XE)
+        .build()
+        .unwrap();
+    for api in ["config", "locks"] {
+        client
+            .restrict()
+            .single_restriction(api, "ZGly")
+            .build()
+            .unwrap()
+            .set_restriction(details.clone())
+            .unwrap();
+    }
+
+    let req = client
+        .restrict()
+        .single_restriction("config", "ZGly")
+        .build()
+        .unwrap();
+    assert_eq!(req.get_restriction().unwrap().details, details);
+
+    // The restrictions of all APIs are paged through one after the other
+    let mut all = client
+        .restrict()
+        .all_range(None::<String>, RangeQueryContext::new())
+        .build()
+        .unwrap();
+    let apis: Vec<_> = all.entries().map(|r| r.unwrap().api).collect();
+    assert_eq!(apis, vec![Api::Config, Api::Locks]);
+
+    req.delete_restriction().unwrap();
+    assert_eq!(status(req.get_restriction().unwrap_err()), Some(StatusCode::NOT_FOUND));
+}
+
+// Access tokens expire and are refreshed transparently
+#[test]
+fn expired_token() {
+    let server = MockServer::builder()
+        .token_ttl(Duration::from_secs(60))
+        .start();
+    let client = build_client(&server);
+    let req = client.metadata().encoded_key("a2V5").build().unwrap();
+
+    req.put_value_unencoded("value").unwrap();
+    server.advance(Duration::from_secs(61));
+    assert_eq!(req.get_value().unwrap(), Value::from_unencoded("value"));
+
+    // Requests without a token issued by the server are rejected
+    let req = client
+        .metadata()
+        .token("abc.123.def")
+        .encoded_key("a2V5")
+        .build()
+        .unwrap();
+    assert_eq!(status(req.get_value().unwrap_err()), Some(StatusCode::UNAUTHORIZED));
+}
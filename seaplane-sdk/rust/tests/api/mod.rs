@@ -1,12 +1,22 @@
 // We have to go through this little bit of indirection because of how integration directory
 // structure works.
 
+#[cfg(all(feature = "identity_api_v1", feature = "locks_api_v1", feature = "metadata_api_v1"))]
+mod client;
 #[cfg(feature = "compute_api_v2")]
 mod compute_v2;
 #[cfg(feature = "locks_api_v1")]
 mod locks_v1;
 #[cfg(feature = "metadata_api_v1")]
 mod metadata_v1;
+#[cfg(all(
+    feature = "identity_api_v1",
+    feature = "compute_api_v2",
+    feature = "locks_api_v1",
+    feature = "metadata_api_v1",
+    feature = "restrict_api_v1"
+))]
+mod mock_server;
 #[cfg(feature = "restrict_api_v1")]
 mod restrict_v1;
 #[cfg(feature = "identity_api_v1")]
@@ -19,3 +29,14 @@ use once_cell::sync::Lazy;
 // MockServer::connect("127.0.0.1:5000")
 // static MOCK_SERVER: Lazy<MockServer> = Lazy::new(|| MockServer::connect("127.0.0.1:5000"));
 static MOCK_SERVER: Lazy<MockServer> = Lazy::new(MockServer::start);
+
+// Drives an async request to completion. The mocks themselves are registered and asserted outside
+// of the runtime so the shared mock server isn't tied to the lifetime of any one test's runtime.
+#[cfg(feature = "async")]
+fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
@@ -1,6 +1,6 @@
 use httpmock::{prelude::*, Method, Then, When};
 use seaplane::api::{
-    restrict::v1::{RestrictRequestBuilder, RestrictionDetails},
+    restrict::v1::{RestrictRequestBuilder, Restriction, RestrictionDetails},
     shared::v1::RangeQueryContext,
 };
 use serde_json::json;
@@ -200,3 +200,150 @@ fn get_all_page() {
     mock.assert();
     assert_eq!(resp, serde_json::from_value(resp_json).unwrap());
 }
+
+fn restriction(api: &str, directory: &str) -> serde_json::Value {
+    json!({
+        "api": api,
+        "directory": directory,
+        "details": {
+            "regions_allowed": ["XE"],
+            "regions_denied": [],
+            "providers_allowed": [],
+            "providers_denied": []
+        },
+        "state": "Enforced"
+    })
+}
+
+fn directories(restrictions: Vec<Restriction>) -> Vec<String> {
+    restrictions
+        .into_iter()
+        .map(|r| r.directory.encoded().to_owned())
+        .collect()
+}
+
+// GET /restrict/ following `next_api` and `next_key`, under a base path of its own so the mock of
+// `get_all_page` can't answer its requests
+#[test]
+fn get_entries() {
+    let mut first = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/paged/v1/restrict/")
+            .matches(|req| req.query_params.as_ref().map_or(true, |q| q.is_empty()));
+        then(
+            t,
+            json!({
+                "next_api": "Locks",
+                "next_key": "Yw",
+                "restrictions": [restriction("Config", "YQ"), restriction("Config", "Yg")]
+            }),
+        );
+    });
+    let mut second = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/paged/v1/restrict/")
+            .query_param("from_api", "Locks")
+            .query_param("from", "base64:Yw");
+        then(t, json!({"restrictions": [restriction("Locks", "Yw")]}));
+    });
+
+    // Only the pages which are consumed are requested
+    let mut req = partial_build()
+        .base_url(MOCK_SERVER.url("/paged/"))
+        .all_range::<String>(None, RangeQueryContext::new())
+        .build()
+        .unwrap();
+    let restrictions = req.entries().take(2).collect::<Result<_, _>>().unwrap();
+    assert_eq!(directories(restrictions), vec!["YQ", "Yg"]);
+    first.assert_hits(1);
+    second.assert_hits(0);
+
+    let mut req = partial_build()
+        .base_url(MOCK_SERVER.url("/paged/"))
+        .all_range::<String>(None, RangeQueryContext::new())
+        .build()
+        .unwrap();
+    let restrictions = req.entries().collect::<Result<_, _>>().unwrap();
+    assert_eq!(directories(restrictions), vec!["YQ", "Yg", "Yw"]);
+    first.assert_hits(2);
+    second.assert_hits(1);
+
+    first.delete();
+    second.delete();
+}
+
+// A page which can't be read ends the iteration after its error
+#[test]
+fn get_entries_error() {
+    let mut first = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/paged/v1/restrict/locks/")
+            .matches(|req| req.query_params.as_ref().map_or(true, |q| q.is_empty()));
+        then(
+            t,
+            json!({
+                "next_api": "Locks",
+                "next_key": "Yg",
+                "restrictions": [restriction("Locks", "YQ")]
+            }),
+        );
+    });
+    let mut second = MOCK_SERVER.mock(|w, t| {
+        when(w, GET, "/paged/v1/restrict/locks/").query_param("from", "base64:Yg");
+        t.status(404)
+            .header("content-type", "application/json")
+            .json_body(json!({"status": 404, "title": "Not Found"}));
+    });
+
+    let mut req = partial_build()
+        .base_url(MOCK_SERVER.url("/paged/"))
+        .api_range("locks", RangeQueryContext::new())
+        .build()
+        .unwrap();
+    let mut restrictions = req.entries();
+    assert_eq!(restrictions.next().unwrap().unwrap().directory.encoded(), "YQ");
+    assert!(restrictions.next().unwrap().is_err());
+    assert!(restrictions.next().is_none());
+    drop(restrictions);
+    first.assert_hits(1);
+    second.assert_hits(1);
+
+    first.delete();
+    second.delete();
+}
+
+#[cfg(feature = "async")]
+mod async_tests {
+    use super::{super::block_on, *};
+
+    // GET /restrict/{api}/base64:{key}/
+    #[test]
+    fn get_restriction() {
+        let resp_json = json!({
+            "api": "Config",
+            "directory": "Zm9vL2Jhcg",
+            "details": {
+                "regions_allowed": ["XE"],
+                "regions_denied": [],
+                "providers_allowed": [],
+                "providers_denied": []
+            },
+            "state": "Enforced"
+        });
+
+        let mut mock = MOCK_SERVER.mock(|w, t| {
+            when(w, GET, "/v1/restrict/config/base64:Zm9vL2Jhcg/");
+            then(t, resp_json.clone());
+        });
+
+        let req = partial_build()
+            .single_restriction("config", "Zm9vL2Jhcg")
+            .build_async()
+            .unwrap();
+        let resp = block_on(req.get_restriction()).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(resp, serde_json::from_value(resp_json).unwrap());
+    }
+}
@@ -1,5 +1,8 @@
 use httpmock::prelude::*;
-use seaplane::api::identity::v1::TokenRequest;
+use seaplane::{
+    api::identity::v1::{TokenProvider, TokenRequest},
+    error::SeaplaneError,
+};
 use serde_json::json;
 
 use super::MOCK_SERVER;
@@ -55,3 +58,219 @@ fn access_token_json() {
 
     assert_eq!(resp, serde_json::from_value(resp_json).unwrap());
 }
+
+// An unsigned JWT expiring in the year 2100
+const FRESH_JWT: &str = "abc.eyJleHAiOjQxMDI0NDQ4MDB9.def";
+// An unsigned JWT which expired in 1970
+const EXPIRED_JWT: &str = "abc.eyJleHAiOjF9.def";
+
+fn token_json(jwt: &str) -> serde_json::Value {
+    json!({"token": jwt, "tenant": "tnt-abcdef1234567890", "subdomain": "pequod"})
+}
+
+fn build_provider(api_key: &str) -> TokenProvider {
+    TokenProvider::new(
+        TokenRequest::builder()
+            .api_key(api_key)
+            .base_url(MOCK_SERVER.base_url()),
+    )
+    .unwrap()
+}
+
+// The access token is only requested once while it's fresh
+#[test]
+fn token_provider_caches() {
+    let mut mock = MOCK_SERVER.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/token")
+            .header("authorization", "Bearer cached");
+        then.status(201).json_body(token_json(FRESH_JWT));
+    });
+
+    // Clones, including those on other threads, share the cached token
+    let provider = build_provider("cached");
+    let shared = provider.clone();
+    let token = std::thread::spawn(move || shared.token().unwrap());
+    assert_eq!(token.join().unwrap(), FRESH_JWT);
+    assert_eq!(provider.token().unwrap(), FRESH_JWT);
+
+    mock.assert_hits(1);
+    mock.delete();
+}
+
+// An access token that has expired is requested again
+#[test]
+fn token_provider_refreshes_expired() {
+    let mut mock = MOCK_SERVER.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/token")
+            .header("authorization", "Bearer expired");
+        then.status(201).json_body(token_json(EXPIRED_JWT));
+    });
+
+    let provider = build_provider("expired");
+    assert_eq!(provider.token().unwrap(), EXPIRED_JWT);
+    assert_eq!(provider.token().unwrap(), EXPIRED_JWT);
+
+    mock.assert_hits(2);
+    mock.delete();
+}
+
+// A request rejected with a 401 is retried once with a new token
+#[cfg(feature = "metadata_api_v1")]
+#[test]
+fn token_provider_retries_unauthorized() {
+    use seaplane::api::metadata::v1::{MetadataRequest, Value};
+
+    let stale_jwt = "stale.eyJleHAiOjQxMDI0NDQ4MDB9.def";
+    let mut stale_token = MOCK_SERVER.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/token")
+            .header("authorization", "Bearer retry");
+        then.status(201).json_body(token_json(stale_jwt));
+    });
+    let provider = build_provider("retry");
+    provider.token().unwrap();
+    stale_token.delete();
+
+    // The cached token is revoked server side, so the next one handed out is different
+    let mut fresh_token = MOCK_SERVER.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/token")
+            .header("authorization", "Bearer retry");
+        then.status(201).json_body(token_json(FRESH_JWT));
+    });
+    let mut rejected = MOCK_SERVER.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/config/base64:cmV0cnk")
+            .header("authorization", format!("Bearer {stale_jwt}"));
+        then.status(401)
+            .json_body(json!({"status": 401, "title": "Unauthorized"}));
+    });
+    let mut accepted = MOCK_SERVER.mock(|when, then| {
+        when.method(GET)
+            .path("/v1/config/base64:cmV0cnk")
+            .header("authorization", format!("Bearer {FRESH_JWT}"));
+        then.status(200)
+            .json_body(json!({"key": "cmV0cnk", "value": "b2s"}));
+    });
+
+    let req = MetadataRequest::builder()
+        .token_provider(provider)
+        .base_url(MOCK_SERVER.base_url())
+        .encoded_key("cmV0cnk")
+        .build()
+        .unwrap();
+    assert_eq!(req.get_value().unwrap(), Value::from_encoded("b2s"));
+
+    rejected.assert_hits(1);
+    fresh_token.assert_hits(1);
+    accepted.assert_hits(1);
+    for mock in [&mut fresh_token, &mut rejected, &mut accepted] {
+        mock.delete();
+    }
+}
+
+// A request still rejected after a new token is requested returns the error
+#[cfg(feature = "metadata_api_v1")]
+#[test]
+fn token_provider_retries_once() {
+    use seaplane::api::metadata::v1::MetadataRequest;
+
+    let mut token = MOCK_SERVER.mock(|when, then| {
+        when.method(POST)
+            .path("/v1/token")
+            .header("authorization", "Bearer once");
+        then.status(201).json_body(token_json(FRESH_JWT));
+    });
+    let mut rejected = MOCK_SERVER.mock(|when, then| {
+        when.method(GET).path("/v1/config/base64:b25jZQ");
+        then.status(401)
+            .json_body(json!({"status": 401, "title": "Unauthorized"}));
+    });
+
+    let req = MetadataRequest::builder()
+        .token_provider(build_provider("once"))
+        .base_url(MOCK_SERVER.base_url())
+        .encoded_key("b25jZQ")
+        .build()
+        .unwrap();
+    match req.get_value().unwrap_err() {
+        SeaplaneError::ApiResponse(ae) => assert!(ae.is_http_unauthorized()),
+        e => panic!("unexpected error {e:?}"),
+    }
+
+    rejected.assert_hits(2);
+    token.assert_hits(2);
+    rejected.delete();
+    token.delete();
+}
+
+#[cfg(feature = "async")]
+mod async_tests {
+    use seaplane::api::identity::v1::AsyncTokenRequest;
+
+    use super::{super::block_on, *};
+
+    // Accept: application/json POST /token
+    #[test]
+    fn access_token_json() {
+        let resp_json = json!({"token": "abc.123.def", "tenant": "tnt-abcdef1234567890", "subdomain": "pequod"});
+        let mut mock = MOCK_SERVER.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/token")
+                .header("authorization", "Bearer def456")
+                .header("accept", "application/json");
+            then.status(201).json_body(resp_json.clone());
+        });
+
+        let req = AsyncTokenRequest::builder()
+            .api_key("def456")
+            .base_url(MOCK_SERVER.base_url())
+            .build_async()
+            .unwrap();
+        let resp = block_on(req.access_token_json()).unwrap();
+
+        // Ensure the endpoint was hit, then remove the mock so it can't shadow the blocking test of
+        // the same endpoint
+        mock.assert();
+        mock.delete();
+
+        assert_eq!(resp, serde_json::from_value(resp_json).unwrap());
+    }
+
+    // The async request types use and cache the provider's token
+    #[cfg(feature = "metadata_api_v1")]
+    #[test]
+    fn token_provider() {
+        use seaplane::api::metadata::v1::{MetadataRequest, Value};
+
+        let mut token = MOCK_SERVER.mock(|when, then| {
+            when.method(POST)
+                .path("/v1/token")
+                .header("authorization", "Bearer async-provider");
+            then.status(201).json_body(token_json(FRESH_JWT));
+        });
+        let mut value = MOCK_SERVER.mock(|when, then| {
+            when.method(GET)
+                .path("/v1/config/base64:YXN5bmM")
+                .header("authorization", format!("Bearer {FRESH_JWT}"));
+            then.status(200)
+                .json_body(json!({"key": "YXN5bmM", "value": "b2s"}));
+        });
+
+        let req = MetadataRequest::builder()
+            .token_provider(build_provider("async-provider"))
+            .base_url(MOCK_SERVER.base_url())
+            .encoded_key("YXN5bmM")
+            .build_async()
+            .unwrap();
+        assert_eq!(block_on(req.get_value()).unwrap(), Value::from_encoded("b2s"));
+        assert_eq!(block_on(req.get_value()).unwrap(), Value::from_encoded("b2s"));
+
+        token.assert_hits(1);
+        value.assert_hits(2);
+        token.delete();
+        value.delete();
+    }
+}